@@ -29,7 +29,7 @@ namespace Prql.Compiler
         public string Hint { get; set; }
 
         /// <summary>
-        /// Character offset of error origin within a source file.
+        /// Byte offsets of error origin within a source file.
         /// </summary>
         public Span Span { get; set; }
 
@@ -4,7 +4,7 @@ namespace Prql.Compiler
 {
     /// <summary>
     /// Identifier of a location in source.
-    /// Contains offsets in terms of chars.
+    /// Contains offsets in terms of bytes of the UTF-8 source.
     /// </summary>
     [StructLayout(LayoutKind.Sequential, CharSet = CharSet.Ansi)]
     public struct Span
@@ -0,0 +1,142 @@
+//! A DuckDB extension for PRQL, with:
+//!
+//! - `prql_to_sql(prql_query)`, a scalar function which compiles PRQL to SQL
+//!   for DuckDB.
+//! - `prql(prql_query)`, a table macro which compiles PRQL and returns the
+//!   results of the query.
+#![cfg(not(target_family = "wasm"))]
+
+use std::error::Error;
+
+use duckdb::core::{DataChunkHandle, Inserter, LogicalTypeHandle, LogicalTypeId};
+use duckdb::ffi::duckdb_string_t;
+use duckdb::types::DuckString;
+use duckdb::vscalar::{ScalarFunctionSignature, VScalar};
+use duckdb::vtab::arrow::WritableVector;
+use duckdb::Connection;
+use prqlc::sql::Dialect;
+use prqlc::{DisplayOptions, ErrorMessages, Options, Target};
+
+/// The `prql` table macro. DuckDB's C API doesn't allow a table function to
+/// plan a query, so `prql` is a macro over DuckDB's `query` table function,
+/// which plans the SQL within the calling query and transaction.
+const PRQL_MACRO: &str =
+    "CREATE OR REPLACE MACRO prql(q) AS TABLE SELECT * FROM query(prql_to_sql(q))";
+
+/// Entry point of the loadable extension.
+///
+/// # Safety
+///
+/// Called by DuckDB when loading the extension.
+#[cfg(feature = "loadable-extension")]
+#[duckdb_loadable_macros::duckdb_entrypoint_c_api(ext_name = "prql", min_duckdb_version = "v1.2.0")]
+pub unsafe fn extension_entrypoint(con: Connection) -> Result<(), Box<dyn Error>> {
+    register(&con)?;
+    Ok(())
+}
+
+/// Register the functions of the extension on a connection.
+pub fn register(con: &Connection) -> duckdb::Result<()> {
+    con.register_scalar_function::<PrqlToSql>("prql_to_sql")?;
+    con.execute_batch(PRQL_MACRO)
+}
+
+/// Compile PRQL to SQL for DuckDB, regardless of the target of the query.
+pub fn compile(prql_query: &str) -> Result<String, ErrorMessages> {
+    let options = Options::default()
+        .with_target(Target::Sql(Some(Dialect::DuckDb)))
+        .no_signature()
+        .with_display(DisplayOptions::Plain);
+
+    prqlc::compile(prql_query, &options)
+}
+
+/// The `prql_to_sql` scalar function.
+struct PrqlToSql;
+
+impl VScalar for PrqlToSql {
+    type State = ();
+
+    unsafe fn invoke(
+        _: &Self::State,
+        input: &mut DataChunkHandle,
+        output: &mut dyn WritableVector,
+    ) -> Result<(), Box<dyn Error>> {
+        let len = input.len();
+        let queries = input.flat_vector(0);
+        let queries = queries.as_slice_with_len::<duckdb_string_t>(len);
+
+        let output = output.flat_vector();
+        for (i, query) in queries.iter().enumerate() {
+            let query = DuckString::new(&mut { *query }).as_str().to_string();
+            output.insert(i, compile(&query)?.as_str());
+        }
+        Ok(())
+    }
+
+    fn signatures() -> Vec<ScalarFunctionSignature> {
+        vec![ScalarFunctionSignature::exact(
+            vec![LogicalTypeHandle::from(LogicalTypeId::Varchar)],
+            LogicalTypeHandle::from(LogicalTypeId::Varchar),
+        )]
+    }
+}
+
+// Run with `cargo test --no-default-features --features=bundled`.
+#[cfg(all(test, feature = "bundled"))]
+mod test {
+    use super::*;
+
+    #[test]
+    fn functions() {
+        let con = Connection::open_in_memory().unwrap();
+        register(&con).unwrap();
+        con.execute_batch(
+            "CREATE TABLE invoices AS SELECT * FROM (VALUES (1, 5.5), (2, 20.0), (3, 12.0)) AS t(id, total)",
+        )
+        .unwrap();
+
+        let sql: String = con
+            .query_row("SELECT prql_to_sql('from invoices | take 1')", [], |row| {
+                row.get(0)
+            })
+            .unwrap();
+        assert_eq!(sql, "SELECT\n  *\nFROM\n  invoices\nLIMIT\n  1\n");
+
+        let mut statement = con
+            .prepare("SELECT id FROM prql('from invoices | filter total > 10 | sort id')")
+            .unwrap();
+        let ids: Vec<i32> = statement
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(ids, vec![2, 3]);
+
+        let err = con
+            .query_row(
+                "SELECT prql_to_sql('from invoices | select {id} | filter total > 10')",
+                [],
+                |row| row.get::<_, String>(0),
+            )
+            .unwrap_err();
+        assert!(err.to_string().contains("Unknown name `total`"), "{err}");
+    }
+
+    #[test]
+    fn prql_within_transaction() {
+        let con = Connection::open_in_memory().unwrap();
+        register(&con).unwrap();
+        con.execute_batch("CREATE TABLE t AS SELECT 1 AS a")
+            .unwrap();
+
+        // `prql` sees the uncommitted changes of the calling transaction
+        con.execute_batch("BEGIN; INSERT INTO t VALUES (2)")
+            .unwrap();
+        let count: i64 = con
+            .query_row("SELECT count(*) FROM prql('from t')", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(count, 2);
+        con.execute_batch("ROLLBACK").unwrap();
+    }
+}
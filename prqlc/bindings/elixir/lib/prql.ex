@@ -72,6 +72,97 @@ defmodule PRQL do
     end
   end
 
+  @doc ~S"""
+  Compile a project of multiple `PRQL` files, given as a map of paths to
+  sources, to `SQL` query.
+
+  The root module is the file whose name starts with an uppercase letter, such
+  as `Project.prql`; other files are submodules named after their path. Errors
+  include the path of the file they are in.
+
+  ## Options
+
+  Accepts the options of `compile/2`, along with:
+
+    * `:main_path` - Dotted path of the relation to compile, such as
+    `"reports.monthly"`. Defaults to the main pipeline of the root module.
+
+  ## Examples
+
+      iex> sources = %{
+      ...>   "Project.prql" => "from shared.top_albums | select {title}",
+      ...>   "shared.prql" => "let top_albums = (from albums | take 10)"
+      ...> }
+      iex> PRQL.compile_tree(sources, main_path: "shared.top_albums", format: false, signature_comment: false)
+      {:ok, "SELECT * FROM albums LIMIT 10"}
+  """
+  @spec compile_tree(%{binary() => binary()}, [compile_opts() | {:main_path, binary()}]) ::
+          {:ok, binary()} | {:error, binary()}
+  def compile_tree(sources, opts \\ []) when is_map(sources) and is_list(opts) do
+    {main_path, opts} = Keyword.pop(opts, :main_path)
+    PRQL.Native.compile_tree(sources, main_path, struct(CompileOptions, opts))
+  end
+
+  @doc """
+  The same as `compile_tree/2` but raises `PRQL.PRQLError` exception in case of error
+  """
+  @spec compile_tree!(%{binary() => binary()}, [compile_opts() | {:main_path, binary()}]) ::
+          binary()
+  def compile_tree!(sources, opts \\ []) do
+    case compile_tree(sources, opts) do
+      {:ok, result} -> result
+      {:error, reason} -> raise PRQL.PRQLError, reason
+    end
+  end
+
+  @doc ~S"""
+  Compile a `PRQL` query like `compile/2`, along with the warnings of the
+  compilation and information about the query.
+
+  Returns `{:ok, json}` on success, where `json` is a `JSON` string of an object
+  with the `sql`, the `warnings`, the `columns` of the result, the `tables`
+  which the query reads from and the `dialect` of the SQL. Accepts the options
+  of `compile/2`.
+  """
+  @spec compile_full(binary(), [compile_opts()]) :: {:ok, binary()} | {:error, binary()}
+  def compile_full(prql_query, opts \\ []) when is_binary(prql_query) and is_list(opts) do
+    PRQL.Native.compile_full(prql_query, struct(CompileOptions, opts))
+  end
+
+  @doc """
+  The same as `compile_full/2` but raises `PRQL.PRQLError` exception in case of error
+  """
+  @spec compile_full!(binary(), [compile_opts()]) :: binary()
+  def compile_full!(prql_query, opts \\ []) do
+    case compile_full(prql_query, opts) do
+      {:ok, result} -> result
+      {:error, reason} -> raise PRQL.PRQLError, reason
+    end
+  end
+
+  @doc """
+  Compile a project of multiple `PRQL` files like `compile_tree/2`, with the
+  result of `compile_full/2`. Accepts the options of `compile_tree/2`.
+  """
+  @spec compile_full_tree(%{binary() => binary()}, [compile_opts() | {:main_path, binary()}]) ::
+          {:ok, binary()} | {:error, binary()}
+  def compile_full_tree(sources, opts \\ []) when is_map(sources) and is_list(opts) do
+    {main_path, opts} = Keyword.pop(opts, :main_path)
+    PRQL.Native.compile_full_tree(sources, main_path, struct(CompileOptions, opts))
+  end
+
+  @doc """
+  The same as `compile_full_tree/2` but raises `PRQL.PRQLError` exception in case of error
+  """
+  @spec compile_full_tree!(%{binary() => binary()}, [compile_opts() | {:main_path, binary()}]) ::
+          binary()
+  def compile_full_tree!(sources, opts \\ []) do
+    case compile_full_tree(sources, opts) do
+      {:ok, result} -> result
+      {:error, reason} -> raise PRQL.PRQLError, reason
+    end
+  end
+
   @doc """
   PRQL to PL AST
   """
@@ -4,6 +4,12 @@ defmodule PRQL.Native do
 
   def compile(_prql_query, _options), do: e()
 
+  def compile_tree(_sources, _main_path, _options), do: e()
+
+  def compile_full(_prql_query, _options), do: e()
+
+  def compile_full_tree(_sources, _main_path, _options), do: e()
+
   def prql_to_pl(_prql_query), do: e()
 
   def pl_to_rq(_pl_json), do: e()
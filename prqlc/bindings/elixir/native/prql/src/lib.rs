@@ -4,6 +4,7 @@
 // likely because of the `NifStruct` derive.
 #![allow(clippy::needless_borrow)]
 
+use std::collections::HashMap;
 use std::default::Default;
 
 use rustler::{Atom, NifResult, NifStruct, NifTuple};
@@ -128,6 +129,56 @@ pub fn compile(prql_query: &str, options: CompileOptions) -> NifResult<Response>
     to_result_tuple(prqlc::compile(prql_query, &options.into()))
 }
 
+#[rustler::nif]
+/// compile a project of multiple prql files, given as a map of paths to
+/// sources, into sql
+pub fn compile_tree(
+    sources: HashMap<String, String>,
+    main_path: Option<String>,
+    options: CompileOptions,
+) -> NifResult<Response> {
+    let tree = source_tree(sources);
+    let main_path = prqlc::split_main_path(&main_path.unwrap_or_default());
+
+    to_result_tuple(prqlc::compile_tree(&tree, &main_path, &options.into()))
+}
+
+#[rustler::nif]
+/// compile a prql query into sql, along with the warnings of the compilation
+/// and information about the query, as json
+pub fn compile_full(prql_query: &str, options: CompileOptions) -> NifResult<Response> {
+    to_result_tuple(
+        prqlc::compile_full(prql_query, &options.into())
+            .and_then(|x| prqlc::json::from_compile_result(&x)),
+    )
+}
+
+#[rustler::nif]
+/// compile a project of multiple prql files like `compile_tree`, with the
+/// result of `compile_full`
+pub fn compile_full_tree(
+    sources: HashMap<String, String>,
+    main_path: Option<String>,
+    options: CompileOptions,
+) -> NifResult<Response> {
+    let tree = source_tree(sources);
+    let main_path = prqlc::split_main_path(&main_path.unwrap_or_default());
+
+    to_result_tuple(
+        prqlc::compile_full_tree(&tree, &main_path, &options.into())
+            .and_then(|x| prqlc::json::from_compile_result(&x)),
+    )
+}
+
+fn source_tree(sources: HashMap<String, String>) -> prqlc::SourceTree {
+    prqlc::SourceTree::new(
+        sources
+            .into_iter()
+            .map(|(path, source)| (path.into(), source)),
+        None,
+    )
+}
+
 #[rustler::nif]
 /// convert a prql query into PL AST
 pub fn prql_to_pl(prql_query: &str) -> NifResult<Response> {
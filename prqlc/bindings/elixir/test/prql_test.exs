@@ -17,6 +17,56 @@ defmodule PRQLTest do
               """}
   end
 
+  test "compiles a project of multiple files" do
+    sources = %{
+      "Project.prql" => "from shared.top_albums | select {title}",
+      "shared.prql" => "let top_albums = (from albums | take 10)"
+    }
+
+    assert PRQL.compile_tree(sources, format: false, signature_comment: false) ==
+             {:ok,
+              "WITH top_albums AS (SELECT * FROM albums LIMIT 10) SELECT title FROM top_albums"}
+  end
+
+  test "compile with warnings and information about the query" do
+    {:ok, json} = PRQL.compile_full("from albums | aggregate {n = count_distinct title}")
+    {:ok, result} = Jason.decode(json)
+
+    assert result["tables"] == ["albums"]
+    assert [_deprecated] = result["warnings"]
+
+    sources = %{
+      "Project.prql" => "from shared.top_albums",
+      "shared.prql" => "let top_albums = (from albums | take 10)"
+    }
+
+    {:ok, json} =
+      PRQL.compile_full_tree(sources,
+        main_path: "shared.top_albums",
+        format: false,
+        signature_comment: false
+      )
+
+    assert %{"sql" => "SELECT * FROM albums LIMIT 10"} = Jason.decode!(json)
+  end
+
+  test "return the file of errors in a project" do
+    sources = %{
+      "Project.prql" => "from shared.top_albums | select {title}",
+      "shared.prql" =>
+        "let top_albums = (from albums | select {title, year} | filter yaer > 2000)"
+    }
+
+    {:error, error_string} = PRQL.compile_tree(sources, @compile_opts)
+    {:ok, %{"inner" => [error]}} = Jason.decode(error_string)
+
+    assert error["location"] == %{
+             "start" => [0, 62],
+             "end" => [0, 66],
+             "path" => "shared.prql"
+           }
+  end
+
   test "return errors on invalid query" do
     {:ok, expected_json} =
       Jason.decode(~S"""
@@ -24,11 +74,12 @@ defmodule PRQLTest do
         "inner": [
           {
             "kind": "Error",
-            "code": null,
+            "code": "E0005",
             "reason": "Unknown name `invalid`",
             "hints": [],
+            "fixes": [],
             "span": "1:0-7",
-            "display": "Error: \n   ╭─[ :1:1 ]\n   │\n 1 │ invalid\n   │ ───┬───  \n   │    ╰───── Unknown name `invalid`\n───╯\n",
+            "display": "[E0005] Error: \n   ╭─[ :1:1 ]\n   │\n 1 │ invalid\n   │ ───┬───  \n   │    ╰───── Unknown name `invalid`\n───╯\n",
             "location": {
               "start": [0, 0],
               "end": [0, 7]
@@ -0,0 +1,134 @@
+package prqlc
+
+/*
+#include "prqlc.h"
+*/
+import "C"
+
+import (
+	"fmt"
+	"strings"
+)
+
+// Error is returned when PRQL fails to compile, with a Message for each error.
+type Error struct {
+	Messages []Message
+}
+
+// Error returns the messages, annotated with the source they refer to.
+func (e *Error) Error() string {
+	var b strings.Builder
+	for i, message := range e.Messages {
+		if i > 0 {
+			b.WriteString("\n")
+		}
+		b.WriteString(message.String())
+	}
+	return b.String()
+}
+
+// MessageKind is the kind of a Message. Currently only KindError is
+// implemented.
+type MessageKind int
+
+const (
+	KindError MessageKind = iota
+	KindWarning
+	KindLint
+)
+
+func (k MessageKind) String() string {
+	switch k {
+	case KindError:
+		return "Error"
+	case KindWarning:
+		return "Warning"
+	case KindLint:
+		return "Lint"
+	}
+	return fmt.Sprintf("MessageKind(%d)", int(k))
+}
+
+// Message is an error of the compilation, mirroring the `Message` of the C API.
+type Message struct {
+	Kind MessageKind
+	// Code is the machine-readable identifier of the error, such as "E0005",
+	// or empty.
+	Code string
+	// Reason is the plain text of the error.
+	Reason string
+	// Hints are suggestions of how to fix the error.
+	Hints []string
+	// Span is the byte offsets of the origin of the error within its
+	// source, or nil.
+	Span *Span
+	// Display is the source annotated with the reason and hints, or empty.
+	Display string
+	// Location is the line and column of the origin of the error within its
+	// source, or nil.
+	Location *SourceLocation
+}
+
+// String returns the annotated source of the message, or its code and reason
+// when it has none.
+func (m Message) String() string {
+	if m.Display != "" {
+		return m.Display
+	}
+	if m.Code != "" {
+		return fmt.Sprintf("[%s] %s: %s", m.Code, m.Kind, m.Reason)
+	}
+	return fmt.Sprintf("%s: %s", m.Kind, m.Reason)
+}
+
+// Span is a range of byte offsets within the UTF-8 encoding of a source.
+type Span struct {
+	Start int
+	End   int
+}
+
+// SourceLocation is a range of lines and columns within a source, all of which
+// are 0-based.
+type SourceLocation struct {
+	StartLine   int
+	StartColumn int
+	EndLine     int
+	EndColumn   int
+	// Path is the path of the file within a project, or empty when compiling a
+	// single source.
+	Path string
+}
+
+func newMessage(m *C.struct_Message) Message {
+	message := Message{
+		Kind:   MessageKind(m.kind),
+		Reason: C.GoString(m.reason),
+	}
+	if m.code != nil {
+		message.Code = C.GoString(*m.code)
+	}
+	if m.hint != nil {
+		message.Hints = strings.Split(C.GoString(*m.hint), "\n")
+	}
+	if m.span != nil {
+		message.Span = &Span{
+			Start: int(m.span.start),
+			End:   int(m.span.end),
+		}
+	}
+	if m.display != nil {
+		message.Display = C.GoString(*m.display)
+	}
+	if m.location != nil {
+		message.Location = &SourceLocation{
+			StartLine:   int(m.location.start_line),
+			StartColumn: int(m.location.start_col),
+			EndLine:     int(m.location.end_line),
+			EndColumn:   int(m.location.end_col),
+		}
+		if m.location.path != nil {
+			message.Location.Path = C.GoString(m.location.path)
+		}
+	}
+	return message
+}
@@ -0,0 +1,3 @@
+module github.com/PRQL/prql/prqlc/bindings/go
+
+go 1.21
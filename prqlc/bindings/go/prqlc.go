@@ -0,0 +1,180 @@
+// Package prqlc compiles PRQL to SQL, by binding to the C API of the `prqlc`
+// Rust library, `prqlc-c`.
+//
+// The library must be built before building this package:
+//
+//	cargo build --package prqlc-c --release
+package prqlc
+
+/*
+#cgo CFLAGS: -I${SRCDIR}/../prqlc-c
+#cgo LDFLAGS: ${SRCDIR}/../../../target/release/libprqlc_c.a -pthread -ldl -lm
+#cgo darwin LDFLAGS: -framework CoreFoundation
+
+#include <stdlib.h>
+
+#include "prqlc.h"
+*/
+import "C"
+
+import (
+	"sort"
+	"unsafe"
+)
+
+// Options of the compilation, mirroring the `Options` of the C API.
+//
+// The zero value compiles to SQL without formatting nor a signature comment,
+// using the target of the query header. DefaultOptions returns the defaults of
+// the compiler.
+type Options struct {
+	// Format passes the SQL through a formatter that splits it into multiple
+	// lines and prettifies indentation and spacing.
+	Format bool
+
+	// Target is the dialect to compile to, such as "sql.postgres". When empty
+	// or "sql.any", the target of the query header is used. Targets lists the
+	// available targets.
+	Target string
+
+	// SignatureComment emits the compiler signature as a comment after the SQL.
+	SignatureComment bool
+}
+
+// DefaultOptions returns the default options of the compiler, which format the
+// SQL and emit a signature comment.
+func DefaultOptions() Options {
+	return Options{
+		Format:           true,
+		Target:           "sql.any",
+		SignatureComment: true,
+	}
+}
+
+// Compile compiles a PRQL query to SQL.
+func Compile(prql string, opts Options) (string, error) {
+	query := C.CString(prql)
+	defer C.free(unsafe.Pointer(query))
+	options := opts.toC()
+	defer C.free(unsafe.Pointer(options.target))
+
+	return result(C.compile(query, &options))
+}
+
+// CompileTree compiles a project of multiple PRQL files to SQL.
+//
+// sources maps the path of each file to its source. The root module is the file
+// whose name starts with an uppercase letter, such as "Project.prql"; other
+// files are submodules named after their path. mainPath is the dotted path of
+// the relation to compile, such as "reports.monthly"; when it is empty, the main
+// pipeline of the root module is compiled. The locations of errors include the
+// path of their file.
+func CompileTree(sources map[string]string, mainPath string, opts Options) (string, error) {
+	paths := make([]string, 0, len(sources))
+	for path := range sources {
+		paths = append(paths, path)
+	}
+	sort.Strings(paths)
+
+	cPaths := make([]*C.char, len(paths))
+	cSources := make([]*C.char, len(paths))
+	for i, path := range paths {
+		cPaths[i] = C.CString(path)
+		defer C.free(unsafe.Pointer(cPaths[i]))
+		cSources[i] = C.CString(sources[path])
+		defer C.free(unsafe.Pointer(cSources[i]))
+	}
+	var pathsPtr, sourcesPtr **C.char
+	if len(paths) > 0 {
+		pathsPtr = &cPaths[0]
+		sourcesPtr = &cSources[0]
+	}
+
+	cMainPath := C.CString(mainPath)
+	defer C.free(unsafe.Pointer(cMainPath))
+	options := opts.toC()
+	defer C.free(unsafe.Pointer(options.target))
+
+	return result(C.compile_tree(pathsPtr, sourcesPtr, C.size_t(len(paths)), cMainPath, &options))
+}
+
+// PrqlToPL converts a PRQL query to PL AST, serialized as JSON.
+func PrqlToPL(prql string) (string, error) {
+	query := C.CString(prql)
+	defer C.free(unsafe.Pointer(query))
+
+	return result(C.prql_to_pl(query))
+}
+
+// PLToRQ resolves PL AST and lowers it to RQ AST, both serialized as JSON.
+func PLToRQ(plJSON string) (string, error) {
+	pl := C.CString(plJSON)
+	defer C.free(unsafe.Pointer(pl))
+
+	return result(C.pl_to_rq(pl))
+}
+
+// RQToSQL converts RQ AST, serialized as JSON, to SQL.
+func RQToSQL(rqJSON string, opts Options) (string, error) {
+	rq := C.CString(rqJSON)
+	defer C.free(unsafe.Pointer(rq))
+	options := opts.toC()
+	defer C.free(unsafe.Pointer(options.target))
+
+	return result(C.rq_to_sql(rq, &options))
+}
+
+// Lineage computes the column-level lineage of a PRQL query, serialized as
+// JSON. See the docs of `prqlc debug lineage` for its format.
+func Lineage(prql string) (string, error) {
+	query := C.CString(prql)
+	defer C.free(unsafe.Pointer(query))
+
+	return result(C.prql_lineage(query))
+}
+
+// Targets lists the targets which can be passed in Options, such as
+// "sql.postgres".
+func Targets() []string {
+	var length C.size_t
+	targets := unsafe.Slice(C.prqlc_get_targets(&length), length)
+
+	names := make([]string, len(targets))
+	for i, target := range targets {
+		names[i] = C.GoString(target)
+	}
+	return names
+}
+
+// Version returns the version of the compiler, such as "0.13.11".
+func Version() string {
+	return C.GoString(C.prqlc_version())
+}
+
+// toC converts the options. Their target must be freed by the caller.
+func (opts Options) toC() C.struct_Options {
+	options := C.struct_Options{
+		format:            C.bool(opts.Format),
+		signature_comment: C.bool(opts.SignatureComment),
+	}
+	if opts.Target != "" {
+		options.target = C.CString(opts.Target)
+	}
+	return options
+}
+
+// result converts a CompileResult, and destroys it.
+func result(res C.CompileResult) (string, error) {
+	defer C.result_destroy(res)
+
+	if res.messages_len == 0 {
+		return C.GoString(res.output), nil
+	}
+
+	messages := unsafe.Slice(res.messages, res.messages_len)
+	err := &Error{Messages: make([]Message, len(messages))}
+	for i, message := range messages {
+		err.Messages[i] = newMessage(&message)
+	}
+	return "", err
+}
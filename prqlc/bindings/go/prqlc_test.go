@@ -0,0 +1,157 @@
+package prqlc
+
+import (
+	"errors"
+	"os"
+	"path/filepath"
+	"strings"
+	"testing"
+)
+
+func TestCompile(t *testing.T) {
+	sql, err := Compile("from albums | select {title} | take 3", Options{Target: "sql.mysql"})
+	if err != nil {
+		t.Fatal(err)
+	}
+	if want := "SELECT title FROM albums LIMIT 3"; sql != want {
+		t.Errorf("got %q, want %q", sql, want)
+	}
+
+	sql, err = Compile("from albums", DefaultOptions())
+	if err != nil {
+		t.Fatal(err)
+	}
+	if !strings.Contains(sql, "-- Generated by PRQL compiler") {
+		t.Errorf("missing signature in %q", sql)
+	}
+}
+
+// Compiles the queries of the `compile-files` example of the Rust library.
+func TestCompileExampleQueries(t *testing.T) {
+	paths, err := filepath.Glob("../../prqlc/examples/compile-files/queries/*.prql")
+	if err != nil {
+		t.Fatal(err)
+	}
+	if len(paths) == 0 {
+		t.Fatal("no example queries found")
+	}
+
+	for _, path := range paths {
+		t.Run(filepath.Base(path), func(t *testing.T) {
+			prql, err := os.ReadFile(path)
+			if err != nil {
+				t.Fatal(err)
+			}
+			sql, err := Compile(string(prql), DefaultOptions())
+			if err != nil {
+				t.Fatal(err)
+			}
+			if !strings.Contains(sql, "SELECT") {
+				t.Errorf("unexpected SQL %q", sql)
+			}
+		})
+	}
+}
+
+func TestCompileError(t *testing.T) {
+	_, err := Compile("from albums | select {title, year} | filter yaer > 2000", Options{})
+
+	var prqlErr *Error
+	if !errors.As(err, &prqlErr) {
+		t.Fatalf("expected *Error, got %v", err)
+	}
+	if len(prqlErr.Messages) != 1 {
+		t.Fatalf("expected 1 message, got %d", len(prqlErr.Messages))
+	}
+
+	message := prqlErr.Messages[0]
+	if message.Kind != KindError || message.Code != "E0005" || message.Reason != "Unknown name `yaer`" {
+		t.Errorf("unexpected message %+v", message)
+	}
+	if len(message.Hints) == 0 || message.Hints[0] != "did you mean `year`?" {
+		t.Errorf("unexpected hints %q", message.Hints)
+	}
+	if message.Span == nil || *message.Span != (Span{Start: 44, End: 48}) {
+		t.Errorf("unexpected span %v", message.Span)
+	}
+	if message.Location == nil || message.Location.StartLine != 0 || message.Location.StartColumn != 44 {
+		t.Errorf("unexpected location %v", message.Location)
+	}
+	if !strings.Contains(err.Error(), "Unknown name `yaer`") {
+		t.Errorf("unexpected error text %q", err.Error())
+	}
+
+	_, err = Compile("from albums", Options{Target: "sql.oracle"})
+	if !errors.As(err, &prqlErr) || prqlErr.Messages[0].Code != "E0011" {
+		t.Errorf("expected an invalid target error, got %v", err)
+	}
+}
+
+func TestCompileTree(t *testing.T) {
+	sources := map[string]string{
+		"Project.prql": "from shared.top_albums | select {title}",
+		"shared.prql":  "let top_albums = (from albums | take 3)",
+	}
+
+	sql, err := CompileTree(sources, "", Options{})
+	if err != nil {
+		t.Fatal(err)
+	}
+	if want := "WITH top_albums AS (SELECT * FROM albums LIMIT 3) SELECT title FROM top_albums"; sql != want {
+		t.Errorf("got %q, want %q", sql, want)
+	}
+
+	sql, err = CompileTree(sources, "shared.top_albums", Options{})
+	if err != nil {
+		t.Fatal(err)
+	}
+	if want := "SELECT * FROM albums LIMIT 3"; sql != want {
+		t.Errorf("got %q, want %q", sql, want)
+	}
+
+	sources["shared.prql"] = "let top_albums = (from albums | select {title, year} | filter yaer > 2000)"
+	_, err = CompileTree(sources, "", Options{})
+	var prqlErr *Error
+	if !errors.As(err, &prqlErr) {
+		t.Fatalf("expected *Error, got %v", err)
+	}
+	if location := prqlErr.Messages[0].Location; location == nil || location.Path != "shared.prql" {
+		t.Errorf("unexpected location %v", location)
+	}
+}
+
+func TestIntermediateRepresentations(t *testing.T) {
+	pl, err := PrqlToPL("from albums | select {title}")
+	if err != nil {
+		t.Fatal(err)
+	}
+	rq, err := PLToRQ(pl)
+	if err != nil {
+		t.Fatal(err)
+	}
+	sql, err := RQToSQL(rq, Options{})
+	if err != nil {
+		t.Fatal(err)
+	}
+	if want := "SELECT title FROM albums"; sql != want {
+		t.Errorf("got %q, want %q", sql, want)
+	}
+
+	lineage, err := Lineage("from albums | select {title}")
+	if err != nil {
+		t.Fatal(err)
+	}
+	if !strings.Contains(lineage, `"frames"`) {
+		t.Errorf("unexpected lineage %q", lineage)
+	}
+}
+
+func TestTargets(t *testing.T) {
+	targets := Targets()
+	if len(targets) < 2 || targets[0] != "sql.any" {
+		t.Errorf("unexpected targets %q", targets)
+	}
+	if Version() == "" {
+		t.Error("empty version")
+	}
+}
@@ -0,0 +1,72 @@
+package org.prql.prql4j;
+
+/**
+ * Compilation options, mirroring the `Options` of the prqlc Rust crate.
+ */
+public class CompileOptions {
+    private boolean format = true;
+    private String target = "sql.any";
+    private boolean signatureComment = true;
+    private boolean color = false;
+    private String display = "plain";
+
+    /**
+     * pass generated SQL through a formatter that splits it into multiple lines and prettifies indentation and spacing. Defaults to true.
+     */
+    public CompileOptions format(boolean format) {
+        this.format = format;
+        return this;
+    }
+
+    /**
+     * target dialect, such as sql.mysql etc. Defaults to sql.any, which uses the target of the query header. Please refer <a href="https://github.com/PRQL/prql/blob/main/web/book/src/project/target.md">PRQL Target and Version</a>
+     */
+    public CompileOptions target(String target) {
+        this.target = target;
+        return this;
+    }
+
+    /**
+     * emit the compiler signature as a comment after the generated SQL. Defaults to true.
+     */
+    public CompileOptions signatureComment(boolean signatureComment) {
+        this.signatureComment = signatureComment;
+        return this;
+    }
+
+    /**
+     * deprecated: use {@link #display(String)} instead.
+     */
+    public CompileOptions color(boolean color) {
+        this.color = color;
+        return this;
+    }
+
+    /**
+     * how errors are displayed: "plain" or "ansi_color". Defaults to "plain".
+     */
+    public CompileOptions display(String display) {
+        this.display = display;
+        return this;
+    }
+
+    public boolean getFormat() {
+        return format;
+    }
+
+    public String getTarget() {
+        return target;
+    }
+
+    public boolean getSignatureComment() {
+        return signatureComment;
+    }
+
+    public boolean getColor() {
+        return color;
+    }
+
+    public String getDisplay() {
+        return display;
+    }
+}
@@ -0,0 +1,82 @@
+package org.prql.prql4j;
+
+import java.util.Arrays;
+import java.util.Collections;
+import java.util.List;
+
+/**
+ * An error of the compilation, mirroring the `ErrorMessage` of the prqlc Rust crate.
+ */
+public class ErrorMessage {
+    private final String kind;
+    private final String code;
+    private final String reason;
+    private final List<String> hints;
+    private final Span span;
+    private final String display;
+    private final SourceLocation location;
+
+    public ErrorMessage(String kind, String code, String reason, String[] hints, Span span, String display, SourceLocation location) {
+        this.kind = kind;
+        this.code = code;
+        this.reason = reason;
+        this.hints = Collections.unmodifiableList(Arrays.asList(hints));
+        this.span = span;
+        this.display = display;
+        this.location = location;
+    }
+
+    /**
+     * @return "Error", "Warning" or "Lint"
+     */
+    public String getKind() {
+        return kind;
+    }
+
+    /**
+     * @return machine-readable identifier of the error, such as "E0005", or null
+     */
+    public String getCode() {
+        return code;
+    }
+
+    /**
+     * @return plain text of the error
+     */
+    public String getReason() {
+        return reason;
+    }
+
+    /**
+     * @return suggestions of how to fix the error
+     */
+    public List<String> getHints() {
+        return hints;
+    }
+
+    /**
+     * @return byte offsets of the error within its source file, or null
+     */
+    public Span getSpan() {
+        return span;
+    }
+
+    /**
+     * @return annotated code, containing the cause and hints, or null
+     */
+    public String getDisplay() {
+        return display;
+    }
+
+    /**
+     * @return line and column of the error within its source file, or null
+     */
+    public SourceLocation getLocation() {
+        return location;
+    }
+
+    @Override
+    public String toString() {
+        return display != null ? display : reason;
+    }
+}
@@ -0,0 +1,21 @@
+package org.prql.prql4j;
+
+import java.util.Arrays;
+import java.util.Collections;
+import java.util.List;
+
+/**
+ * Thrown when PRQL fails to compile, with an {@link ErrorMessage} for each error.
+ */
+public class PrqlCompileException extends Exception {
+    private final List<ErrorMessage> errors;
+
+    public PrqlCompileException(String message, ErrorMessage[] errors) {
+        super(message);
+        this.errors = Collections.unmodifiableList(Arrays.asList(errors));
+    }
+
+    public List<ErrorMessage> getErrors() {
+        return errors;
+    }
+}
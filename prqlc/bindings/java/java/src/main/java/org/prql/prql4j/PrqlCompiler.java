@@ -1,6 +1,7 @@
 package org.prql.prql4j;
 
 import java.io.IOException;
+import java.util.Map;
 
 public class PrqlCompiler {
 
@@ -11,11 +12,147 @@ public class PrqlCompiler {
      * @param format format SQL or not
      * @param signature comment signature or not
      * @return SQL
-     * @throws Exception PRQL compile exception
+     * @throws PrqlCompileException PRQL compile exception
      */
-    public static native String toSql(String query, String target, boolean format, boolean signature) throws Exception;
-    public static native String toJson(String query) throws Exception;
-    public static native String format(String query) throws Exception;
+    public static String toSql(String query, String target, boolean format, boolean signature) throws PrqlCompileException {
+        return toSql(query, new CompileOptions().target(target).format(format).signatureComment(signature));
+    }
+
+    /**
+     * compile PRQL to SQL
+     * @param query PRQL query
+     * @param options compilation options, or null for the defaults
+     * @return SQL
+     * @throws PrqlCompileException PRQL compile exception
+     */
+    public static String toSql(String query, CompileOptions options) throws PrqlCompileException {
+        return compile(query, options);
+    }
+
+    /**
+     * compile a PRQL project of multiple files to SQL
+     * @param sources PRQL sources by their path, such as "Project.prql"; the root module is the file whose name starts with an uppercase letter
+     * @param mainPath dotted path of the relation to compile, such as "reports.monthly", or null for the main pipeline of the root module
+     * @param target target dialect, such as sql.mysql etc. Please refer <a href="https://github.com/PRQL/prql/blob/main/web/book/src/project/target.md">PRQL Target and Version</a>
+     * @param format format SQL or not
+     * @param signature comment signature or not
+     * @return SQL
+     * @throws PrqlCompileException PRQL compile exception, which names the file of each error
+     */
+    public static String toSql(Map<String, String> sources, String mainPath, String target, boolean format, boolean signature) throws PrqlCompileException {
+        return toSql(sources, mainPath, new CompileOptions().target(target).format(format).signatureComment(signature));
+    }
+
+    /**
+     * compile a PRQL project of multiple files to SQL
+     * @param sources PRQL sources by their path, such as "Project.prql"; the root module is the file whose name starts with an uppercase letter
+     * @param mainPath dotted path of the relation to compile, such as "reports.monthly", or null for the main pipeline of the root module
+     * @param options compilation options, or null for the defaults
+     * @return SQL
+     * @throws PrqlCompileException PRQL compile exception, which names the file of each error
+     */
+    public static String toSql(Map<String, String> sources, String mainPath, CompileOptions options) throws PrqlCompileException {
+        String[] paths = sources.keySet().toArray(new String[0]);
+        return compileTree(paths, contents(sources, paths), mainPath, options);
+    }
+
+    /**
+     * compile PRQL to SQL, along with the warnings of the compilation and information about the query
+     * @param query PRQL query
+     * @param options compilation options, or null for the defaults
+     * @return JSON of an object with the sql, the warnings, the columns of the result, the tables which the query reads from and the dialect of the SQL
+     * @throws PrqlCompileException PRQL compile exception
+     */
+    public static native String compileFull(String query, CompileOptions options) throws PrqlCompileException;
+
+    /**
+     * compile a PRQL project of multiple files to SQL, along with the warnings of the compilation and information about the query
+     * @param sources PRQL sources by their path, such as "Project.prql"; the root module is the file whose name starts with an uppercase letter
+     * @param mainPath dotted path of the relation to compile, such as "reports.monthly", or null for the main pipeline of the root module
+     * @param options compilation options, or null for the defaults
+     * @return JSON of an object like the one of {@link #compileFull(String, CompileOptions)}
+     * @throws PrqlCompileException PRQL compile exception, which names the file of each error
+     */
+    public static String compileFull(Map<String, String> sources, String mainPath, CompileOptions options) throws PrqlCompileException {
+        String[] paths = sources.keySet().toArray(new String[0]);
+        return compileFullTree(paths, contents(sources, paths), mainPath, options);
+    }
+
+    private static String[] contents(Map<String, String> sources, String[] paths) {
+        String[] contents = new String[paths.length];
+        for (int i = 0; i < paths.length; i++) {
+            contents[i] = sources.get(paths[i]);
+        }
+        return contents;
+    }
+
+    /**
+     * convert PRQL to PL AST, serialized as JSON
+     * @param query PRQL query
+     * @return PL as JSON
+     * @throws PrqlCompileException PRQL compile exception
+     */
+    public static String toJson(String query) throws PrqlCompileException {
+        return prqlToPl(query);
+    }
+
+    private static native String compile(String query, CompileOptions options) throws PrqlCompileException;
+    private static native String compileTree(String[] paths, String[] sources, String mainPath, CompileOptions options) throws PrqlCompileException;
+    private static native String compileFullTree(String[] paths, String[] sources, String mainPath, CompileOptions options) throws PrqlCompileException;
+
+    /**
+     * format PRQL
+     * @param query PRQL query
+     * @return formatted PRQL
+     * @throws PrqlCompileException PRQL compile exception
+     */
+    public static native String format(String query) throws PrqlCompileException;
+
+    /**
+     * convert PRQL to PL AST, serialized as JSON. PL is documented in the <a href="https://docs.rs/prqlc/latest/prqlc/ir/pl">prqlc Rust crate</a>
+     * @param query PRQL query
+     * @return PL as JSON
+     * @throws PrqlCompileException PRQL compile exception
+     */
+    public static native String prqlToPl(String query) throws PrqlCompileException;
+
+    /**
+     * convert PL AST, serialized as JSON, to PRQL
+     * @param plJson PL as JSON
+     * @return PRQL
+     * @throws PrqlCompileException PRQL compile exception
+     */
+    public static native String plToPrql(String plJson) throws PrqlCompileException;
+
+    /**
+     * resolve PL AST and lower it to RQ AST, both serialized as JSON. RQ is documented in the <a href="https://docs.rs/prqlc/latest/prqlc/ir/rq">prqlc Rust crate</a>
+     * @param plJson PL as JSON
+     * @return RQ as JSON
+     * @throws PrqlCompileException PRQL compile exception
+     */
+    public static native String plToRq(String plJson) throws PrqlCompileException;
+
+    /**
+     * convert RQ AST, serialized as JSON, to SQL
+     * @param rqJson RQ as JSON
+     * @param options compilation options, or null for the defaults
+     * @return SQL
+     * @throws PrqlCompileException PRQL compile exception
+     */
+    public static native String rqToSql(String rqJson, CompileOptions options) throws PrqlCompileException;
+
+    /**
+     * compute the column-level lineage of a PRQL query, serialized as JSON. See the docs of `prqlc debug lineage`
+     * @param query PRQL query
+     * @return lineage as JSON
+     * @throws PrqlCompileException PRQL compile exception
+     */
+    public static native String lineage(String query) throws PrqlCompileException;
+
+    /**
+     * @return names of the targets to compile to, such as sql.mysql
+     */
+    public static native String[] getTargets();
 
     static {
         try {
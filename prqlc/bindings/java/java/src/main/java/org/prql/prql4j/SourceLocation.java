@@ -0,0 +1,43 @@
+package org.prql.prql4j;
+
+/**
+ * Location within a source file. Lines and columns are 0-based.
+ */
+public class SourceLocation {
+    private final long startLine;
+    private final long startColumn;
+    private final long endLine;
+    private final long endColumn;
+    private final String path;
+
+    public SourceLocation(long startLine, long startColumn, long endLine, long endColumn, String path) {
+        this.startLine = startLine;
+        this.startColumn = startColumn;
+        this.endLine = endLine;
+        this.endColumn = endColumn;
+        this.path = path;
+    }
+
+    public long getStartLine() {
+        return startLine;
+    }
+
+    public long getStartColumn() {
+        return startColumn;
+    }
+
+    public long getEndLine() {
+        return endLine;
+    }
+
+    public long getEndColumn() {
+        return endColumn;
+    }
+
+    /**
+     * @return path of the source file within the project, or null when compiling a single source
+     */
+    public String getPath() {
+        return path;
+    }
+}
@@ -0,0 +1,22 @@
+package org.prql.prql4j;
+
+/**
+ * Byte offsets of a part of a source file, in its UTF-8 encoding.
+ */
+public class Span {
+    private final long start;
+    private final long end;
+
+    public Span(long start, long end) {
+        this.start = start;
+        this.end = end;
+    }
+
+    public long getStart() {
+        return start;
+    }
+
+    public long getEnd() {
+        return end;
+    }
+}
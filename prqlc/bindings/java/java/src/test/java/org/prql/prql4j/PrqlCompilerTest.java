@@ -1,5 +1,9 @@
 package org.prql.prql4j;
 
+import java.util.Arrays;
+import java.util.HashMap;
+import java.util.Map;
+
 import org.junit.Test;
 
 public class PrqlCompilerTest {
@@ -21,4 +25,126 @@ public class PrqlCompilerTest {
     public void compileWithError() throws Exception {
        PrqlCompiler.toSql("from table | filter id >> 1", "sql.mysql", true, true);
     }
+
+    @Test
+    public void compileTree() throws Exception {
+        Map<String, String> sources = new HashMap<>();
+        sources.put("Project.prql", "from shared.top_albums | select {title}");
+        sources.put("shared.prql", "let top_albums = (from albums | take 10)");
+
+        String found = PrqlCompiler.toSql(sources, null, "sql.any", false, false);
+        assert "WITH top_albums AS (SELECT * FROM albums LIMIT 10) SELECT title FROM top_albums".equals(found);
+
+        found = PrqlCompiler.toSql(sources, "shared.top_albums", "sql.any", false, false);
+        assert "SELECT * FROM albums LIMIT 10".equals(found);
+    }
+
+    @Test
+    public void compileFull() throws Exception {
+        String found = PrqlCompiler.compileFull("from albums | aggregate {n = count_distinct title}", null);
+        assert found.contains("\"tables\":[\"albums\"]");
+        assert found.contains("`count_distinct` is deprecated");
+
+        Map<String, String> sources = new HashMap<>();
+        sources.put("Project.prql", "from shared.top_albums");
+        sources.put("shared.prql", "let top_albums = (from albums | take 10)");
+        CompileOptions options = new CompileOptions().format(false).signatureComment(false);
+        found = PrqlCompiler.compileFull(sources, "shared.top_albums", options);
+        assert found.contains("\"sql\":\"SELECT * FROM albums LIMIT 10\"");
+    }
+
+    @Test
+    public void compileTreeWithError() throws Exception {
+        Map<String, String> sources = new HashMap<>();
+        sources.put("Project.prql", "from shared.top_albums | select {title}");
+        sources.put("shared.prql", "let top_albums = (from albums | select {title, year} | filter yaer > 2000)");
+
+        try {
+            PrqlCompiler.toSql(sources, null, "sql.any", false, false);
+            assert false;
+        } catch (Exception e) {
+            assert e.getMessage().contains("shared.prql:1:63");
+        }
+    }
+
+    @Test
+    public void compileWithOptions() throws Exception {
+        CompileOptions options = new CompileOptions().target("sql.mysql").format(false).signatureComment(false);
+        String found = PrqlCompiler.toSql("from my_table | take 5", options);
+        assert "SELECT * FROM my_table LIMIT 5".equals(found);
+
+        found = PrqlCompiler.toSql("from my_table", null);
+        assert found.contains("-- Generated by PRQL compiler");
+    }
+
+    @Test
+    public void compileWithInvalidTarget() throws Exception {
+        try {
+            PrqlCompiler.toSql("from my_table", new CompileOptions().target("sql.oracle"));
+            assert false;
+        } catch (PrqlCompileException e) {
+            assert "E0011".equals(e.getErrors().get(0).getCode());
+        }
+    }
+
+    @Test
+    public void structuredErrors() throws Exception {
+        try {
+            PrqlCompiler.toSql("from employees | select {name, age} | filter agee > 30", null);
+            assert false;
+        } catch (PrqlCompileException e) {
+            assert e.getErrors().size() == 1;
+            ErrorMessage error = e.getErrors().get(0);
+            assert "Error".equals(error.getKind());
+            assert "E0005".equals(error.getCode());
+            assert error.getReason().contains("agee");
+            assert error.getSpan().getStart() == 45;
+            assert error.getSpan().getEnd() == 49;
+            assert error.getLocation().getStartLine() == 0;
+            assert error.getLocation().getStartColumn() == 45;
+            assert error.getLocation().getPath() == null;
+            assert error.getHints().contains("did you mean `age`?");
+            assert e.getMessage().contains(":1:46");
+        }
+    }
+
+    @Test
+    public void compileTreeWithStructuredError() throws Exception {
+        Map<String, String> sources = new HashMap<>();
+        sources.put("Project.prql", "from shared.top_albums | select {title}");
+        sources.put("shared.prql", "let top_albums = (from albums | select {title, year} | filter yaer > 2000)");
+
+        try {
+            PrqlCompiler.toSql(sources, null, new CompileOptions());
+            assert false;
+        } catch (PrqlCompileException e) {
+            assert "shared.prql".equals(e.getErrors().get(0).getLocation().getPath());
+        }
+    }
+
+    @Test
+    public void intermediateRepresentations() throws Exception {
+        String pl = PrqlCompiler.prqlToPl("from my_table | select {a, b}");
+        assert pl.contains("\"my_table\"");
+        assert "from my_table\nselect {a, b}\n".equals(PrqlCompiler.plToPrql(pl));
+
+        String rq = PrqlCompiler.plToRq(pl);
+        assert rq.contains("\"relation\"");
+
+        String sql = PrqlCompiler.rqToSql(rq, new CompileOptions().format(false).signatureComment(false));
+        assert "SELECT a, b FROM my_table".equals(sql);
+    }
+
+    @Test
+    public void lineage() throws Exception {
+        String found = PrqlCompiler.lineage("from my_table | select {a, b}");
+        assert found.contains("\"frames\"");
+    }
+
+    @Test
+    public void targets() {
+        String[] targets = PrqlCompiler.getTargets();
+        assert Arrays.asList(targets).contains("sql.any");
+        assert Arrays.asList(targets).contains("sql.postgres");
+    }
 }
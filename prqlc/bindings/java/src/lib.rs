@@ -1,37 +1,73 @@
 use std::str::FromStr;
 
-use jni::objects::{JClass, JString};
-use jni::sys::{jboolean, jstring};
+use jni::objects::{JClass, JObject, JObjectArray, JString, JThrowable, JValue};
+use jni::sys::{jobjectArray, jstring};
 use jni::JNIEnv;
-use prqlc::{json, pl_to_prql, prql_to_pl, ErrorMessages, Options, Target};
+use prqlc::{
+    format_prql, json, prql_to_pl, DisplayOptions, Error, ErrorMessage, ErrorMessages, Options,
+    SourceTree, Target,
+};
 
 #[no_mangle]
 #[allow(non_snake_case)]
-pub extern "system" fn Java_org_prql_prql4j_PrqlCompiler_toSql(
+pub extern "system" fn Java_org_prql_prql4j_PrqlCompiler_compile(
     mut env: JNIEnv,
     _class: JClass,
     query: JString,
-    target: JString,
-    format: jboolean,
-    signature: jboolean,
+    options: JObject,
 ) -> jstring {
-    let prql_query: String = env
-        .get_string(&query)
-        .expect("Couldn't get java string!")
-        .into();
-    let target_str: String = env
-        .get_string(&target)
-        .expect("Couldn't get java string")
-        .into();
-    let prql_dialect: Target = Target::from_str(&target_str).unwrap_or(Target::Sql(None));
-    let opt = Options {
-        format: format != 0,
-        target: prql_dialect,
-        signature_comment: signature != 0,
-        // TODO: add support for `display`
-        ..Default::default()
-    };
-    let result = prqlc::compile(&prql_query, &opt);
+    let prql_query = java_string(&mut env, &query);
+    let result = java_options(&mut env, &options).and_then(|opt| prqlc::compile(&prql_query, &opt));
+    java_string_with_exception(result, &mut env)
+}
+
+#[no_mangle]
+#[allow(non_snake_case)]
+pub extern "system" fn Java_org_prql_prql4j_PrqlCompiler_compileTree(
+    mut env: JNIEnv,
+    _class: JClass,
+    paths: JObjectArray,
+    sources: JObjectArray,
+    main_path: JString,
+    options: JObject,
+) -> jstring {
+    let tree = java_source_tree(&mut env, &paths, &sources);
+    let main_path = java_main_path(&mut env, &main_path);
+    let result = java_options(&mut env, &options)
+        .and_then(|opt| prqlc::compile_tree(&tree, &main_path, &opt));
+    java_string_with_exception(result, &mut env)
+}
+
+#[no_mangle]
+#[allow(non_snake_case)]
+pub extern "system" fn Java_org_prql_prql4j_PrqlCompiler_compileFull(
+    mut env: JNIEnv,
+    _class: JClass,
+    query: JString,
+    options: JObject,
+) -> jstring {
+    let prql_query = java_string(&mut env, &query);
+    let result = java_options(&mut env, &options)
+        .and_then(|opt| prqlc::compile_full(&prql_query, &opt))
+        .and_then(|x| json::from_compile_result(&x));
+    java_string_with_exception(result, &mut env)
+}
+
+#[no_mangle]
+#[allow(non_snake_case)]
+pub extern "system" fn Java_org_prql_prql4j_PrqlCompiler_compileFullTree(
+    mut env: JNIEnv,
+    _class: JClass,
+    paths: JObjectArray,
+    sources: JObjectArray,
+    main_path: JString,
+    options: JObject,
+) -> jstring {
+    let tree = java_source_tree(&mut env, &paths, &sources);
+    let main_path = java_main_path(&mut env, &main_path);
+    let result = java_options(&mut env, &options)
+        .and_then(|opt| prqlc::compile_full_tree(&tree, &main_path, &opt))
+        .and_then(|x| json::from_compile_result(&x));
     java_string_with_exception(result, &mut env)
 }
 
@@ -42,39 +78,323 @@ pub extern "system" fn Java_org_prql_prql4j_PrqlCompiler_format(
     _class: JClass,
     query: JString,
 ) -> jstring {
-    let prql_query: String = env
-        .get_string(&query)
-        .expect("Couldn't get java string!")
-        .into();
-    let result = prql_to_pl(&prql_query).and_then(|x| pl_to_prql(&x));
+    let prql_query = java_string(&mut env, &query);
+    let result = format_prql(&prql_query, &Default::default());
     java_string_with_exception(result, &mut env)
 }
 
 #[no_mangle]
 #[allow(non_snake_case)]
-pub extern "system" fn Java_org_prql_prql4j_PrqlCompiler_toJson(
+pub extern "system" fn Java_org_prql_prql4j_PrqlCompiler_prqlToPl(
     mut env: JNIEnv,
     _class: JClass,
     query: JString,
 ) -> jstring {
-    let prql_query: String = env
-        .get_string(&query)
-        .expect("Couldn't get java string!")
-        .into();
+    let prql_query = java_string(&mut env, &query);
     let result = prql_to_pl(&prql_query).and_then(|x| json::from_pl(&x));
     java_string_with_exception(result, &mut env)
 }
 
+#[no_mangle]
+#[allow(non_snake_case)]
+pub extern "system" fn Java_org_prql_prql4j_PrqlCompiler_plToPrql(
+    mut env: JNIEnv,
+    _class: JClass,
+    pl_json: JString,
+) -> jstring {
+    let pl_json = java_string(&mut env, &pl_json);
+    let result = json::to_pl(&pl_json).and_then(|x| prqlc::pl_to_prql(&x));
+    java_string_with_exception(result, &mut env)
+}
+
+#[no_mangle]
+#[allow(non_snake_case)]
+pub extern "system" fn Java_org_prql_prql4j_PrqlCompiler_plToRq(
+    mut env: JNIEnv,
+    _class: JClass,
+    pl_json: JString,
+) -> jstring {
+    let pl_json = java_string(&mut env, &pl_json);
+    let result = json::to_pl(&pl_json)
+        .and_then(prqlc::pl_to_rq)
+        .and_then(|x| json::from_rq(&x));
+    java_string_with_exception(result, &mut env)
+}
+
+#[no_mangle]
+#[allow(non_snake_case)]
+pub extern "system" fn Java_org_prql_prql4j_PrqlCompiler_rqToSql(
+    mut env: JNIEnv,
+    _class: JClass,
+    rq_json: JString,
+    options: JObject,
+) -> jstring {
+    let rq_json = java_string(&mut env, &rq_json);
+    let result = java_options(&mut env, &options)
+        .and_then(|opt| json::to_rq(&rq_json).and_then(|x| prqlc::rq_to_sql(x, &opt)));
+    java_string_with_exception(result, &mut env)
+}
+
+#[no_mangle]
+#[allow(non_snake_case)]
+pub extern "system" fn Java_org_prql_prql4j_PrqlCompiler_lineage(
+    mut env: JNIEnv,
+    _class: JClass,
+    query: JString,
+) -> jstring {
+    let prql_query = java_string(&mut env, &query);
+    let result = prql_to_pl(&prql_query)
+        .and_then(prqlc::internal::pl_to_lineage)
+        .and_then(|x| prqlc::internal::json::from_lineage(&x));
+    java_string_with_exception(result, &mut env)
+}
+
+#[no_mangle]
+#[allow(non_snake_case)]
+pub extern "system" fn Java_org_prql_prql4j_PrqlCompiler_getTargets(
+    mut env: JNIEnv,
+    _class: JClass,
+) -> jobjectArray {
+    let targets = Target::names();
+    let array = env
+        .new_object_array(targets.len() as i32, "java/lang/String", JObject::null())
+        .expect("Couldn't create java array!");
+    for (i, target) in targets.into_iter().enumerate() {
+        let target = env
+            .new_string(target)
+            .expect("Couldn't create java string!");
+        env.set_object_array_element(&array, i as i32, target)
+            .expect("Couldn't set java array element!");
+    }
+    array.into_raw()
+}
+
+/// Options of the compiler, from the fields of a Java `CompileOptions`, or the
+/// defaults of `CompileOptions` when `options` is null.
+fn java_options(env: &mut JNIEnv, options: &JObject) -> Result<Options, ErrorMessages> {
+    if options.is_null() {
+        return convert_options(true, None, true, false, None);
+    }
+
+    let mut bool_field = |name| {
+        env.get_field(options, name, "Z")
+            .and_then(|x| x.z())
+            .expect("Couldn't get java field!")
+    };
+    let format = bool_field("format");
+    let signature_comment = bool_field("signatureComment");
+    let color = bool_field("color");
+
+    let mut string_field = |name| {
+        let value = env
+            .get_field(options, name, "Ljava/lang/String;")
+            .and_then(|x| x.l())
+            .expect("Couldn't get java field!");
+        let value = JString::from(value);
+        (!value.is_null()).then(|| java_string(env, &value))
+    };
+    let target = string_field("target");
+    let display = string_field("display");
+
+    convert_options(
+        format,
+        target.as_deref(),
+        signature_comment,
+        color,
+        display.as_deref(),
+    )
+}
+
+fn convert_options(
+    format: bool,
+    target: Option<&str>,
+    signature_comment: bool,
+    color: bool,
+    display: Option<&str>,
+) -> Result<Options, ErrorMessages> {
+    let target = Target::from_str(target.unwrap_or("sql.any"))?;
+    let display = DisplayOptions::from_str(display.unwrap_or("plain")).map_err(|e| {
+        ErrorMessages::from(Error::new_simple(format!("Invalid display option: {e}")))
+    })?;
+
+    Ok(Options {
+        format,
+        target,
+        signature_comment,
+        color,
+        display,
+        ..Default::default()
+    })
+}
+
+fn java_string(env: &mut JNIEnv, string: &JString) -> String {
+    env.get_string(string)
+        .expect("Couldn't get java string!")
+        .into()
+}
+
+fn java_source_tree(env: &mut JNIEnv, paths: &JObjectArray, sources: &JObjectArray) -> SourceTree {
+    let paths = java_string_array(env, paths);
+    let sources = java_string_array(env, sources);
+    SourceTree::new(paths.into_iter().map(Into::into).zip(sources), None)
+}
+
+fn java_main_path(env: &mut JNIEnv, main_path: &JString) -> Vec<String> {
+    if main_path.is_null() {
+        Vec::new()
+    } else {
+        prqlc::split_main_path(&java_string(env, main_path))
+    }
+}
+
+fn java_string_array(env: &mut JNIEnv, array: &JObjectArray) -> Vec<String> {
+    let len = env
+        .get_array_length(array)
+        .expect("Couldn't get java array length!");
+    (0..len)
+        .map(|i| {
+            let element = env
+                .get_object_array_element(array, i)
+                .expect("Couldn't get java array element!");
+            java_string(env, &JString::from(element))
+        })
+        .collect()
+}
+
 fn java_string_with_exception(result: Result<String, ErrorMessages>, env: &mut JNIEnv) -> jstring {
-    if let Ok(text) = result {
-        env.new_string(text)
+    match result {
+        Ok(text) => env
+            .new_string(text)
             .expect("Couldn't create java string!")
-            .into_raw()
-    } else {
-        let exception = env.find_class("java/lang/Exception").unwrap();
-        if let Err(e) = env.throw_new(exception, result.err().unwrap().to_string()) {
-            println!("Error throwing exception: {e:?}");
+            .into_raw(),
+        Err(errors) => {
+            if let Err(e) = throw_compile_exception(env, errors) {
+                println!("Error throwing exception: {e:?}");
+            }
+            std::ptr::null_mut() as jstring
         }
-        std::ptr::null_mut() as jstring
+    }
+}
+
+/// Throw a `PrqlCompileException`, with the rendered errors as its message and
+/// an `ErrorMessage` for each error.
+fn throw_compile_exception(env: &mut JNIEnv, errors: ErrorMessages) -> jni::errors::Result<()> {
+    let message = env.new_string(errors.to_string())?;
+    let array = env.new_object_array(
+        errors.inner.len() as i32,
+        "org/prql/prql4j/ErrorMessage",
+        JObject::null(),
+    )?;
+    for (i, error) in errors.inner.into_iter().enumerate() {
+        let error = java_error_message(env, error)?;
+        env.set_object_array_element(&array, i as i32, error)?;
+    }
+
+    let exception = env.new_object(
+        "org/prql/prql4j/PrqlCompileException",
+        "(Ljava/lang/String;[Lorg/prql/prql4j/ErrorMessage;)V",
+        &[JValue::Object(&message), JValue::Object(&array)],
+    )?;
+    env.throw(JThrowable::from(exception))
+}
+
+fn java_error_message<'a>(
+    env: &mut JNIEnv<'a>,
+    error: ErrorMessage,
+) -> jni::errors::Result<JObject<'a>> {
+    let kind = env.new_string(format!("{:?}", error.kind))?;
+    let code = java_nullable_string(env, error.code)?;
+    let reason = env.new_string(error.reason)?;
+    let hints = env.new_object_array(
+        error.hints.len() as i32,
+        "java/lang/String",
+        JObject::null(),
+    )?;
+    for (i, hint) in error.hints.into_iter().enumerate() {
+        let hint = env.new_string(hint)?;
+        env.set_object_array_element(&hints, i as i32, hint)?;
+    }
+    let span = match error.span {
+        Some(span) => env.new_object(
+            "org/prql/prql4j/Span",
+            "(JJ)V",
+            &[
+                JValue::Long(span.start as i64),
+                JValue::Long(span.end as i64),
+            ],
+        )?,
+        None => JObject::null(),
+    };
+    let display = java_nullable_string(env, error.display)?;
+    let location = match error.location {
+        Some(location) => {
+            let path =
+                java_nullable_string(env, location.path.map(|p| p.to_string_lossy().into_owned()))?;
+            env.new_object(
+                "org/prql/prql4j/SourceLocation",
+                "(JJJJLjava/lang/String;)V",
+                &[
+                    JValue::Long(location.start.0 as i64),
+                    JValue::Long(location.start.1 as i64),
+                    JValue::Long(location.end.0 as i64),
+                    JValue::Long(location.end.1 as i64),
+                    JValue::Object(&path),
+                ],
+            )?
+        }
+        None => JObject::null(),
+    };
+
+    env.new_object(
+        "org/prql/prql4j/ErrorMessage",
+        "(Ljava/lang/String;Ljava/lang/String;Ljava/lang/String;[Ljava/lang/String;Lorg/prql/prql4j/Span;Ljava/lang/String;Lorg/prql/prql4j/SourceLocation;)V",
+        &[
+            JValue::Object(&kind),
+            JValue::Object(&code),
+            JValue::Object(&reason),
+            JValue::Object(&hints),
+            JValue::Object(&span),
+            JValue::Object(&display),
+            JValue::Object(&location),
+        ],
+    )
+}
+
+fn java_nullable_string<'a>(
+    env: &mut JNIEnv<'a>,
+    string: Option<String>,
+) -> jni::errors::Result<JObject<'a>> {
+    match string {
+        Some(string) => Ok(env.new_string(string)?.into()),
+        None => Ok(JObject::null()),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use prqlc::sql::Dialect;
+
+    use super::*;
+
+    #[test]
+    fn options_from_java() {
+        let options = convert_options(false, Some("sql.mysql"), false, false, None).unwrap();
+        assert!(!options.format);
+        assert!(!options.signature_comment);
+        assert!(matches!(options.target, Target::Sql(Some(Dialect::MySql))));
+        assert!(matches!(options.display, DisplayOptions::Plain));
+
+        let options = convert_options(true, None, true, false, Some("ansi_color")).unwrap();
+        assert!(matches!(options.target, Target::Sql(None)));
+        assert!(matches!(options.display, DisplayOptions::AnsiColor));
+    }
+
+    #[test]
+    fn invalid_options_from_java() {
+        let err = convert_options(true, Some("sql.oracle"), true, false, None).unwrap_err();
+        assert!(err.to_string().contains("sql.oracle"), "{err}");
+
+        let err = convert_options(true, None, true, false, Some("html")).unwrap_err();
+        assert!(err.to_string().contains("Invalid display option"), "{err}");
     }
 }
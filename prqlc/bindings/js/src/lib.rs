@@ -1,8 +1,10 @@
 #![cfg(target_family = "wasm")]
 
+use std::collections::HashMap;
+use std::path::PathBuf;
 use std::{default::Default, str::FromStr};
 
-use prqlc::Target;
+use prqlc::{Target, WithErrorInfo};
 use wasm_bindgen::prelude::*;
 
 #[wasm_bindgen]
@@ -13,6 +15,37 @@ pub fn compile(prql_query: &str, options: Option<CompileOptions>) -> Option<Stri
     )
 }
 
+/// Like `compile`, but returns JSON of an object with the SQL, the warnings
+/// of the compilation and information about the query.
+#[wasm_bindgen]
+pub fn compile_full(prql_query: &str, options: Option<CompileOptions>) -> Option<String> {
+    return_or_throw(
+        prqlc::compile_full(prql_query, &options.map(|x| x.into()).unwrap_or_default())
+            .and_then(|x| prqlc::json::from_compile_result(&x)),
+    )
+}
+
+/// Compile a project of multiple PRQL files, given as an object that maps
+/// paths to sources. `main_path` is the dotted path of the relation to
+/// compile, such as `reports.monthly`; it defaults to the main pipeline of the
+/// root module.
+#[wasm_bindgen]
+pub fn compile_tree(
+    sources: JsValue,
+    main_path: Option<String>,
+    options: Option<CompileOptions>,
+) -> Option<String> {
+    let main_path = prqlc::split_main_path(&main_path.unwrap_or_default());
+
+    return_or_throw(source_tree(sources).and_then(|tree| {
+        prqlc::compile_tree(
+            &tree,
+            &main_path,
+            &options.map(|x| x.into()).unwrap_or_default(),
+        )
+    }))
+}
+
 #[wasm_bindgen]
 pub fn prql_to_pl(prql_query: &str) -> Option<String> {
     return_or_throw(
@@ -31,6 +64,14 @@ pub fn pl_to_prql(pl_json: &str) -> Option<String> {
     )
 }
 
+#[wasm_bindgen]
+pub fn format_prql(prql_query: &str, options: Option<FormatOptions>) -> Option<String> {
+    return_or_throw(prqlc::format_prql(
+        prql_query,
+        &options.map(|x| x.into()).unwrap_or_default(),
+    ))
+}
+
 #[wasm_bindgen]
 pub fn pl_to_rq(pl_json: &str) -> Option<String> {
     return_or_throw(
@@ -123,6 +164,65 @@ impl From<CompileOptions> for prqlc::Options {
     }
 }
 
+/// Options for formatting PRQL.
+#[wasm_bindgen]
+#[derive(Clone)]
+pub struct FormatOptions {
+    /// Maximum number of characters per line, 50 by default.
+    pub max_width: u16,
+
+    /// Number of spaces per indentation level, 2 by default.
+    pub indent_width: u16,
+
+    /// Write each transform of a nested pipeline on its own line.
+    pub break_pipelines: bool,
+
+    /// Emit a comma after the last item of multi-line tuples and arrays.
+    pub trailing_commas: bool,
+
+    /// Align the `=` of assignments in multi-line tuples.
+    pub align_assignments: bool,
+}
+
+impl Default for FormatOptions {
+    fn default() -> Self {
+        let o = prqlc::FormatOptions::default();
+        Self {
+            max_width: o.max_width,
+            indent_width: o.indent_width,
+            break_pipelines: o.break_pipelines,
+            trailing_commas: o.trailing_commas,
+            align_assignments: o.align_assignments,
+        }
+    }
+}
+
+#[wasm_bindgen]
+impl FormatOptions {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl From<FormatOptions> for prqlc::FormatOptions {
+    fn from(o: FormatOptions) -> Self {
+        prqlc::FormatOptions {
+            max_width: o.max_width,
+            indent_width: o.indent_width,
+            break_pipelines: o.break_pipelines,
+            trailing_commas: o.trailing_commas,
+            align_assignments: o.align_assignments,
+        }
+    }
+}
+
+fn source_tree(sources: JsValue) -> Result<prqlc::SourceTree, prqlc::ErrorMessages> {
+    let sources: HashMap<PathBuf, String> = serde_wasm_bindgen::from_value(sources)
+        .map_err(|e| prqlc::Error::new_simple(e.to_string()).with_code("E0023"))?;
+    Ok(prqlc::SourceTree::new(sources, None))
+}
+
 fn return_or_throw(result: Result<String, prqlc::ErrorMessages>) -> Option<String> {
     // When the `console_error_panic_hook` feature is enabled, we can call the
     // `set_panic_hook` function at least once during initialization, and then
@@ -72,6 +72,56 @@ describe("prqlc-js", () => {
     });
   });
 
+  describe("compile_full", () => {
+    it("should return the SQL with information about the query", () => {
+      const prql = "from employees | select {name, n = 1}";
+      const res = JSON.parse(prqlc.compile_full(prql));
+      assert.equal(res.sql, prqlc.compile(prql));
+      assert.deepStrictEqual(res.tables, ["employees"]);
+      assert.equal(res.dialect, "Generic");
+      assert.deepStrictEqual(res.warnings, []);
+    });
+  });
+
+  describe("compile_tree", () => {
+    const sources = {
+      "Project.prql": "from shared.top_albums | select {title}",
+      "shared.prql": "let top_albums = (from albums | take 10)",
+    };
+    const opts = new prqlc.CompileOptions();
+    opts.format = false;
+    opts.signature_comment = false;
+
+    it("should compile the main pipeline of the root module", () => {
+      assert.equal(
+        prqlc.compile_tree(sources, undefined, opts),
+        "WITH top_albums AS (SELECT * FROM albums LIMIT 10) SELECT title FROM top_albums",
+      );
+    });
+
+    it("should compile the relation at the main path", () => {
+      assert.equal(
+        prqlc.compile_tree(sources, "shared.top_albums", opts),
+        "SELECT * FROM albums LIMIT 10",
+      );
+    });
+
+    it("should report the file of an error", () => {
+      const broken = {
+        ...sources,
+        "shared.prql":
+          "let top_albums = (from albums | select {title, year} | filter yaer > 2000)",
+      };
+      try {
+        prqlc.compile_tree(broken);
+        assert.fail("should throw");
+      } catch (error) {
+        const errorMessages = JSON.parse(error.message).inner;
+        assert.equal(errorMessages[0].location.path, "shared.prql");
+      }
+    });
+  });
+
   describe("prql_to_pl", () => {
     it("should return valid json from valid prql", () => {
       JSON.parse(prqlc.prql_to_pl(employee_prql));
@@ -99,6 +149,22 @@ describe("prqlc-js", () => {
     });
   });
 
+  describe("format_prql", () => {
+    it("should retain comments", () => {
+      const res = prqlc.format_prql("from a   # all\nselect b");
+      assert.equal(res, "from a  # all\nselect b\n");
+    });
+
+    it("should accept FormatOptions", () => {
+      const opts = new prqlc.FormatOptions();
+      opts.max_width = 10;
+      opts.trailing_commas = false;
+
+      const res = prqlc.format_prql("derive {x = 1, y = 2}", opts);
+      assert.equal(res, "derive {\n  x = 1,\n  y = 2\n}\n");
+    });
+  });
+
   describe("get_targets", () => {
     it("return a list of targets", () => {
       const targets = new prqlc.get_targets();
@@ -26,7 +26,7 @@ final class Message
      */
     public ?string $hint = null;
     /**
-     * Character offset of error origin within a source file.
+     * Byte offsets of error origin within a source file.
      */
     public ?Span $span = null;
     /**
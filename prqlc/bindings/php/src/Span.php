@@ -6,7 +6,7 @@ namespace Prql\Compiler;
 
 /**
  * Identifier of a location in source.
- * Contains offsets in terms of chars.
+ * Contains offsets in terms of bytes of the UTF-8 source.
  */
 final class Span
 {
@@ -59,6 +59,26 @@ int main() {
     return 1;
   result_destroy(res);
 
+  // projects of multiple files
+  const char *paths[] = {"Project.prql", "shared.prql"};
+  const char *sources[] = {"from shared.top_albums | select {title}",
+                           "let top_albums = (from albums | take 3)"};
+  res = compile_tree(paths, sources, 2, NULL, &opts);
+  print_result(res);
+  if (res.messages_len != 0)
+    return 1;
+  result_destroy(res);
+
+  sources[1] = "let top_albums = (from albums | select {title, year} | "
+               "filter yaer > 2000)";
+  res = compile_tree(paths, sources, 2, NULL, NULL);
+  print_result(res);
+  if (res.messages_len == 0 || res.messages[0].location == NULL ||
+      res.messages[0].location->path == NULL)
+    return 1;
+  printf("Error in: %s\n\n", res.messages[0].location->path);
+  result_destroy(res);
+
   // intermediate results
   res = prql_to_pl(prql_query);
   print_result(res);
@@ -13,6 +13,18 @@
 #include <stdint.h>
 #include <stdlib.h>
 #define FFI_SCOPE "PRQL"
+#define PRQLC_VERSION "0.13.11"
+
+/**
+ * Version of the ABI of this library: the layout of its structs and the
+ * signatures of its functions.
+ *
+ * It is incremented on any change which breaks code compiled against an
+ * earlier version of the header, and is independent of the version of the
+ * compiler. Compare it with `prqlc_abi_version()` to check that the header
+ * matches the library which is linked.
+ */
+#define PRQLC_ABI_VERSION 1
 
 /**
  * Compile message kind. Currently only Error is implemented.
@@ -25,7 +37,7 @@ typedef enum MessageKind {
 
 /**
  * Identifier of a location in source.
- * Contains offsets in terms of chars.
+ * Contains offsets in terms of bytes of the UTF-8 source.
  */
 typedef struct Span {
   size_t start;
@@ -40,6 +52,11 @@ typedef struct SourceLocation {
   size_t start_col;
   size_t end_line;
   size_t end_col;
+  /**
+   * Path of the source file within the project, or null when compiling a
+   * single source
+   */
+  const char *path;
 } SourceLocation;
 
 /**
@@ -66,7 +83,7 @@ typedef struct Message {
    */
   const char *const *hint;
   /**
-   * Character offset of error origin within a source file
+   * Byte offsets of error origin within a source file
    */
   const struct Span *span;
   /**
@@ -131,6 +148,64 @@ typedef struct Options {
 struct CompileResult compile(const char *prql_query,
                              const struct Options *options);
 
+/**
+ * Compile a project of multiple PRQL files into a SQL string.
+ *
+ * The project is given as `sources_len` paths, with the source of each path at
+ * the same index of `sources`. `main_path` is the dotted path of the relation
+ * to compile, such as `reports.monthly`; when it is null or empty, the main
+ * pipeline of the root module is compiled. The locations of errors include the
+ * path of the file they are in.
+ *
+ * See `Options` struct for available compilation options.
+ *
+ * # Safety
+ *
+ * This function assumes zero-terminated input strings and that `paths` and
+ * `sources` each point to `sources_len` strings.
+ * Calling code is responsible for freeing memory allocated for `CompileResult`
+ * by calling `result_destroy`.
+ */
+struct CompileResult compile_tree(const char *const *paths,
+                                  const char *const *sources,
+                                  size_t sources_len,
+                                  const char *main_path,
+                                  const struct Options *options);
+
+/**
+ * Compile a PRQL string like `compile`, along with the warnings of the
+ * compilation and information about the query.
+ *
+ * The output is JSON of an object with the `sql`, the `warnings`, the
+ * `columns` of the result, the `tables` which the query reads from and the
+ * `dialect` of the SQL.
+ *
+ * # Safety
+ *
+ * This function assumes zero-terminated input strings.
+ * Calling code is responsible for freeing memory allocated for `CompileResult`
+ * by calling `result_destroy`.
+ */
+struct CompileResult compile_full(const char *prql_query,
+                                  const struct Options *options);
+
+/**
+ * Compile a project of multiple PRQL files like `compile_tree`, with the
+ * output of `compile_full`.
+ *
+ * # Safety
+ *
+ * This function assumes zero-terminated input strings and that `paths` and
+ * `sources` each point to `sources_len` strings.
+ * Calling code is responsible for freeing memory allocated for `CompileResult`
+ * by calling `result_destroy`.
+ */
+struct CompileResult compile_full_tree(const char *const *paths,
+                                       const char *const *sources,
+                                       size_t sources_len,
+                                       const char *main_path,
+                                       const struct Options *options);
+
 /**
  * Build PL AST from a PRQL string. PL in documented in the
  * [prqlc Rust crate](https://docs.rs/prqlc/latest/prqlc/ir/pl).
@@ -182,6 +257,43 @@ struct CompileResult pl_to_rq(const char *pl_json);
 struct CompileResult rq_to_sql(const char *rq_json,
                                const struct Options *options);
 
+/**
+ * Compute the column-level lineage of a PRQL string, serialized as JSON. See
+ * the docs of the `prqlc debug lineage` command for its format.
+ *
+ * # Safety
+ *
+ * This function assumes zero-terminated input strings.
+ * Calling code is responsible for freeing memory allocated for `CompileResult`
+ * by calling `result_destroy`.
+ */
+struct CompileResult prql_lineage(const char *prql_query);
+
+/**
+ * List the targets which can be passed in `Options`, such as `sql.postgres`.
+ *
+ * Returns an array of `*len` strings, followed by a null pointer. `len` may be
+ * null.
+ *
+ * # Safety
+ *
+ * `len` must be null or point to a `size_t`. The array is owned by the library
+ * and must not be freed.
+ */
+const char *const *prqlc_get_targets(size_t *len);
+
+/**
+ * Version of the compiler, such as `0.13.11`.
+ *
+ * The string is owned by the library and must not be freed.
+ */
+const char *prqlc_version(void);
+
+/**
+ * Version of the ABI of the library. See `PRQLC_ABI_VERSION`.
+ */
+uint32_t prqlc_abi_version(void);
+
 /**
  * Destroy a `CompileResult` once you are done with it.
  *
@@ -14,9 +14,19 @@
 #include <new>
 #include <ostream>
 #define FFI_SCOPE "PRQL"
+#define PRQLC_VERSION "0.13.11"
 
 namespace prqlc {
 
+/// Version of the ABI of this library: the layout of its structs and the
+/// signatures of its functions.
+///
+/// It is incremented on any change which breaks code compiled against an
+/// earlier version of the header, and is independent of the version of the
+/// compiler. Compare it with `prqlc_abi_version()` to check that the header
+/// matches the library which is linked.
+constexpr static const uint32_t PRQLC_ABI_VERSION = 1;
+
 /// Compile message kind. Currently only Error is implemented.
 enum class MessageKind {
   Error,
@@ -25,7 +35,7 @@ enum class MessageKind {
 };
 
 /// Identifier of a location in source.
-/// Contains offsets in terms of chars.
+/// Contains offsets in terms of bytes of the UTF-8 source.
 struct Span {
   size_t start;
   size_t end;
@@ -37,6 +47,9 @@ struct SourceLocation {
   size_t start_col;
   size_t end_line;
   size_t end_col;
+  /// Path of the source file within the project, or null when compiling a
+  /// single source
+  const char *path;
 };
 
 /// Compile result message.
@@ -52,7 +65,7 @@ struct Message {
   const char *reason;
   /// A list of suggestions of how to fix the error
   const char *const *hint;
-  /// Character offset of error origin within a source file
+  /// Byte offsets of error origin within a source file
   const Span *span;
   /// Annotated code, containing cause and hints.
   const char *const *display;
@@ -101,6 +114,57 @@ extern "C" {
 /// by calling `result_destroy`.
 CompileResult compile(const char *prql_query, const Options *options);
 
+/// Compile a project of multiple PRQL files into a SQL string.
+///
+/// The project is given as `sources_len` paths, with the source of each path at
+/// the same index of `sources`. `main_path` is the dotted path of the relation
+/// to compile, such as `reports.monthly`; when it is null or empty, the main
+/// pipeline of the root module is compiled. The locations of errors include the
+/// path of the file they are in.
+///
+/// See `Options` struct for available compilation options.
+///
+/// # Safety
+///
+/// This function assumes zero-terminated input strings and that `paths` and
+/// `sources` each point to `sources_len` strings.
+/// Calling code is responsible for freeing memory allocated for `CompileResult`
+/// by calling `result_destroy`.
+CompileResult compile_tree(const char *const *paths,
+                           const char *const *sources,
+                           size_t sources_len,
+                           const char *main_path,
+                           const Options *options);
+
+/// Compile a PRQL string like `compile`, along with the warnings of the
+/// compilation and information about the query.
+///
+/// The output is JSON of an object with the `sql`, the `warnings`, the
+/// `columns` of the result, the `tables` which the query reads from and the
+/// `dialect` of the SQL.
+///
+/// # Safety
+///
+/// This function assumes zero-terminated input strings.
+/// Calling code is responsible for freeing memory allocated for `CompileResult`
+/// by calling `result_destroy`.
+CompileResult compile_full(const char *prql_query, const Options *options);
+
+/// Compile a project of multiple PRQL files like `compile_tree`, with the
+/// output of `compile_full`.
+///
+/// # Safety
+///
+/// This function assumes zero-terminated input strings and that `paths` and
+/// `sources` each point to `sources_len` strings.
+/// Calling code is responsible for freeing memory allocated for `CompileResult`
+/// by calling `result_destroy`.
+CompileResult compile_full_tree(const char *const *paths,
+                                const char *const *sources,
+                                size_t sources_len,
+                                const char *main_path,
+                                const Options *options);
+
 /// Build PL AST from a PRQL string. PL in documented in the
 /// [prqlc Rust crate](https://docs.rs/prqlc/latest/prqlc/ir/pl).
 ///
@@ -145,6 +209,35 @@ CompileResult pl_to_rq(const char *pl_json);
 /// by calling `result_destroy`.
 CompileResult rq_to_sql(const char *rq_json, const Options *options);
 
+/// Compute the column-level lineage of a PRQL string, serialized as JSON. See
+/// the docs of the `prqlc debug lineage` command for its format.
+///
+/// # Safety
+///
+/// This function assumes zero-terminated input strings.
+/// Calling code is responsible for freeing memory allocated for `CompileResult`
+/// by calling `result_destroy`.
+CompileResult prql_lineage(const char *prql_query);
+
+/// List the targets which can be passed in `Options`, such as `sql.postgres`.
+///
+/// Returns an array of `*len` strings, followed by a null pointer. `len` may be
+/// null.
+///
+/// # Safety
+///
+/// `len` must be null or point to a `size_t`. The array is owned by the library
+/// and must not be freed.
+const char *const *prqlc_get_targets(size_t *len);
+
+/// Version of the compiler, such as `0.13.11`.
+///
+/// The string is owned by the library and must not be freed.
+const char *prqlc_version();
+
+/// Version of the ABI of the library. See `PRQLC_ABI_VERSION`.
+uint32_t prqlc_abi_version();
+
 /// Destroy a `CompileResult` once you are done with it.
 ///
 /// # Safety
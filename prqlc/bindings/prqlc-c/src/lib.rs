@@ -5,11 +5,21 @@ extern crate libc;
 use std::ffi::CStr;
 use std::ffi::CString;
 use std::str::FromStr;
+use std::sync::OnceLock;
 
 use libc::{c_char, size_t};
 use prqlc::ErrorMessages;
 use prqlc::Target;
 
+/// Version of the ABI of this library: the layout of its structs and the
+/// signatures of its functions.
+///
+/// It is incremented on any change which breaks code compiled against an
+/// earlier version of the header, and is independent of the version of the
+/// compiler. Compare it with `prqlc_abi_version()` to check that the header
+/// matches the library which is linked.
+pub const PRQLC_ABI_VERSION: u32 = 1;
+
 /// Compile a PRQL string into a SQL string.
 ///
 /// This is a wrapper for: `prql_to_pl`, `pl_to_rq` and `rq_to_sql` without converting to JSON
@@ -43,6 +53,98 @@ pub unsafe extern "C" fn compile(
     result_into_c_str(result)
 }
 
+/// Compile a project of multiple PRQL files into a SQL string.
+///
+/// The project is given as `sources_len` paths, with the source of each path at
+/// the same index of `sources`. `main_path` is the dotted path of the relation
+/// to compile, such as `reports.monthly`; when it is null or empty, the main
+/// pipeline of the root module is compiled. The locations of errors include the
+/// path of the file they are in.
+///
+/// See `Options` struct for available compilation options.
+///
+/// # Safety
+///
+/// This function assumes zero-terminated input strings and that `paths` and
+/// `sources` each point to `sources_len` strings.
+/// Calling code is responsible for freeing memory allocated for `CompileResult`
+/// by calling `result_destroy`.
+#[no_mangle]
+pub unsafe extern "C" fn compile_tree(
+    paths: *const *const c_char,
+    sources: *const *const c_char,
+    sources_len: size_t,
+    main_path: *const c_char,
+    options: *const Options,
+) -> CompileResult {
+    let tree = convert_source_tree(paths, sources, sources_len);
+    let main_path = convert_main_path(main_path);
+
+    let options = options.as_ref().map(convert_options).transpose();
+
+    let result =
+        options.and_then(|opts| prqlc::compile_tree(&tree, &main_path, &opts.unwrap_or_default()));
+
+    result_into_c_str(result)
+}
+
+/// Compile a PRQL string like `compile`, along with the warnings of the
+/// compilation and information about the query.
+///
+/// The output is JSON of an object with the `sql`, the `warnings`, the
+/// `columns` of the result, the `tables` which the query reads from and the
+/// `dialect` of the SQL.
+///
+/// # Safety
+///
+/// This function assumes zero-terminated input strings.
+/// Calling code is responsible for freeing memory allocated for `CompileResult`
+/// by calling `result_destroy`.
+#[no_mangle]
+pub unsafe extern "C" fn compile_full(
+    prql_query: *const c_char,
+    options: *const Options,
+) -> CompileResult {
+    let prql_query: String = c_str_to_string(prql_query);
+
+    let options = options.as_ref().map(convert_options).transpose();
+
+    let result = options
+        .and_then(|opts| prqlc::compile_full(&prql_query, &opts.unwrap_or_default()))
+        .and_then(|x| prqlc::json::from_compile_result(&x));
+
+    result_into_c_str(result)
+}
+
+/// Compile a project of multiple PRQL files like `compile_tree`, with the
+/// output of `compile_full`.
+///
+/// # Safety
+///
+/// This function assumes zero-terminated input strings and that `paths` and
+/// `sources` each point to `sources_len` strings.
+/// Calling code is responsible for freeing memory allocated for `CompileResult`
+/// by calling `result_destroy`.
+#[no_mangle]
+pub unsafe extern "C" fn compile_full_tree(
+    paths: *const *const c_char,
+    sources: *const *const c_char,
+    sources_len: size_t,
+    main_path: *const c_char,
+    options: *const Options,
+) -> CompileResult {
+    let tree = convert_source_tree(paths, sources, sources_len);
+    let main_path = convert_main_path(main_path);
+
+    let options = options.as_ref().map(convert_options).transpose();
+
+    let result = options
+        .and_then(|opts| prqlc::compile_full_tree(&tree, &main_path, &opts.unwrap_or_default()))
+        .and_then(|x| prqlc::json::from_compile_result(&x));
+
+    result_into_c_str(result)
+}
+
 /// Build PL AST from a PRQL string. PL in documented in the
 /// [prqlc Rust crate](https://docs.rs/prqlc/latest/prqlc/ir/pl).
 ///
@@ -61,7 +163,8 @@ pub unsafe extern "C" fn prql_to_pl(prql_query: *const c_char) -> CompileResult
 
     let result = Ok(prql_query.as_str())
         .and_then(prqlc::prql_to_pl)
-        .and_then(|x| prqlc::json::from_pl(&x));
+        .and_then(|x| prqlc::json::from_pl(&x))
+        .map_err(|e| e.composed(&prql_query.into()));
     result_into_c_str(result)
 }
 
@@ -118,6 +221,63 @@ pub unsafe extern "C" fn rq_to_sql(
     result_into_c_str(result)
 }
 
+/// Compute the column-level lineage of a PRQL string, serialized as JSON. See
+/// the docs of the `prqlc debug lineage` command for its format.
+///
+/// # Safety
+///
+/// This function assumes zero-terminated input strings.
+/// Calling code is responsible for freeing memory allocated for `CompileResult`
+/// by calling `result_destroy`.
+#[no_mangle]
+pub unsafe extern "C" fn prql_lineage(prql_query: *const c_char) -> CompileResult {
+    let prql_query: String = c_str_to_string(prql_query);
+
+    let result = Ok(prql_query.as_str())
+        .and_then(prqlc::prql_to_pl)
+        .and_then(prqlc::internal::pl_to_lineage)
+        .and_then(|x| prqlc::internal::json::from_lineage(&x))
+        .map_err(|e| e.composed(&prql_query.into()));
+    result_into_c_str(result)
+}
+
+/// List the targets which can be passed in `Options`, such as `sql.postgres`.
+///
+/// Returns an array of `*len` strings, followed by a null pointer. `len` may be
+/// null.
+///
+/// # Safety
+///
+/// `len` must be null or point to a `size_t`. The array is owned by the library
+/// and must not be freed.
+#[no_mangle]
+pub unsafe extern "C" fn prqlc_get_targets(len: *mut size_t) -> *const *const c_char {
+    static TARGETS: OnceLock<StaticStrings> = OnceLock::new();
+    let targets = TARGETS.get_or_init(|| StaticStrings::new(Target::names()));
+
+    if let Some(len) = len.as_mut() {
+        *len = targets.pointers.len() - 1;
+    }
+    targets.pointers.as_ptr()
+}
+
+/// Version of the compiler, such as `0.13.11`.
+///
+/// The string is owned by the library and must not be freed.
+#[no_mangle]
+pub extern "C" fn prqlc_version() -> *const c_char {
+    static VERSION: OnceLock<CString> = OnceLock::new();
+    VERSION
+        .get_or_init(|| CString::new(prqlc::compiler_version().to_string()).unwrap_or_default())
+        .as_ptr()
+}
+
+/// Version of the ABI of the library. See `PRQLC_ABI_VERSION`.
+#[no_mangle]
+pub extern "C" fn prqlc_abi_version() -> u32 {
+    PRQLC_ABI_VERSION
+}
+
 /// Compilation options
 #[repr(C)]
 pub struct Options {
@@ -170,7 +330,7 @@ pub struct Message {
     pub reason: *const libc::c_char,
     /// A list of suggestions of how to fix the error
     pub hint: *const *const libc::c_char,
-    /// Character offset of error origin within a source file
+    /// Byte offsets of error origin within a source file
     pub span: *const Span,
 
     /// Annotated code, containing cause and hints.
@@ -180,7 +340,7 @@ pub struct Message {
 }
 
 /// Identifier of a location in source.
-/// Contains offsets in terms of chars.
+/// Contains offsets in terms of bytes of the UTF-8 source.
 // Make sure to keep in sync with prqlc::Span
 #[repr(C)]
 pub struct Span {
@@ -189,7 +349,8 @@ pub struct Span {
 }
 
 /// Location within a source file.
-// Make sure to keep in sync with prqlc::SourceLocation
+// Make sure to keep in sync with prqlc::SourceLocation. New fields go last,
+// so that the offsets of the existing fields don't change.
 #[repr(C)]
 pub struct SourceLocation {
     pub start_line: size_t,
@@ -197,6 +358,10 @@ pub struct SourceLocation {
 
     pub end_line: size_t,
     pub end_col: size_t,
+
+    /// Path of the source file within the project, or null when compiling a
+    /// single source
+    pub path: *const libc::c_char,
 }
 
 /// Destroy a `CompileResult` once you are done with it.
@@ -232,14 +397,19 @@ pub unsafe extern "C" fn result_destroy(res: CompileResult) {
             drop(Box::from_raw(e.display as *mut *const libc::c_char));
         }
         if !e.location.is_null() {
-            drop(Box::from_raw(e.location as *mut SourceLocation));
+            let location = Box::from_raw(e.location as *mut SourceLocation);
+            if !location.path.is_null() {
+                drop(CString::from_raw(location.path as *mut libc::c_char));
+            }
         }
     }
-    drop(Vec::from_raw_parts(
-        res.messages as *mut i8,
-        res.messages_len,
-        res.messages_len,
-    ));
+    if !res.messages.is_null() {
+        drop(Vec::from_raw_parts(
+            res.messages as *mut Message,
+            res.messages_len,
+            res.messages_len,
+        ));
+    }
     drop(CString::from_raw(res.output as *mut libc::c_char));
 }
 
@@ -303,6 +473,39 @@ fn convert_source_location(x: prqlc::SourceLocation) -> SourceLocation {
         start_col: x.start.1,
         end_line: x.end.0,
         end_col: x.end.1,
+        path: x.path.map_or(::std::ptr::null(), |p| {
+            convert_string(p.to_string_lossy().into_owned())
+        }),
+    }
+}
+
+/// Strings which live for the lifetime of the library, along with a
+/// null-terminated array of pointers to them.
+struct StaticStrings {
+    // Owns the strings which `pointers` point to.
+    _strings: Vec<CString>,
+    pointers: Vec<*const c_char>,
+}
+
+// The strings are never mutated nor freed.
+unsafe impl Send for StaticStrings {}
+unsafe impl Sync for StaticStrings {}
+
+impl StaticStrings {
+    fn new(strings: Vec<String>) -> Self {
+        let strings: Vec<_> = strings
+            .into_iter()
+            .map(|x| CString::new(x).unwrap_or_default())
+            .collect();
+        let pointers = strings
+            .iter()
+            .map(|x| x.as_ptr())
+            .chain(std::iter::once(std::ptr::null()))
+            .collect();
+        StaticStrings {
+            _strings: strings,
+            pointers,
+        }
     }
 }
 
@@ -311,6 +514,30 @@ unsafe fn c_str_to_string(c_str: *const c_char) -> String {
     CStr::from_ptr(c_str).to_string_lossy().into_owned()
 }
 
+unsafe fn convert_source_tree(
+    paths: *const *const c_char,
+    sources: *const *const c_char,
+    sources_len: size_t,
+) -> prqlc::SourceTree {
+    prqlc::SourceTree::new(
+        (0..sources_len).map(|i| {
+            (
+                c_str_to_string(*paths.add(i)).into(),
+                c_str_to_string(*sources.add(i)),
+            )
+        }),
+        None,
+    )
+}
+
+unsafe fn convert_main_path(main_path: *const c_char) -> Vec<String> {
+    if main_path.is_null() {
+        Vec::new()
+    } else {
+        prqlc::split_main_path(&c_str_to_string(main_path))
+    }
+}
+
 fn convert_options(o: &Options) -> Result<prqlc::Options, prqlc::ErrorMessages> {
     let target = if !o.target.is_null() {
         Some(unsafe { c_str_to_string(o.target) })
@@ -329,3 +556,89 @@ fn convert_options(o: &Options) -> Result<prqlc::Options, prqlc::ErrorMessages>
         .with_target(target)
         .with_signature_comment(o.signature_comment))
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn targets() {
+        let mut len = 0;
+        let targets = unsafe { prqlc_get_targets(&mut len) };
+        let targets: Vec<_> = (0..=len)
+            .map(|i| unsafe { *targets.add(i) })
+            .map(|x| (!x.is_null()).then(|| unsafe { c_str_to_string(x) }))
+            .collect();
+
+        assert_eq!(targets.len(), Target::names().len() + 1);
+        assert_eq!(targets[0].as_deref(), Some("sql.any"));
+        assert_eq!(targets[len], None);
+    }
+
+    #[test]
+    fn compile_full() {
+        let query = CString::new("from a | aggregate {n = count_distinct b}").unwrap();
+        let res = unsafe { super::compile_full(query.as_ptr(), std::ptr::null()) };
+        assert_eq!(res.messages_len, 0);
+        let output: serde_json::Value =
+            serde_json::from_str(&unsafe { c_str_to_string(res.output) }).unwrap();
+        assert_eq!(output["tables"], serde_json::json!(["a"]));
+        assert_eq!(output["warnings"].as_array().unwrap().len(), 1);
+        unsafe { result_destroy(res) };
+
+        let strings = [
+            "Project.prql",
+            "shared.prql",
+            "from shared.x",
+            "let x = (from a)",
+        ]
+        .map(|x| CString::new(x).unwrap());
+        let pointers: Vec<_> = strings.iter().map(|x| x.as_ptr()).collect();
+        let main_path = CString::new("shared.x").unwrap();
+        let res = unsafe {
+            compile_full_tree(
+                pointers[..2].as_ptr(),
+                pointers[2..].as_ptr(),
+                2,
+                main_path.as_ptr(),
+                std::ptr::null(),
+            )
+        };
+        assert_eq!(res.messages_len, 0);
+        let output: serde_json::Value =
+            serde_json::from_str(&unsafe { c_str_to_string(res.output) }).unwrap();
+        assert_eq!(output["tables"], serde_json::json!(["a"]));
+        unsafe { result_destroy(res) };
+    }
+
+    #[test]
+    fn lineage() {
+        let query = CString::new("from a | select {b}").unwrap();
+        let res = unsafe { prql_lineage(query.as_ptr()) };
+        assert_eq!(res.messages_len, 0);
+        let output = unsafe { c_str_to_string(res.output) };
+        assert!(output.contains(r#""frames""#), "{output}");
+        unsafe { result_destroy(res) };
+
+        let query = CString::new("from a | select {b} | filter c").unwrap();
+        let res = unsafe { prql_lineage(query.as_ptr()) };
+        assert_eq!(res.messages_len, 1);
+        let message = unsafe { &*res.messages };
+        assert!(!message.span.is_null() && !message.location.is_null());
+        unsafe { result_destroy(res) };
+    }
+
+    #[test]
+    fn versions() {
+        let version = unsafe { c_str_to_string(prqlc_version()) };
+        assert_eq!(version, prqlc::compiler_version().to_string());
+        assert_eq!(prqlc_abi_version(), PRQLC_ABI_VERSION);
+
+        // `PRQLC_VERSION` of the headers is updated on release; check it's
+        // the version of this crate.
+        let define = format!(r#"#define PRQLC_VERSION "{}""#, env!("CARGO_PKG_VERSION"));
+        for header in [include_str!("../prqlc.h"), include_str!("../prqlc.hpp")] {
+            assert!(header.contains(&define), "missing `{define}`");
+        }
+    }
+}
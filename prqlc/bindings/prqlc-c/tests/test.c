@@ -0,0 +1,171 @@
+// Tests of the C API. Run with `make` in this directory.
+
+#include <stdio.h>
+#include <string.h>
+
+#include <prqlc.h>
+
+static int failures = 0;
+
+#define CHECK(cond)                                                            \
+  do {                                                                         \
+    if (!(cond)) {                                                             \
+      fprintf(stderr, "%s:%d: check failed: %s\n", __FILE__, __LINE__, #cond); \
+      failures++;                                                              \
+    }                                                                          \
+  } while (0)
+
+static Options no_signature(void) {
+  Options opts;
+  opts.format = false;
+  opts.signature_comment = false;
+  opts.target = "sql.any";
+  return opts;
+}
+
+static void test_versions(void) {
+  CHECK(prqlc_abi_version() == PRQLC_ABI_VERSION);
+  CHECK(strncmp(prqlc_version(), PRQLC_VERSION, strlen(PRQLC_VERSION)) == 0);
+}
+
+static void test_compile(void) {
+  Options opts = no_signature();
+  opts.target = "sql.mysql";
+  CompileResult res = compile("from albums | select {title} | take 3", &opts);
+  CHECK(res.messages_len == 0);
+  CHECK(strcmp(res.output, "SELECT title FROM albums LIMIT 3") == 0);
+  result_destroy(res);
+}
+
+static void test_messages(void) {
+  CompileResult res =
+      compile("from albums | select {title, year} | filter yaer > 2000", NULL);
+  CHECK(res.messages_len == 1);
+  CHECK(*res.output == '\0');
+
+  const Message *m = &res.messages[0];
+  CHECK(m->kind == Error);
+  CHECK(m->code != NULL && strcmp(*m->code, "E0005") == 0);
+  CHECK(strcmp(m->reason, "Unknown name `yaer`") == 0);
+  CHECK(m->hint != NULL && strstr(*m->hint, "did you mean `year`?") != NULL);
+  CHECK(m->span != NULL && m->span->start == 44 && m->span->end == 48);
+  CHECK(m->display != NULL);
+  CHECK(m->location != NULL && m->location->start_line == 0 &&
+        m->location->start_col == 44 && m->location->path == NULL);
+  result_destroy(res);
+
+  Options opts = no_signature();
+  opts.target = "sql.oracle";
+  res = compile("from albums", &opts);
+  CHECK(res.messages_len == 1);
+  CHECK(res.messages[0].code != NULL &&
+        strcmp(*res.messages[0].code, "E0011") == 0);
+  result_destroy(res);
+}
+
+static void test_compile_tree(void) {
+  Options opts = no_signature();
+  const char *paths[] = {"Project.prql", "shared.prql"};
+  const char *sources[] = {"from shared.top_albums | select {title}",
+                           "let top_albums = (from albums | take 3)"};
+
+  CompileResult res = compile_tree(paths, sources, 2, NULL, &opts);
+  CHECK(res.messages_len == 0);
+  CHECK(strcmp(res.output, "WITH top_albums AS (SELECT * FROM albums LIMIT "
+                           "3) SELECT title FROM top_albums") == 0);
+  result_destroy(res);
+
+  res = compile_tree(paths, sources, 2, "shared.top_albums", &opts);
+  CHECK(res.messages_len == 0);
+  CHECK(strcmp(res.output, "SELECT * FROM albums LIMIT 3") == 0);
+  result_destroy(res);
+
+  sources[1] = "let top_albums = (from albums | select {title, year} | "
+               "filter yaer > 2000)";
+  res = compile_tree(paths, sources, 2, NULL, &opts);
+  CHECK(res.messages_len == 1);
+  CHECK(res.messages[0].location != NULL &&
+        res.messages[0].location->path != NULL &&
+        strcmp(res.messages[0].location->path, "shared.prql") == 0);
+  result_destroy(res);
+}
+
+static void test_compile_full(void) {
+  Options opts = no_signature();
+  CompileResult res =
+      compile_full("from albums | aggregate {n = count_distinct title}", &opts);
+  CHECK(res.messages_len == 0);
+  CHECK(strstr(res.output, "\"tables\":[\"albums\"]") != NULL);
+  CHECK(strstr(res.output, "`count_distinct` is deprecated") != NULL);
+  result_destroy(res);
+
+  const char *paths[] = {"Project.prql", "shared.prql"};
+  const char *sources[] = {"from shared.top_albums",
+                           "let top_albums = (from albums | take 3)"};
+  res = compile_full_tree(paths, sources, 2, "shared.top_albums", &opts);
+  CHECK(res.messages_len == 0);
+  CHECK(strstr(res.output, "\"sql\":\"SELECT * FROM albums LIMIT 3\"") !=
+        NULL);
+  result_destroy(res);
+}
+
+static void test_intermediate(void) {
+  Options opts = no_signature();
+  CompileResult pl = prql_to_pl("from albums | select {title}");
+  CHECK(pl.messages_len == 0);
+  CompileResult rq = pl_to_rq(pl.output);
+  CHECK(rq.messages_len == 0);
+  CompileResult sql = rq_to_sql(rq.output, &opts);
+  CHECK(sql.messages_len == 0);
+  CHECK(strcmp(sql.output, "SELECT title FROM albums") == 0);
+  result_destroy(pl);
+  result_destroy(rq);
+  result_destroy(sql);
+
+  pl = prql_to_pl("from albums | select {title");
+  CHECK(pl.messages_len == 1 && pl.messages[0].location != NULL);
+  result_destroy(pl);
+}
+
+static void test_lineage(void) {
+  CompileResult res = prql_lineage("from albums | select {title}");
+  CHECK(res.messages_len == 0);
+  CHECK(strstr(res.output, "\"frames\"") != NULL);
+  result_destroy(res);
+
+  res = prql_lineage("from albums | select {title} | filter year");
+  CHECK(res.messages_len == 1 && res.messages[0].span != NULL);
+  result_destroy(res);
+}
+
+static void test_targets(void) {
+  size_t len = 0;
+  const char *const *targets = prqlc_get_targets(&len);
+  CHECK(len > 1);
+  CHECK(strcmp(targets[0], "sql.any") == 0);
+  CHECK(targets[len] == NULL);
+
+  int found = 0;
+  for (const char *const *t = prqlc_get_targets(NULL); *t != NULL; t++) {
+    found |= strcmp(*t, "sql.postgres") == 0;
+  }
+  CHECK(found);
+}
+
+int main(void) {
+  test_versions();
+  test_compile();
+  test_messages();
+  test_compile_tree();
+  test_compile_full();
+  test_intermediate();
+  test_lineage();
+  test_targets();
+
+  if (failures != 0) {
+    fprintf(stderr, "%d checks failed\n", failures);
+    return 1;
+  }
+  printf("All checks passed\n");
+  return 0;
+}
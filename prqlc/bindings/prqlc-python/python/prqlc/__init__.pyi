@@ -1,4 +1,4 @@
-from typing import List, Optional
+from typing import Any, Dict, List, Mapping, Optional, Sequence, Tuple, Union
 
 class CompileOptions:
     def __init__(
@@ -9,11 +9,72 @@ class CompileOptions:
         signature_comment: bool = True,
     ) -> None: ...
 
+class FormatOptions:
+    def __init__(
+        self,
+        *,
+        max_width: int = 50,
+        indent_width: int = 2,
+        break_pipelines: bool = False,
+        trailing_commas: bool = True,
+        align_assignments: bool = False,
+    ) -> None: ...
+
+class OutputColumn:
+    name: Optional[str]
+    type: Optional[str]
+    wildcard: bool
+
+class CompileResult:
+    sql: str
+    warnings: List[str]
+    columns: List[OutputColumn]
+    tables: List[str]
+    dialect: str
+
+class Fix:
+    span: Tuple[int, int]
+    replacement: str
+
+class SourceLocation:
+    start: Tuple[int, int]
+    end: Tuple[int, int]
+    path: Optional[str]
+
+class ErrorMessage:
+    kind: str
+    code: Optional[str]
+    reason: str
+    hints: List[str]
+    fixes: List[Fix]
+    span: Optional[Tuple[int, int]]
+    display: Optional[str]
+    location: Optional[SourceLocation]
+
+class PrqlCompileError(ValueError):
+    errors: List[ErrorMessage]
+
 def compile(prql_query: str, options: Optional[CompileOptions] = None) -> str: ...
+def compile_full(
+    prql_query: str, options: Optional[CompileOptions] = None
+) -> CompileResult: ...
+def compile_tree(
+    sources: Dict[str, str],
+    main_path: Optional[str] = None,
+    options: Optional[CompileOptions] = None,
+) -> str: ...
+def format_prql(prql_query: str, options: Optional[FormatOptions] = None) -> str: ...
 def prql_to_pl(prql_query: str) -> str: ...
 def pl_to_rq(pl_json: str) -> str: ...
 def pl_to_prql(pl_json: str) -> str: ...
 def rq_to_sql(rq_json: str) -> str: ...
 def get_targets() -> List[str]: ...
+def execute(
+    prql_query: str,
+    connection: Any,
+    params: Optional[Union[Mapping[str, Any], Sequence[Any]]] = None,
+    options: Optional[CompileOptions] = None,
+) -> Any: ...
+def detect_target(connection: Any) -> str: ...
 
 __version__: str
@@ -1,6 +1,7 @@
 import json
 
 import prqlc
+import pytest
 
 
 def test_all() -> None:
@@ -84,8 +85,125 @@ def test_compile_options() -> None:
     )
 
 
+def test_compile_full() -> None:
+    result = prqlc.compile_full(
+        "from employees | select {name, n = count_distinct title}",
+        prqlc.CompileOptions(target="sql.postgres"),
+    )
+
+    assert result.sql.startswith("SELECT")
+    assert result.tables == ["employees"]
+    assert result.dialect == "postgres"
+    assert [c.name for c in result.columns] == ["name", "n"]
+    assert "`count_distinct` is deprecated" in result.warnings[0]
+
+
+def test_compile_tree() -> None:
+    sources = {
+        "Project.prql": "from shared.top_albums | select {title}",
+        "shared.prql": "let top_albums = (from albums | take 10)",
+    }
+    options = prqlc.CompileOptions(format=False, signature_comment=False)
+
+    assert prqlc.compile_tree(sources, options=options) == (
+        "WITH top_albums AS (SELECT * FROM albums LIMIT 10) SELECT title FROM top_albums"
+    )
+    assert (
+        prqlc.compile_tree(sources, "shared.top_albums", options)
+        == "SELECT * FROM albums LIMIT 10"
+    )
+
+    sources["shared.prql"] = (
+        "let top_albums = (from albums | select {title, year} | filter yaer > 2000)"
+    )
+    with pytest.raises(prqlc.PrqlCompileError, match="shared.prql:1:63") as exc_info:
+        prqlc.compile_tree(sources)
+    assert exc_info.value.errors[0].location.path == "shared.prql"
+
+
+def test_compile_error() -> None:
+    with pytest.raises(prqlc.PrqlCompileError) as exc_info:
+        prqlc.compile("from employees | select {name, age} | filter agee > 30")
+
+    [error] = exc_info.value.errors
+    assert error.kind == "Error"
+    assert error.code == "E0005"
+    assert error.reason == "Unknown name `agee`"
+    assert error.hints[0] == "did you mean `age`?"
+    assert error.span == (45, 49)
+    assert error.fixes[0].replacement == "age"
+    assert error.location.start == (0, 45)
+    assert error.location.end == (0, 49)
+    assert error.location.path is None
+
+    # It's a `ValueError`, which the bindings raised before
+    assert isinstance(exc_info.value, ValueError)
+
+
+def test_execute() -> None:
+    import sqlite3
+
+    pytest.importorskip("pyarrow")
+
+    connection = sqlite3.connect(":memory:")
+    connection.executescript(
+        "CREATE TABLE tracks (name TEXT, plays INTEGER);"
+        "INSERT INTO tracks VALUES ('a', 1), ('b', 5), ('c', 10);"
+    )
+    assert prqlc.detect_target(connection) == "sql.sqlite"
+
+    table = prqlc.execute(
+        "from tracks | filter plays > $min_plays | select {name}",
+        connection,
+        {"min_plays": 2},
+    )
+    assert table.column_names == ["name"]
+    assert table.column("name").to_pylist() == ["b", "c"]
+
+    table = prqlc.execute("from tracks | filter plays < $1 | select {name}", connection, [5])
+    assert table.column("name").to_pylist() == ["a"]
+
+    with pytest.raises(ValueError, match="missing a value for `\\$min_plays`"):
+        prqlc.execute("from tracks | filter plays > $min_plays", connection)
+
+
+def test_execute_duckdb() -> None:
+    pytest.importorskip("pyarrow")
+    duckdb = pytest.importorskip("duckdb")
+
+    connection = duckdb.connect()
+    connection.execute(
+        "CREATE TABLE tracks AS "
+        "SELECT * FROM (VALUES ('a', 1), ('b', 5), ('c', 10)) t(name, plays)"
+    )
+    assert prqlc.detect_target(connection) == "sql.duckdb"
+
+    table = prqlc.execute(
+        "from tracks | filter plays > $min_plays | select {name}",
+        connection,
+        {"min_plays": 2},
+    )
+    assert table.column("name").to_pylist() == ["b", "c"]
+
+
 def test_debug_functions() -> None:
     prql_query = "from invoices | select { id, customer_id }"
 
     lineage = json.loads(prqlc.debug.prql_lineage(prql_query))
     assert lineage.keys() == {"frames", "nodes", "ast"}
+
+
+def test_format_options() -> None:
+    """
+    Test formatting with FormatOptions
+    """
+    prql_query = "from a  # comment\nderive {x = 1, yy = 2}"
+
+    assert prqlc.format_prql(prql_query) == (
+        "from a  # comment\nderive {x = 1, yy = 2}\n"
+    )
+
+    options = prqlc.FormatOptions(max_width=10, align_assignments=True)
+    assert prqlc.format_prql(prql_query, options) == (
+        "from a  # comment\nderive {\n  x  = 1,\n  yy = 2,\n}\n"
+    )
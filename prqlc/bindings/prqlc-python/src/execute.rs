@@ -0,0 +1,473 @@
+//! Execution of PRQL queries on database connections, returning the results
+//! as Arrow tables.
+//!
+//! The compiler targets the dialect of the connection, and the parameters of
+//! the query (`$name` or `$1`) are bound with the placeholders of its driver.
+//! We drive the connection and `pyarrow` through their Python APIs, so none of
+//! them are dependencies of the package.
+
+use prqlc_lib::sql::Dialect;
+use prqlc_lib::Target;
+use pyo3::exceptions::{PyImportError, PyTypeError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyList, PyTuple};
+
+use crate::{compile_error, convert_options, CompileOptions};
+
+/// Compile a PRQL query for the dialect of a connection, execute it with the
+/// connection and return the results as a `pyarrow.Table`.
+///
+/// `connection` can be a DuckDB connection, an SQLAlchemy engine or
+/// connection, a Polars `SQLContext` or a DB-API connection. `params` are the
+/// values of the query's parameters, as a mapping of names for `$name`, or a
+/// sequence for `$1`, `$2` and so on. The target of `options` overrides the
+/// dialect of the connection, unless it's `sql.any`.
+#[pyfunction]
+#[pyo3(signature = (prql_query, connection, params=None, options=None))]
+pub fn execute<'py>(
+    prql_query: &str,
+    connection: &Bound<'py, PyAny>,
+    params: Option<&Bound<'py, PyAny>>,
+    options: Option<CompileOptions>,
+) -> PyResult<Bound<'py, PyAny>> {
+    let py = connection.py();
+    let (kind, dialect) = detect(connection)?;
+
+    let options = options.unwrap_or_else(|| {
+        CompileOptions::new(
+            false,
+            false,
+            "sql.any".to_string(),
+            false,
+            "plain".to_string(),
+        )
+    });
+    let mut options = convert_options(options)
+        .map_err(|_| PyErr::new::<PyValueError, _>("Invalid options".to_string()))?;
+    if matches!(options.target, Target::Sql(None)) {
+        options.target = Target::Sql(Some(dialect));
+    }
+    options.signature_comment = false;
+
+    let sql = prqlc_lib::compile(prql_query, &options)
+        .map_err(|err| compile_error(err.to_string(), err))?;
+
+    let style = match kind {
+        ConnectionKind::SqlAlchemy => ParamStyle::Named,
+        ConnectionKind::DbApi => paramstyle_of(connection)?,
+        ConnectionKind::DuckDb | ConnectionKind::Polars => ParamStyle::Qmark,
+    };
+    let bound = bind_params(&sql, style);
+    let args = if bound.params.is_empty() {
+        None
+    } else if kind == ConnectionKind::Polars {
+        return Err(PyErr::new::<PyValueError, _>(
+            "Polars doesn't support parameters of queries",
+        ));
+    } else {
+        Some(param_values(py, &bound.params, style, params)?)
+    };
+
+    match kind {
+        ConnectionKind::DuckDb => connection
+            .call_method1("execute", (bound.sql, args))?
+            .call_method0("fetch_arrow_table"),
+        ConnectionKind::Polars => {
+            let kwargs = PyDict::new(py);
+            kwargs.set_item("eager", true)?;
+            connection
+                .call_method("execute", (bound.sql,), Some(&kwargs))?
+                .call_method0("to_arrow")
+        }
+        ConnectionKind::SqlAlchemy => {
+            let text = py
+                .import("sqlalchemy")?
+                .call_method1("text", (bound.sql,))?;
+            // An engine, which we connect for the query, or a connection
+            let owned = !connection.hasattr("exec_driver_sql")?;
+            let conn = if owned {
+                connection.call_method0("connect")?
+            } else {
+                connection.clone()
+            };
+            let result = conn
+                .call_method1("execute", (text, args))
+                .and_then(|result| {
+                    let columns = result.call_method0("keys")?.extract()?;
+                    let rows = result.call_method0("fetchall")?;
+                    to_arrow(py, columns, &rows)
+                });
+            if owned {
+                conn.call_method0("close")?;
+            }
+            result
+        }
+        ConnectionKind::DbApi => {
+            let cursor = connection.call_method0("cursor")?;
+            let result = match args {
+                Some(args) => cursor.call_method1("execute", (bound.sql, args)),
+                None => cursor.call_method1("execute", (bound.sql,)),
+            }
+            .and_then(|_| {
+                // Drivers such as ADBC can return Arrow themselves
+                if cursor.hasattr("fetch_arrow_table")? {
+                    return cursor.call_method0("fetch_arrow_table");
+                }
+                let (columns, rows) = fetch_all(&cursor)?;
+                to_arrow(py, columns, &rows)
+            });
+            cursor.call_method0("close")?;
+            result
+        }
+    }
+}
+
+/// The target which `execute` compiles to for a connection, such as
+/// `sql.duckdb`.
+#[pyfunction]
+pub fn detect_target(connection: &Bound<'_, PyAny>) -> PyResult<String> {
+    let (_, dialect) = detect(connection)?;
+    Ok(format!("sql.{dialect}"))
+}
+
+/// How a connection executes SQL.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ConnectionKind {
+    DuckDb,
+    SqlAlchemy,
+    Polars,
+    DbApi,
+}
+
+/// Determine the kind and the dialect of a connection from the module of its
+/// type.
+fn detect(connection: &Bound<'_, PyAny>) -> PyResult<(ConnectionKind, Dialect)> {
+    let ty = connection.get_type();
+    let module: String = ty.getattr("__module__")?.extract()?;
+    let root = module.split('.').next().unwrap_or_default();
+
+    Ok(match root.trim_start_matches('_') {
+        "duckdb" => (ConnectionKind::DuckDb, Dialect::DuckDb),
+        "sqlalchemy" => {
+            let name: String = connection.getattr("dialect")?.getattr("name")?.extract()?;
+            (ConnectionKind::SqlAlchemy, dialect_of_sqlalchemy(&name))
+        }
+        "polars" => (ConnectionKind::Polars, Dialect::Postgres),
+        _ if connection.hasattr("cursor")? => (ConnectionKind::DbApi, dialect_of_module(&module)),
+        _ => {
+            return Err(PyErr::new::<PyTypeError, _>(format!(
+                "expected a DuckDB connection, an SQLAlchemy engine or connection, a Polars \
+                `SQLContext` or a DB-API connection, but found `{module}.{}`",
+                ty.name()?
+            )))
+        }
+    })
+}
+
+/// The dialect of a DB-API connection, from the module of its driver.
+fn dialect_of_module(module: &str) -> Dialect {
+    if module.starts_with("google.cloud.bigquery") {
+        return Dialect::BigQuery;
+    }
+    match module.split('.').next().unwrap_or_default() {
+        "sqlite3" | "pysqlite2" | "adbc_driver_sqlite" => Dialect::SQLite,
+        "psycopg" | "psycopg2" | "pg8000" | "adbc_driver_postgresql" => Dialect::Postgres,
+        "pymysql" | "MySQLdb" | "mysql" | "mariadb" => Dialect::MySql,
+        "pymssql" => Dialect::MsSql,
+        "snowflake" | "adbc_driver_snowflake" => Dialect::Snowflake,
+        "clickhouse_driver" | "clickhouse_connect" => Dialect::ClickHouse,
+        "redshift_connector" => Dialect::Redshift,
+        _ => Dialect::Generic,
+    }
+}
+
+/// The dialect of an SQLAlchemy engine, from the name of its dialect.
+fn dialect_of_sqlalchemy(name: &str) -> Dialect {
+    match name {
+        "postgresql" => Dialect::Postgres,
+        "mysql" | "mariadb" => Dialect::MySql,
+        "sqlite" => Dialect::SQLite,
+        "mssql" => Dialect::MsSql,
+        "duckdb" => Dialect::DuckDb,
+        "snowflake" => Dialect::Snowflake,
+        "bigquery" => Dialect::BigQuery,
+        "clickhouse" | "clickhousedb" => Dialect::ClickHouse,
+        "redshift" => Dialect::Redshift,
+        _ => Dialect::Generic,
+    }
+}
+
+/// Placeholders of parameters, as the `paramstyle` of DB-API drivers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ParamStyle {
+    /// `?`
+    Qmark,
+    /// `:1`
+    Numeric,
+    /// `:name`
+    Named,
+    /// `%s`
+    Format,
+    /// `%(name)s`
+    Pyformat,
+}
+
+/// The `paramstyle` of the driver of a DB-API connection.
+fn paramstyle_of(connection: &Bound<'_, PyAny>) -> PyResult<ParamStyle> {
+    let module: String = connection.get_type().getattr("__module__")?.extract()?;
+    let root = module.split('.').next().unwrap_or_default();
+    let paramstyle: Option<String> = connection
+        .py()
+        .import(root)
+        .and_then(|m| m.getattr("paramstyle"))
+        .and_then(|p| p.extract())
+        .ok();
+
+    Ok(match paramstyle.as_deref() {
+        Some("numeric") => ParamStyle::Numeric,
+        Some("named") => ParamStyle::Named,
+        Some("format") => ParamStyle::Format,
+        Some("pyformat") => ParamStyle::Pyformat,
+        _ => ParamStyle::Qmark,
+    })
+}
+
+/// SQL with the parameters replaced by placeholders.
+#[derive(Debug, PartialEq, Eq)]
+struct BoundSql {
+    sql: String,
+
+    /// Names of the parameters, without the `$`, in the order of their
+    /// placeholders.
+    params: Vec<String>,
+}
+
+/// Replace the parameters of SQL, such as `$name` or `$1`, with placeholders of
+/// `style`. Parameters within quotes and comments are left as they are.
+fn bind_params(sql: &str, style: ParamStyle) -> BoundSql {
+    let bound = replace_params(sql, style);
+
+    // Drivers which format parameters into the SQL need a literal `%` to be
+    // escaped, but only when there are parameters. We escape before replacing,
+    // so we don't escape our own placeholders.
+    if !bound.params.is_empty() && matches!(style, ParamStyle::Format | ParamStyle::Pyformat) {
+        return replace_params(&sql.replace('%', "%%"), style);
+    }
+    bound
+}
+
+fn replace_params(sql: &str, style: ParamStyle) -> BoundSql {
+    let chars: Vec<char> = sql.chars().collect();
+    let is_name_char = |c: char| c.is_alphanumeric() || c == '_';
+
+    let mut params = Vec::new();
+    let mut res = String::with_capacity(sql.len());
+    let mut quote: Option<char> = None;
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => {}
+            None if matches!(c, '\'' | '"' | '`') => quote = Some(c),
+            None if c == '-' && chars.get(i + 1) == Some(&'-') => quote = Some('\n'),
+            None if c == '$'
+                && chars.get(i + 1).is_some_and(|c| is_name_char(*c))
+                && !(i > 0 && is_name_char(chars[i - 1])) =>
+            {
+                let len = chars[i + 1..]
+                    .iter()
+                    .take_while(|c| is_name_char(**c))
+                    .count();
+                let name: String = chars[i + 1..i + 1 + len].iter().collect();
+                params.push(name);
+                res += &placeholder(style, params.len(), params.last().unwrap());
+                i += 1 + len;
+                continue;
+            }
+            None => {}
+        }
+        res.push(c);
+        i += 1;
+    }
+
+    BoundSql { sql: res, params }
+}
+
+fn placeholder(style: ParamStyle, position: usize, name: &str) -> String {
+    match style {
+        ParamStyle::Qmark => "?".to_string(),
+        ParamStyle::Numeric => format!(":{position}"),
+        ParamStyle::Named => format!(":{}", placeholder_name(name)),
+        ParamStyle::Format => "%s".to_string(),
+        ParamStyle::Pyformat => format!("%({})s", placeholder_name(name)),
+    }
+}
+
+/// Named placeholders can't start with a digit, so `$1` becomes `:p1`.
+fn placeholder_name(name: &str) -> String {
+    if name.starts_with(|c: char| c.is_ascii_digit()) {
+        format!("p{name}")
+    } else {
+        name.to_string()
+    }
+}
+
+/// The arguments for the placeholders of `params`, from the values given to
+/// `execute`.
+fn param_values<'py>(
+    py: Python<'py>,
+    params: &[String],
+    style: ParamStyle,
+    values: Option<&Bound<'py, PyAny>>,
+) -> PyResult<Bound<'py, PyAny>> {
+    let value_of = |name: &str| -> PyResult<Bound<'py, PyAny>> {
+        let missing = || PyErr::new::<PyValueError, _>(format!("missing a value for `${name}`"));
+        let values = values.ok_or_else(missing)?;
+        let position = name.parse::<usize>().ok();
+
+        if values.hasattr("keys")? {
+            if values.contains(name)? {
+                return values.get_item(name);
+            }
+            match position {
+                Some(position) if values.contains(position)? => values.get_item(position),
+                _ => Err(missing()),
+            }
+        } else {
+            match position {
+                Some(position) if position >= 1 && position <= values.len()? => {
+                    values.get_item(position - 1)
+                }
+                _ => Err(missing()),
+            }
+        }
+    };
+
+    Ok(match style {
+        ParamStyle::Qmark | ParamStyle::Numeric | ParamStyle::Format => {
+            let args = params
+                .iter()
+                .map(|name| value_of(name))
+                .collect::<PyResult<Vec<_>>>()?;
+            PyTuple::new(py, args)?.into_any()
+        }
+        ParamStyle::Named | ParamStyle::Pyformat => {
+            let args = PyDict::new(py);
+            for name in params {
+                args.set_item(placeholder_name(name), value_of(name)?)?;
+            }
+            args.into_any()
+        }
+    })
+}
+
+/// Names of the columns and the rows of the result of a DB-API cursor.
+fn fetch_all<'py>(cursor: &Bound<'py, PyAny>) -> PyResult<(Vec<String>, Bound<'py, PyAny>)> {
+    let description = cursor.getattr("description")?;
+    let columns = if description.is_none() {
+        Vec::new()
+    } else {
+        description
+            .try_iter()?
+            .map(|column| column?.get_item(0)?.extract())
+            .collect::<PyResult<_>>()?
+    };
+    Ok((columns, cursor.call_method0("fetchall")?))
+}
+
+/// Convert rows into a `pyarrow.Table`, with a column for each name.
+fn to_arrow<'py>(
+    py: Python<'py>,
+    columns: Vec<String>,
+    rows: &Bound<'py, PyAny>,
+) -> PyResult<Bound<'py, PyAny>> {
+    let pyarrow = py.import("pyarrow").map_err(|_| {
+        PyErr::new::<PyImportError, _>(
+            "`execute` returns Arrow tables, which requires `pyarrow`; install it with \
+            `pip install pyarrow`",
+        )
+    })?;
+
+    let rows: Vec<Bound<'py, PyAny>> = rows.try_iter()?.collect::<PyResult<_>>()?;
+    let arrays = (0..columns.len())
+        .map(|i| {
+            let values = rows
+                .iter()
+                .map(|row| row.get_item(i))
+                .collect::<PyResult<Vec<_>>>()?;
+            pyarrow.call_method1("array", (PyList::new(py, values)?,))
+        })
+        .collect::<PyResult<Vec<_>>>()?;
+
+    let kwargs = PyDict::new(py);
+    kwargs.set_item("names", columns)?;
+    pyarrow
+        .getattr("Table")?
+        .call_method("from_arrays", (arrays,), Some(&kwargs))
+}
+
+#[cfg(test)]
+mod test {
+    use insta::assert_snapshot;
+
+    use super::*;
+
+    #[test]
+    fn bind_params_styles() {
+        let sql = "SELECT a, '$x' AS \"$y\" FROM t WHERE a = $1 AND b = $name AND c = $1 -- $z";
+
+        let bound = bind_params(sql, ParamStyle::Qmark);
+        assert_eq!(bound.params, vec!["1", "name", "1"]);
+        assert_snapshot!(bound.sql, @r#"SELECT a, '$x' AS "$y" FROM t WHERE a = ? AND b = ? AND c = ? -- $z"#);
+        assert_snapshot!(bind_params(sql, ParamStyle::Numeric).sql, @r#"SELECT a, '$x' AS "$y" FROM t WHERE a = :1 AND b = :2 AND c = :3 -- $z"#);
+        assert_snapshot!(bind_params(sql, ParamStyle::Named).sql, @r#"SELECT a, '$x' AS "$y" FROM t WHERE a = :p1 AND b = :name AND c = :p1 -- $z"#);
+        assert_snapshot!(bind_params(sql, ParamStyle::Pyformat).sql, @r#"SELECT a, '$x' AS "$y" FROM t WHERE a = %(p1)s AND b = %(name)s AND c = %(p1)s -- $z"#);
+    }
+
+    #[test]
+    fn bind_params_escapes_percent() {
+        let sql = "SELECT * FROM t WHERE a LIKE '%x' AND b = $1";
+        assert_snapshot!(bind_params(sql, ParamStyle::Format).sql, @"SELECT * FROM t WHERE a LIKE '%%x' AND b = %s");
+
+        // Without parameters, drivers don't format the SQL
+        let sql = "SELECT * FROM t WHERE a LIKE '%x'";
+        assert_eq!(bind_params(sql, ParamStyle::Format).sql, sql);
+    }
+
+    #[test]
+    fn execute_with_sqlite() {
+        Python::initialize();
+        Python::attach(|py| {
+            if py.import("pyarrow").is_err() {
+                eprintln!("skipping, as `pyarrow` is not installed");
+                return;
+            }
+
+            let conn = py
+                .import("sqlite3")
+                .unwrap()
+                .call_method1("connect", (":memory:",))
+                .unwrap();
+            conn.call_method1(
+                "executescript",
+                ("CREATE TABLE t (a INTEGER, b TEXT); INSERT INTO t VALUES (1, 'x'), (2, 'y');",),
+            )
+            .unwrap();
+
+            assert_eq!(detect_target(&conn).unwrap(), "sql.sqlite");
+
+            let params = PyDict::new(py);
+            params.set_item("min", 1).unwrap();
+            let table = execute(
+                "from t | filter a > $min | select {b}",
+                &conn,
+                Some(params.as_any()),
+                None,
+            )
+            .unwrap();
+            let rows = table.call_method0("to_pylist").unwrap();
+            assert_snapshot!(rows.repr().unwrap().to_string(), @"[{'b': 'y'}]");
+        });
+    }
+}
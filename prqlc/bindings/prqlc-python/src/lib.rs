@@ -1,8 +1,19 @@
 #![cfg(not(target_family = "wasm"))]
+use std::collections::HashMap;
 use std::str::FromStr;
 
-use prqlc_lib::ErrorMessages;
-use pyo3::{exceptions, prelude::*};
+use prqlc_lib::{ErrorMessages, SourceTree};
+use pyo3::{create_exception, exceptions, prelude::*};
+
+mod execute;
+
+create_exception!(
+    prqlc,
+    PrqlCompileError,
+    exceptions::PyValueError,
+    "Raised when PRQL fails to compile. The `errors` attribute holds an \
+    `ErrorMessage` for each error."
+);
 
 #[pyfunction]
 #[pyo3(signature = (prql_query, options=None))]
@@ -14,21 +25,72 @@ pub fn compile(prql_query: &str, options: Option<CompileOptions>) -> PyResult<St
     };
 
     prqlc_lib::compile(prql_query, &options.unwrap_or_default())
-        .map_err(|err| PyErr::new::<exceptions::PyValueError, _>(err.to_string()))
+        .map_err(|err| compile_error(err.to_string(), err))
+}
+
+#[pyfunction]
+#[pyo3(signature = (prql_query, options=None))]
+pub fn compile_full(prql_query: &str, options: Option<CompileOptions>) -> PyResult<CompileResult> {
+    let Ok(options) = options.map(convert_options).transpose() else {
+        return Err(PyErr::new::<exceptions::PyValueError, _>(
+            "Invalid options".to_string(),
+        ));
+    };
+
+    prqlc_lib::compile_full(prql_query, &options.unwrap_or_default())
+        .map(CompileResult::from)
+        .map_err(|err| compile_error(err.to_string(), err))
+}
+
+/// Compile a project of multiple PRQL files, given as a mapping of paths to
+/// sources. `main_path` is the dotted path of the relation to compile, such as
+/// `reports.monthly`; it defaults to the main pipeline of the root module.
+#[pyfunction]
+#[pyo3(signature = (sources, main_path=None, options=None))]
+pub fn compile_tree(
+    sources: HashMap<String, String>,
+    main_path: Option<&str>,
+    options: Option<CompileOptions>,
+) -> PyResult<String> {
+    let Ok(options) = options.map(convert_options).transpose() else {
+        return Err(PyErr::new::<exceptions::PyValueError, _>(
+            "Invalid options".to_string(),
+        ));
+    };
+
+    let tree = SourceTree::new(
+        sources
+            .into_iter()
+            .map(|(path, source)| (path.into(), source)),
+        None,
+    );
+    let main_path = prqlc_lib::split_main_path(main_path.unwrap_or_default());
+
+    prqlc_lib::compile_tree(&tree, &main_path, &options.unwrap_or_default())
+        .map_err(|err| compile_error(err.to_string(), err))
 }
 
 #[pyfunction]
 pub fn prql_to_pl(prql_query: &str) -> PyResult<String> {
     prqlc_lib::prql_to_pl(prql_query)
         .and_then(|x| prqlc_lib::json::from_pl(&x))
-        .map_err(|err| PyErr::new::<exceptions::PyValueError, _>(err.to_json()))
+        .map_err(|err| compile_error(err.to_json(), err))
 }
 
 #[pyfunction]
 pub fn pl_to_prql(pl_json: &str) -> PyResult<String> {
     prqlc_lib::json::to_pl(pl_json)
         .and_then(|x| prqlc_lib::pl_to_prql(&x))
-        .map_err(|err| PyErr::new::<exceptions::PyValueError, _>(err.to_json()))
+        .map_err(|err| compile_error(err.to_json(), err))
+}
+
+#[pyfunction]
+#[pyo3(signature = (prql_query, options=None))]
+pub fn format_prql(prql_query: &str, options: Option<FormatOptions>) -> PyResult<String> {
+    let options = options.map(prqlc_lib::FormatOptions::from);
+
+    prqlc_lib::format_prql(prql_query, &options.unwrap_or_default())
+        .map_err(|err| compile_error(err.to_json(), err))
 }
 
 #[pyfunction]
@@ -36,7 +98,7 @@ pub fn pl_to_rq(pl_json: &str) -> PyResult<String> {
     prqlc_lib::json::to_pl(pl_json)
         .and_then(prqlc_lib::pl_to_rq)
         .and_then(|x| prqlc_lib::json::from_rq(&x))
-        .map_err(|err| PyErr::new::<exceptions::PyValueError, _>(err.to_json()))
+        .map_err(|err| compile_error(err.to_json(), err))
 }
 
 #[pyfunction]
@@ -52,7 +114,7 @@ pub fn rq_to_sql(rq_json: &str, options: Option<CompileOptions>) -> PyResult<Str
                     .unwrap_or_default(),
             )
         })
-        .map_err(|err| PyErr::new::<exceptions::PyValueError, _>(err.to_json()))
+        .map_err(|err| compile_error(err.to_json(), err))
 }
 
 mod debug {
@@ -63,7 +125,7 @@ mod debug {
         prqlc_lib::prql_to_pl(prql_query)
             .and_then(prqlc_lib::internal::pl_to_lineage)
             .and_then(|x| prqlc_lib::internal::json::from_lineage(&x))
-            .map_err(|err| PyErr::new::<exceptions::PyValueError, _>(err.to_json()))
+            .map_err(|err| compile_error(err.to_json(), err))
     }
 
     #[pyfunction]
@@ -71,20 +133,32 @@ mod debug {
         prqlc_lib::json::to_pl(pl_json)
             .and_then(prqlc_lib::internal::pl_to_lineage)
             .and_then(|x| prqlc_lib::internal::json::from_lineage(&x))
-            .map_err(|err| PyErr::new::<exceptions::PyValueError, _>(err.to_json()))
+            .map_err(|err| compile_error(err.to_json(), err))
     }
 }
 
 #[pymodule]
 fn prqlc(_py: Python, m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_function(wrap_pyfunction!(compile, m)?)?;
+    m.add_function(wrap_pyfunction!(compile_full, m)?)?;
+    m.add_function(wrap_pyfunction!(compile_tree, m)?)?;
     m.add_function(wrap_pyfunction!(prql_to_pl, m)?)?;
     m.add_function(wrap_pyfunction!(pl_to_prql, m)?)?;
+    m.add_function(wrap_pyfunction!(format_prql, m)?)?;
     m.add_function(wrap_pyfunction!(pl_to_rq, m)?)?;
     m.add_function(wrap_pyfunction!(rq_to_sql, m)?)?;
     m.add_function(wrap_pyfunction!(get_targets, m)?)?;
+    m.add_function(wrap_pyfunction!(execute::execute, m)?)?;
+    m.add_function(wrap_pyfunction!(execute::detect_target, m)?)?;
 
     m.add_class::<CompileOptions>()?;
+    m.add_class::<FormatOptions>()?;
+    m.add_class::<CompileResult>()?;
+    m.add_class::<OutputColumn>()?;
+    m.add_class::<ErrorMessage>()?;
+    m.add_class::<SourceLocation>()?;
+    m.add_class::<Fix>()?;
+    m.add("PrqlCompileError", m.py().get_type::<PrqlCompileError>())?;
     // From https://github.com/PyO3/maturin/issues/100
     m.add("__version__", env!("CARGO_PKG_VERSION"))?;
 
@@ -157,9 +231,222 @@ fn convert_options(o: CompileOptions) -> Result<prqlc_lib::Options, prqlc_lib::E
         display: prqlc_lib::DisplayOptions::from_str(&o.display).map_err(|e| ErrorMessages {
             inner: vec![Error::new_simple(format!("Invalid display option: {e}")).into()],
         })?,
+        ..Default::default()
     })
 }
 
+/// The result of `compile_full`: the SQL, along with the warnings of the
+/// compilation and information about the query.
+#[pyclass(get_all)]
+#[derive(Clone, Debug)]
+pub struct CompileResult {
+    pub sql: String,
+
+    /// Issues which don't prevent compilation, such as the use of deprecated
+    /// functions.
+    pub warnings: Vec<String>,
+
+    /// Columns of the result of the query.
+    pub columns: Vec<OutputColumn>,
+
+    /// Names of the database tables which the query reads from.
+    pub tables: Vec<String>,
+
+    /// The dialect of the SQL, such as "postgres".
+    pub dialect: String,
+}
+
+/// A column of the result of a query.
+#[pyclass(get_all)]
+#[derive(Clone, Debug)]
+pub struct OutputColumn {
+    /// The name of the column, if it has one.
+    pub name: Option<String>,
+
+    /// The type of the column in PRQL syntax, if it could be inferred.
+    #[pyo3(name = "type")]
+    pub ty: Option<String>,
+
+    /// Whether this stands for any other columns of a relation, as with
+    /// `SELECT *`.
+    pub wildcard: bool,
+}
+
+impl From<prqlc_lib::CompileResult> for CompileResult {
+    fn from(result: prqlc_lib::CompileResult) -> Self {
+        CompileResult {
+            sql: result.sql,
+            warnings: result.warnings.iter().map(|w| w.to_string()).collect(),
+            columns: result
+                .columns
+                .into_iter()
+                .map(|column| match column {
+                    prqlc_lib::OutputColumn::Single { name, ty } => OutputColumn {
+                        name,
+                        ty,
+                        wildcard: false,
+                    },
+                    prqlc_lib::OutputColumn::Wildcard => OutputColumn {
+                        name: None,
+                        ty: None,
+                        wildcard: true,
+                    },
+                })
+                .collect(),
+            tables: result.tables,
+            dialect: result.dialect.to_string(),
+        }
+    }
+}
+
+/// An error of the compilation, as serialized by `ErrorMessages::to_json`.
+#[pyclass(get_all)]
+#[derive(Clone, Debug)]
+pub struct ErrorMessage {
+    /// "Error", "Warning" or "Lint".
+    pub kind: String,
+
+    /// Machine-readable identifier of the error, such as "E0005".
+    pub code: Option<String>,
+
+    /// Plain text of the error.
+    pub reason: String,
+
+    /// Suggestions of how to fix the error.
+    pub hints: Vec<String>,
+
+    /// Edits of the source which would fix the error, the most likely first.
+    pub fixes: Vec<Fix>,
+
+    /// Start and end byte offsets of the error within its source file.
+    pub span: Option<(usize, usize)>,
+
+    /// Annotated code, containing the cause and hints.
+    pub display: Option<String>,
+
+    /// Line and column of the error within its source file.
+    pub location: Option<SourceLocation>,
+}
+
+/// Location within a source file.
+#[pyclass(get_all)]
+#[derive(Clone, Debug)]
+pub struct SourceLocation {
+    /// Line and column (both 0-based) of the start of the location.
+    pub start: (usize, usize),
+
+    /// Line and column (both 0-based) of the end of the location.
+    pub end: (usize, usize),
+
+    /// Path of the source file within the project, or `None` when compiling a
+    /// single source.
+    pub path: Option<String>,
+}
+
+/// An edit of the source which would fix an error.
+#[pyclass(get_all)]
+#[derive(Clone, Debug)]
+pub struct Fix {
+    /// Start and end byte offsets of the text to replace.
+    pub span: (usize, usize),
+
+    /// Text to replace the span with.
+    pub replacement: String,
+}
+
+impl From<prqlc_lib::ErrorMessage> for ErrorMessage {
+    fn from(e: prqlc_lib::ErrorMessage) -> Self {
+        ErrorMessage {
+            kind: format!("{:?}", e.kind),
+            code: e.code,
+            reason: e.reason,
+            hints: e.hints,
+            fixes: e
+                .fixes
+                .into_iter()
+                .map(|fix| Fix {
+                    span: (fix.span.start, fix.span.end),
+                    replacement: fix.replacement,
+                })
+                .collect(),
+            span: e.span.map(|span| (span.start, span.end)),
+            display: e.display,
+            location: e.location.map(|location| SourceLocation {
+                start: location.start,
+                end: location.end,
+                path: location.path.map(|p| p.to_string_lossy().into_owned()),
+            }),
+        }
+    }
+}
+
+/// Convert errors of the compiler into a `PrqlCompileError`, with `message` as
+/// its message and the errors in its `errors` attribute.
+fn compile_error(message: String, err: ErrorMessages) -> PyErr {
+    let errors: Vec<ErrorMessage> = err.inner.into_iter().map(ErrorMessage::from).collect();
+
+    Python::attach(|py| {
+        let py_err = PrqlCompileError::new_err(message);
+        match py_err.value(py).setattr("errors", errors) {
+            Ok(()) => py_err,
+            Err(e) => e,
+        }
+    })
+}
+
+/// Options for formatting PRQL.
+#[pyclass]
+#[derive(Clone, Debug)]
+pub struct FormatOptions {
+    /// Maximum number of characters per line, 50 by default.
+    pub max_width: u16,
+
+    /// Number of spaces per indentation level, 2 by default.
+    pub indent_width: u16,
+
+    /// Write each transform of a nested pipeline on its own line.
+    pub break_pipelines: bool,
+
+    /// Emit a comma after the last item of multi-line tuples and arrays.
+    pub trailing_commas: bool,
+
+    /// Align the `=` of assignments in multi-line tuples.
+    pub align_assignments: bool,
+}
+
+#[pymethods]
+impl FormatOptions {
+    #[new]
+    #[pyo3(signature = (*, max_width=50, indent_width=2, break_pipelines=false, trailing_commas=true, align_assignments=false))]
+    pub fn new(
+        max_width: u16,
+        indent_width: u16,
+        break_pipelines: bool,
+        trailing_commas: bool,
+        align_assignments: bool,
+    ) -> Self {
+        FormatOptions {
+            max_width,
+            indent_width,
+            break_pipelines,
+            trailing_commas,
+            align_assignments,
+        }
+    }
+}
+
+impl From<FormatOptions> for prqlc_lib::FormatOptions {
+    fn from(o: FormatOptions) -> Self {
+        prqlc_lib::FormatOptions {
+            max_width: o.max_width,
+            indent_width: o.indent_width,
+            break_pipelines: o.break_pipelines,
+            trailing_commas: o.trailing_commas,
+            align_assignments: o.align_assignments,
+        }
+    }
+}
+
 #[pyfunction]
 pub fn get_targets() -> Vec<String> {
     prqlc_lib::Target::names()
@@ -194,6 +481,78 @@ mod test {
         );
     }
 
+    #[test]
+    fn compile_full_for_python() {
+        let result = compile_full("from employees | select {name, n = 1}", None).unwrap();
+
+        assert_eq!(result.tables, vec!["employees"]);
+        assert_eq!(result.dialect, "generic");
+        assert_eq!(result.columns[1].name.as_deref(), Some("n"));
+        assert_eq!(result.columns[1].ty.as_deref(), Some("int"));
+        assert!(result.warnings.is_empty());
+    }
+
+    #[test]
+    fn compile_tree_for_python() {
+        let sources = HashMap::from([
+            (
+                "Project.prql".to_string(),
+                "from shared.top_albums | select {title}".to_string(),
+            ),
+            (
+                "shared.prql".to_string(),
+                "let top_albums = (from albums | take 10)".to_string(),
+            ),
+        ]);
+        let opts = CompileOptions::new(
+            false,
+            false,
+            "sql.any".to_string(),
+            false,
+            "plain".to_string(),
+        );
+
+        assert_snapshot!(
+            compile_tree(sources.clone(), None, Some(opts.clone())).unwrap(),
+            @"WITH top_albums AS (SELECT * FROM albums LIMIT 10) SELECT title FROM top_albums"
+        );
+        assert_snapshot!(
+            compile_tree(sources, Some("shared.top_albums"), Some(opts)).unwrap(),
+            @"SELECT * FROM albums LIMIT 10"
+        );
+    }
+
+    #[test]
+    fn compile_error_for_python() {
+        Python::initialize();
+        let err = compile(
+            "from employees | select {name, age} | filter agee > 30",
+            None,
+        )
+        .unwrap_err();
+
+        Python::attach(|py| {
+            assert!(err.is_instance_of::<PrqlCompileError>(py));
+            assert!(err.is_instance_of::<exceptions::PyValueError>(py));
+
+            let errors: Vec<ErrorMessage> =
+                err.value(py).getattr("errors").unwrap().extract().unwrap();
+            assert_eq!(errors.len(), 1);
+
+            let error = &errors[0];
+            assert_eq!(error.kind, "Error");
+            assert_eq!(error.code.as_deref(), Some("E0005"));
+            assert_eq!(error.reason, "Unknown name `agee`");
+            assert_eq!(error.hints[0], "did you mean `age`?");
+            assert_eq!(error.span, Some((45, 49)));
+            assert_eq!(error.fixes[0].replacement, "age");
+
+            let location = error.location.as_ref().unwrap();
+            assert_eq!((location.start, location.end), ((0, 45), (0, 49)));
+            assert_eq!(location.path, None);
+        });
+    }
+
     #[test]
     fn parse_pipeline() {
         let opts = Some(CompileOptions {
@@ -228,6 +587,21 @@ mod test {
         ");
     }
 
+    #[test]
+    fn format_with_options() {
+        let opts = Some(FormatOptions::new(20, 4, false, false, true));
+
+        assert_snapshot!(
+            format_prql("from artists # all of them\nderive {a = 1, bbb = 2}", opts).unwrap(),
+            @r"
+        from artists  # all of them
+        derive {
+            a   = 1,
+            bbb = 2
+        }
+        ");
+    }
+
     #[test]
     fn debug_prql_lineage() {
         assert_snapshot!(
@@ -0,0 +1,158 @@
+use std::str::FromStr;
+
+use magnus::prelude::*;
+use magnus::scan_args::{get_kwargs, scan_args};
+use magnus::{function, Error, ExceptionClass, RClass, RHash, RModule, Ruby, Value};
+use prqlc_lib::{DisplayOptions, ErrorMessage, ErrorMessages, Options, Target};
+
+#[magnus::init(name = "prqlc")]
+fn init(ruby: &Ruby) -> Result<(), Error> {
+    let module = ruby.define_module("Prqlc")?;
+
+    module.define_module_function("compile", function!(compile, -1))?;
+    module.define_module_function("prql_to_pl", function!(prql_to_pl, 1))?;
+    module.define_module_function("pl_to_prql", function!(pl_to_prql, 1))?;
+    module.define_module_function("pl_to_rq", function!(pl_to_rq, 1))?;
+    module.define_module_function("rq_to_sql", function!(rq_to_sql, -1))?;
+    module.define_module_function("format", function!(format, 1))?;
+    module.define_module_function("targets", function!(targets, 0))?;
+
+    Ok(())
+}
+
+/// `Prqlc.compile(prql_query, format: true, target: "sql.any", signature_comment: true, display: "plain")`
+fn compile(ruby: &Ruby, args: &[Value]) -> Result<String, Error> {
+    let args = scan_args::<(String,), (), (), (), RHash, ()>(args)?;
+    let (prql_query,) = args.required;
+    let options = options(ruby, args.keywords)?;
+
+    prqlc_lib::compile(&prql_query, &options).map_err(|e| compile_error(ruby, e))
+}
+
+/// `Prqlc.prql_to_pl(prql_query)`
+fn prql_to_pl(ruby: &Ruby, prql_query: String) -> Result<String, Error> {
+    prqlc_lib::prql_to_pl(&prql_query)
+        .and_then(|x| prqlc_lib::json::from_pl(&x))
+        .map_err(|e| compile_error(ruby, e.composed(&prql_query.into())))
+}
+
+/// `Prqlc.pl_to_prql(pl_json)`
+fn pl_to_prql(ruby: &Ruby, pl_json: String) -> Result<String, Error> {
+    prqlc_lib::json::to_pl(&pl_json)
+        .and_then(|x| prqlc_lib::pl_to_prql(&x))
+        .map_err(|e| compile_error(ruby, e))
+}
+
+/// `Prqlc.pl_to_rq(pl_json)`
+fn pl_to_rq(ruby: &Ruby, pl_json: String) -> Result<String, Error> {
+    prqlc_lib::json::to_pl(&pl_json)
+        .and_then(prqlc_lib::pl_to_rq)
+        .and_then(|x| prqlc_lib::json::from_rq(&x))
+        .map_err(|e| compile_error(ruby, e))
+}
+
+/// `Prqlc.rq_to_sql(rq_json, **options)`, with the options of `Prqlc.compile`
+fn rq_to_sql(ruby: &Ruby, args: &[Value]) -> Result<String, Error> {
+    let args = scan_args::<(String,), (), (), (), RHash, ()>(args)?;
+    let (rq_json,) = args.required;
+    let options = options(ruby, args.keywords)?;
+
+    prqlc_lib::json::to_rq(&rq_json)
+        .and_then(|x| prqlc_lib::rq_to_sql(x, &options))
+        .map_err(|e| compile_error(ruby, e))
+}
+
+/// `Prqlc.format(prql_query)`
+fn format(ruby: &Ruby, prql_query: String) -> Result<String, Error> {
+    prqlc_lib::format_prql(&prql_query, &Default::default())
+        .map_err(|e| compile_error(ruby, e.composed(&prql_query.into())))
+}
+
+/// `Prqlc.targets`
+fn targets() -> Vec<String> {
+    Target::names()
+}
+
+/// Convert the keyword arguments of the compiler options. Unlike
+/// `Options::default`, errors are displayed as plain text by default.
+fn options(ruby: &Ruby, keywords: RHash) -> Result<Options, Error> {
+    let kwargs =
+        get_kwargs::<_, (), (Option<bool>, Option<String>, Option<bool>, Option<String>), ()>(
+            keywords,
+            &[],
+            &["format", "target", "signature_comment", "display"],
+        )?;
+    let (format, target, signature_comment, display) = kwargs.optional;
+
+    let target = Target::from_str(target.as_deref().unwrap_or("sql.any"))
+        .map_err(|e| compile_error(ruby, e.into()))?;
+    let display = DisplayOptions::from_str(display.as_deref().unwrap_or("plain")).map_err(|e| {
+        Error::new(
+            ruby.exception_arg_error(),
+            format!("Invalid display option: {e}"),
+        )
+    })?;
+
+    Ok(Options {
+        format: format.unwrap_or(true),
+        target,
+        signature_comment: signature_comment.unwrap_or(true),
+        color: false,
+        display,
+        ..Default::default()
+    })
+}
+
+/// Build a `Prqlc::CompileError`, with the rendered errors as its message and
+/// a `Prqlc::ErrorMessage` for each error.
+fn compile_error(ruby: &Ruby, errors: ErrorMessages) -> Error {
+    let build = || -> Result<Error, Error> {
+        let module: RModule = ruby.class_object().const_get("Prqlc")?;
+        let class: ExceptionClass = module.const_get("CompileError")?;
+
+        let messages = ruby.ary_new_capa(errors.inner.len());
+        for error in &errors.inner {
+            messages.push(error_message(ruby, module, error)?)?;
+        }
+        let exception = class.new_instance((errors.to_string(), messages))?;
+        Ok(exception.into())
+    };
+    build().unwrap_or_else(|e| e)
+}
+
+fn error_message(ruby: &Ruby, module: RModule, error: &ErrorMessage) -> Result<Value, Error> {
+    let class: RClass = module.const_get("ErrorMessage")?;
+
+    let span = match &error.span {
+        Some(span) => Some(ruby.range_new(span.start, span.end, true)?),
+        None => None,
+    };
+    let location = match &error.location {
+        Some(location) => {
+            let class: RClass = module.const_get("SourceLocation")?;
+            Some(
+                class.new_instance((
+                    location.start.0,
+                    location.start.1,
+                    location.end.0,
+                    location.end.1,
+                    location
+                        .path
+                        .as_ref()
+                        .map(|p| p.to_string_lossy().into_owned()),
+                ))?,
+            )
+        }
+        None => None,
+    };
+
+    class.new_instance((
+        format!("{:?}", error.kind),
+        error.code.clone(),
+        error.reason.clone(),
+        ruby.ary_from_vec(error.hints.clone()),
+        span,
+        error.display.clone(),
+        location,
+    ))
+}
@@ -0,0 +1,28 @@
+# frozen_string_literal: true
+
+require_relative "prqlc/version"
+
+# Ruby bindings for prqlc, the PRQL compiler.
+module Prqlc
+  # Raised when PRQL fails to compile. Its message is the rendered error text.
+  class CompileError < StandardError
+    # An ErrorMessage for each error
+    attr_reader :errors
+
+    def initialize(message = nil, errors = [])
+      super(message)
+      @errors = errors
+    end
+  end
+
+  # An error of the compilation, mirroring the `ErrorMessage` of the prqlc
+  # crate. `span` is the Range of byte offsets of the error within the UTF-8
+  # encoding of its source.
+  ErrorMessage = Struct.new(:kind, :code, :reason, :hints, :span, :display, :location)
+
+  # Location of an error, in 0-based lines and columns. `path` is the path of
+  # the file within a project, or nil.
+  SourceLocation = Struct.new(:start_line, :start_column, :end_line, :end_column, :path)
+end
+
+require_relative "prqlc/prqlc"
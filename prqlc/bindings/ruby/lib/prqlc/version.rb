@@ -0,0 +1,5 @@
+# frozen_string_literal: true
+
+module Prqlc
+  VERSION = "0.13.11"
+end
@@ -0,0 +1,57 @@
+# frozen_string_literal: true
+
+require "minitest/autorun"
+require "prqlc"
+
+class TestPrqlc < Minitest::Test
+  def test_compile
+    options = { target: "sql.mysql", format: false, signature_comment: false }
+    sql = Prqlc.compile("from albums | select {title} | take 3", **options)
+    assert_equal "SELECT title FROM albums LIMIT 3", sql
+
+    assert_includes Prqlc.compile("from albums"), "-- Generated by PRQL compiler"
+  end
+
+  def test_intermediate_representations
+    pl = Prqlc.prql_to_pl("from albums | select {title}")
+    assert_equal "from albums\nselect {title}\n", Prqlc.pl_to_prql(pl)
+
+    rq = Prqlc.pl_to_rq(pl)
+    sql = Prqlc.rq_to_sql(rq, format: false, signature_comment: false)
+    assert_equal "SELECT title FROM albums", sql
+  end
+
+  def test_format
+    assert_equal "from albums\nselect {title}\n", Prqlc.format("from albums | select {title}")
+  end
+
+  def test_targets
+    assert_equal "sql.any", Prqlc.targets.first
+    assert_includes Prqlc.targets, "sql.postgres"
+  end
+
+  def test_compile_error
+    error = assert_raises(Prqlc::CompileError) do
+      Prqlc.compile("from albums | select {title, year} | filter yaer > 2000")
+    end
+    assert_includes error.message, "Unknown name `yaer`"
+    assert_equal 1, error.errors.length
+
+    message = error.errors.first
+    assert_equal "Error", message.kind
+    assert_equal "E0005", message.code
+    assert_equal "Unknown name `yaer`", message.reason
+    assert_includes message.hints, "did you mean `year`?"
+    assert_equal 44...48, message.span
+    assert_equal [0, 44], [message.location.start_line, message.location.start_column]
+    assert_nil message.location.path
+  end
+
+  def test_invalid_options
+    error = assert_raises(Prqlc::CompileError) { Prqlc.compile("from albums", target: "sql.oracle") }
+    assert_equal "E0011", error.errors.first.code
+
+    assert_raises(ArgumentError) { Prqlc.compile("from albums", display: "html") }
+    assert_raises(ArgumentError) { Prqlc.compile("from albums", colour: true) }
+  end
+end
@@ -0,0 +1,430 @@
+//! A SQLite extension for PRQL, with:
+//!
+//! - `prql_to_sql(prql_query)`, a scalar function which compiles PRQL to SQL
+//!   for SQLite.
+//! - `prql(prql_query)`, a table-valued function which compiles PRQL and
+//!   returns each row of the results as a JSON object, as in
+//!   `SELECT * FROM prql('from tracks')`.
+//! - `prql`, a virtual table module whose tables have the columns of their
+//!   query, as in `CREATE VIRTUAL TABLE t USING prql('from tracks')`.
+#![cfg(not(target_family = "wasm"))]
+
+use std::ffi::c_int;
+use std::marker::PhantomData;
+
+use prqlc::sql::Dialect;
+use prqlc::{DisplayOptions, ErrorMessages, Options, Target};
+use rusqlite::functions::FunctionFlags;
+use rusqlite::types::Value;
+use rusqlite::vtab::{
+    escape_double_quote, read_only_module, Context, CreateVTab, IndexConstraintOp, IndexInfo, VTab,
+    VTabConnection, VTabCursor, VTabKind, Values,
+};
+use rusqlite::{ffi, Connection, Error, Result};
+
+/// Entry point of the loadable extension.
+///
+/// # Safety
+///
+/// Called by SQLite when loading the extension.
+#[cfg(feature = "loadable-extension")]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub unsafe extern "C" fn sqlite3_extension_init(
+    db: *mut ffi::sqlite3,
+    pz_err_msg: *mut *mut std::ffi::c_char,
+    p_api: *mut ffi::sqlite3_api_routines,
+) -> c_int {
+    Connection::extension_init2(db, pz_err_msg, p_api, |con| {
+        register(&con)?;
+        Ok(false)
+    })
+}
+
+/// Register the function and the module of the extension on a connection.
+pub fn register(con: &Connection) -> Result<()> {
+    con.create_scalar_function(
+        "prql_to_sql",
+        1,
+        FunctionFlags::SQLITE_UTF8 | FunctionFlags::SQLITE_DETERMINISTIC,
+        |ctx| {
+            let prql_query: String = ctx.get(0)?;
+            compile(&prql_query).map_err(|e| Error::UserFunctionError(e.into()))
+        },
+    )?;
+    con.create_module("prql", read_only_module::<PrqlTab>(), None)
+}
+
+/// Compile PRQL to SQL for SQLite, regardless of the target of the query.
+pub fn compile(prql_query: &str) -> Result<String, ErrorMessages> {
+    let options = Options::default()
+        .with_target(Target::Sql(Some(Dialect::SQLite)))
+        .no_signature()
+        .with_display(DisplayOptions::Plain);
+
+    prqlc::compile(prql_query, &options)
+}
+
+/// Schema of the eponymous `prql` table, the table-valued function. Its columns
+/// can't depend on the query, so each row is a JSON object in `value`.
+const EPONYMOUS_SCHEMA: &str = "CREATE TABLE x(value, query HIDDEN)";
+const COLUMN_QUERY: c_int = 1;
+
+/// A virtual table of the `prql` module.
+///
+/// SQLite requires the columns of a virtual table when it's created, so a
+/// table created with a query compiles and prepares it then, and it's run
+/// each time the table is scanned. The eponymous `prql` table instead gets its
+/// query from its hidden `query` column, which is the argument of the
+/// table-valued function.
+#[repr(C)]
+struct PrqlTab {
+    /// Base class; must be first.
+    base: ffi::sqlite3_vtab,
+    /// Connection of the virtual table, which runs the query.
+    con: Connection,
+    /// SQL of the query the table was created with; `None` for the eponymous
+    /// table.
+    sql: Option<String>,
+}
+
+unsafe impl<'vtab> VTab<'vtab> for PrqlTab {
+    type Aux = ();
+    type Cursor = PrqlTabCursor<'vtab>;
+
+    fn connect(
+        db: &mut VTabConnection,
+        _aux: Option<&()>,
+        args: &[&[u8]],
+    ) -> Result<(String, Self)> {
+        let con = unsafe { Connection::from_handle(db.handle()) }?;
+
+        // The first three arguments are the names of the module, the database
+        // and the table. The eponymous table has no others.
+        let prql_query = match &args[3..] {
+            [] => {
+                let vtab = PrqlTab {
+                    base: ffi::sqlite3_vtab::default(),
+                    con,
+                    sql: None,
+                };
+                return Ok((EPONYMOUS_SCHEMA.to_string(), vtab));
+            }
+            [prql_query] => std::str::from_utf8(prql_query).map_err(Error::Utf8Error)?,
+            _ => {
+                return Err(Error::ModuleError(
+                    "expected a single argument, the PRQL query".to_string(),
+                ))
+            }
+        };
+        let sql = compile(&unquote(prql_query)).map_err(|e| Error::ModuleError(e.to_string()))?;
+
+        let columns: Vec<String> = column_names(&con, &sql)?
+            .iter()
+            .map(|name| format!("\"{}\"", escape_double_quote(name)))
+            .collect();
+        if columns.is_empty() {
+            return Err(Error::ModuleError("the query has no columns".to_string()));
+        }
+        let schema = format!("CREATE TABLE x({})", columns.join(", "));
+
+        let vtab = PrqlTab {
+            base: ffi::sqlite3_vtab::default(),
+            con,
+            sql: Some(sql),
+        };
+        Ok((schema, vtab))
+    }
+
+    fn best_index(&self, info: &mut IndexInfo) -> Result<()> {
+        if self.sql.is_some() {
+            info.set_estimated_cost(1_000_000.);
+            return Ok(());
+        }
+
+        // The eponymous table consumes its query argument, which SQLite
+        // passes as an equality constraint on the `query` column.
+        let query = info.constraints().enumerate().find(|(_, constraint)| {
+            constraint.column() == COLUMN_QUERY
+                && constraint.operator() == IndexConstraintOp::SQLITE_INDEX_CONSTRAINT_EQ
+        });
+        match query {
+            Some((i, constraint)) if constraint.is_usable() => {
+                let mut usage = info.constraint_usage(i);
+                usage.set_argv_index(1);
+                usage.set_omit(true);
+                info.set_idx_num(1);
+                info.set_estimated_cost(1.);
+                Ok(())
+            }
+            // Another plan may make the query usable, such as when it's a
+            // column of a table joined before this one.
+            Some(_) => Err(Error::SqliteFailure(
+                ffi::Error::new(ffi::SQLITE_CONSTRAINT),
+                None,
+            )),
+            None => Err(Error::ModuleError(
+                "prql requires a PRQL query, as in `SELECT * FROM prql('from t')`".to_string(),
+            )),
+        }
+    }
+
+    fn open(&'vtab mut self) -> Result<PrqlTabCursor<'vtab>> {
+        Ok(PrqlTabCursor {
+            base: ffi::sqlite3_vtab_cursor::default(),
+            query: None,
+            rows: Vec::new(),
+            row: 0,
+            phantom: PhantomData,
+        })
+    }
+}
+
+impl CreateVTab<'_> for PrqlTab {
+    const KIND: VTabKind = VTabKind::Eponymous;
+}
+
+fn column_names(con: &Connection, sql: &str) -> Result<Vec<String>> {
+    let statement = con.prepare(sql)?;
+    Ok(statement
+        .column_names()
+        .into_iter()
+        .map(str::to_string)
+        .collect())
+}
+
+/// Wraps `sql` in a query which returns each of its rows as a JSON object.
+fn json_rows(con: &Connection, sql: &str) -> Result<String> {
+    let fields: Vec<String> = column_names(con, sql)?
+        .iter()
+        .map(|name| {
+            let key = name.replace('\'', "''");
+            format!("'{key}', \"{}\"", escape_double_quote(name))
+        })
+        .collect();
+    Ok(format!(
+        "SELECT json_object({}) FROM ({sql})",
+        fields.join(", ")
+    ))
+}
+
+/// Argument of a module, without the quotes of an SQL string literal.
+fn unquote(arg: &str) -> String {
+    let arg = arg.trim();
+    match arg.strip_prefix('\'').and_then(|x| x.strip_suffix('\'')) {
+        Some(inner) => inner.replace("''", "'"),
+        None => arg.to_string(),
+    }
+}
+
+/// A cursor over the results of a `prql` virtual table, which are read when
+/// the scan starts.
+#[repr(C)]
+struct PrqlTabCursor<'vtab> {
+    /// Base class; must be first.
+    base: ffi::sqlite3_vtab_cursor,
+    /// The query argument of the eponymous table.
+    query: Option<String>,
+    rows: Vec<Vec<Value>>,
+    row: usize,
+    phantom: PhantomData<&'vtab PrqlTab>,
+}
+
+impl PrqlTabCursor<'_> {
+    fn vtab(&self) -> &PrqlTab {
+        unsafe { &*(self.base.pVtab as *const PrqlTab) }
+    }
+}
+
+unsafe impl VTabCursor for PrqlTabCursor<'_> {
+    fn filter(&mut self, idx_num: c_int, _idx_str: Option<&str>, args: &Values<'_>) -> Result<()> {
+        let query: Option<String> = if idx_num == 1 {
+            Some(args.get(0)?)
+        } else {
+            None
+        };
+
+        let rows = {
+            let vtab = self.vtab();
+            let sql = match (&vtab.sql, &query) {
+                (Some(sql), _) => sql.clone(),
+                (None, Some(prql_query)) => {
+                    let sql = compile(prql_query).map_err(|e| Error::ModuleError(e.to_string()))?;
+                    json_rows(&vtab.con, &sql)?
+                }
+                (None, None) => {
+                    return Err(Error::ModuleError("prql requires a PRQL query".to_string()))
+                }
+            };
+            let mut statement = vtab.con.prepare(&sql)?;
+            let len = statement.column_count();
+            let rows = statement
+                .query_map([], |row| (0..len).map(|i| row.get(i)).collect())?
+                .collect::<Result<_>>()?;
+            rows
+        };
+
+        self.query = query;
+        self.rows = rows;
+        self.row = 0;
+        Ok(())
+    }
+
+    fn next(&mut self) -> Result<()> {
+        self.row += 1;
+        Ok(())
+    }
+
+    fn eof(&self) -> bool {
+        self.row >= self.rows.len()
+    }
+
+    fn column(&self, ctx: &mut Context, col: c_int) -> Result<()> {
+        if self.vtab().sql.is_none() && col == COLUMN_QUERY {
+            return ctx.set_result(&self.query);
+        }
+        let value = usize::try_from(col)
+            .ok()
+            .and_then(|col| self.rows[self.row].get(col))
+            .ok_or_else(|| Error::ModuleError(format!("column index out of bounds: {col}")))?;
+        ctx.set_result(value)
+    }
+
+    fn rowid(&self) -> Result<i64> {
+        Ok(self.row as i64 + 1)
+    }
+}
+
+// Run with `cargo test --no-default-features --features=bundled`.
+#[cfg(all(test, feature = "bundled"))]
+mod test {
+    use super::*;
+
+    fn connection() -> Connection {
+        let con = Connection::open_in_memory().unwrap();
+        register(&con).unwrap();
+        con.execute_batch(
+            "CREATE TABLE invoices (id INTEGER, customer TEXT, total REAL);
+             INSERT INTO invoices VALUES (1, 'Ann', 5.5), (2, 'Bob', 20.0), (3, 'O''Neil', 12.0);",
+        )
+        .unwrap();
+        con
+    }
+
+    #[test]
+    fn prql_to_sql() {
+        let con = connection();
+
+        let sql: String = con
+            .query_row("SELECT prql_to_sql('from invoices | take 1')", [], |row| {
+                row.get(0)
+            })
+            .unwrap();
+        assert_eq!(sql, "SELECT\n  *\nFROM\n  invoices\nLIMIT\n  1\n");
+
+        let err = con
+            .query_row(
+                "SELECT prql_to_sql('from invoices | select {id} | filter total > 10')",
+                [],
+                |row| row.get::<_, String>(0),
+            )
+            .unwrap_err();
+        assert!(err.to_string().contains("Unknown name `total`"), "{err}");
+    }
+
+    #[test]
+    fn prql_table_valued_function() {
+        let con = connection();
+
+        let mut statement = con
+            .prepare("SELECT * FROM prql('from invoices | filter total > 10 | select {id, customer} | sort id')")
+            .unwrap();
+        assert_eq!(statement.column_names(), vec!["value"]);
+        let rows: Vec<String> = statement
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .collect::<Result<_>>()
+            .unwrap();
+        assert_eq!(
+            rows,
+            vec![
+                r#"{"id":2,"customer":"Bob"}"#,
+                r#"{"id":3,"customer":"O'Neil"}"#
+            ]
+        );
+
+        // The fields can be read with SQLite's JSON operators, and the query
+        // can come from a parameter.
+        let total: f64 = con
+            .query_row(
+                "SELECT sum(value ->> 'total') FROM prql(?1)",
+                ["from invoices | filter id > 1"],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(total, 32.0);
+
+        let err = con
+            .prepare("SELECT * FROM prql")
+            .and_then(|mut statement| statement.query_row([], |_| Ok(())))
+            .unwrap_err();
+        assert!(err.to_string().contains("requires a PRQL query"), "{err}");
+
+        let err = con
+            .query_row(
+                "SELECT * FROM prql('from invoices | select {id} | filter total > 10')",
+                [],
+                |_| Ok(()),
+            )
+            .unwrap_err();
+        assert!(err.to_string().contains("Unknown name `total`"), "{err}");
+    }
+
+    #[test]
+    fn prql_virtual_table() {
+        let con = connection();
+        con.execute_batch(
+            "CREATE VIRTUAL TABLE big_invoices USING prql(
+                'from invoices | filter total > 10 && customer != ''Zed'' | select {id, customer} | sort id'
+            )",
+        )
+        .unwrap();
+
+        let mut statement = con
+            .prepare("SELECT id, customer FROM big_invoices")
+            .unwrap();
+        assert_eq!(statement.column_names(), vec!["id", "customer"]);
+        let rows: Vec<(i64, String)> = statement
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .unwrap()
+            .collect::<Result<_>>()
+            .unwrap();
+        assert_eq!(
+            rows,
+            vec![(2, "Bob".to_string()), (3, "O'Neil".to_string())]
+        );
+
+        // The query runs on each scan, so sees later changes.
+        con.execute("INSERT INTO invoices VALUES (4, 'Cy', 30.0)", [])
+            .unwrap();
+        let count: i64 = con
+            .query_row("SELECT count(*) FROM big_invoices", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(count, 3);
+    }
+
+    #[test]
+    fn prql_virtual_table_errors() {
+        let con = connection();
+
+        let err = con
+            .execute_batch("CREATE VIRTUAL TABLE t USING prql('from invoices | select {id} | filter total > 10')")
+            .unwrap_err();
+        assert!(err.to_string().contains("Unknown name `total`"), "{err}");
+
+        let err = con
+            .execute_batch("CREATE VIRTUAL TABLE t USING prql('from a', 'from b')")
+            .unwrap_err();
+        assert!(err.to_string().contains("a single argument"), "{err}");
+    }
+}
@@ -0,0 +1,145 @@
+//! Arguments of the macros.
+use std::str::FromStr;
+
+use prqlc::Target;
+use syn::parse::{Parse, ParseStream};
+use syn::{braced, Ident, LitBool, LitStr, Token};
+
+use crate::schema::Schema;
+
+/// Arguments of a macro: a string literal, followed by options as
+/// `key = value`, separated by commas.
+pub(crate) struct Args {
+    /// The query of `prql_to_sql!`, or the path of `include_prql!`.
+    pub literal: Option<LitStr>,
+    /// Path of the file of `prql!`, as an alternative to the query.
+    pub path: Option<LitStr>,
+    pub target: Target,
+    pub format: bool,
+    pub signature_comment: bool,
+    /// Dotted path of the relation to compile, such as `reports.monthly`.
+    pub main: Option<LitStr>,
+    pub schema: Option<Schema>,
+}
+
+impl Args {
+    /// Path of the relation to compile, as passed to [prqlc::compile_tree].
+    pub fn main_path(&self) -> Vec<String> {
+        let Some(main) = &self.main else {
+            return Vec::new();
+        };
+        prqlc::split_main_path(&main.value())
+    }
+
+    pub fn options(&self) -> prqlc::Options {
+        prqlc::Options::default()
+            .with_format(self.format)
+            .with_signature_comment(self.signature_comment)
+            .with_target(self.target.clone())
+            .with_display(prqlc::DisplayOptions::Plain)
+    }
+}
+
+impl Parse for Args {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut args = Args {
+            literal: None,
+            path: None,
+            target: Target::Sql(None),
+            format: false,
+            signature_comment: false,
+            main: None,
+            schema: None,
+        };
+
+        if input.peek(LitStr) {
+            args.literal = Some(input.parse()?);
+            if !input.is_empty() {
+                input.parse::<Token![,]>()?;
+            }
+        }
+
+        while !input.is_empty() {
+            let key: Ident = input.parse()?;
+            input.parse::<Token![=]>()?;
+
+            match key.to_string().as_str() {
+                "target" => {
+                    let target: LitStr = input.parse()?;
+                    args.target = Target::from_str(&target.value()).map_err(|_| {
+                        let targets = Target::names().join(", ");
+                        syn::Error::new(
+                            target.span(),
+                            format!("unknown target; available targets are: {targets}"),
+                        )
+                    })?;
+                }
+                "format" => args.format = input.parse::<LitBool>()?.value,
+                "signature_comment" => args.signature_comment = input.parse::<LitBool>()?.value,
+                "main" => args.main = Some(input.parse()?),
+                "path" => args.path = Some(input.parse()?),
+                "schema" => {
+                    let content;
+                    braced!(content in input);
+                    args.schema = Some(content.parse()?);
+                }
+                _ => {
+                    return Err(syn::Error::new(
+                        key.span(),
+                        "unknown option; expected one of `target`, `format`, \
+                         `signature_comment`, `main`, `path` or `schema`",
+                    ))
+                }
+            }
+
+            if !input.is_empty() {
+                input.parse::<Token![,]>()?;
+            }
+        }
+
+        Ok(args)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use prqlc::sql::Dialect;
+    use quote::quote;
+
+    use super::*;
+
+    #[test]
+    fn parse_args() {
+        let args: Args = syn::parse2(quote! {
+            "from albums", target = "sql.postgres", format = true, main = "reports.monthly",
+        })
+        .unwrap();
+        assert_eq!(args.literal.as_ref().unwrap().value(), "from albums");
+        assert!(matches!(args.target, Target::Sql(Some(Dialect::Postgres))));
+        assert!(args.format);
+        assert!(!args.signature_comment);
+        assert_eq!(args.main_path(), vec!["reports", "monthly"]);
+
+        let args: Args = syn::parse2(quote! { path = "queries/albums.prql" }).unwrap();
+        assert!(args.literal.is_none());
+        assert_eq!(args.path.unwrap().value(), "queries/albums.prql");
+    }
+
+    #[test]
+    fn parse_args_errors() {
+        let err = syn::parse2::<Args>(quote! { "from albums", target = "sql.oracle" })
+            .err()
+            .unwrap();
+        assert!(err.to_string().starts_with("unknown target"), "{err}");
+
+        let err = syn::parse2::<Args>(quote! { "from albums", dialect = "postgres" })
+            .err()
+            .unwrap();
+        assert!(err.to_string().starts_with("unknown option"), "{err}");
+
+        let err = syn::parse2::<Args>(quote! { "from albums", format = "yes" })
+            .err()
+            .unwrap();
+        assert_eq!(err.to_string(), "expected boolean literal");
+    }
+}
@@ -0,0 +1,146 @@
+//! Errors of PRQL as errors of the Rust compiler.
+use std::path::Path;
+
+use proc_macro2::Span;
+use prqlc::{ErrorMessages, SourceTree};
+
+use crate::schema::Schema;
+use crate::sources::Query;
+
+/// Convert errors of PRQL into a compile error for each of them, with the
+/// annotated source of the error as its message.
+///
+/// Errors of an inline query point at their span within the string literal,
+/// when the Rust compiler supports spans within literals, and otherwise at the
+/// whole literal.
+pub(crate) fn to_syn_error(
+    errors: ErrorMessages,
+    query: &Query,
+    schema: Option<&Schema>,
+    tree: &SourceTree,
+) -> syn::Error {
+    let lit = query.literal();
+
+    let mut errors = errors.inner.into_iter().map(|error| {
+        let span = error
+            .span
+            .and_then(|span| {
+                let path = tree.get_path(span.source_id)?;
+                if schema.is_some() && path == Path::new(crate::sources::SCHEMA_PATH) {
+                    return schema.map(|s| s.span);
+                }
+                let Query::Inline(lit) = query else {
+                    return None;
+                };
+                let range = literal_range(&lit.token().to_string(), &lit.value(), span)?;
+                lit.token().subspan(range)
+            })
+            .unwrap_or_else(|| lit.span());
+
+        let message = error.display.unwrap_or(error.reason);
+        syn::Error::new(span, message.trim_end())
+    });
+
+    let mut result = errors
+        .next()
+        .unwrap_or_else(|| syn::Error::new(Span::call_site(), "PRQL failed to compile"));
+    for error in errors {
+        result.combine(error);
+    }
+    result
+}
+
+/// Byte range within the source of a string literal of a span of PRQL, which
+/// is in bytes of the value of the literal.
+fn literal_range(repr: &str, value: &str, span: prqlc::Span) -> Option<std::ops::Range<usize>> {
+    value.get(span.start..span.end)?;
+    let start = literal_offset(repr, span.start)?;
+    let end = literal_offset(repr, span.end)?;
+    Some(start..end)
+}
+
+/// Offset within the source of a string literal of a byte offset within its
+/// value, accounting for escapes.
+fn literal_offset(repr: &str, offset: usize) -> Option<usize> {
+    if let Some(raw) = repr.strip_prefix('r') {
+        return Some(1 + raw.find('"')? + 1 + offset);
+    }
+
+    let mut chars = repr.char_indices().skip(1).peekable();
+    let mut value_offset = 0;
+    while let Some((i, c)) = chars.next() {
+        if value_offset >= offset {
+            return Some(i);
+        }
+        match c {
+            '"' => return None,
+            '\\' => match chars.next()?.1 {
+                // a line continuation, which skips the whitespace of the next
+                // line
+                '\n' => while chars.next_if(|(_, c)| c.is_whitespace()).is_some() {},
+                'x' => {
+                    chars.nth(1)?;
+                    value_offset += 1;
+                }
+                'u' => {
+                    let mut code = String::new();
+                    for (_, c) in chars.by_ref() {
+                        match c {
+                            '{' => {}
+                            '}' => break,
+                            c => code.push(c),
+                        }
+                    }
+                    let c = u32::from_str_radix(&code, 16)
+                        .ok()
+                        .and_then(char::from_u32)?;
+                    value_offset += c.len_utf8();
+                }
+                _ => value_offset += 1,
+            },
+            c => value_offset += c.len_utf8(),
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn span(start: usize, end: usize) -> prqlc::Span {
+        prqlc::Span {
+            start,
+            end,
+            source_id: 1,
+        }
+    }
+
+    #[test]
+    fn literal_ranges() {
+        // `yaer` within each of the literals
+        let value = "from albums | filter yaer > 2000";
+        assert_eq!(
+            literal_range(&format!("{value:?}"), value, span(21, 25)),
+            Some(22..26)
+        );
+        assert_eq!(
+            literal_range(&format!("r#\"{value}\"#"), value, span(21, 25)),
+            Some(24..28)
+        );
+
+        // the span of an actual error, which is in bytes after `å`
+        let value = "from `ålbums`\nselect {title}\nfilter yaer > 2000";
+        let repr = r#""from `ålbums`\nselect {title}\nfilter yaer > 2000""#;
+        let error = prqlc::compile(value, &prqlc::Options::default()).unwrap_err();
+        let error_span = error.inner[0].span.unwrap();
+        assert_eq!(&value[error_span.start..error_span.end], "yaer");
+        assert_eq!(&repr[40..44], "yaer");
+        assert_eq!(literal_range(repr, value, error_span), Some(40..44));
+
+        let repr = "\"from albums \\\n    | filter \\u{79}aer > 2000\"";
+        let value = "from albums | filter yaer > 2000";
+        assert_eq!(&repr[28..37], "\\u{79}aer");
+        assert_eq!(literal_range(repr, value, span(21, 25)), Some(28..37));
+    }
+}
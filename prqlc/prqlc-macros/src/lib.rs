@@ -8,30 +8,288 @@
 //! ```
 //!
 //! "at build time" means that PRQL will be compiled during Rust compilation,
-//! producing errors alongside Rust errors. Limited to string literals.
-use proc_macro::{Literal, TokenStream, TokenTree};
-use syn::{Expr, ExprLit, Lit};
+//! producing errors alongside Rust errors.
+//!
+//! Each macro takes options after the PRQL, as `key = value`:
+//!
+//! - `target`: the target to compile to, such as `"sql.postgres"`; defaults to
+//!   `"sql.any"`, which uses the target of the query header.
+//! - `format` and `signature_comment`: whether to format the SQL and whether to
+//!   add the signature comment; both default to `false`.
+//! - `main`: the dotted path of the relation to compile, such as
+//!   `"reports.monthly"`; defaults to the main pipeline of the root module.
+//! - `schema`: the tables which the query reads from, with the types of their
+//!   columns. Names which aren't columns of a declared table are errors.
+//!
+//! ```
+//! use prqlc_macros::{include_prql, prql, prql_to_sql};
+//!
+//! let sql = prql_to_sql!(
+//!     "from albums | filter year > 2000 | select {title}",
+//!     target = "sql.postgres",
+//!     schema = { albums { album_id: int, title: text, year: int } },
+//! );
+//!
+//! // A file, or a project of multiple files, relative to `Cargo.toml`.
+//! let sql = include_prql!("tests/queries/project", main = "reports.by_year");
+//!
+//! // A struct for the rows of the result, with `SQL` and `COLUMNS` constants.
+//! prql! {
+//!     #[derive(Debug, PartialEq)]
+//!     pub struct Album {
+//!         // The types of computed columns must be declared.
+//!         next_year: i64,
+//!     }
+//!     "from albums | derive {next_year = year + 1} | select {title, next_year}",
+//!     schema = { albums { album_id: int, title: text, year: int } },
+//! }
+//! let album = Album::from(("Blue".to_string(), 1972));
+//! assert_eq!(Album::COLUMNS, ["title", "next_year"]);
+//! ```
+use proc_macro::TokenStream;
+use syn::parse::{Parse, ParseStream};
+use syn::DeriveInput;
+
+use crate::args::Args;
+use crate::sources::Query;
+
+mod args;
+mod errors;
+mod row;
+mod schema;
+mod sources;
 
+/// Compile a PRQL string literal to a SQL string literal.
 #[proc_macro]
 pub fn prql_to_sql(input: TokenStream) -> TokenStream {
-    let input: Expr = syn::parse(input).unwrap();
-
-    let prql_string = match input {
-        Expr::Lit(ExprLit {
-            lit: Lit::Str(lit_str),
-            ..
-        }) => lit_str.value(),
-        _ => panic!("prql! proc macro expected a string"),
+    expand_prql_to_sql(input.into())
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Compile a PRQL file, or a project of multiple files, to a SQL string
+/// literal.
+///
+/// The path is relative to the directory of the crate's `Cargo.toml`. A
+/// directory, or a file whose name starts with an uppercase letter such as
+/// `Project.prql`, is a project with the other `.prql` files of the directory
+/// as its modules.
+#[proc_macro]
+pub fn include_prql(input: TokenStream) -> TokenStream {
+    expand_include_prql(input.into())
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Declare a struct for the rows of the result of a PRQL query, with a field
+/// for each of its columns, as well as `SQL` and `COLUMNS` associated
+/// constants and a `From` implementation for a tuple of the fields.
+///
+/// The types of columns are inferred when they're known, such as for the
+/// columns of tables declared with `schema`; other columns must be declared as
+/// fields of the struct.
+#[proc_macro]
+pub fn prql(input: TokenStream) -> TokenStream {
+    expand_prql(input.into())
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+fn expand_prql_to_sql(input: proc_macro2::TokenStream) -> syn::Result<proc_macro2::TokenStream> {
+    let args: Args = syn::parse2(input)?;
+    let query = match (&args.literal, &args.path) {
+        (Some(literal), None) => Query::Inline(literal.clone()),
+        (_, Some(path)) => {
+            return Err(syn::Error::new(
+                path.span(),
+                "`prql_to_sql!` takes a query; use `include_prql!` for files",
+            ))
+        }
+        (None, None) => {
+            return Err(syn::Error::new(
+                proc_macro2::Span::call_site(),
+                "expected a PRQL string literal",
+            ))
+        }
     };
+    let (sql, files) = compile(&query, &args)?;
+    Ok(quote::quote! { { #files #sql } })
+}
 
-    let opts = prqlc::Options::default().no_format().no_signature();
+fn expand_include_prql(input: proc_macro2::TokenStream) -> syn::Result<proc_macro2::TokenStream> {
+    let args: Args = syn::parse2(input)?;
+    let Some(path) = args.literal.clone().or_else(|| args.path.clone()) else {
+        return Err(syn::Error::new(
+            proc_macro2::Span::call_site(),
+            "expected the path of a PRQL file",
+        ));
+    };
+    let (sql, files) = compile(&Query::File(path), &args)?;
+    Ok(quote::quote! { { #files #sql } })
+}
 
-    let sql_string = match prqlc::compile(&prql_string, &opts) {
-        Ok(r) => r,
-        Err(err) => {
-            panic!("{}", err);
+fn expand_prql(input: proc_macro2::TokenStream) -> syn::Result<proc_macro2::TokenStream> {
+    let PrqlInput { item, args } = syn::parse2(input)?;
+    let query = match (&args.literal, &args.path) {
+        (Some(literal), None) => Query::Inline(literal.clone()),
+        (None, Some(path)) => Query::File(path.clone()),
+        _ => {
+            return Err(syn::Error::new(
+                item.ident.span(),
+                "expected either a PRQL string literal or a `path`, after the struct",
+            ))
         }
     };
 
-    TokenStream::from_iter(vec![TokenTree::Literal(Literal::string(&sql_string))])
+    let sources = query.sources(args.schema.as_ref())?;
+    let result = prqlc::compile_full_tree(&sources.tree, &args.main_path(), &args.options())
+        .map_err(|e| errors::to_syn_error(e, &query, args.schema.as_ref(), &sources.tree))?;
+
+    let row = row::row_struct(item, &result.columns, &result.sql)?;
+    let files = include_files(&sources.files);
+    Ok(quote::quote! { #row #files })
+}
+
+/// The input of `prql!`: a struct, then the arguments of the other macros.
+struct PrqlInput {
+    item: DeriveInput,
+    args: Args,
+}
+
+impl Parse for PrqlInput {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        Ok(PrqlInput {
+            item: input.parse()?,
+            args: input.parse()?,
+        })
+    }
+}
+
+/// Compile a query, returning the SQL and the items which make the crate
+/// depend on the files of the query.
+fn compile(query: &Query, args: &Args) -> syn::Result<(String, proc_macro2::TokenStream)> {
+    let sources = query.sources(args.schema.as_ref())?;
+
+    let sql = prqlc::compile_tree(&sources.tree, &args.main_path(), &args.options())
+        .map_err(|e| errors::to_syn_error(e, query, args.schema.as_ref(), &sources.tree))?;
+    Ok((sql, include_files(&sources.files)))
+}
+
+/// Items which include the files of a query, so the crate is rebuilt when they
+/// change. New files of a project aren't noticed until then.
+fn include_files(files: &[std::path::PathBuf]) -> proc_macro2::TokenStream {
+    let files = files.iter().map(|f| f.to_string_lossy().into_owned());
+    quote::quote! {
+        #(const _: &str = include_str!(#files);)*
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use quote::quote;
+
+    use super::*;
+
+    #[test]
+    fn prql_to_sql_options() {
+        let tokens = expand_prql_to_sql(quote! {
+            "from albums | select {title} | take 3", target = "sql.postgres", format = true
+        })
+        .unwrap();
+        assert_eq!(
+            tokens.to_string(),
+            r#"{ "SELECT\n  title\nFROM\n  albums\nLIMIT\n  3\n" }"#
+        );
+    }
+
+    #[test]
+    fn prql_to_sql_errors() {
+        let err = expand_prql_to_sql(quote! {
+            "from albums | filter yaer > 2000",
+            schema = { albums { title: text, year: int } }
+        })
+        .unwrap_err();
+        let message = err.to_string();
+        assert!(message.starts_with("[E0005] Error:"), "{message}");
+        assert!(message.contains("Unknown name `yaer`"), "{message}");
+        assert!(message.contains("did you mean `year`?"), "{message}");
+
+        let err = expand_prql_to_sql(quote! { include = "albums.prql" }).unwrap_err();
+        assert!(err.to_string().starts_with("unknown option"), "{err}");
+    }
+
+    #[test]
+    fn prql_struct() {
+        let tokens = expand_prql(quote! {
+            #[derive(Debug)]
+            pub struct Album {
+                next_year: i64,
+            }
+            "from albums | derive {next_year = year + 1} | select {title, next_year, year}",
+            schema = { albums { album_id: int, title: text, year: int } },
+        })
+        .unwrap();
+
+        let expected = quote! {
+            #[derive(Debug)]
+            pub struct Album {
+                pub title: ::std::string::String,
+                next_year: i64,
+                pub year: i64,
+            }
+
+            impl Album {
+                /// SQL of the query.
+                pub const SQL: &'static str =
+                    "SELECT title, year + 1 AS next_year, year FROM albums";
+
+                /// Names of the columns of the result, in the order of the fields.
+                pub const COLUMNS: &'static [&'static str] = &["title", "next_year", "year"];
+            }
+
+            impl ::core::convert::From<(::std::string::String, i64, i64,)> for Album {
+                fn from(row: (::std::string::String, i64, i64,)) -> Self {
+                    let (title, next_year, year,) = row;
+                    Self { title, next_year, year }
+                }
+            }
+        };
+        assert_eq!(tokens.to_string(), expected.to_string());
+    }
+
+    #[test]
+    fn prql_struct_errors() {
+        let error = |input| expand_prql(input).unwrap_err().to_string();
+
+        // a computed column, whose type must be declared
+        assert_eq!(
+            error(quote! {
+                struct Album;
+                "from albums | derive {decade = year // 10 * 10} | select {title, decade}",
+                schema = { albums { title: text, year: int } },
+            }),
+            "the type of column `decade` isn't known; declare it as a field, as in \
+             `struct Album { decade: i64 }`, or declare the column of its table with `schema`"
+        );
+
+        // the columns of a table without a schema
+        assert_eq!(
+            error(quote! { struct Album; "from albums" }),
+            "the columns of a table of the query aren't known; declare them with `schema`"
+        );
+
+        assert_eq!(
+            error(quote! {
+                struct Album { artist: String }
+                "from albums | select {title}",
+                schema = { albums { title: text } },
+            }),
+            "not a column of the result; its columns are `title`"
+        );
+
+        assert!(
+            error(quote! { struct Album; "from albums | select {titel}" })
+                .contains("the type of column `titel` isn't known")
+        );
+    }
 }
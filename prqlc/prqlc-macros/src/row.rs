@@ -0,0 +1,153 @@
+//! The struct of a row of the result of a query, generated by `prql!`.
+use proc_macro2::TokenStream;
+use prqlc::OutputColumn;
+use quote::quote;
+use syn::{Data, DeriveInput, Fields, Ident};
+
+/// The Rust type of a column of a PRQL type, for the types which can be
+/// declared with `schema`.
+fn rust_type(ty: &str) -> Option<TokenStream> {
+    Some(match ty {
+        "int" => quote!(i64),
+        "float" => quote!(f64),
+        "bool" => quote!(bool),
+        "text" | "date" | "time" | "timestamp" => quote!(::std::string::String),
+        _ => return None,
+    })
+}
+
+/// The struct of `item`, with a field for each column of the result, along
+/// with the SQL and the names of the columns as associated constants.
+///
+/// Fields declared in `item` are kept as they are; the types of other fields
+/// are the types of the columns, when they are known, such as for the columns
+/// of a table declared with `schema`.
+pub(crate) fn row_struct(
+    item: DeriveInput,
+    columns: &[OutputColumn],
+    sql: &str,
+) -> syn::Result<TokenStream> {
+    let DeriveInput {
+        attrs,
+        vis,
+        ident,
+        generics,
+        data,
+    } = item;
+    if !generics.params.is_empty() {
+        return Err(syn::Error::new_spanned(
+            generics,
+            "the struct of a query can't be generic",
+        ));
+    }
+    let mut declared = match data {
+        Data::Struct(data) => match data.fields {
+            Fields::Named(fields) => fields.named.into_iter().collect(),
+            Fields::Unit => Vec::new(),
+            fields @ Fields::Unnamed(_) => {
+                return Err(syn::Error::new_spanned(
+                    fields,
+                    "the fields of the struct of a query must be named",
+                ))
+            }
+        },
+        _ => return Err(syn::Error::new(ident.span(), "expected a struct")),
+    };
+
+    let mut names = Vec::new();
+    let mut fields = Vec::new();
+    for (index, column) in columns.iter().enumerate() {
+        let (name, ty) =
+            match column {
+                OutputColumn::Single { name, ty } => (name, ty),
+                OutputColumn::Wildcard => return Err(syn::Error::new(
+                    ident.span(),
+                    "the columns of a table of the query aren't known; declare them with `schema`",
+                )),
+            };
+        let Some(name) = name else {
+            return Err(syn::Error::new(
+                ident.span(),
+                format!(
+                    "column {} of the result has no name; name it, as in `select {{x = ...}}`",
+                    index + 1
+                ),
+            ));
+        };
+        if names.contains(name) {
+            return Err(syn::Error::new(
+                ident.span(),
+                format!("the result has more than one column named `{name}`; rename them"),
+            ));
+        }
+        names.push(name.clone());
+
+        let field_ident = syn::parse_str::<Ident>(name)
+            .or_else(|_| syn::parse_str::<Ident>(&format!("r#{name}")))
+            .map_err(|_| {
+                syn::Error::new(
+                    ident.span(),
+                    format!("column `{name}` isn't a valid name of a field; rename it"),
+                )
+            })?;
+
+        let position = declared.iter().position(|field| {
+            field
+                .ident
+                .as_ref()
+                .is_some_and(|f| f.to_string().trim_start_matches("r#") == name)
+        });
+        let field = if let Some(position) = position {
+            declared.remove(position)
+        } else {
+            let ty = (ty.as_deref()).and_then(rust_type).ok_or_else(|| {
+                syn::Error::new(
+                    ident.span(),
+                    format!(
+                        "the type of column `{name}` isn't known; declare it as a field, as \
+                             in `struct {ident} {{ {field_ident}: i64 }}`, or declare the \
+                             column of its table with `schema`"
+                    ),
+                )
+            })?;
+            syn::parse_quote!(#vis #field_ident: #ty)
+        };
+        fields.push(field);
+    }
+
+    if let Some(field) = declared.first() {
+        let names = names.iter().map(|n| format!("`{n}`")).collect::<Vec<_>>();
+        return Err(syn::Error::new_spanned(
+            field,
+            format!(
+                "not a column of the result; its columns are {}",
+                names.join(", ")
+            ),
+        ));
+    }
+
+    let field_idents: Vec<_> = fields.iter().map(|f| f.ident.clone().unwrap()).collect();
+    let types: Vec<_> = fields.iter().map(|f| f.ty.clone()).collect();
+
+    Ok(quote! {
+        #(#attrs)*
+        #vis struct #ident {
+            #(#fields,)*
+        }
+
+        impl #ident {
+            /// SQL of the query.
+            #vis const SQL: &'static str = #sql;
+
+            /// Names of the columns of the result, in the order of the fields.
+            #vis const COLUMNS: &'static [&'static str] = &[#(#names),*];
+        }
+
+        impl ::core::convert::From<(#(#types,)*)> for #ident {
+            fn from(row: (#(#types,)*)) -> Self {
+                let (#(#field_idents,)*) = row;
+                Self { #(#field_idents),* }
+            }
+        }
+    })
+}
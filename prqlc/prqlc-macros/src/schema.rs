@@ -0,0 +1,131 @@
+//! Declarations of the tables which queries read from.
+use proc_macro2::Span;
+use syn::ext::IdentExt;
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
+use syn::{braced, Ident, Token};
+
+/// Tables with the types of their columns, declared as
+/// `schema = { albums { title: text, year: int }, my_schema.tracks { ... } }`.
+pub(crate) struct Schema {
+    pub tables: Vec<Table>,
+    pub span: Span,
+}
+
+pub(crate) struct Table {
+    /// Name of the table, with the names of its database schemas.
+    pub path: Vec<Ident>,
+    pub columns: Vec<(Ident, Ident)>,
+}
+
+/// Names of the types of columns, which are the primitive types of PRQL.
+const TYPES: [&str; 7] = ["int", "float", "bool", "text", "date", "time", "timestamp"];
+
+impl Schema {
+    /// Declarations of the tables in PRQL, as the contents of the
+    /// `default_db` module.
+    pub fn to_prql(&self) -> String {
+        let mut prql = String::new();
+        for table in &self.tables {
+            let (name, modules) = table.path.split_last().unwrap();
+            for module in modules {
+                prql += &format!("module {module} {{\n");
+            }
+
+            let columns: Vec<_> = table
+                .columns
+                .iter()
+                .map(|(name, ty)| format!("{} = {ty}", ident_to_prql(name)))
+                .collect();
+            prql += &format!("let {name} <[{{{}}}]>\n", columns.join(", "));
+
+            prql += &"}\n".repeat(modules.len());
+        }
+        prql
+    }
+}
+
+/// Name of a column in PRQL, without the `r#` prefix of a raw identifier.
+fn ident_to_prql(ident: &Ident) -> String {
+    let name = ident.to_string();
+    name.strip_prefix("r#").map(str::to_string).unwrap_or(name)
+}
+
+impl Parse for Schema {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let span = input.span();
+        let tables = Punctuated::<Table, Token![,]>::parse_terminated(input)?;
+        Ok(Schema {
+            tables: tables.into_iter().collect(),
+            span,
+        })
+    }
+}
+
+impl Parse for Table {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let path = Punctuated::<Ident, Token![.]>::parse_separated_nonempty(input)?;
+
+        let content;
+        braced!(content in input);
+        let columns = Punctuated::<Column, Token![,]>::parse_terminated(&content)?;
+
+        Ok(Table {
+            path: path.into_iter().collect(),
+            columns: columns.into_iter().map(|c| (c.name, c.ty)).collect(),
+        })
+    }
+}
+
+struct Column {
+    name: Ident,
+    ty: Ident,
+}
+
+impl Parse for Column {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let name = input.call(Ident::parse_any)?;
+        input.parse::<Token![:]>()?;
+        let ty: Ident = input.parse()?;
+
+        if !TYPES.contains(&ty.to_string().as_str()) {
+            return Err(syn::Error::new(
+                ty.span(),
+                format!("unknown type; expected one of {}", TYPES.join(", ")),
+            ));
+        }
+        Ok(Column { name, ty })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use quote::quote;
+
+    use super::*;
+
+    #[test]
+    fn schema_to_prql() {
+        let schema: Schema = syn::parse2(quote! {
+            albums { album_id: int, title: text, type: text },
+            music.tracks { name: text, milliseconds: int },
+        })
+        .unwrap();
+
+        assert_eq!(
+            schema.to_prql(),
+            "let albums <[{album_id = int, title = text, type = text}]>\n\
+             module music {\n\
+             let tracks <[{name = text, milliseconds = int}]>\n\
+             }\n"
+        );
+    }
+
+    #[test]
+    fn schema_unknown_type() {
+        let err = syn::parse2::<Schema>(quote! { albums { title: string } })
+            .err()
+            .unwrap();
+        assert!(err.to_string().starts_with("unknown type"), "{err}");
+    }
+}
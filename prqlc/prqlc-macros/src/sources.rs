@@ -0,0 +1,116 @@
+//! Sources of the queries of the macros.
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use prqlc::SourceTree;
+use syn::LitStr;
+
+use crate::schema::Schema;
+
+/// The PRQL of a macro: either a string literal, or a path to a file or a
+/// directory, relative to the directory of the crate's `Cargo.toml`.
+pub(crate) enum Query {
+    Inline(LitStr),
+    File(LitStr),
+}
+
+/// Sources of a query, along with the files they were read from, which the
+/// crate must be rebuilt for when they change.
+pub(crate) struct Sources {
+    pub tree: SourceTree,
+    pub files: Vec<PathBuf>,
+}
+
+/// Path of the file of declarations of the schema in a source tree, which is
+/// the `default_db` module.
+pub(crate) const SCHEMA_PATH: &str = "default_db.prql";
+
+impl Query {
+    pub fn literal(&self) -> &LitStr {
+        match self {
+            Query::Inline(lit) | Query::File(lit) => lit,
+        }
+    }
+
+    /// Read the sources of the query.
+    ///
+    /// A directory is a project of multiple files, as with `prqlc compile`. So
+    /// is a file whose name starts with an uppercase letter, such as
+    /// `Project.prql`, which is the root module of the other `.prql` files of
+    /// its directory. Any other file is compiled on its own.
+    pub fn sources(&self, schema: Option<&Schema>) -> syn::Result<Sources> {
+        let (mut tree, files) = match self {
+            Query::Inline(lit) => (
+                SourceTree::new([("".into(), lit.value())], None),
+                Vec::new(),
+            ),
+            Query::File(lit) => read_path(lit)?,
+        };
+
+        if let Some(schema) = schema {
+            if tree.sources.contains_key(Path::new(SCHEMA_PATH)) {
+                return Err(syn::Error::new(
+                    schema.span,
+                    format!("`{SCHEMA_PATH}` of the project already declares the schema"),
+                ));
+            }
+            tree.insert(SCHEMA_PATH.into(), schema.to_prql());
+        }
+        Ok(Sources { tree, files })
+    }
+}
+
+fn read_path(lit: &LitStr) -> syn::Result<(SourceTree, Vec<PathBuf>)> {
+    let error = |e: std::io::Error| syn::Error::new(lit.span(), format!("{e}"));
+
+    let manifest_dir = std::env::var("CARGO_MANIFEST_DIR").map_err(|_| {
+        syn::Error::new(
+            lit.span(),
+            "`CARGO_MANIFEST_DIR` isn't set; build with cargo",
+        )
+    })?;
+    let path = Path::new(&manifest_dir).join(lit.value());
+
+    let root = if path.is_dir() {
+        path.as_path()
+    } else if starts_with_uppercase(&path) {
+        path.parent().unwrap()
+    } else {
+        // like an inline query, so it's the root module alongside the schema
+        let content = fs::read_to_string(&path).map_err(error)?;
+        return Ok((SourceTree::new([("".into(), content)], None), vec![path]));
+    };
+
+    let mut files = Vec::new();
+    find_prql_files(root, &mut files).map_err(error)?;
+    files.sort();
+
+    let mut sources = Vec::new();
+    for file in &files {
+        let content = fs::read_to_string(file).map_err(error)?;
+        sources.push((file.strip_prefix(root).unwrap().to_path_buf(), content));
+    }
+    if sources.is_empty() {
+        return Err(syn::Error::new(lit.span(), "no `.prql` files found"));
+    }
+    Ok((SourceTree::new(sources, Some(root.to_path_buf())), files))
+}
+
+fn find_prql_files(dir: &Path, files: &mut Vec<PathBuf>) -> std::io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            find_prql_files(&path, files)?;
+        } else if path.extension().is_some_and(|x| x == "prql") {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+fn starts_with_uppercase(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|x| x.to_str())
+        .and_then(|x| x.chars().next())
+        .is_some_and(char::is_uppercase)
+}
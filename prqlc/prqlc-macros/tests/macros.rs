@@ -0,0 +1,83 @@
+use prqlc_macros::{include_prql, prql, prql_to_sql};
+
+#[test]
+fn test_prql_to_sql() {
+    let sql: &str = prql_to_sql!("from albums | select {title, artist_id}");
+    assert_eq!(sql, "SELECT title, artist_id FROM albums");
+
+    let sql = prql_to_sql!(
+        "from albums | filter year > 2000 | take 3",
+        target = "sql.mssql",
+        schema = {
+            albums {
+                title: text,
+                year: int,
+            }
+        },
+    );
+    assert_eq!(
+        sql,
+        "SELECT title, year FROM albums WHERE year > 2000 \
+         ORDER BY (SELECT NULL) OFFSET 0 ROWS FETCH FIRST 3 ROWS ONLY"
+    );
+}
+
+#[test]
+fn test_include_prql() {
+    let sql = include_prql!("tests/queries/recent_albums.prql", format = true);
+    assert_eq!(
+        sql,
+        "SELECT\n  title,\n  year\nFROM\n  albums\nWHERE\n  year > 2000\n"
+    );
+
+    let sql = include_prql!("tests/queries/project/Project.prql");
+    assert_eq!(
+        sql,
+        "WITH top_albums AS (SELECT * FROM albums ORDER BY year DESC LIMIT 10) \
+         SELECT title FROM top_albums ORDER BY year DESC"
+    );
+
+    let sql = include_prql!("tests/queries/project", main = "reports.by_year");
+    assert_eq!(
+        sql,
+        "SELECT year, COUNT(*) AS albums FROM albums GROUP BY year"
+    );
+}
+
+prql! {
+    #[derive(Debug, PartialEq)]
+    struct RecentAlbum;
+    path = "tests/queries/recent_albums.prql",
+    schema = { albums { album_id: int, title: text, year: int } },
+}
+
+prql! {
+    #[derive(Debug, PartialEq)]
+    struct AlbumsByYear {
+        n_albums: i64,
+    }
+    "from albums | group year (aggregate {n_albums = count this}) | sort year",
+    target = "sql.sqlite",
+    schema = { albums { album_id: int, title: text, year: int } },
+}
+
+#[test]
+fn test_prql_struct() {
+    assert_eq!(
+        RecentAlbum::SQL,
+        "SELECT title, year FROM albums WHERE year > 2000"
+    );
+    assert_eq!(RecentAlbum::COLUMNS, ["title", "year"]);
+    assert_eq!(
+        RecentAlbum::from(("Currents".to_string(), 2015)),
+        RecentAlbum {
+            title: "Currents".to_string(),
+            year: 2015,
+        }
+    );
+
+    assert_eq!(AlbumsByYear::COLUMNS, ["year", "n_albums"]);
+    let row: AlbumsByYear = (1970, 12).into();
+    assert_eq!(row.year, 1970);
+    assert_eq!(row.n_albums, 12);
+}
@@ -14,9 +14,19 @@ pub struct Error {
     pub hints: Vec<String>,
     /// Machine readable identifier error code eg, "E0001"
     pub code: Option<&'static str>,
+    /// Edits which would fix the error, the most likely first
+    pub fixes: Vec<Fix>,
     // pub source: ErrorSource
 }
 
+/// A machine-applicable edit of the source: replaces the code at `span` with
+/// `replacement`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Fix {
+    pub span: Span,
+    pub replacement: String,
+}
+
 #[derive(Clone, Debug, Default)]
 pub enum ErrorSource {
     Lexer(String),
@@ -78,6 +88,7 @@ impl Error {
             reason,
             hints: Vec::new(),
             code: None,
+            fixes: Vec::new(),
             // source: ErrorSource::default()
         }
     }
@@ -91,6 +102,7 @@ impl Error {
             issue: Some(issue_no),
             details: None,
         })
+        .with_code("E0022")
     }
 
     /// Used for things that you *think* should never happen, but are not sure.
@@ -99,6 +111,7 @@ impl Error {
             issue: None,
             details: Some(details.to_string()),
         })
+        .with_code("E0022")
     }
 }
 
@@ -175,6 +188,8 @@ pub trait WithErrorInfo: Sized {
 
     fn with_code(self, code: &'static str) -> Self;
 
+    fn push_fix(self, fix: Fix) -> Self;
+
     fn with_source(self, source: ErrorSource) -> Self;
 }
 
@@ -204,6 +219,11 @@ impl WithErrorInfo for Error {
         self
     }
 
+    fn push_fix(mut self, fix: Fix) -> Self {
+        self.fixes.push(fix);
+        self
+    }
+
     fn with_source(self, _source: ErrorSource) -> Self {
         // self.source = source;
         self
@@ -231,6 +251,10 @@ impl<T, E: WithErrorInfo> WithErrorInfo for Result<T, E> {
         self.map_err(|e| e.with_code(code))
     }
 
+    fn push_fix(self, fix: Fix) -> Self {
+        self.map_err(|e| e.push_fix(fix))
+    }
+
     fn with_source(self, source: ErrorSource) -> Self {
         self.map_err(|e| e.with_source(source))
     }
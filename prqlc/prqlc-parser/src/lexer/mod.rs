@@ -19,23 +19,9 @@ type ParserError<'a> = extra::Err<Simple<'a, char>>;
 
 /// Convert a chumsky Simple error to our internal Error type
 fn convert_lexer_error(source: &str, error: &Simple<'_, char>, source_id: u16) -> E {
-    // Get span information from the Simple error
-    // NOTE: When parsing &str, SimpleSpan uses BYTE offsets, not character offsets!
-    // We need to convert byte offsets to character offsets for compatibility with our error reporting.
-    let byte_span = error.span();
-    let byte_start = byte_span.start();
-    let byte_end = byte_span.end();
-
-    // Convert byte offsets to character offsets
-    let char_start = source[..byte_start].chars().count();
-    let char_end = source[..byte_end].chars().count();
-
-    // Extract the "found" text using character-based slicing
-    let found: String = source
-        .chars()
-        .skip(char_start)
-        .take(char_end - char_start)
-        .collect();
+    // Spans are byte offsets, like the ones of chumsky when parsing &str.
+    let span = error.span();
+    let found = &source[span.start()..span.end()];
 
     // If found is empty, report as "end of input", otherwise wrap in quotes
     let found_display = if found.is_empty() {
@@ -47,7 +33,9 @@ fn convert_lexer_error(source: &str, error: &Simple<'_, char>, source_id: u16) -
     // Create a new Error with the extracted information
     let error_source = format!(
         "Unexpected {} at position {}..{}",
-        found_display, char_start, char_end
+        found_display,
+        span.start(),
+        span.end()
     );
 
     WithErrorInfo::with_span(
@@ -55,12 +43,13 @@ fn convert_lexer_error(source: &str, error: &Simple<'_, char>, source_id: u16) -
             found: found_display,
         }),
         Some(crate::span::Span {
-            start: char_start,
-            end: char_end,
+            start: span.start(),
+            end: span.end(),
             source_id,
         }),
     )
     .with_source(ErrorSource::Lexer(error_source))
+    .with_code("E0003")
 }
 
 /// Lex PRQL into LR, returning both the LR and any errors encountered
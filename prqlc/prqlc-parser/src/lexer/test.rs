@@ -392,13 +392,16 @@ fn test_single_curly_quote() {
             Error {
                 kind: Error,
                 span: Some(
-                    0:0-1,
+                    0:0-3,
                 ),
                 reason: Unexpected {
                     found: "'’'",
                 },
                 hints: [],
-                code: None,
+                code: Some(
+                    "E0003",
+                ),
+                fixes: [],
             },
         ],
     )
@@ -442,13 +445,16 @@ fn test_mississippi_curly_quotes() {
             Error {
                 kind: Error,
                 span: Some(
-                    0:22-23,
+                    0:22-25,
                 ),
                 reason: Unexpected {
                     found: "'’'",
                 },
                 hints: [],
-                code: None,
+                code: Some(
+                    "E0003",
+                ),
+                fixes: [],
             },
         ],
     )
@@ -490,7 +496,10 @@ fn test_interpolation_empty() {
                     found: "end of input",
                 },
                 hints: [],
-                code: None,
+                code: Some(
+                    "E0003",
+                ),
+                fixes: [],
             },
         ],
     )
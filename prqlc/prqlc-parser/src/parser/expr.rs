@@ -11,13 +11,19 @@ use crate::lexer::lr::TokenKind;
 use crate::parser::interpolation;
 use crate::parser::pr::*;
 use crate::parser::types::type_expr;
-use crate::parser::{ctrl, ident_part, keyword, new_line, sequence, with_doc_comment};
+use crate::parser::{
+    closing_bracket, ctrl, ident_part, is_stmt_start, keyword, new_line, sequence, skip_until,
+    with_doc_comment,
+};
 use crate::span::Span;
 
 use super::pipe;
 use super::ParserError;
 
-pub(crate) fn expr_call<'a, I>() -> impl Parser<'a, I, Expr, ParserError<'a>> + Clone
+/// An expression, a function call, a function definition or a pipeline. With
+/// `recover`, steps of the pipeline which fail to parse are replaced with
+/// error nodes.
+pub(crate) fn expr_call<'a, I>(recover: bool) -> impl Parser<'a, I, Expr, ParserError<'a>> + Clone
 where
     I: Input<'a, Token = lr::Token, Span = Span> + BorrowInput<'a>,
 {
@@ -26,7 +32,7 @@ where
     choice((
         lambda_func(expr.clone()).boxed(),
         func_call(expr.clone()).boxed(),
-        pipeline(expr).boxed(),
+        pipeline(expr, recover).boxed(),
     ))
     .boxed()
 }
@@ -56,7 +62,7 @@ where
         let pipeline_expr = {
             use chumsky::recovery::{skip_then_retry_until, via_parser};
 
-            pipeline(nested_expr.clone())
+            pipeline(nested_expr.clone(), false)
                 .padded_by(new_line().repeated())
                 .delimited_by(
                     ctrl('('),
@@ -337,19 +343,46 @@ where
 }
 
 /// A pipeline of `expr`, separated by pipes. Doesn't require parentheses.
-pub(crate) fn pipeline<'a, I, E>(expr: E) -> impl Parser<'a, I, Expr, ParserError<'a>> + Clone + 'a
+///
+/// With `recover`, a step which fails to parse is skipped up to the next pipe
+/// and replaced with an error node, so the following steps are still parsed.
+pub(crate) fn pipeline<'a, I, E>(
+    expr: E,
+    recover: bool,
+) -> impl Parser<'a, I, Expr, ParserError<'a>> + Clone + 'a
 where
     I: Input<'a, Token = lr::Token, Span = Span> + BorrowInput<'a>,
     E: Parser<'a, I, Expr, ParserError<'a>> + Clone + 'a,
 {
+    use chumsky::recovery::via_parser;
+
     // expr has to be a param, because it can be either a normal expr() or a
     // recursive expr called from within expr(), which causes a stack overflow
 
     // TODO: do we need the `maybe_aliased` here rather than in `expr`? We had
     // tried `with_doc_comment(expr)` in #4775 (and push an aliased expr into
     // `expr`) but couldn't get it work.
-    with_doc_comment(maybe_aliased(expr))
-        .separated_by(pipe())
+    let step = with_doc_comment(maybe_aliased(expr));
+    let step = if recover {
+        // New lines are also pipes, so we don't skip over the start of the
+        // next statement.
+        let step_end = choice((new_line(), ctrl('|'), ctrl(','), end()));
+        step.then_ignore(step_end.clone().or(closing_bracket()).rewind())
+            .recover_with(via_parser(
+                any_ref()
+                    .filter(|t: &&lr::Token| {
+                        !is_stmt_start(&t.kind) && t.kind != TokenKind::Keyword("into".into())
+                    })
+                    .rewind()
+                    .ignore_then(skip_until(step_end))
+                    .map_with(|_, extra| ExprKind::Error.into_expr(extra.span())),
+            ))
+            .boxed()
+    } else {
+        step.boxed()
+    };
+
+    step.separated_by(pipe())
         .at_least(1)
         .collect::<Vec<_>>()
         .map_with(|exprs: Vec<Expr>, extra| {
@@ -624,7 +657,7 @@ mod tests {
             new_line()
                 .repeated()
                 .collect::<Vec<_>>()
-                .ignore_then(expr_call())
+                .ignore_then(expr_call(false))
                 .then_ignore(new_line().repeated())
                 .then_ignore(end())
         )
@@ -659,7 +692,7 @@ mod tests {
             new_line()
                 .repeated()
                 .collect::<Vec<_>>()
-                .ignore_then(pipeline(expr_call()))
+                .ignore_then(pipeline(expr_call(false), false))
                 .then_ignore(new_line().repeated())
                 .then_ignore(end())
         )
@@ -684,7 +717,7 @@ mod tests {
             new_line()
                 .repeated()
                 .collect::<Vec<_>>()
-                .ignore_then(expr_call())
+                .ignore_then(expr_call(false))
                 .then_ignore(end())
         )
     }
@@ -768,7 +801,10 @@ mod tests {
                     found: "b",
                 },
                 hints: [],
-                code: None,
+                code: Some(
+                    "E0004",
+                ),
+                fixes: [],
             },
         ]
         "#);
@@ -924,7 +960,10 @@ mod tests {
                     found: "z",
                 },
                 hints: [],
-                code: None,
+                code: Some(
+                    "E0004",
+                ),
+                fixes: [],
             },
         ]
         "#);
@@ -63,7 +63,8 @@ pub(crate) fn parse(string: String, span_base: Span) -> Result<Vec<InterpolateIt
                     }
                 };
 
-                WithErrorInfo::with_span(Error::new_simple(message), Some(span))
+                let error = WithErrorInfo::with_span(Error::new_simple(message), Some(span));
+                WithErrorInfo::with_code(error, "E0004")
             })
             .collect());
     }
@@ -25,6 +25,16 @@ mod types;
 // because it logs using the logging framework in `prqlc`.
 
 pub fn parse_lr_to_pr(source_id: u16, lr: Vec<lr::Token>) -> (Option<Vec<pr::Stmt>>, Vec<Error>) {
+    parse_lr(source_id, lr, false)
+}
+
+/// Parses tokens into statements. With `recover`, statements and pipeline
+/// steps which fail to parse are skipped and replaced with error nodes.
+fn parse_lr(
+    source_id: u16,
+    lr: Vec<lr::Token>,
+    recover: bool,
+) -> (Option<Vec<pr::Stmt>>, Vec<Error>) {
     // Filter out comments - we don't want them in the AST
     let semantic_tokens: Vec<_> = lr
         .into_iter()
@@ -60,7 +70,7 @@ pub fn parse_lr_to_pr(source_id: u16, lr: Vec<lr::Token>) -> (Option<Vec<pr::Stm
             }
         });
 
-    let parse_result = stmt::source().parse(input);
+    let parse_result = stmt::source(recover).parse(input);
     let (pr, parse_errors) = parse_result.into_output_errors();
 
     let errors = parse_errors.into_iter().map(|e| e.into()).collect();
@@ -69,6 +79,24 @@ pub fn parse_lr_to_pr(source_id: u16, lr: Vec<lr::Token>) -> (Option<Vec<pr::Stm
     (pr, errors)
 }
 
+/// Lex and parse PRQL, recovering from errors at statement and pipeline-step
+/// boundaries.
+///
+/// Returns a partial AST, with [pr::StmtKind::Error] and [pr::ExprKind::Error]
+/// in place of the parts that failed to parse, along with all diagnostics. The
+/// AST is `None` only if lexing fails.
+pub fn parse_with_recovery(source: &str, source_id: u16) -> (Option<Vec<pr::Stmt>>, Vec<Error>) {
+    let (tokens, mut errors) = crate::lexer::lex_source_recovery(source, source_id);
+
+    let Some(tokens) = tokens else {
+        return (None, errors);
+    };
+    let (stmts, parse_errors) = parse_lr(source_id, tokens, true);
+    errors.extend(parse_errors);
+
+    (stmts, errors)
+}
+
 fn ident_part<'a, I>() -> impl Parser<'a, I, String, ParserError<'a>> + Clone
 where
     I: Input<'a, Token = lr::Token, Span = Span> + BorrowInput<'a>,
@@ -186,6 +214,61 @@ where
         .padded_by(new_line().repeated())
 }
 
+fn closing_bracket<'a, I>() -> impl Parser<'a, I, (), ParserError<'a>> + Clone
+where
+    I: Input<'a, Token = lr::Token, Span = Span> + BorrowInput<'a>,
+{
+    choice((ctrl(')'), ctrl('}'), ctrl(']')))
+}
+
+/// Tokens which can only start a statement.
+fn stmt_start<'a, I>() -> impl Parser<'a, I, (), ParserError<'a>> + Clone
+where
+    I: Input<'a, Token = lr::Token, Span = Span> + BorrowInput<'a>,
+{
+    any_ref()
+        .filter(|t: &&lr::Token| is_stmt_start(&t.kind))
+        .ignored()
+}
+
+fn is_stmt_start(kind: &TokenKind) -> bool {
+    match kind {
+        TokenKind::Keyword(k) => ["let", "module", "type", "import"].contains(&k.as_str()),
+        TokenKind::Annotate | TokenKind::DocComment(_) => true,
+        _ => false,
+    }
+}
+
+/// Skips tokens up to (but excluding) the point where `terminator` matches,
+/// keeping brackets balanced. Consumes at least one token. Used to recover from
+/// errors, so that parsing can continue after the skipped tokens.
+fn skip_until<'a, I, T>(terminator: T) -> impl Parser<'a, I, (), ParserError<'a>> + Clone + 'a
+where
+    I: Input<'a, Token = lr::Token, Span = Span> + BorrowInput<'a>,
+    T: Parser<'a, I, (), ParserError<'a>> + Clone + 'a,
+{
+    let balanced = recursive(|balanced| {
+        let group = |open, close| {
+            balanced
+                .clone()
+                .repeated()
+                .delimited_by(ctrl(open), ctrl(close))
+        };
+        choice((
+            group('(', ')'),
+            group('{', '}'),
+            group('[', ']'),
+            any_ref().and_is(closing_bracket().not()).ignored(),
+        ))
+    });
+
+    balanced
+        .and_is(terminator.not())
+        .repeated()
+        .at_least(1)
+        .boxed()
+}
+
 fn pipe<'a, I>() -> impl Parser<'a, I, (), ParserError<'a>> + Clone
 where
     I: Input<'a, Token = lr::Token, Span = Span> + BorrowInput<'a>,
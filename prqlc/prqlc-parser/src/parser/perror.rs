@@ -79,7 +79,7 @@ where
         RichReason::Custom(msg) => Error::new_simple(msg.to_string()),
     };
 
-    error.with_span(Some(span))
+    error.with_span(Some(span)).with_code("E0004")
 }
 
 impl<'a> From<Rich<'a, crate::lexer::lr::Token, Span>> for Error {
@@ -118,7 +118,7 @@ mod tests {
     #[test]
     fn test_error_messages() {
         let error1 = simple_error("test error");
-        assert_snapshot!(error1.to_string(), @r#"Error { kind: Error, span: None, reason: Simple("test error"), hints: [], code: None }"#);
+        assert_snapshot!(error1.to_string(), @r#"Error { kind: Error, span: None, reason: Simple("test error"), hints: [], code: None, fixes: [] }"#);
 
         let error2 = simple_error("another error").with_span(Some(crate::span::Span {
             start: 0,
@@ -136,6 +136,7 @@ mod tests {
             ),
             hints: [],
             code: None,
+            fixes: [],
         }
         "#);
     }
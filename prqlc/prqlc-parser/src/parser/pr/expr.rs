@@ -80,6 +80,10 @@ pub enum ExprKind {
     /// When used instead of function body, the function will be translated to a RQ operator.
     /// Contains ident of the RQ operator.
     Internal(String),
+
+    /// An expression that failed to parse. Only produced by
+    /// [crate::parser::parse_with_recovery].
+    Error,
 }
 
 impl ExprKind {
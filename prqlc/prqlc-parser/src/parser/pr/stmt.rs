@@ -55,6 +55,10 @@ pub enum StmtKind {
     TypeDef(TypeDef),
     ModuleDef(ModuleDef),
     ImportDef(ImportDef),
+
+    /// A statement that failed to parse. Only produced by
+    /// [crate::parser::parse_with_recovery].
+    Error,
 }
 
 #[derive(Debug, PartialEq, Clone, Serialize, Deserialize, JsonSchema)]
@@ -7,7 +7,9 @@ use itertools::Itertools;
 use semver::VersionReq;
 
 use super::expr::{expr, expr_call, ident, pipeline};
-use super::{ctrl, ident_part, into_stmt, keyword, new_line, pipe, with_doc_comment};
+use super::{
+    ctrl, ident_part, into_stmt, keyword, new_line, pipe, skip_until, stmt_start, with_doc_comment,
+};
 use crate::lexer::lr;
 use crate::lexer::lr::{Literal, TokenKind};
 use crate::parser::pr::*;
@@ -16,15 +18,16 @@ use crate::span::Span;
 
 use super::ParserError;
 
-/// The top-level parser for a PRQL file
-pub fn source<'a, I>() -> impl Parser<'a, I, Vec<Stmt>, ParserError<'a>> + Clone
+/// The top-level parser for a PRQL file. With `recover`, statements and
+/// pipeline steps which fail to parse are replaced with error nodes.
+pub fn source<'a, I>(recover: bool) -> impl Parser<'a, I, Vec<Stmt>, ParserError<'a>> + Clone
 where
     I: Input<'a, Token = lr::Token, Span = Span> + BorrowInput<'a> + chumsky::input::ValueInput<'a>,
 {
     with_doc_comment(query_def())
         .or_not()
         .map(|opt| opt.into_iter().collect::<Vec<_>>())
-        .then(module_contents())
+        .then(module_contents(recover))
         .map(|(mut first, mut second)| {
             first.append(&mut second);
             first
@@ -36,7 +39,7 @@ where
         .boxed()
 }
 
-fn module_contents<'a, I>() -> impl Parser<'a, I, Vec<Stmt>, ParserError<'a>> + Clone
+fn module_contents<'a, I>(recover: bool) -> impl Parser<'a, I, Vec<Stmt>, ParserError<'a>> + Clone
 where
     I: Input<'a, Token = lr::Token, Span = Span> + BorrowInput<'a> + chumsky::input::ValueInput<'a>,
 {
@@ -74,11 +77,16 @@ where
         let stmt_kind = new_line()
             .repeated()
             .collect::<Vec<_>>()
-            .ignore_then(choice((module_def, type_def(), import_def(), var_def())));
+            .ignore_then(choice((
+                module_def,
+                type_def(),
+                import_def(),
+                var_def(recover),
+            )));
 
         // Currently doc comments need to be before the annotation; probably
         // should relax this?
-        with_doc_comment(
+        let stmt = with_doc_comment(
             annotation
                 .repeated()
                 .collect::<Vec<_>>()
@@ -86,13 +94,37 @@ where
                 .map_with(|(annotations, kind), extra| {
                     into_stmt((annotations, kind), extra.span())
                 }),
-        )
-        .repeated()
-        .collect()
+        );
+        let stmt = if recover {
+            stmt.recover_with(chumsky::recovery::via_parser(stmt_recovery()))
+                .boxed()
+        } else {
+            stmt.boxed()
+        };
+
+        stmt.repeated().collect()
     })
     .boxed()
 }
 
+/// Skips a statement that failed to parse, up to the start of the next
+/// statement, which is a blank line, a keyword starting a statement, an
+/// annotation or a doc comment.
+fn stmt_recovery<'a, I>() -> impl Parser<'a, I, Stmt, ParserError<'a>> + Clone + 'a
+where
+    I: Input<'a, Token = lr::Token, Span = Span> + BorrowInput<'a> + chumsky::input::ValueInput<'a>,
+{
+    let stmt_end = new_line()
+        .then(new_line().or(stmt_start()))
+        .ignored()
+        .or(ctrl('}'));
+
+    new_line()
+        .repeated()
+        .ignore_then(skip_until(stmt_end))
+        .map_with(|_, extra| into_stmt((Vec::new(), StmtKind::Error), extra.span()))
+}
+
 fn query_def<'a, I>() -> impl Parser<'a, I, Stmt, ParserError<'a>> + Clone
 where
     I: Input<'a, Token = lr::Token, Span = Span> + BorrowInput<'a> + chumsky::input::ValueInput<'a>,
@@ -171,7 +203,7 @@ where
 /// - `let foo = 5`
 /// - `from artists` — captured as a "main"
 /// - `from artists | into x` — captured as an "into"`
-fn var_def<'a, I>() -> impl Parser<'a, I, StmtKind, ParserError<'a>> + Clone
+fn var_def<'a, I>(recover: bool) -> impl Parser<'a, I, StmtKind, ParserError<'a>> + Clone
 where
     I: Input<'a, Token = lr::Token, Span = Span> + BorrowInput<'a> + chumsky::input::ValueInput<'a>,
 {
@@ -181,7 +213,12 @@ where
         .ignore_then(keyword("let"))
         .ignore_then(ident_part())
         .then(type_expr().delimited_by(ctrl('<'), ctrl('>')).or_not())
-        .then(ctrl('=').ignore_then(expr_call()).map(Box::new).or_not())
+        .then(
+            ctrl('=')
+                .ignore_then(expr_call(recover))
+                .map(Box::new)
+                .or_not(),
+        )
         .map(|((name, ty), value)| {
             StmtKind::VarDef(VarDef {
                 name,
@@ -195,7 +232,7 @@ where
     let main_or_into = new_line()
         .repeated()
         .collect::<Vec<_>>()
-        .ignore_then(pipeline(expr_call()))
+        .ignore_then(pipeline(expr_call(recover), recover))
         .map(Box::new)
         .then(
             pipe()
@@ -254,7 +291,7 @@ mod tests {
     fn parse_module_contents(source: &str) -> Result<Vec<Stmt>, Vec<Error>> {
         crate::parse_test!(
             source,
-            module_contents()
+            module_contents(false)
                 .then_ignore(new_line().repeated())
                 .then_ignore(end())
         )
@@ -263,14 +300,14 @@ mod tests {
     fn parse_var_def(source: &str) -> Result<StmtKind, Vec<Error>> {
         crate::parse_test!(
             source,
-            var_def()
+            var_def(false)
                 .then_ignore(new_line().repeated())
                 .then_ignore(end())
         )
     }
 
     fn parse_module_contents_complete(source: &str) -> Result<Vec<Stmt>, Vec<Error>> {
-        crate::parse_test!(source, module_contents().then_ignore(end()))
+        crate::parse_test!(source, module_contents(false).then_ignore(end()))
     }
 
     #[test]
@@ -361,7 +398,10 @@ mod tests {
                     found: "keyword into",
                 },
                 hints: [],
-                code: None,
+                code: Some(
+                    "E0004",
+                ),
+                fixes: [],
             },
             Error {
                 kind: Error,
@@ -372,7 +412,10 @@ mod tests {
                     "Expected one of import statement, module definition, new line, pipeline, something else, type definition or variable definition, but didn't find anything before the end.",
                 ),
                 hints: [],
-                code: None,
+                code: Some(
+                    "E0004",
+                ),
+                fixes: [],
             },
         ]
         "#);
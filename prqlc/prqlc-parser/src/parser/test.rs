@@ -65,7 +65,7 @@ fn parse_expr(source: &str) -> Result<Expr, Vec<Error>> {
         super::new_line()
             .repeated()
             .collect::<Vec<_>>()
-            .ignore_then(super::expr::expr_call())
+            .ignore_then(super::expr::expr_call(false))
     )
 }
 
@@ -1321,3 +1321,135 @@ fn test_lookup_02() {
     span: "0:0-7"
     "#);
 }
+
+#[test]
+fn test_recovery_pipeline_step() {
+    let (stmts, errors) = super::parse_with_recovery(
+        r#"
+from artists
+derive x = 1 +
+filter c > 1
+
+let y = 5
+"#,
+        0,
+    );
+
+    assert_eq!(errors.len(), 1);
+    assert_yaml_snapshot!(stmts.unwrap(), @r#"
+    - VarDef:
+        kind: Main
+        name: main
+        value:
+          Pipeline:
+            exprs:
+              - FuncCall:
+                  name:
+                    Ident:
+                      - from
+                    span: "0:1-5"
+                  args:
+                    - Ident:
+                        - artists
+                      span: "0:6-13"
+                span: "0:1-13"
+              - Error: ~
+                span: "0:14-28"
+              - FuncCall:
+                  name:
+                    Ident:
+                      - filter
+                    span: "0:29-35"
+                  args:
+                    - Binary:
+                        left:
+                          Ident:
+                            - c
+                          span: "0:36-37"
+                        op: Gt
+                        right:
+                          Literal:
+                            Integer: 1
+                          span: "0:40-41"
+                      span: "0:36-41"
+                span: "0:29-41"
+          span: "0:1-41"
+      span: "0:0-41"
+    - VarDef:
+        kind: Let
+        name: y
+        value:
+          Literal:
+            Integer: 5
+          span: "0:51-52"
+      span: "0:41-52"
+    "#);
+}
+
+#[test]
+fn test_recovery_stmt() {
+    let (stmts, errors) = super::parse_with_recovery(
+        r#"
+let = 5
+let x = {a = 1
+  b = 2}
+
+from artists
+select name
+"#,
+        0,
+    );
+
+    assert_eq!(errors.len(), 2);
+    assert_yaml_snapshot!(stmts.unwrap(), @r#"
+    - Error: ~
+      span: "0:0-8"
+    - VarDef:
+        kind: Let
+        name: x
+        value:
+          Tuple:
+            - Literal:
+                Integer: 1
+              span: "0:22-23"
+              alias: a
+          span: "0:17-32"
+      span: "0:8-32"
+    - VarDef:
+        kind: Main
+        name: main
+        value:
+          Pipeline:
+            exprs:
+              - FuncCall:
+                  name:
+                    Ident:
+                      - from
+                    span: "0:34-38"
+                  args:
+                    - Ident:
+                        - artists
+                      span: "0:39-46"
+                span: "0:34-46"
+              - FuncCall:
+                  name:
+                    Ident:
+                      - select
+                    span: "0:47-53"
+                  args:
+                    - Ident:
+                        - name
+                      span: "0:54-58"
+                span: "0:47-58"
+          span: "0:34-58"
+      span: "0:32-58"
+    "#);
+}
+
+#[test]
+fn test_recovery_lexer_error() {
+    let (stmts, errors) = super::parse_with_recovery("from artists | select ’", 0);
+
+    assert!(stmts.is_none());
+    assert_eq!(errors.len(), 1);
+}
@@ -45,7 +45,7 @@ pub(crate) fn parse_source(source: &str) -> Result<Vec<Stmt>, Vec<Error>> {
             }
         });
 
-    let parse_result = stmt::source().parse(input);
+    let parse_result = stmt::source(false).parse(input);
     let (ast, parse_errors) = parse_result.into_output_errors();
 
     if !parse_errors.is_empty() {
@@ -74,13 +74,16 @@ fn test_error_unicode_string() {
         Error {
             kind: Error,
             span: Some(
-                0:22-23,
+                0:22-25,
             ),
             reason: Unexpected {
                 found: "'’'",
             },
             hints: [],
-            code: None,
+            code: Some(
+                "E0003",
+            ),
+            fixes: [],
         },
     ]
     "#);
@@ -91,13 +94,16 @@ fn test_error_unicode_string() {
         Error {
             kind: Error,
             span: Some(
-                0:22-23,
+                0:22-25,
             ),
             reason: Unexpected {
                 found: "'’'",
             },
             hints: [],
-            code: None,
+            code: Some(
+                "E0003",
+            ),
+            fixes: [],
         },
     ]
     "#);
@@ -118,7 +124,10 @@ fn test_error_unexpected() {
                 found: "!",
             },
             hints: [],
-            code: None,
+            code: Some(
+                "E0004",
+            ),
+            fixes: [],
         },
     ]
     "#);
@@ -2014,7 +2023,10 @@ fn doc_comment() {
                 "unexpected #! This is a doc comment\n",
             ),
             hints: [],
-            code: None,
+            code: Some(
+                "E0004",
+            ),
+            fixes: [],
         },
     ]
     "#);
@@ -0,0 +1,848 @@
+//! A cache for compiling a project repeatedly as its files change, such as in
+//! `prqlc watch` or an editor.
+//!
+//! Each file is parsed once for each version of its contents. Like
+//! [crate::compile], each compilation resolves the whole tree, so that errors
+//! in any file are reported, but only the top-level statements of the files
+//! which have changed, and of the files which depend on them, are resolved
+//! again. The declarations of all other statements are reused from the last
+//! compilation. The main query is only lowered and translated into SQL again
+//! when a file it depends on changes.
+//!
+//! Files depend on each other through the names they reference: a file which
+//! references `dates.spine` depends on the files of the modules `dates` and
+//! `dates.spine`. Names are looked up from each module the reference is in
+//! outwards, so a reference within module `reports` also depends on
+//! `reports.dates`. This over-approximates the dependencies, since a name may
+//! also refer to a column or a declaration within the same file, but it never
+//! misses one.
+//!
+//! Resolving a statement may also infer tables and columns in `default_db`,
+//! which other statements see. So a statement is only reused when the
+//! declarations it may read or has changed are the same as when it was
+//! resolved.
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+
+use crate::debug::{self, DebugLogger};
+use crate::ir::decl::{Decl, Module, RootModule};
+use crate::ir::pl;
+use crate::parser::{self, find_root, os_path_to_prql_path, parse_source};
+use crate::semantic::{self, NS_DEFAULT_DB, NS_STD};
+use crate::utils::{walk_stmts, IdGenerator};
+use crate::warnings::{collect_warnings, emit_warning};
+use crate::WithErrorInfo;
+use crate::{display_errors, pr, sql};
+use crate::{CompiledRelation, ErrorMessages, ErrorSource, Errors, Options, SourceTree};
+
+/// Keeps parsed files and compiled queries between compilations of a source
+/// tree.
+///
+/// ```
+/// use prqlc::{CompilationCache, Options, SourceTree};
+///
+/// let mut cache = CompilationCache::new();
+/// let mut sources = SourceTree::new(
+///     [
+///         ("Project.prql".into(), "from dates.spine".to_string()),
+///         ("dates.prql".into(), "let spine = (from days)".to_string()),
+///         ("other.prql".into(), "let x = (from y)".to_string()),
+///     ],
+///     None,
+/// );
+/// let options = Options::default().no_signature();
+/// let sql = cache.compile(&sources, &options).unwrap();
+///
+/// // `Project.prql` doesn't depend on `other.prql`, so editing it only
+/// // resolves `other.prql` again, rather than compiling the query again.
+/// sources.insert("other.prql".into(), "let x = (from z)".to_string());
+/// assert_eq!(cache.compile(&sources, &options).unwrap(), sql);
+/// assert_eq!(cache.stats().queries_compiled, 1);
+/// ```
+#[derive(Debug, Default)]
+pub struct CompilationCache {
+    files: HashMap<PathBuf, ParsedFile>,
+
+    /// Files which have been parsed, added or removed since the tree was last
+    /// resolved.
+    changed: BTreeSet<PathBuf>,
+
+    /// Compiled queries, by the path of their root file and of their main
+    /// relation.
+    queries: HashMap<(PathBuf, Vec<String>), Compiled<String>>,
+
+    /// All compiled relations, by the path of their root file.
+    relations: HashMap<PathBuf, Compiled<Vec<CompiledRelation>>>,
+
+    /// The top-level statements of the last resolved tree, by their name and
+    /// the number of statements with the same name before them.
+    resolved: HashMap<(String, usize), ResolvedStmt>,
+
+    /// Generates the ids of all resolved statements, so that the ids of the
+    /// declarations which are reused never clash with new ones.
+    id: IdGenerator<usize>,
+
+    stats: CacheStats,
+}
+
+#[derive(Debug)]
+struct ParsedFile {
+    hash: u64,
+    source_id: u16,
+    stmts: Vec<pr::Stmt>,
+
+    /// The path of the module the file defines, which is empty for the root.
+    module_path: Vec<String>,
+
+    /// Fully qualified paths of all modules and declarations which the file
+    /// may reference.
+    references: HashSet<Vec<String>>,
+}
+
+/// A resolved top-level statement, with the declarations it depends on and
+/// those it declared or changed.
+#[derive(Debug)]
+struct ResolvedStmt {
+    stmt: pl::Stmt,
+
+    /// Declarations which the statement may read or has changed, before it was
+    /// resolved.
+    before: HashMap<Name, Option<Decl>>,
+
+    /// Declarations which the statement has changed, after it was resolved.
+    after: HashMap<Name, Option<Decl>>,
+
+    spans: HashMap<usize, pr::Span>,
+
+    /// Warnings of resolving the statement, which are reported again when it
+    /// is reused.
+    warnings: Vec<crate::Error>,
+}
+
+/// A declaration which top-level statements may share.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum Name {
+    /// A declaration of the root module, other than `default_db`.
+    Root(String),
+
+    /// A table or module in `default_db`, which may be inferred.
+    Database(String),
+}
+
+#[derive(Debug)]
+struct Compiled<T> {
+    /// A hash of the files the output depends on and of the options.
+    fingerprint: u64,
+    output: T,
+    warnings: Vec<crate::Error>,
+}
+
+/// Counts of the work done and avoided by a [CompilationCache].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    pub files_parsed: usize,
+    pub files_reused: usize,
+    pub queries_compiled: usize,
+    pub queries_reused: usize,
+    /// Top-level statements, mostly the modules of files, which were resolved.
+    pub stmts_resolved: usize,
+    /// Top-level statements whose resolved declarations were reused rather
+    /// than resolved again.
+    pub stmts_reused: usize,
+}
+
+impl CompilationCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        self.stats
+    }
+
+    /// Parses a source tree like [crate::prql_to_pl_tree], reusing the
+    /// statements of files which haven't changed since they were last parsed.
+    pub fn parse(&mut self, sources: &SourceTree) -> Result<pr::ModuleDef, ErrorMessages> {
+        self.parse_tree(sources, &DebugLogger::default())
+            .map_err(|e| ErrorMessages::from(e).composed(sources))
+    }
+
+    /// Compiles the main query of a source tree like [crate::compile]. The
+    /// files which have changed, and their dependents, are resolved again,
+    /// but the query is only lowered again when a file it depends on, or
+    /// `options`, have changed.
+    pub fn compile(
+        &mut self,
+        sources: &SourceTree,
+        options: &Options,
+    ) -> Result<String, ErrorMessages> {
+        self.compile_tree(sources, &[], options)
+    }
+
+    /// Compiles the relation at `main_path` like [crate::compile_tree],
+    /// caching it like [CompilationCache::compile].
+    pub fn compile_tree(
+        &mut self,
+        sources: &SourceTree,
+        main_path: &[String],
+        options: &Options,
+    ) -> Result<String, ErrorMessages> {
+        debug::with_message_log(&options.debug_log, || {
+            let root_mod = self.parse_and_resolve(sources, options)?;
+
+            // parsing succeeded, so there is a root
+            let root = find_root(sources).unwrap().clone();
+            let main_files = (self.files.iter())
+                .filter(|(path, file)| {
+                    **path == root
+                        || !file.module_path.is_empty()
+                            && !main_path.is_empty()
+                            && (file.module_path.starts_with(main_path)
+                                || main_path.starts_with(&file.module_path))
+                })
+                .map(|(path, _)| path.as_path());
+            let mut hasher = self.fingerprint(self.dependencies(main_files), options);
+            hasher.write(main_path.join(".").as_bytes());
+            let fingerprint = hasher.finish();
+
+            let key = (root, main_path.to_vec());
+            reuse_or_compile(
+                &mut self.queries,
+                key,
+                fingerprint,
+                options,
+                &mut self.stats,
+                || {
+                    semantic::lower(root_mod, main_path, None, &options.debug_log)
+                        .map_err(|e| e.with_source(ErrorSource::NameResolver))
+                        .and_then(|rq| {
+                            sql::compile(rq, options).map_err(|e| e.with_source(ErrorSource::SQL))
+                        })
+                        .map_err(|e| {
+                            display_errors(ErrorMessages::from(e).composed(sources), options)
+                        })
+                },
+            )
+        })
+    }
+
+    /// Compiles each public relation like [crate::compile_relations], caching
+    /// them like [CompilationCache::compile]. Since they are compiled
+    /// together, they are all compiled again when any file changes.
+    pub fn compile_relations(
+        &mut self,
+        sources: &SourceTree,
+        options: &Options,
+        create_views: bool,
+    ) -> Result<Vec<CompiledRelation>, ErrorMessages> {
+        debug::with_message_log(&options.debug_log, || {
+            let root_mod = self.parse_and_resolve(sources, options)?;
+
+            let root = find_root(sources).unwrap().clone();
+            let mut hasher =
+                self.fingerprint(self.files.keys().map(PathBuf::as_path).collect(), options);
+            hasher.write_u8(create_views as u8);
+            let fingerprint = hasher.finish();
+
+            reuse_or_compile(
+                &mut self.relations,
+                root,
+                fingerprint,
+                options,
+                &mut self.stats,
+                || {
+                    semantic::lower_all(root_mod, None, &options.debug_log)
+                        .map_err(|e| e.with_source(ErrorSource::NameResolver).into())
+                        .and_then(|queries| crate::relations_to_sql(queries, options, create_views))
+                        .map_err(|e| {
+                            display_errors(ErrorMessages::from(e).composed(sources), options)
+                        })
+                },
+            )
+        })
+    }
+
+    fn parse_and_resolve(
+        &mut self,
+        sources: &SourceTree,
+        options: &Options,
+    ) -> Result<RootModule, ErrorMessages> {
+        let ast = self
+            .parse_tree(sources, &options.debug_log)
+            .map_err(|e| display_errors(ErrorMessages::from(e).composed(sources), options))?;
+        self.resolve(ast, &options.debug_log)
+            .map_err(|e| e.with_source(ErrorSource::NameResolver))
+            .map_err(|e| display_errors(ErrorMessages::from(e).composed(sources), options))
+    }
+
+    /// Files which depend on the file at `path`, directly or through other
+    /// files, as of the last parse. These are the files affected by a change
+    /// to `path`.
+    pub fn dependents(&self, path: &Path) -> Vec<PathBuf> {
+        let mut dependents = BTreeSet::new();
+        let mut queue = vec![path];
+        while let Some(dependency) = queue.pop() {
+            for (path, file) in &self.files {
+                if !dependents.contains(path) && self.references_file(file, dependency) {
+                    dependents.insert(path.clone());
+                    queue.push(path);
+                }
+            }
+        }
+        dependents.remove(path);
+        dependents.into_iter().collect()
+    }
+
+    /// Resolves a parsed tree, reusing the top-level statements which are
+    /// unchanged since the last call and don't depend on a changed file.
+    fn resolve(&mut self, ast: pr::ModuleDef, log: &DebugLogger) -> crate::Result<RootModule> {
+        let mut affected = self.changed.clone();
+        for path in &self.changed {
+            affected.extend(self.dependents(path));
+        }
+
+        let stmts = semantic::expand_with_std(ast, log)?;
+        let mut root_module = semantic::new_root_module();
+
+        log.stage(debug::Stage::Semantic(debug::StageSemantic::Resolver));
+        let mut resolved = HashMap::new();
+        for stmt in stmts {
+            let name = stmt.name().to_string();
+            let occurrence = resolved.keys().filter(|(n, _)| *n == name).count();
+            let key = (name, occurrence);
+
+            let is_affected = (self.files_of(&stmt).iter()).any(|path| affected.contains(*path));
+            let reusable = self.resolved.remove(&key).filter(|previous| {
+                previous.stmt == stmt
+                    && !is_affected
+                    && previous
+                        .before
+                        .iter()
+                        .all(|(name, decl)| name.get(&root_module.module) == decl.as_ref())
+            });
+
+            let resolved_stmt = if let Some(previous) = reusable {
+                for (name, decl) in &previous.after {
+                    name.set(&mut root_module.module, decl.clone());
+                }
+                root_module.span_map.extend(previous.spans.clone());
+                previous.warnings.iter().cloned().for_each(emit_warning);
+                self.stats.stmts_reused += 1;
+                previous
+            } else {
+                let reads = self.reads(&self.files_of(&stmt), &key.0);
+                let resolved_stmt = self.resolve_stmt(&mut root_module, stmt, reads)?;
+                self.stats.stmts_resolved += 1;
+                resolved_stmt
+            };
+            resolved.insert(key, resolved_stmt);
+        }
+        log.entry(|| debug::DebugEntryKind::ReprDecl(root_module.clone()));
+
+        self.resolved = resolved;
+        self.changed.clear();
+        Ok(root_module)
+    }
+
+    /// Resolves a top-level statement into the root module, recording the
+    /// declarations it reads and changes.
+    fn resolve_stmt(
+        &mut self,
+        root_module: &mut RootModule,
+        stmt: pl::Stmt,
+        reads: HashSet<Name>,
+    ) -> crate::Result<ResolvedStmt> {
+        let before = declarations(&root_module.module, stmt.name());
+
+        // the resolver only adds to the span map, so resolve into an empty one
+        // to find the spans of this statement
+        let span_map = std::mem::take(&mut root_module.span_map);
+        let (result, warnings) =
+            collect_warnings(|| semantic::resolve_stmt(root_module, &mut self.id, stmt.clone()));
+        let spans = std::mem::replace(&mut root_module.span_map, span_map);
+        root_module.span_map.extend(spans.clone());
+        warnings.iter().cloned().for_each(emit_warning);
+        result?;
+
+        let after = declarations(&root_module.module, stmt.name());
+        let writes: HashSet<Name> = (before.keys().chain(after.keys()))
+            .filter(|name| before.get(*name) != after.get(*name))
+            .chain([Name::Root(stmt.name().to_string())].iter())
+            .cloned()
+            .collect();
+
+        Ok(ResolvedStmt {
+            before: (reads.iter().chain(&writes))
+                .map(|name| (name.clone(), before.get(name).cloned()))
+                .collect(),
+            after: (writes.iter())
+                .map(|name| (name.clone(), after.get(name).cloned()))
+                .collect(),
+            stmt,
+            spans,
+            warnings,
+        })
+    }
+
+    fn parse_tree(
+        &mut self,
+        sources: &SourceTree,
+        log: &DebugLogger,
+    ) -> Result<pr::ModuleDef, Errors> {
+        // forget files which have been removed
+        let removed = (self.files.keys())
+            .filter(|path| !sources.sources.contains_key(*path))
+            .cloned()
+            .collect::<Vec<_>>();
+        for path in removed {
+            self.files.remove(&path);
+            self.changed.insert(path);
+        }
+
+        let root = find_root(sources).cloned();
+        parser::parse_with(sources, log, |path, content, source_id| {
+            let hash = hash_str(content);
+            if let Some(file) = self.files.get(path) {
+                if file.hash == hash && file.source_id == source_id {
+                    self.stats.files_reused += 1;
+                    return Ok(file.stmts.clone());
+                }
+            }
+
+            let stmts = parse_source(content, source_id, log)?;
+            self.stats.files_parsed += 1;
+            let module_path = if Some(path) == root.as_deref() {
+                Vec::new()
+            } else {
+                os_path_to_prql_path(path).unwrap_or_default()
+            };
+            let file = ParsedFile {
+                hash,
+                source_id,
+                references: references(&stmts, &module_path),
+                module_path,
+                stmts: stmts.clone(),
+            };
+            self.files.insert(path.to_path_buf(), file);
+            self.changed.insert(path.to_path_buf());
+            Ok(stmts)
+        })
+    }
+
+    /// Hashes the files which a query depends on, and the options.
+    fn fingerprint(&self, files: BTreeSet<&Path>, options: &Options) -> DefaultHasher {
+        let mut hasher = DefaultHasher::new();
+        for path in files {
+            hasher.write(path.to_string_lossy().as_bytes());
+            hasher.write_u8(0);
+            hasher.write_u64(self.files[path].hash);
+        }
+        hasher.write(serde_json::to_string(options).unwrap().as_bytes());
+        hasher
+    }
+
+    /// Files which the files at `paths` depend on, directly or through other
+    /// files, including themselves.
+    fn dependencies<'a>(&'a self, paths: impl IntoIterator<Item = &'a Path>) -> BTreeSet<&'a Path> {
+        let mut dependencies = BTreeSet::new();
+        let mut queue = Vec::new();
+        for path in paths {
+            if dependencies.insert(path) {
+                queue.push(path);
+            }
+        }
+        while let Some(path) = queue.pop() {
+            let file = &self.files[path];
+            for dependency in self.files.keys() {
+                if !dependencies.contains(dependency.as_path())
+                    && self.references_file(file, dependency)
+                {
+                    dependencies.insert(dependency);
+                    queue.push(dependency);
+                }
+            }
+        }
+        dependencies
+    }
+
+    /// Files which a top-level statement was parsed from: the file of its
+    /// span, and the files of the modules within it.
+    fn files_of(&self, stmt: &pl::Stmt) -> BTreeSet<&Path> {
+        let module = match &stmt.kind {
+            pl::StmtKind::ModuleDef(_) => Some(stmt.name()),
+            _ => None,
+        };
+        (self.files.iter())
+            .filter(|(_, file)| {
+                stmt.span.map(|span| span.source_id) == Some(file.source_id)
+                    || module.is_some() && file.module_path.first().map(String::as_str) == module
+            })
+            .map(|(path, _)| path.as_path())
+            .collect()
+    }
+
+    /// Declarations which the statement named `name`, parsed from `files`,
+    /// may read: those which the files, or their dependencies, reference.
+    fn reads(&self, files: &BTreeSet<&Path>, name: &str) -> HashSet<Name> {
+        let mut reads = HashSet::from([Name::Root(name.to_string())]);
+        for path in self.dependencies(files.iter().copied()) {
+            for reference in &self.files[path].references {
+                match reference.as_slice() {
+                    [name] => {
+                        reads.insert(Name::Database(name.clone()));
+                        if name != NS_STD {
+                            reads.insert(Name::Root(name.clone()));
+                        }
+                    }
+                    [db, name] if db == NS_DEFAULT_DB => {
+                        reads.insert(Name::Database(name.clone()));
+                    }
+                    _ => {}
+                }
+            }
+        }
+        reads
+    }
+
+    /// Whether `file` references the module defined by the file at `path`.
+    fn references_file(&self, file: &ParsedFile, path: &Path) -> bool {
+        let Ok(module_path) = os_path_to_prql_path(path) else {
+            return false;
+        };
+        file.references.contains(&module_path)
+    }
+}
+
+/// Reuses the output compiled for `key` when it has the same fingerprint, or
+/// compiles it again. With a debug log, it is always compiled again, so that
+/// the log has all stages.
+fn reuse_or_compile<K: Hash + Eq, T: Clone>(
+    outputs: &mut HashMap<K, Compiled<T>>,
+    key: K,
+    fingerprint: u64,
+    options: &Options,
+    stats: &mut CacheStats,
+    compile: impl FnOnce() -> Result<T, ErrorMessages>,
+) -> Result<T, ErrorMessages> {
+    if let Some(compiled) = outputs.get(&key) {
+        if compiled.fingerprint == fingerprint && !options.debug_log.is_enabled() {
+            stats.queries_reused += 1;
+            compiled.warnings.iter().cloned().for_each(emit_warning);
+            return Ok(compiled.output.clone());
+        }
+    }
+
+    let (output, warnings) = collect_warnings(compile);
+    warnings.iter().cloned().for_each(emit_warning);
+    let output = output?;
+
+    stats.queries_compiled += 1;
+    let compiled = Compiled {
+        fingerprint,
+        output: output.clone(),
+        warnings,
+    };
+    outputs.insert(key, compiled);
+    Ok(output)
+}
+
+/// Collects the fully qualified paths which the idents and imports within
+/// `module` may refer to: each prefix of an ident, relative to each of the
+/// modules it is within.
+fn references(stmts: &[pr::Stmt], module: &[String]) -> HashSet<Vec<String>> {
+    let mut references = HashSet::new();
+    collect_references(stmts, module, &mut references);
+    references
+}
+
+fn collect_references(
+    stmts: &[pr::Stmt],
+    module: &[String],
+    references: &mut HashSet<Vec<String>>,
+) {
+    for stmt in stmts {
+        match &stmt.kind {
+            pr::StmtKind::ModuleDef(module_def) => {
+                let module = [module, std::slice::from_ref(&module_def.name)].concat();
+                collect_references(&module_def.stmts, &module, references);
+            }
+            pr::StmtKind::ImportDef(import) => insert_reference(&import.name, module, references),
+            _ => walk_stmts(std::slice::from_ref(stmt), &mut |expr| {
+                if let pr::ExprKind::Ident(ident) = &expr.kind {
+                    insert_reference(ident, module, references);
+                }
+            }),
+        }
+    }
+}
+
+fn insert_reference(ident: &pr::Ident, module: &[String], references: &mut HashSet<Vec<String>>) {
+    let ident = ident.iter().cloned().collect::<Vec<_>>();
+    for scope in 0..=module.len() {
+        for len in 1..=ident.len() {
+            references.insert([&module[..scope], &ident[..len]].concat());
+        }
+    }
+}
+
+impl Name {
+    fn get<'a>(&self, module: &'a Module) -> Option<&'a Decl> {
+        match self {
+            Name::Root(name) => module.names.get(name),
+            Name::Database(name) => database(module)?.names.get(name),
+        }
+    }
+
+    fn set(&self, module: &mut Module, decl: Option<Decl>) {
+        let (names, name) = match self {
+            Name::Root(name) => (&mut module.names, name),
+            Name::Database(name) => {
+                let Some(database) = (module.names.get_mut(NS_DEFAULT_DB))
+                    .and_then(|decl| decl.kind.as_module_mut())
+                else {
+                    return;
+                };
+                (&mut database.names, name)
+            }
+        };
+        match decl {
+            Some(decl) => names.insert(name.clone(), decl),
+            None => names.remove(name),
+        };
+    }
+}
+
+fn database(module: &Module) -> Option<&Module> {
+    module.names.get(NS_DEFAULT_DB)?.kind.as_module()
+}
+
+/// The declarations of the root module which statements may share, including
+/// the one named `own`. The other declarations of std are left out, since
+/// only std itself declares them.
+fn declarations(module: &Module, own: &str) -> HashMap<Name, Decl> {
+    let mut declarations = HashMap::new();
+    for (name, decl) in &module.names {
+        if name != NS_DEFAULT_DB && (name != NS_STD || name == own) {
+            declarations.insert(Name::Root(name.clone()), decl.clone());
+        }
+    }
+    for (name, decl) in database(module).into_iter().flat_map(|db| &db.names) {
+        declarations.insert(Name::Database(name.clone()), decl.clone());
+    }
+    declarations
+}
+
+fn hash_str(content: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    hasher.write(content.as_bytes());
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::{Path, PathBuf};
+
+    use super::*;
+
+    fn project() -> SourceTree {
+        SourceTree::new(
+            [
+                ("Project.prql".into(), "from reports.monthly".to_string()),
+                (
+                    "reports.prql".into(),
+                    "let monthly = (from dates.spine | take 10)".to_string(),
+                ),
+                ("dates.prql".into(), "let spine = (from days)".to_string()),
+                ("other.prql".into(), "let x = (from y)".to_string()),
+            ],
+            None,
+        )
+    }
+
+    #[test]
+    fn test_reuse_parsed_files() {
+        let mut cache = CompilationCache::new();
+        let mut sources = project();
+        cache.parse(&sources).unwrap();
+        assert_eq!(cache.stats().files_parsed, 4);
+
+        sources.insert("other.prql".into(), "let x = (from z)".to_string());
+        cache.parse(&sources).unwrap();
+        let stats = cache.stats();
+        assert_eq!((stats.files_parsed, stats.files_reused), (5, 3));
+    }
+
+    #[test]
+    fn test_recompile_dependents() {
+        let options = Options::default().no_signature().no_format();
+        let mut cache = CompilationCache::new();
+        let mut sources = project();
+        cache.compile(&sources, &options).unwrap();
+
+        // not a dependency of the main query
+        sources.insert("other.prql".into(), "let x = (from z)".to_string());
+        cache.compile(&sources, &options).unwrap();
+        assert_eq!(cache.stats().queries_compiled, 1);
+        assert_eq!(cache.stats().queries_reused, 1);
+
+        // a dependency of a dependency
+        sources.insert(
+            "dates.prql".into(),
+            "let spine = (from calendar)".to_string(),
+        );
+        let sql = cache.compile(&sources, &options).unwrap();
+        assert_eq!(cache.stats().queries_compiled, 2);
+        assert!(sql.contains("FROM calendar"));
+
+        // other options
+        cache
+            .compile(&sources, &options.clone().with_format(true))
+            .unwrap();
+        assert_eq!(cache.stats().queries_compiled, 3);
+    }
+
+    #[test]
+    fn test_resolve_errors_of_other_files() {
+        let options = Options::default().no_signature().no_format();
+        let mut cache = CompilationCache::new();
+        let mut sources = project();
+        let sql = cache.compile(&sources, &options).unwrap();
+
+        // not a dependency of the main query, but compiling fails like it
+        // does without a cache
+        sources.insert("other.prql".into(), "let x = (from y | foo)".to_string());
+        let error = cache.compile(&sources, &options).unwrap_err();
+        let expected = CompilationCache::new()
+            .compile(&sources, &options)
+            .unwrap_err();
+        assert_eq!(error.to_string(), expected.to_string());
+        assert_eq!(cache.stats().queries_compiled, 1);
+
+        sources.insert("other.prql".into(), "let x = (from z)".to_string());
+        assert_eq!(cache.compile(&sources, &options).unwrap(), sql);
+        assert_eq!(cache.stats().queries_reused, 1);
+    }
+
+    #[test]
+    fn test_resolve_dependents() {
+        let options = Options::default().no_signature().no_format();
+        let mut cache = CompilationCache::new();
+        let mut sources = project();
+        cache.compile(&sources, &options).unwrap();
+        // std, the modules of the three files, and the main query
+        assert_eq!(cache.stats().stmts_resolved, 5);
+        assert_eq!(cache.stats().stmts_reused, 0);
+
+        // no other file depends on `other`
+        sources.insert("other.prql".into(), "let x = (from z)".to_string());
+        let sql = cache.compile(&sources, &options).unwrap();
+        assert_eq!(cache.stats().stmts_resolved, 6);
+        assert_eq!(cache.stats().stmts_reused, 4);
+        assert_eq!(
+            sql,
+            CompilationCache::new().compile(&sources, &options).unwrap()
+        );
+
+        // `reports` and the main query depend on `dates`
+        sources.insert(
+            "dates.prql".into(),
+            "let spine = (from calendar)".to_string(),
+        );
+        let sql = cache.compile(&sources, &options).unwrap();
+        assert_eq!(cache.stats().stmts_resolved, 9);
+        assert_eq!(cache.stats().stmts_reused, 6);
+        assert!(sql.contains("FROM calendar"));
+        assert_eq!(
+            sql,
+            CompilationCache::new().compile(&sources, &options).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_resolve_inferred_tables() {
+        let options = Options::default().no_signature().no_format();
+        let mut cache = CompilationCache::new();
+        let mut sources = project();
+        sources.insert(
+            "other.prql".into(),
+            "let x = (from days | select {a})".to_string(),
+        );
+        cache.compile(&sources, &options).unwrap();
+
+        // `other` doesn't reference `dates`, but it infers the columns of
+        // `days`, which `reports` reads through `dates`
+        sources.insert(
+            "other.prql".into(),
+            "let x = (from days | select {b})".to_string(),
+        );
+        let sql = cache.compile(&sources, &options).unwrap();
+        assert_eq!(cache.stats().stmts_resolved, 8);
+        assert_eq!(
+            sql,
+            CompilationCache::new().compile(&sources, &options).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_compile_tree_and_relations() {
+        let options = Options::default().no_signature().no_format();
+        let mut cache = CompilationCache::new();
+        let mut sources = project();
+        let main_path = ["reports".to_string(), "monthly".to_string()];
+        cache.compile_tree(&sources, &main_path, &options).unwrap();
+        let relations = cache.compile_relations(&sources, &options, false).unwrap();
+        assert_eq!(cache.stats().queries_compiled, 2);
+
+        // `reports.monthly` doesn't depend on `other`, but the relations do
+        sources.insert("other.prql".into(), "let x = (from z)".to_string());
+        let sql = cache.compile_tree(&sources, &main_path, &options).unwrap();
+        assert_eq!(cache.stats().queries_reused, 1);
+        assert_eq!(
+            sql,
+            crate::compile_tree(&sources, &main_path, &options).unwrap()
+        );
+        assert_ne!(
+            cache.compile_relations(&sources, &options, false).unwrap(),
+            relations
+        );
+        assert_eq!(cache.stats().queries_compiled, 3);
+    }
+
+    #[test]
+    fn test_report_warnings_again() {
+        let options = Options::default().no_signature().no_format();
+        let mut cache = CompilationCache::new();
+        let mut sources = project();
+        sources.insert(
+            "other.prql".into(),
+            "let x = (from y | aggregate {n = count_distinct z})".to_string(),
+        );
+        let (_, warnings) = crate::collect_warnings(|| cache.compile(&sources, &options));
+        assert_eq!(warnings.len(), 1);
+
+        // the statement of `other` is reused, and so is the query
+        let (_, warnings) = crate::collect_warnings(|| cache.compile(&sources, &options));
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(cache.stats().queries_reused, 1);
+        assert_eq!(cache.stats().stmts_resolved, 5);
+    }
+
+    #[test]
+    fn test_dependents() {
+        let mut cache = CompilationCache::new();
+        let mut sources = project();
+        // `dates.spine` doesn't reference the top-level module `spine`
+        sources.insert("spine.prql".into(), "let x = 5".to_string());
+        cache.parse(&sources).unwrap();
+
+        assert_eq!(
+            cache.dependents(Path::new("dates.prql")),
+            vec![PathBuf::from("Project.prql"), PathBuf::from("reports.prql")]
+        );
+        assert!(cache.dependents(Path::new("other.prql")).is_empty());
+        assert!(cache.dependents(Path::new("spine.prql")).is_empty());
+    }
+}
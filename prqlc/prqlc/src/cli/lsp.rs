@@ -1,11 +1,19 @@
+use std::collections::HashMap;
 use std::error::Error;
 
-use lsp_types::OneOf;
+use lsp_types::notification::{
+    DidChangeTextDocument, DidCloseTextDocument, DidOpenTextDocument, Notification as _,
+};
+use lsp_types::request::{CodeActionRequest, GotoDefinition};
 use lsp_types::{
-    request::GotoDefinition, GotoDefinitionResponse, InitializeParams, ServerCapabilities,
+    CodeAction, CodeActionKind, CodeActionOrCommand, CodeActionParams, CodeActionResponse,
+    DidChangeTextDocumentParams, DidCloseTextDocumentParams, DidOpenTextDocumentParams,
+    GotoDefinitionResponse, InitializeParams, OneOf, Position, Range, ServerCapabilities,
+    TextDocumentSyncCapability, TextDocumentSyncKind, TextEdit, WorkspaceEdit,
 };
 
 use lsp_server::{Connection, ExtractError, Message, Request, RequestId, Response};
+use prqlc::{CompilationCache, SourceTree};
 
 pub fn run() -> Result<(), Box<dyn Error + Sync + Send>> {
     // Note that  we must have our logging only write out to stderr.
@@ -18,6 +26,8 @@ pub fn run() -> Result<(), Box<dyn Error + Sync + Send>> {
     // Run the server and wait for the two threads to end (typically by trigger LSP Exit event).
     let server_capabilities = serde_json::to_value(&ServerCapabilities {
         definition_provider: Some(OneOf::Left(true)),
+        text_document_sync: Some(TextDocumentSyncCapability::Kind(TextDocumentSyncKind::FULL)),
+        code_action_provider: Some(true.into()),
         ..Default::default()
     })
     .unwrap();
@@ -44,6 +54,11 @@ fn main_loop(
 ) -> Result<(), Box<dyn Error + Sync + Send>> {
     let _params: InitializeParams = serde_json::from_value(params).unwrap();
     eprintln!("starting main loop");
+
+    // The open documents, whose contents the client sends in full on every
+    // change. They are keyed by their URI as a string, since `Uri` is not a
+    // good key.
+    let mut documents: HashMap<String, Document> = HashMap::new();
     for msg in &connection.receiver {
         eprintln!("got msg: {msg:?}");
         match msg {
@@ -52,7 +67,7 @@ fn main_loop(
                     return Ok(());
                 }
                 eprintln!("got request: {req:?}");
-                match cast::<GotoDefinition>(req) {
+                let req = match cast::<GotoDefinition>(req) {
                     Ok((id, params)) => {
                         eprintln!("got gotoDefinition request #{id}: {params:?}");
                         let result = Some(GotoDefinitionResponse::Array(Vec::new()));
@@ -68,20 +83,139 @@ fn main_loop(
                     Err(err @ ExtractError::JsonError { .. }) => panic!("{err:?}"),
                     Err(ExtractError::MethodMismatch(req)) => req,
                 };
-                // ...
+                let req = match cast::<CodeActionRequest>(req) {
+                    Ok((id, params)) => {
+                        eprintln!("got codeAction request #{id}: {params:?}");
+                        let result = Some(code_actions(&mut documents, &params));
+                        let result = serde_json::to_value(&result).unwrap();
+                        let resp = Response {
+                            id,
+                            result: Some(result),
+                            error: None,
+                        };
+                        connection.sender.send(Message::Response(resp))?;
+                        continue;
+                    }
+                    Err(err @ ExtractError::JsonError { .. }) => panic!("{err:?}"),
+                    Err(ExtractError::MethodMismatch(req)) => req,
+                };
+                eprintln!("unhandled request: {}", req.method);
             }
             Message::Response(resp) => {
                 eprintln!("got response: {resp:?}");
             }
             Message::Notification(not) => {
                 eprintln!("got notification: {not:?}");
-                return Ok(());
+                match not.method.as_str() {
+                    DidOpenTextDocument::METHOD => {
+                        let params: DidOpenTextDocumentParams = serde_json::from_value(not.params)?;
+                        let document = params.text_document;
+                        documents.insert(document.uri.to_string(), Document::new(document.text));
+                    }
+                    DidChangeTextDocument::METHOD => {
+                        let mut params: DidChangeTextDocumentParams =
+                            serde_json::from_value(not.params)?;
+                        let uri = params.text_document.uri.to_string();
+                        if let Some(change) = params.content_changes.pop() {
+                            documents
+                                .entry(uri)
+                                .and_modify(|d| d.sources = SourceTree::from(&change.text))
+                                .or_insert_with(|| Document::new(change.text));
+                        }
+                    }
+                    DidCloseTextDocument::METHOD => {
+                        let params: DidCloseTextDocumentParams =
+                            serde_json::from_value(not.params)?;
+                        documents.remove(&params.text_document.uri.to_string());
+                    }
+                    _ => {}
+                }
             }
         }
     }
     Ok(())
 }
 
+/// An open document, with a cache which keeps its compilation between
+/// changes.
+struct Document {
+    sources: SourceTree,
+    cache: CompilationCache,
+}
+
+impl Document {
+    fn new(text: String) -> Self {
+        Document {
+            sources: SourceTree::from(text),
+            cache: CompilationCache::new(),
+        }
+    }
+
+    fn text(&self) -> &str {
+        self.sources.sources.values().next().unwrap()
+    }
+}
+
+/// Code actions which apply the fixes of errors within the requested range.
+fn code_actions(
+    documents: &mut HashMap<String, Document>,
+    params: &CodeActionParams,
+) -> CodeActionResponse {
+    let uri = &params.text_document.uri;
+    let Some(document) = documents.get_mut(&uri.to_string()) else {
+        return Vec::new();
+    };
+    let options = prqlc::Options::default();
+    let Err(errors) = document.cache.compile(&document.sources, &options) else {
+        return Vec::new();
+    };
+    let text = document.text();
+
+    let mut actions = Vec::new();
+    for error in &errors.inner {
+        for (index, fix) in error.fixes.iter().enumerate() {
+            let range = Range::new(
+                position_of(text, fix.span.start),
+                position_of(text, fix.span.end),
+            );
+            if range.end < params.range.start || params.range.end < range.start {
+                continue;
+            }
+
+            let edit = TextEdit {
+                range,
+                new_text: fix.replacement.clone(),
+            };
+            actions.push(CodeActionOrCommand::CodeAction(CodeAction {
+                title: format!("Replace with `{}`", fix.replacement),
+                kind: Some(CodeActionKind::QUICKFIX),
+                edit: Some(WorkspaceEdit {
+                    changes: Some(HashMap::from([(uri.clone(), vec![edit])])),
+                    ..Default::default()
+                }),
+                is_preferred: Some(index == 0),
+                ..Default::default()
+            }));
+        }
+    }
+    actions
+}
+
+/// Converts an offset in bytes into a position, whose column LSP counts in
+/// UTF-16 code units.
+fn position_of(text: &str, offset: usize) -> Position {
+    let mut position = Position::default();
+    for (_, c) in text.char_indices().take_while(|(i, _)| *i < offset) {
+        if c == '\n' {
+            position.line += 1;
+            position.character = 0;
+        } else {
+            position.character += c.len_utf16() as u32;
+        }
+    }
+    position
+}
+
 fn cast<R>(req: Request) -> Result<(RequestId, R::Params), ExtractError<Request>>
 where
     R: lsp_types::request::Request,
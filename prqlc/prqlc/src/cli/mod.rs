@@ -2,24 +2,27 @@
 
 use std::collections::HashMap;
 use std::env;
-use std::fs::File;
+use std::fs::{self, File};
 use std::io::{self, BufWriter, Read, Write};
-use std::ops::Range;
 use std::path::{Path, PathBuf};
 use std::process::exit;
+use std::slice;
 use std::str::FromStr;
 
-use anstream::{eprintln, println};
+use anstream::{eprint, eprintln, println};
 use anyhow::anyhow;
 use anyhow::bail;
 use anyhow::Result;
 use ariadne::Source;
+use clap::builder::{PossibleValuesParser, TypedValueParser};
 use clap::{CommandFactory, Parser, Subcommand, ValueHint};
 use clio::has_extension;
 use clio::Output;
 use is_terminal::IsTerminal;
 use itertools::Itertools;
 use schemars::schema_for;
+use serde::de::DeserializeOwned;
+use strum::VariantNames;
 
 use prqlc::compiler_version;
 use prqlc::debug;
@@ -29,14 +32,18 @@ use prqlc::pr;
 use prqlc::semantic;
 use prqlc::semantic::reporting::FrameCollector;
 use prqlc::utils::maybe_strip_colors;
-use prqlc::{pl_to_prql, pl_to_rq_tree, prql_to_pl, prql_to_pl_tree, prql_to_tokens, rq_to_sql};
-use prqlc::{Options, SourceTree, Target};
+use prqlc::{collect_warnings, prql_to_tokens, relations_to_script};
+use prqlc::{format_prql, lint_tree, pl_to_prql, prql_to_pl, prql_to_pl_tree};
+use prqlc::{
+    CompilationCache, FormatOptions, LintOptions, LintRule, Manifest, Options, SourceTree, Target,
+};
 
 mod docs_generator;
 mod highlight;
 mod jinja;
 #[cfg(feature = "lsp")]
 mod lsp;
+mod packages;
 #[cfg(test)]
 mod test;
 mod watch;
@@ -45,14 +52,6 @@ mod watch;
 pub fn main() -> color_eyre::eyre::Result<()> {
     let cli = Cli::parse();
 
-    // redirect all log messages into the [debug::DebugLog]
-    if has_debug_log(&cli) {
-        static LOGGER: debug::MessageLogger = debug::MessageLogger;
-        log::set_logger(&LOGGER)
-            .map(|()| log::set_max_level(log::LevelFilter::max()))
-            .unwrap();
-    }
-
     color_eyre::install()?;
     cli.color.write_global();
 
@@ -118,11 +117,74 @@ enum Command {
         format: Format,
     },
 
-    /// Parse & generate PRQL code back
+    /// Format PRQL code, retaining comments
+    ///
+    /// Options are read from the closest `.prqlfmt.toml` in the directory of
+    /// the input or any of its parents, or otherwise from the `[format]`
+    /// section of the closest `prql.toml`. Flags override them.
     #[command(name = "fmt")]
     Format {
         #[arg(value_parser, default_value = "-", value_hint(ValueHint::AnyPath))]
         input: clio::ClioPath,
+
+        #[command(flatten)]
+        format_args: FormatArgs,
+
+        /// Don't write any files; print a diff of each file that would be
+        /// reformatted and exit with an error if there are any
+        #[arg(long)]
+        check: bool,
+
+        /// Read the source from stdin and write it to stdout. The input path
+        /// is only used to find the config file and to name the source.
+        #[arg(long)]
+        stdin: bool,
+    },
+
+    /// Check PRQL code for likely mistakes
+    ///
+    /// Rules can be disabled in the closest `.prqllint.toml` in the directory
+    /// of the input or any of its parents, with `allow = ["rule_name"]`, or
+    /// for a single statement with `@{lint={allow=[rule_name]}}`. Exits with
+    /// an error if there are any lints.
+    #[command(name = "lint")]
+    Lint {
+        #[arg(value_parser, default_value = "-", value_hint(ValueHint::AnyPath))]
+        input: clio::ClioPath,
+
+        /// Disable a rule, in addition to those disabled in the config file
+        #[arg(
+            long,
+            value_name = "RULE",
+            value_parser = PossibleValuesParser::new(LintRule::VARIANTS)
+                .map(|s| LintRule::from_str(&s).unwrap()),
+        )]
+        allow: Vec<LintRule>,
+    },
+
+    /// Compile a project, as configured by its `prql.toml`
+    ///
+    /// Compiles the project once for each target in the manifest, writing
+    /// SQL into the output directory of the target. Packages which the project
+    /// depends on are mounted as modules, and recorded in `prql.lock`.
+    #[command(name = "build")]
+    Build {
+        /// Directory containing `prql.toml`
+        #[arg(default_value = ".", value_hint(ValueHint::DirPath))]
+        project: PathBuf,
+
+        /// Only build this target of the manifest
+        #[arg(long = "target", value_name = "NAME")]
+        target_name: Option<String>,
+
+        /// Fail rather than update `prql.lock`, if the packages which the
+        /// project depends on have changed
+        #[arg(long)]
+        locked: bool,
+
+        /// Fail if compilation emits any warnings
+        #[arg(long)]
+        deny_warnings: bool,
     },
 
     /// Parse the whole project and collect it into a single PRQL source file
@@ -158,6 +220,30 @@ enum Command {
         /// File path into which to write the debug log to.
         #[arg(long, env = "PRQLC_DEBUG_LOG")]
         debug_log: Option<PathBuf>,
+
+        /// Compile every public relation into its own statement, rather than
+        /// only the main pipeline. Relations whose names start with `_` are
+        /// private.
+        #[arg(long)]
+        all_relations: bool,
+
+        /// Wrap each statement in `CREATE VIEW`
+        #[arg(long, requires = "all_relations")]
+        create_views: bool,
+
+        /// Write each relation into its own `<name>.sql` file in this
+        /// directory, rather than writing a single script
+        #[arg(long, value_name = "DIR", requires = "all_relations")]
+        output_dir: Option<PathBuf>,
+
+        /// Fail if compilation emits any warnings
+        #[arg(long)]
+        deny_warnings: bool,
+
+        /// Apply the fixes suggested for errors to the input files before
+        /// compiling them
+        #[arg(long)]
+        fix: bool,
     },
 
     /// Watch a directory and compile .prql files to .sql files
@@ -167,6 +253,12 @@ enum Command {
     #[command(name = "list-targets")]
     ListTargets,
 
+    /// Print a long-form explanation of an error code, such as `E0005`
+    Explain {
+        /// The error code, as shown in error messages
+        code: String,
+    },
+
     /// Language Server Protocol
     #[command(hide = true)]
     Lsp,
@@ -267,6 +359,51 @@ pub struct IoArgs {
     main_path: Option<String>,
 }
 
+/// Overrides of the options in `.prqlfmt.toml`
+#[derive(clap::Args, Default, Debug, Clone)]
+pub struct FormatArgs {
+    /// Maximum number of characters per line [default: 50]
+    #[arg(long)]
+    max_width: Option<u16>,
+
+    /// Number of spaces per indentation level [default: 2]
+    #[arg(long)]
+    indent_width: Option<u16>,
+
+    /// Write each transform of nested pipelines on its own line [default: false]
+    #[arg(long)]
+    break_pipelines: Option<bool>,
+
+    /// Emit a comma after the last item of multi-line tuples and arrays [default: true]
+    #[arg(long)]
+    trailing_commas: Option<bool>,
+
+    /// Align the `=` of assignments in multi-line tuples [default: false]
+    #[arg(long)]
+    align_assignments: Option<bool>,
+}
+
+impl FormatArgs {
+    fn apply(&self, mut options: FormatOptions) -> FormatOptions {
+        if let Some(max_width) = self.max_width {
+            options.max_width = max_width;
+        }
+        if let Some(indent_width) = self.indent_width {
+            options.indent_width = indent_width;
+        }
+        if let Some(break_pipelines) = self.break_pipelines {
+            options.break_pipelines = break_pipelines;
+        }
+        if let Some(trailing_commas) = self.trailing_commas {
+            options.trailing_commas = trailing_commas;
+        }
+        if let Some(align_assignments) = self.align_assignments {
+            options.align_assignments = align_assignments;
+        }
+        options
+    }
+}
+
 #[derive(clap::ValueEnum, Clone, Debug)]
 enum DocsFormat {
     Html,
@@ -292,35 +429,22 @@ impl Command {
         match self {
             Command::Watch(command) => watch::run(command),
             Command::ListTargets => self.list_targets(),
+            Command::Explain { code } => explain(code),
             // Format is handled differently to the other IO commands, since it
             // always writes to the same output.
-            Command::Format { input } => {
-                let sources = read_files(input)?;
-                let root = sources.root;
-
-                for (path, source) in sources.sources {
-                    let ast = prql_to_pl(&source)?;
-
-                    // If we're writing to stdout (though could this be nicer?
-                    // We're discarding many of the benefits of Clio here...)
-                    if path.as_os_str() == "" {
-                        let mut output: Output = Output::new(input.path())?;
-                        output.write_all(&pl_to_prql(&ast)?.into_bytes())?;
-                        break;
-                    }
-
-                    let path_buf = root
-                        .as_ref()
-                        .map_or_else(|| path.clone(), |root| root.join(&path));
-                    let path_str = path_buf.to_str().ok_or_else(|| {
-                        anyhow!("Path `{}` is not valid UTF-8", path_buf.display())
-                    })?;
-                    let mut output: Output = Output::new(path_str)?;
-
-                    output.write_all(&pl_to_prql(&ast)?.into_bytes())?;
-                }
-                Ok(())
-            }
+            Command::Format {
+                input,
+                format_args,
+                check,
+                stdin,
+            } => format(input, format_args, *check, *stdin),
+            Command::Lint { input, allow } => lint(input, allow),
+            Command::Build {
+                project,
+                target_name,
+                locked,
+                deny_warnings,
+            } => build(project, target_name.as_deref(), *locked, *deny_warnings),
             Command::ShellCompletion { shell } => {
                 shell.generate(&mut Cli::command(), &mut std::io::stdout());
                 Ok(())
@@ -360,11 +484,7 @@ impl Command {
     }
 
     fn execute<'a>(&self, sources: &'a mut SourceTree, main_path: &'a str) -> Result<Vec<u8>> {
-        let main_path = main_path
-            .split('.')
-            .filter(|x| !x.is_empty())
-            .map(str::to_string)
-            .collect_vec();
+        let main_path = prqlc::split_main_path(main_path);
 
         Ok(match self {
             Command::Parse { format, .. } => {
@@ -410,7 +530,7 @@ impl Command {
                 let root_mod = prql_to_pl(&source)?;
 
                 // resolve
-                let ctx = semantic::resolve(root_mod)?;
+                let ctx = semantic::resolve(root_mod, &debug::DebugLogger::default())?;
 
                 let frames = if let Ok((main, _)) = ctx.find_main_rel(&[]) {
                     semantic::reporting::collect_frames(*main.clone().into_relation_var().unwrap())
@@ -457,29 +577,59 @@ impl Command {
                 format,
                 target,
                 debug_log,
-                ..
+                all_relations,
+                create_views,
+                output_dir,
+                deny_warnings,
+                fix,
+                io_args,
             } => {
-                if debug_log.is_some() {
-                    debug::log_start();
-                }
-
                 let opts = Options::default()
                     .with_target(Target::from_str(target).map_err(prqlc::ErrorMessages::from)?)
                     .with_signature_comment(*signature_comment)
                     .with_format(*format);
 
-                let res = prql_to_pl_tree(sources)
-                    .and_then(|pl| {
-                        pl_to_rq_tree(pl, &main_path, &[semantic::NS_DEFAULT_DB.to_string()])
-                    })
-                    .and_then(|rq| rq_to_sql(rq, &opts))
-                    .map_err(|e| e.composed(sources));
+                // each round of fixes compiles the sources again, so share a
+                // cache between them
+                let mut cache = CompilationCache::new();
+                let mut compile = |sources: &SourceTree, opts: &Options| {
+                    let (all_relations, create_views) = (*all_relations, *create_views);
+                    compile_sources(
+                        &mut cache,
+                        sources,
+                        &main_path,
+                        opts,
+                        all_relations,
+                        create_views,
+                    )
+                };
 
-                if let Some(path) = debug_log {
-                    write_log(path)?;
+                if *fix {
+                    fix_sources(sources, io_args.input.path(), |sources| {
+                        compile(sources, &opts).map(drop)
+                    })?;
                 }
 
-                res?.as_bytes().to_vec()
+                let (res, warnings) = with_debug_log(debug_log.as_deref(), |log| {
+                    let opts = opts.clone().with_debug_log(log);
+                    collect_warnings(|| compile(sources, &opts))
+                })?;
+
+                match report_warnings(res, warnings, sources, *deny_warnings)? {
+                    Compiled::Query(sql) => sql.into_bytes(),
+                    Compiled::Relations(relations) => match output_dir {
+                        Some(dir) => {
+                            fs::create_dir_all(dir)?;
+                            for relation in &relations {
+                                let path = dir.join(format!("{}.sql", relation.name));
+                                let script = relations_to_script(slice::from_ref(relation), &opts);
+                                fs::write(path, script)?;
+                            }
+                            Vec::new()
+                        }
+                        None => relations_to_script(&relations, &opts).into_bytes(),
+                    },
+                }
             }
             _ => unreachable!("Other commands shouldn't reach `execute`"),
         })
@@ -545,32 +695,99 @@ impl Command {
     }
 }
 
-fn has_debug_log(cli: &Cli) -> bool {
-    matches!(
-        cli.command,
-        Some(Command::Compile {
-            debug_log: Some(_),
-            ..
-        })
-    )
+/// The output of `prqlc compile`.
+enum Compiled {
+    Query(String),
+    Relations(Vec<prqlc::CompiledRelation>),
 }
 
-pub fn write_log(path: &std::path::Path) -> Result<()> {
-    let debug_log = if let Some(debug_log) = debug::log_finish() {
-        debug_log
+/// Compiles the main relation of `sources`, or each of their relations.
+fn compile_sources(
+    cache: &mut CompilationCache,
+    sources: &SourceTree,
+    main_path: &[String],
+    options: &Options,
+    all_relations: bool,
+    create_views: bool,
+) -> std::result::Result<Compiled, prqlc::ErrorMessages> {
+    if all_relations {
+        (cache.compile_relations(sources, options, create_views)).map(Compiled::Relations)
     } else {
-        return Err(anyhow!(
-            "debug log was started, but it cannot be found after compilation"
-        ));
+        (cache.compile_tree(sources, main_path, options)).map(Compiled::Query)
+    }
+}
+
+/// Compiling again after fixing an error may find another, so fixes are
+/// applied in rounds; this limits how many.
+const MAX_FIX_ROUNDS: usize = 8;
+
+/// Applies the fixes suggested for the errors of `compile`, until it succeeds
+/// or no more errors can be fixed, and writes the fixed files back. A source
+/// read from stdin is only fixed in memory.
+fn fix_sources(
+    sources: &mut SourceTree,
+    input: &Path,
+    mut compile: impl FnMut(&SourceTree) -> std::result::Result<(), prqlc::ErrorMessages>,
+) -> Result<()> {
+    let original = sources.sources.clone();
+    for _ in 0..MAX_FIX_ROUNDS {
+        let Err(errors) = compile(sources) else {
+            break;
+        };
+        if errors.apply_fixes(sources) == 0 {
+            break;
+        }
+    }
+
+    for (path, source) in sources.sources.iter().sorted() {
+        if original.get(path) == Some(source) {
+            continue;
+        }
+
+        // An empty path is a single file or stdin, rather than a directory.
+        let path_buf = if path.as_os_str() == "" {
+            input.to_path_buf()
+        } else {
+            (sources.root.as_ref()).map_or_else(|| path.clone(), |root| root.join(path))
+        };
+        if path_buf.as_os_str() == "-" {
+            eprintln!("Fixed the input");
+        } else {
+            fs::write(&path_buf, source)?;
+            eprintln!("Fixed `{}`", path_buf.display());
+        }
+    }
+    Ok(())
+}
+
+/// Runs `f` with a debug logger, writing its log to `path` if there is one.
+/// Otherwise the logger is disabled.
+fn with_debug_log<R>(path: Option<&Path>, f: impl FnOnce(debug::DebugLogger) -> R) -> Result<R> {
+    let Some(path) = path else {
+        return Ok(f(debug::DebugLogger::default()));
     };
+    let logger = debug::DebugLogger::new();
+
+    // redirect log messages into the [debug::DebugLog] of the compilation
+    // they come from
+    if log::set_logger(&debug::MessageLogger).is_ok() {
+        log::set_max_level(log::LevelFilter::max());
+    }
+
+    let result = f(logger.clone());
+    write_log(path, &logger.take().unwrap())?;
+    Ok(result)
+}
+
+pub fn write_log(path: &std::path::Path, debug_log: &debug::DebugLog) -> Result<()> {
     match path.extension().and_then(|s| s.to_str()) {
         Some("json") => {
             let file = BufWriter::new(File::create(path)?);
-            serde_json::to_writer(file, &debug_log)?;
+            serde_json::to_writer(file, debug_log)?;
         }
         Some("html") => {
             let file = BufWriter::new(File::create(path)?);
-            debug::render_log_to_html(file, &debug_log)?;
+            debug::render_log_to_html(file, debug_log)?;
         }
         _ => {
             return Err(anyhow!("unknown debug log format for file {path:?}"));
@@ -599,6 +816,220 @@ fn read_files(input: &mut clio::ClioPath) -> Result<SourceTree> {
     Ok(SourceTree::new(sources, Some(root.to_path_buf())))
 }
 
+fn format(
+    input: &mut clio::ClioPath,
+    format_args: &FormatArgs,
+    check: bool,
+    stdin: bool,
+) -> Result<()> {
+    let options = match find_config(input.path(), FORMAT_CONFIG_FILE)? {
+        Some(options) => options,
+        None => find_config::<Manifest>(input.path(), Manifest::FILE_NAME)?
+            .map(|manifest| manifest.format)
+            .unwrap_or_default(),
+    };
+    let options = format_args.apply(options);
+    let sources = if stdin {
+        let mut source = String::new();
+        io::stdin().read_to_string(&mut source)?;
+        SourceTree::from(source)
+    } else {
+        read_files(input)?
+    };
+    let root = sources.root;
+
+    let mut unformatted = Vec::new();
+    for (path, source) in sources.sources.into_iter().sorted() {
+        let formatted = format_prql(&source, &options)?;
+
+        // An empty path is a single file or stdin, rather than a directory.
+        let path_buf = if path.as_os_str() == "" {
+            input.path().to_path_buf()
+        } else {
+            root.as_ref()
+                .map_or_else(|| path.clone(), |root| root.join(&path))
+        };
+        let to_stdout = stdin || path_buf.as_os_str() == "-";
+
+        if check {
+            if formatted != source {
+                let name = if path_buf.as_os_str() == "-" {
+                    "<stdin>".to_string()
+                } else {
+                    path_buf.display().to_string()
+                };
+                let diff = similar::TextDiff::from_lines(&source, &formatted);
+                print!("{}", diff.unified_diff().header(&name, &name));
+                unformatted.push(name);
+            }
+        } else if to_stdout {
+            io::stdout().write_all(formatted.as_bytes())?;
+        } else {
+            let path_str = path_buf
+                .to_str()
+                .ok_or_else(|| anyhow!("Path `{}` is not valid UTF-8", path_buf.display()))?;
+            let mut output: Output = Output::new(path_str)?;
+
+            output.write_all(&formatted.into_bytes())?;
+        }
+    }
+
+    if !unformatted.is_empty() {
+        bail!(
+            "{} file(s) would be reformatted: {}",
+            unformatted.len(),
+            unformatted.join(", ")
+        );
+    }
+    Ok(())
+}
+
+fn lint(input: &mut clio::ClioPath, allow: &[LintRule]) -> Result<()> {
+    let mut options: LintOptions = read_config(input.path(), LINT_CONFIG_FILE)?;
+    options.allow.extend(allow);
+
+    let sources = read_files(input)?;
+    let lints = lint_tree(&sources, &options)?;
+    for lint in &lints {
+        eprint!("{lint}");
+    }
+
+    if !lints.is_empty() {
+        bail!("{} lint(s) found", lints.len());
+    }
+    Ok(())
+}
+
+fn explain(code: &str) -> Result<()> {
+    let Some(explanation) = prqlc::explain_error_code(code) else {
+        bail!(
+            "`{code}` is not an error code; known codes are {}",
+            prqlc::error_codes().collect::<Vec<_>>().join(", ")
+        );
+    };
+    print!("{explanation}");
+    Ok(())
+}
+
+const FORMAT_CONFIG_FILE: &str = ".prqlfmt.toml";
+const LINT_CONFIG_FILE: &str = ".prqllint.toml";
+
+/// Reads options from the closest config file named `file_name` in the
+/// directory of the input or any of its parents. When reading from stdin, the
+/// search starts at the current directory.
+fn read_config<T: DeserializeOwned + Default>(input: &Path, file_name: &str) -> Result<T> {
+    Ok(find_config(input, file_name)?.unwrap_or_default())
+}
+
+/// Like [read_config], but returns `None` when there is no config file.
+fn find_config<T: DeserializeOwned>(input: &Path, file_name: &str) -> Result<Option<T>> {
+    let start = if input == Path::new("-") {
+        env::current_dir()?
+    } else {
+        // The path of `--stdin` may not exist yet, as with unsaved buffers of
+        // editors, so we start from the closest ancestor which does.
+        let input = env::current_dir()?.join(input);
+        let existing = input.ancestors().find(|p| p.exists()).unwrap_or(&input);
+        existing.canonicalize()?
+    };
+
+    for dir in start.ancestors() {
+        let path = dir.join(file_name);
+        if path.is_file() {
+            return read_toml(&path).map(Some);
+        }
+    }
+    Ok(None)
+}
+
+fn read_toml<T: DeserializeOwned>(path: &Path) -> Result<T> {
+    let contents = std::fs::read_to_string(path)?;
+    toml::from_str(&contents).map_err(|e| anyhow!("Could not read `{}`: {e}", path.display()))
+}
+
+/// Builds each target of the project in `project_dir` into its output
+/// directory.
+fn build(
+    project_dir: &Path,
+    target_name: Option<&str>,
+    locked: bool,
+    deny_warnings: bool,
+) -> Result<()> {
+    let manifest_path = project_dir.join(Manifest::FILE_NAME);
+    if !manifest_path.is_file() {
+        bail!("Could not find `{}`", manifest_path.display());
+    }
+    let manifest: Manifest = read_toml(&manifest_path)?;
+
+    let mut sources = read_dir(&project_dir.join(&manifest.project.source))?;
+    let lock_file = packages::mount_dependencies(project_dir, &manifest, &mut sources)?;
+    packages::update_lock_file(project_dir, &lock_file, locked)?;
+
+    let targets = if target_name.is_some() {
+        manifest.find_build_target(target_name).map(|t| vec![t])
+    } else {
+        manifest.build_targets()
+    };
+    // targets compile the same sources, so they share a cache
+    let mut cache = CompilationCache::new();
+    for target in targets.map_err(prqlc::ErrorMessages::from)? {
+        let output_dir = project_dir.join(&target.output);
+        fs::create_dir_all(&output_dir)?;
+
+        let (outputs, warnings) = collect_warnings(|| {
+            if target.all_relations {
+                let relations =
+                    cache.compile_relations(&sources, &target.options, target.create_views)?;
+                Ok(relations
+                    .iter()
+                    .map(|relation| {
+                        let script =
+                            relations_to_script(slice::from_ref(relation), &target.options);
+                        (relation.name.to_string(), script)
+                    })
+                    .collect())
+            } else {
+                let sql = cache.compile_tree(&sources, &target.main_path, &target.options)?;
+                Ok(vec![(target.main_path.join("."), sql)])
+            }
+        });
+        let outputs: Vec<(String, String)> =
+            report_warnings(outputs, warnings, &sources, deny_warnings)?;
+
+        for (name, sql) in outputs {
+            let path = output_dir.join(format!("{name}.sql"));
+            fs::write(&path, sql)?;
+            eprintln!("Wrote {}", path.display());
+        }
+    }
+    Ok(())
+}
+
+/// Prints the warnings of a compilation, and fails if there are any and they
+/// are denied. Errors of the compilation take precedence.
+fn report_warnings<T>(
+    result: std::result::Result<T, prqlc::ErrorMessages>,
+    warnings: Vec<prqlc::Error>,
+    sources: &SourceTree,
+    deny_warnings: bool,
+) -> Result<T> {
+    let warnings = prqlc::ErrorMessages::from(prqlc::Errors(warnings)).composed(sources);
+    eprint!("{warnings}");
+
+    let value = result?;
+    if deny_warnings && !warnings.inner.is_empty() {
+        bail!(
+            "{} warning(s) found, and warnings are denied",
+            warnings.inner.len()
+        );
+    }
+    Ok(value)
+}
+
+fn read_dir(path: &Path) -> Result<SourceTree> {
+    read_files(&mut clio::ClioPath::new(path)?)
+}
+
 fn combine_prql_and_frames(source: &str, frames: Vec<(Option<pr::Span>, pl::Lineage)>) -> String {
     let source = Source::from(source);
     let lines = source.lines().collect_vec();
@@ -612,7 +1043,12 @@ fn combine_prql_and_frames(source: &str, frames: Vec<(Option<pr::Span>, pl::Line
     let mut result = Vec::new();
     for (span, frame) in frames {
         if let Some(span) = span {
-            let line_len = source.get_line_range(&Range::from(span)).end - 1;
+            // spans are byte offsets, while `get_line_range` takes chars
+            let last = span.end.saturating_sub(1).max(span.start);
+            let line_len = source
+                .get_byte_line(last)
+                .map_or(lines.len(), |(_, l, _)| l + 1)
+                - 1;
 
             while printed_lines_count < line_len {
                 result.push(
@@ -696,13 +1132,18 @@ sort full
                 format: true,
                 target: "sql.any".to_string(),
                 debug_log: None,
+                all_relations: false,
+                create_views: false,
+                output_dir: None,
+                deny_warnings: false,
+                fix: false,
             },
             &mut "asdf".into(),
             "",
         );
 
         assert_snapshot!(&result.unwrap_err().to_string(), @r"
-        Error:
+        [E0005] Error:
            ╭─[ :1:1 ]
            │
          1 │ asdf
@@ -712,6 +1153,30 @@ sort full
         ");
     }
 
+    #[test]
+    fn compile_deny_warnings() {
+        anstream::ColorChoice::Never.write_global();
+
+        let result = Command::execute(
+            &Command::Compile {
+                io_args: IoArgs::default(),
+                signature_comment: false,
+                format: true,
+                target: "sql.any".to_string(),
+                debug_log: None,
+                all_relations: false,
+                create_views: false,
+                output_dir: None,
+                deny_warnings: true,
+                fix: false,
+            },
+            &mut "from t | aggregate {n = count_distinct x}".into(),
+            "",
+        );
+
+        assert_snapshot!(&result.unwrap_err().to_string(), @"1 warning(s) found, and warnings are denied");
+    }
+
     #[test]
     fn compile() {
         let result = Command::execute(
@@ -721,6 +1186,11 @@ sort full
                 format: true,
                 target: "sql.any".to_string(),
                 debug_log: None,
+                all_relations: false,
+                create_views: false,
+                output_dir: None,
+                deny_warnings: false,
+                fix: false,
             },
             &mut SourceTree::new(
                 [
@@ -0,0 +1,209 @@
+//! Loading the packages a project depends on, and keeping its lock file up to
+//! date.
+
+use std::collections::HashMap;
+use std::fs;
+use std::io::{self, Read};
+use std::path::{Component, Path, PathBuf};
+
+use anyhow::{anyhow, bail, Result};
+use flate2::read::GzDecoder;
+use itertools::Itertools;
+use prqlc::{LockFile, Manifest, Package, PackageSource, SourceTree};
+use sha2::{Digest, Sha256};
+
+use super::{read_dir, read_toml};
+
+/// Loads each dependency of the project in `project_dir`, checks its version,
+/// and mounts it into `sources`. Returns the lock file of the loaded packages.
+pub fn mount_dependencies(
+    project_dir: &Path,
+    manifest: &Manifest,
+    sources: &mut SourceTree,
+) -> Result<LockFile> {
+    let mut locked = Vec::new();
+    for (name, dependency) in &manifest.dependencies {
+        let source = dependency
+            .source(name)
+            .map_err(prqlc::ErrorMessages::from)?;
+        let (package, checksum) = load_package(project_dir, &source)
+            .map_err(|e| anyhow!("Could not load package `{name}` from `{source}`: {e}"))?;
+
+        locked.push(
+            package
+                .lock(name, dependency, checksum)
+                .map_err(prqlc::ErrorMessages::from)?,
+        );
+        sources
+            .insert_dependency(name, package.sources)
+            .map_err(prqlc::ErrorMessages::from)?;
+    }
+    Ok(LockFile::new(locked))
+}
+
+/// Writes the lock file if it has changed. With `locked`, a lock file which
+/// would change is an error instead.
+pub fn update_lock_file(project_dir: &Path, lock_file: &LockFile, locked: bool) -> Result<()> {
+    let path = project_dir.join(LockFile::FILE_NAME);
+    let existing: Option<LockFile> = if path.is_file() {
+        Some(read_toml(&path)?)
+    } else {
+        None
+    };
+    if existing.as_ref() == Some(lock_file) {
+        return Ok(());
+    }
+    // A project without dependencies doesn't need a lock file
+    if existing.is_none() && lock_file.packages.is_empty() {
+        return Ok(());
+    }
+
+    if locked {
+        let changed = existing.map_or_else(
+            || lock_file.packages.iter().map(|p| p.name.clone()).collect(),
+            |existing| lock_file.changed_packages(&existing),
+        );
+        bail!(
+            "`{}` needs to be updated, but `--locked` was passed; changed packages: {}",
+            LockFile::FILE_NAME,
+            changed.join(", ")
+        );
+    }
+
+    let contents = format!(
+        "# This file is generated by `prqlc build`; it's not meant to be edited.\n\n{}",
+        toml::to_string(lock_file)?
+    );
+    fs::write(&path, contents)?;
+    Ok(())
+}
+
+/// Loads a package, along with the checksum of its contents for the lock file.
+fn load_package(project_dir: &Path, source: &PackageSource) -> Result<(Package, String)> {
+    match source {
+        PackageSource::Path(path) => {
+            let dir = project_dir.join(path);
+            let manifest_path = dir.join(Manifest::FILE_NAME);
+            let manifest: Option<Manifest> = if manifest_path.is_file() {
+                Some(read_toml(&manifest_path)?)
+            } else {
+                None
+            };
+            let source_dir = manifest
+                .as_ref()
+                .map_or(Path::new("."), |m| &m.project.source);
+            let sources = read_dir(&dir.join(source_dir))?;
+            let checksum = sources_checksum(&sources);
+            Ok((Package { sources, manifest }, checksum))
+        }
+        PackageSource::Archive(path) => {
+            let archive = fs::read(project_dir.join(path))?;
+            let checksum = checksum(Sha256::new().chain_update(&archive));
+            let is_gzip = path
+                .extension()
+                .is_some_and(|ext| ext == "gz" || ext == "tgz");
+            let files = if is_gzip {
+                read_tar(GzDecoder::new(archive.as_slice()))?
+            } else {
+                read_tar(archive.as_slice())?
+            };
+            Ok((package_from_files(files)?, checksum))
+        }
+    }
+}
+
+/// A hash of the paths and contents of all sources, which changes when any of
+/// them change.
+fn sources_checksum(sources: &SourceTree) -> String {
+    let mut hasher = Sha256::new();
+    for (path, content) in sources.sources.iter().sorted() {
+        let path = path.iter().map(|part| part.to_string_lossy()).join("/");
+        for part in [path.as_bytes(), content.as_bytes()] {
+            hasher.update((part.len() as u64).to_le_bytes());
+            hasher.update(part);
+        }
+    }
+    checksum(hasher)
+}
+
+fn checksum(hasher: Sha256) -> String {
+    format!("sha256:{:x}", hasher.finalize())
+}
+
+/// Builds a package from the files of an archive. Archives usually contain a
+/// single directory named after the package, which is removed from the paths.
+fn package_from_files(files: HashMap<PathBuf, Vec<u8>>) -> Result<Package> {
+    let single_dir = files
+        .keys()
+        .map(|path| {
+            path.components()
+                .next()
+                .map(|c| PathBuf::from(c.as_os_str()))
+        })
+        .all_equal_value()
+        .ok()
+        .flatten()
+        .filter(|_| files.keys().all(|path| path.components().count() > 1));
+    let files: HashMap<PathBuf, Vec<u8>> = match single_dir {
+        Some(dir) => files
+            .into_iter()
+            .map(|(path, content)| (path.strip_prefix(&dir).unwrap().to_path_buf(), content))
+            .collect(),
+        None => files,
+    };
+
+    let manifest: Option<Manifest> = match files.get(Path::new(Manifest::FILE_NAME)) {
+        Some(contents) => Some(toml::from_str(std::str::from_utf8(contents)?)?),
+        None => None,
+    };
+
+    let source_dir = manifest
+        .as_ref()
+        .map_or(Path::new("."), |m| &m.project.source);
+    let source_dir: PathBuf = source_dir
+        .components()
+        .filter(|c| *c != Component::CurDir)
+        .collect();
+
+    let mut sources = Vec::new();
+    for (path, content) in files.into_iter().sorted() {
+        let Ok(path) = path.strip_prefix(&source_dir) else {
+            continue;
+        };
+        if path.extension().is_some_and(|ext| ext == "prql") {
+            sources.push((path.to_path_buf(), String::from_utf8(content)?));
+        }
+    }
+    if sources.is_empty() {
+        bail!("the archive doesn't contain any `.prql` files");
+    }
+    Ok(Package {
+        sources: SourceTree::new(sources, None),
+        manifest,
+    })
+}
+
+/// Reads the regular files of a tar archive.
+fn read_tar(reader: impl Read) -> Result<HashMap<PathBuf, Vec<u8>>> {
+    let mut archive = tar::Archive::new(reader);
+    let mut files = HashMap::new();
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        // directories, links and metadata
+        if !entry.header().entry_type().is_file() {
+            continue;
+        }
+
+        let path: PathBuf = (entry.path()?.components())
+            .filter(|c| matches!(c, Component::Normal(_)))
+            .collect();
+        let mut content = Vec::new();
+        entry.read_to_end(&mut content)?;
+        files.insert(path, content);
+    }
+
+    // A gzip file is only checked against its checksum once it's read to
+    // the end, which the tar reader may not do.
+    io::copy(&mut archive.into_inner(), &mut io::sink())?;
+    Ok(files)
+}
@@ -3,8 +3,8 @@ use std::fs;
 use std::path::Path;
 use std::path::PathBuf;
 use std::process::Command;
-use std::str::FromStr;
 
+use insta::assert_snapshot;
 use insta_cmd::assert_cmd_snapshot;
 use insta_cmd::get_cargo_bin;
 use tempfile::TempDir;
@@ -22,13 +22,16 @@ fn help() {
     Commands:
       parse             Parse into PL AST
       lex               Lex into Lexer Representation
-      fmt               Parse & generate PRQL code back
+      fmt               Format PRQL code, retaining comments
+      lint              Check PRQL code for likely mistakes
+      build             Compile a project, as configured by its `prql.toml`
       collect           Parse the whole project and collect it into a single PRQL source file
       debug             Commands for meant for debugging, prone to change
       experimental      Experimental commands are prone to change
       compile           Parse, resolve, lower into RQ & compile to SQL
       watch             Watch a directory and compile .prql files to .sql files
       list-targets      Show available compile target names
+      explain           Print a long-form explanation of an error code, such as `E0005`
       shell-completion  Print a shell completion for supported shells
       help              Print this message or the help of the given subcommand(s)
 
@@ -51,13 +54,16 @@ fn help() {
     Commands:
       parse             Parse into PL AST
       lex               Lex into Lexer Representation
-      fmt               Parse & generate PRQL code back
+      fmt               Format PRQL code, retaining comments
+      lint              Check PRQL code for likely mistakes
+      build             Compile a project, as configured by its `prql.toml`
       collect           Parse the whole project and collect it into a single PRQL source file
       debug             Commands for meant for debugging, prone to change
       experimental      Experimental commands are prone to change
       compile           Parse, resolve, lower into RQ & compile to SQL
       watch             Watch a directory and compile .prql files to .sql files
       list-targets      Show available compile target names
+      explain           Print a long-form explanation of an error code, such as `E0005`
       shell-completion  Print a shell completion for supported shells
       help              Print this message or the help of the given subcommand(s)
 
@@ -153,6 +159,23 @@ fn compile_help() {
               
               [env: PRQLC_DEBUG_LOG=]
 
+          --all-relations
+              Compile every public relation into its own statement, rather than only the main pipeline.
+              Relations whose names start with `_` are private
+
+          --create-views
+              Wrap each statement in `CREATE VIEW`
+
+          --output-dir <DIR>
+              Write each relation into its own `<name>.sql` file in this directory, rather than writing
+              a single script
+
+          --deny-warnings
+              Fail if compilation emits any warnings
+
+          --fix
+              Apply the fixes suggested for errors to the input files before compiling them
+
           --color <WHEN>
               Controls when to use color
               
@@ -166,10 +189,165 @@ fn compile_help() {
     ");
 }
 
+#[test]
+fn compile_warnings() {
+    let prql = "from tracks | aggregate {n = count_distinct artist}";
+
+    assert_cmd_snapshot!(prqlc_command()
+        .args(["compile", "--hide-signature-comment"])
+        .pass_stdin(prql), @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    SELECT
+      COUNT(DISTINCT artist) AS n
+    FROM
+      tracks
+
+    ----- stderr -----
+    Warning:
+       ╭─[ :1:30 ]
+       │
+     1 │ from tracks | aggregate {n = count_distinct artist}
+       │                              ───────┬──────
+       │                                     ╰──────── `count_distinct` is deprecated
+    ───╯
+    ");
+
+    assert_cmd_snapshot!(prqlc_command()
+        .args(["compile", "--hide-signature-comment", "--deny-warnings"])
+        .pass_stdin(prql), @r"
+    success: false
+    exit_code: 1
+    ----- stdout -----
+
+    ----- stderr -----
+    Warning:
+       ╭─[ :1:30 ]
+       │
+     1 │ from tracks | aggregate {n = count_distinct artist}
+       │                              ───────┬──────
+       │                                     ╰──────── `count_distinct` is deprecated
+    ───╯
+    1 warning(s) found, and warnings are denied
+    ");
+}
+
+#[test]
+fn compile_fix() {
+    let prql = "from tracks | select {title, milliseconds} | derive {seconds = milisecond / 1000}";
+
+    assert_cmd_snapshot!(prqlc_command()
+        .args(["compile", "--hide-signature-comment", "--fix"])
+        .pass_stdin(prql), @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    SELECT
+      title,
+      milliseconds,
+      milliseconds / 1000 AS seconds
+    FROM
+      tracks
+
+    ----- stderr -----
+    Fixed the input
+    ");
+
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let query = temp_dir.path().join("query.prql");
+    fs::write(&query, prql).unwrap();
+
+    prqlc_command()
+        .args(["compile", "--fix", query.to_str().unwrap()])
+        .status()
+        .unwrap();
+    assert_snapshot!(fs::read_to_string(&query).unwrap(), @"from tracks | select {title, milliseconds} | derive {seconds = milliseconds / 1000}");
+}
+
+#[test]
+fn compile_fix_non_ascii() {
+    // the spans of fixes are byte offsets, which differ from char offsets after `é`
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let query = temp_dir.path().join("query.prql");
+    fs::write(
+        &query,
+        r#"from t
+derive {a = "éééééé", b = std.coutn a}
+select {a, b}
+"#,
+    )
+    .unwrap();
+
+    assert_cmd_snapshot!(prqlc_command()
+        .current_dir(temp_dir.path())
+        .args(["compile", "--hide-signature-comment", "--fix", "query.prql"]), @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    SELECT
+      'éééééé' AS a,
+      COUNT(*) OVER () AS b
+    FROM
+      t
+
+    ----- stderr -----
+    Fixed `query.prql`
+    ");
+    assert_snapshot!(fs::read_to_string(&query).unwrap(), @r#"
+    from t
+    derive {a = "éééééé", b = std.count a}
+    select {a, b}
+    "#);
+}
+
+#[test]
+fn explain() {
+    assert_cmd_snapshot!(prqlc_command().args(["explain", "e0001"]), @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    A query has no main pipeline.
+
+    Erroneous code example:
+
+    ```prql
+    let top_albums = (from albums | take 10)
+    ```
+
+    A PRQL query must end with a pipeline which starts with `from`. The result of
+    this pipeline is the result of the query. Declarations such as `let` only
+    define relations which the main pipeline can refer to.
+
+    Add a main pipeline:
+
+    ```prql
+    let top_albums = (from albums | take 10)
+
+    from top_albums
+    ```
+
+    ----- stderr -----
+    ");
+
+    assert_cmd_snapshot!(prqlc_command().args(["explain", "E9999"]), @r"
+    success: false
+    exit_code: 1
+    ----- stdout -----
+
+    ----- stderr -----
+    `E9999` is not an error code; known codes are E0001, E0002, E0003, E0004, E0005, E0006, E0007, E0008, E0009, E0010, E0011, E0012, E0013, E0014, E0015, E0016, E0017, E0018, E0019, E0020, E0021, E0022, E0023
+    ");
+}
+
 #[test]
 fn long_query() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let debug_log = temp_dir.path().join("log_test.html");
+
     assert_cmd_snapshot!(prqlc_command()
-        .args(["compile", "--hide-signature-comment", "--debug-log=log_test.html"])
+        .args(["compile", "--hide-signature-comment", "--debug-log"])
+        .arg(&debug_log)
         .pass_stdin(r#"
 let long_query = (
   from employees
@@ -262,20 +440,18 @@ from long_query
     ");
 
     // don't check the contents, they are very prone to change
-    assert!(PathBuf::from_str("./log_test.html").unwrap().is_file());
+    assert!(debug_log.is_file());
 }
 
 #[test]
 fn compile_project() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let debug_log = temp_dir.path().join("log_test.json");
+
     let mut cmd = prqlc_command();
-    cmd.args([
-        "compile",
-        "--hide-signature-comment",
-        "--debug-log=log_test.json",
-        project_path().to_str().unwrap(),
-        "-",
-        "main",
-    ]);
+    cmd.args(["compile", "--hide-signature-comment", "--debug-log"])
+        .arg(&debug_log)
+        .args([project_path().to_str().unwrap(), "-", "main"]);
 
     assert_cmd_snapshot!(cmd, @r"
     success: true
@@ -322,7 +498,7 @@ fn compile_project() {
     ");
 
     // don't check the contents, they are very prone to change
-    assert!(PathBuf::from_str("./log_test.json").unwrap().is_file());
+    assert!(debug_log.is_file());
 
     assert_cmd_snapshot!(prqlc_command()
       .args([
@@ -355,6 +531,281 @@ fn compile_project() {
     ");
 }
 
+#[test]
+fn compile_all_relations() {
+    assert_cmd_snapshot!(prqlc_command()
+      .args([
+        "compile",
+        "--hide-signature-comment",
+        "--all-relations",
+        "--create-views",
+        project_path().to_str().unwrap(),
+    ]), @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    CREATE VIEW favorite_artists AS
+    WITH table_0 AS (
+      SELECT
+        120 AS artist_id,
+        DATE '2023-05-18' AS last_listen
+      UNION
+      ALL
+      SELECT
+        7 AS artist_id,
+        DATE '2023-05-16' AS last_listen
+    )
+    SELECT
+      artist_id,
+      last_listen
+    FROM
+      table_0;
+
+    CREATE VIEW artists.input AS
+    WITH table_0 AS (
+      SELECT
+        *
+      FROM
+        read_parquet('artists.parquet')
+    )
+    SELECT
+      *
+    FROM
+      table_0;
+
+    ----- stderr -----
+    ");
+
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    assert_cmd_snapshot!(prqlc_command()
+      .args([
+        "compile",
+        "--hide-signature-comment",
+        "--all-relations",
+        "--output-dir",
+        temp_dir.path().to_str().unwrap(),
+        project_path().to_str().unwrap(),
+    ]), @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+
+    ----- stderr -----
+    ");
+    let mut files: Vec<_> = fs::read_dir(temp_dir.path())
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+        .collect();
+    files.sort();
+    assert_snapshot!(files.join(", "), @"artists.input.sql, favorite_artists.sql");
+}
+
+#[test]
+fn build() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let project = temp_dir.path().join("shop");
+    let common = temp_dir.path().join("common");
+    fs::create_dir_all(&project).unwrap();
+    fs::create_dir_all(&common).unwrap();
+
+    fs::write(
+        project.join("prql.toml"),
+        r#"
+[project]
+target = "sql.postgres"
+signature_comment = false
+
+[dependencies]
+common = { path = "../common" }
+
+[targets.postgres]
+
+[targets.duckdb]
+target = "sql.duckdb"
+output = "build/duckdb"
+all_relations = true
+create_views = true
+"#,
+    )
+    .unwrap();
+    fs::write(
+        project.join("Project.prql"),
+        "let large = (from common.recent | filter total > 100)\n\nfrom large\n",
+    )
+    .unwrap();
+    fs::write(
+        common.join("Common.prql"),
+        "let recent = (from orders | filter date > @2024-01-01)\n",
+    )
+    .unwrap();
+
+    assert_cmd_snapshot!(prqlc_command()
+        .args(["build"])
+        .current_dir(&project), @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+
+    ----- stderr -----
+    Wrote ./build/duckdb/common.recent.sql
+    Wrote ./build/duckdb/large.sql
+    Wrote ./build/main.sql
+    ");
+
+    let sql = fs::read_to_string(project.join("build/main.sql")).unwrap();
+    assert_snapshot!(sql, @r"
+    WITH recent AS (
+      SELECT
+        *
+      FROM
+        orders
+      WHERE
+        date > DATE '2024-01-01'
+    ),
+    large AS (
+      SELECT
+        *
+      FROM
+        recent
+      WHERE
+        total > 100
+    )
+    SELECT
+      *
+    FROM
+      large
+    ");
+
+    let sql = fs::read_to_string(project.join("build/duckdb/large.sql")).unwrap();
+    assert_snapshot!(sql, @r"
+    CREATE VIEW large AS
+    SELECT
+      *
+    FROM
+      common.recent
+    WHERE
+      total > 100;
+    ");
+
+    assert_cmd_snapshot!(prqlc_command()
+        .args(["build", "--target", "snowflake"])
+        .current_dir(&project), @r"
+    success: false
+    exit_code: 1
+    ----- stdout -----
+
+    ----- stderr -----
+    [E0021] Error: target `snowflake` is not in the manifest
+    ↳ Hint: available targets: duckdb, postgres
+    ");
+}
+
+#[test]
+fn build_packages() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    copy_dir(&packages_path(), temp_dir.path());
+    let app = temp_dir.path().join("app");
+
+    // a missing lock file is an error with `--locked`
+    assert_cmd_snapshot!(prqlc_command()
+        .args(["build", "--locked"])
+        .current_dir(&app), @r"
+    success: false
+    exit_code: 1
+    ----- stdout -----
+
+    ----- stderr -----
+    `prql.lock` needs to be updated, but `--locked` was passed; changed packages: calendar, dates
+    ");
+
+    assert_cmd_snapshot!(prqlc_command()
+        .args(["build"])
+        .current_dir(&app), @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+
+    ----- stderr -----
+    Wrote ./build/main.sql
+    ");
+    let lock_file = fs::read_to_string(app.join("prql.lock")).unwrap();
+    assert_snapshot!(lock_file, @r#"
+    # This file is generated by `prqlc build`; it's not meant to be edited.
+
+    version = 1
+
+    [[package]]
+    name = "calendar"
+    source = "archive+vendor/calendar-0.3.0.tar.gz"
+    checksum = "sha256:76b5a0bc3102a0f4e31b83ed95bd3b3ead50e2a44882090e5576da5ac34a4c95"
+
+    [[package]]
+    name = "dates"
+    source = "path+../dates"
+    version = "1.4.0"
+    prql_version = "^0.13"
+    checksum = "sha256:91a28bbed59aa04d4e0e754f470a65cd10ba2a38e77dbe20d3cd24b9cebc2a07"
+    "#);
+    assert_snapshot!(fs::read_to_string(app.join("build/main.sql")).unwrap(), @r"
+    WITH spine AS (
+      SELECT
+        day
+      FROM
+        days
+    ),
+    holidays AS (
+      SELECT
+        day,
+        name
+      FROM
+        holiday_table
+    )
+    SELECT
+      spine.day,
+      holidays.day,
+      holidays.name
+    FROM
+      spine
+      INNER JOIN holidays ON spine.day = holidays.day
+    ");
+
+    // changing a package updates the lock file, unless it's `--locked`
+    let dates = temp_dir.path().join("dates/src/Dates.prql");
+    let source = fs::read_to_string(&dates).unwrap();
+    fs::write(&dates, format!("{source}\nlet weekdays = (from spine)\n")).unwrap();
+    assert_cmd_snapshot!(prqlc_command()
+        .args(["build", "--locked"])
+        .current_dir(&app), @r"
+    success: false
+    exit_code: 1
+    ----- stdout -----
+
+    ----- stderr -----
+    `prql.lock` needs to be updated, but `--locked` was passed; changed packages: dates
+    ");
+    assert_eq!(
+        lock_file,
+        fs::read_to_string(app.join("prql.lock")).unwrap()
+    );
+
+    // version requirements are checked
+    fs::write(
+        temp_dir.path().join("dates/prql.toml"),
+        "[project]\nversion = \"2.0.0\"\nsource = \"src\"\n",
+    )
+    .unwrap();
+    assert_cmd_snapshot!(prqlc_command()
+        .args(["build"])
+        .current_dir(&app), @r"
+    success: false
+    exit_code: 1
+    ----- stdout -----
+
+    ----- stderr -----
+    [E0021] Error: package `dates` has version 2.0.0, but version ^1.2 is required
+    ");
+}
+
 #[test]
 fn format() {
     // Test stdin formatting
@@ -386,6 +837,151 @@ fn format() {
     compare_directories(&project_path(), temp_dir.path());
 }
 
+#[test]
+fn format_config() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let query = temp_dir.path().join("query.prql");
+    fs::write(&query, "from tracks | derive {a = 1, bbb = 2}").unwrap();
+    fs::write(
+        temp_dir.path().join(".prqlfmt.toml"),
+        "max_width = 10\nindent_width = 4\nalign_assignments = true\n",
+    )
+    .unwrap();
+
+    prqlc_command()
+        .args(["fmt", query.to_str().unwrap()])
+        .status()
+        .unwrap();
+    assert_snapshot!(fs::read_to_string(&query).unwrap(), @r"
+    from tracks
+    derive {
+        a   = 1,
+        bbb = 2,
+    }
+    ");
+
+    // flags override the config file
+    prqlc_command()
+        .args(["fmt", "--max-width=80", query.to_str().unwrap()])
+        .status()
+        .unwrap();
+    assert_snapshot!(fs::read_to_string(&query).unwrap(), @r"
+    from tracks
+    derive {a = 1, bbb = 2}
+    ");
+
+    // unknown options are an error
+    fs::write(temp_dir.path().join(".prqlfmt.toml"), "tab = 4\n").unwrap();
+    let output = prqlc_command()
+        .args(["fmt", query.to_str().unwrap()])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("unknown field `tab`"));
+
+    // without `.prqlfmt.toml`, options are read from the project manifest
+    fs::remove_file(temp_dir.path().join(".prqlfmt.toml")).unwrap();
+    fs::write(
+        temp_dir.path().join("prql.toml"),
+        "[format]\nmax_width = 10\n",
+    )
+    .unwrap();
+    prqlc_command()
+        .args(["fmt", query.to_str().unwrap()])
+        .status()
+        .unwrap();
+    assert_snapshot!(fs::read_to_string(&query).unwrap(), @r"
+    from tracks
+    derive {
+      a = 1,
+      bbb = 2,
+    }
+    ");
+}
+
+#[test]
+fn format_check() {
+    assert_cmd_snapshot!(prqlc_command().args(["fmt", "--check"]).pass_stdin("from tracks\ntake 20\n"), @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+
+    ----- stderr -----
+    ");
+
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    fs::create_dir(temp_dir.path().join("sub")).unwrap();
+    fs::write(temp_dir.path().join("a.prql"), "from a\nselect b\n").unwrap();
+    fs::write(temp_dir.path().join("sub/b.prql"), "from b|take 3\n").unwrap();
+
+    assert_cmd_snapshot!(prqlc_command().args(["fmt", "--check", "."]).current_dir(temp_dir.path()), @r"
+    success: false
+    exit_code: 1
+    ----- stdout -----
+    --- ./sub/b.prql
+    +++ ./sub/b.prql
+    @@ -1 +1,2 @@
+    -from b|take 3
+    +from b
+    +take 3
+
+    ----- stderr -----
+    1 file(s) would be reformatted: ./sub/b.prql
+    ");
+
+    // files are left untouched
+    assert_eq!(
+        fs::read_to_string(temp_dir.path().join("sub/b.prql")).unwrap(),
+        "from b|take 3\n"
+    );
+}
+
+#[test]
+fn format_stdin() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let query = temp_dir.path().join("query.prql");
+    fs::write(&query, "from tracks").unwrap();
+    fs::write(temp_dir.path().join(".prqlfmt.toml"), "max_width = 10\n").unwrap();
+
+    // the path is used for the config, but the file itself is neither read
+    // nor written
+    assert_cmd_snapshot!(prqlc_command()
+        .args(["fmt", "--stdin", query.to_str().unwrap()])
+        .pass_stdin("from a | derive {x = 1, y = 2}"), @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    from a
+    derive {
+      x = 1,
+      y = 2,
+    }
+
+    ----- stderr -----
+    ");
+    assert_eq!(fs::read_to_string(&query).unwrap(), "from tracks");
+
+    // a path which doesn't exist yet, as with an unsaved buffer
+    let unsaved = temp_dir.path().join("reports").join("unsaved.prql");
+    assert_cmd_snapshot!(prqlc_command()
+        .args(["fmt", "--stdin", unsaved.to_str().unwrap()])
+        .pass_stdin("from a | derive {x = 1, y = 2}"), @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    from a
+    derive {
+      x = 1,
+      y = 2,
+    }
+
+    ----- stderr -----
+    ");
+    assert!(!unsaved.exists());
+}
+
 fn copy_dir(src: &Path, dst: &Path) {
     for entry in WalkDir::new(src) {
         let entry = entry.unwrap();
@@ -573,6 +1169,70 @@ fn shell_completion() {
     }
 }
 
+#[test]
+fn build_packages_malformed_archive() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    copy_dir(&packages_path(), temp_dir.path());
+    let app = temp_dir.path().join("app");
+    let archive = app.join("vendor/calendar-0.3.0.tar.gz");
+
+    // a gzip header with the given flags and fields, and a trailer
+    let gzip = |flags: u8, fields: &[u8]| {
+        let header = [0x1f, 0x8b, 8, flags, 0, 0, 0, 0, 0, 0xff];
+        [&header[..], fields, &[0xff; 8]].concat()
+    };
+
+    // an extra field which is longer than the file, followed by a file name
+    fs::write(&archive, gzip(4 | 8, &[0xff, 0xff])).unwrap();
+    assert_cmd_snapshot!(prqlc_command()
+        .args(["build"])
+        .current_dir(&app), @r"
+    success: false
+    exit_code: 1
+    ----- stdout -----
+
+    ----- stderr -----
+    Could not load package `calendar` from `archive+vendor/calendar-0.3.0.tar.gz`: unexpected end of file
+    ");
+
+    // a file name without its terminating NUL
+    fs::write(&archive, gzip(8, b"abc")).unwrap();
+    assert_cmd_snapshot!(prqlc_command()
+        .args(["build"])
+        .current_dir(&app), @r"
+    success: false
+    exit_code: 1
+    ----- stdout -----
+
+    ----- stderr -----
+    Could not load package `calendar` from `archive+vendor/calendar-0.3.0.tar.gz`: unexpected end of file
+    ");
+
+    // an archive whose checksum doesn't match its contents
+    let mut bytes = fs::read(packages_path().join("app/vendor/calendar-0.3.0.tar.gz")).unwrap();
+    let crc = bytes.len() - 8;
+    bytes[crc] ^= 0xff;
+    fs::write(&archive, bytes).unwrap();
+    assert_cmd_snapshot!(prqlc_command()
+        .args(["build"])
+        .current_dir(&app), @r"
+    success: false
+    exit_code: 1
+    ----- stdout -----
+
+    ----- stderr -----
+    Could not load package `calendar` from `archive+vendor/calendar-0.3.0.tar.gz`: corrupt gzip stream does not have a matching checksum
+    ");
+}
+
+fn packages_path() -> PathBuf {
+    current_dir()
+        .unwrap()
+        .canonicalize()
+        .unwrap()
+        .join("tests/integration/packages")
+}
+
 fn project_path() -> PathBuf {
     current_dir()
         .unwrap()
@@ -609,7 +1269,7 @@ fn compile_no_prql_files() {
     ----- stdout -----
 
     ----- stderr -----
-    Error: No `.prql` files found in the source tree
+    [E0002] Error: No `.prql` files found in the source tree
     ");
 }
 
@@ -714,3 +1374,56 @@ fn lsp() {
     shutting down server
     "###);
 }
+
+#[test]
+fn lint() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let query = temp_dir.path().join("query.prql");
+    fs::write(&query, "from tracks\ntake 20\njoin albums (==album_id)\n").unwrap();
+
+    assert_cmd_snapshot!(prqlc_command().args(["lint", query.to_str().unwrap()]), @r"
+    success: false
+    exit_code: 1
+    ----- stdout -----
+
+    ----- stderr -----
+    Warning:
+       ╭─[ :2:1 ]
+       │
+     2 │ take 20
+       │ ───┬───
+       │    ╰───── `take` without a `sort` returns arbitrary rows
+       │
+       │ Help: add a `sort` before the `take`
+       │
+       │ Note: allow it with `@{lint={allow=take_without_sort}}`
+    ───╯
+    Warning:
+       ╭─[ :3:1 ]
+       │
+     3 │ join albums (==album_id)
+       │ ────────────┬───────────
+       │             ╰───────────── all columns of this join are selected
+       │
+       │ Help: `select` the columns which are needed after the `join`
+       │
+       │ Note: allow it with `@{lint={allow=select_star_join}}`
+    ───╯
+    2 lint(s) found
+    ");
+
+    // rules are disabled by the config and by flags
+    fs::write(
+        temp_dir.path().join(".prqllint.toml"),
+        "allow = [\"take_without_sort\"]\n",
+    )
+    .unwrap();
+    assert_cmd_snapshot!(prqlc_command()
+        .args(["lint", "--allow", "select_star_join", query.to_str().unwrap()]), @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+
+    ----- stderr -----
+    ");
+}
@@ -1,10 +1,15 @@
+use std::collections::HashMap;
 use std::ffi::{OsStr, OsString};
 use std::fs;
-use std::path::Path;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
 
-use anyhow::{anyhow, Result};
+use anyhow::Result;
 use clap::Parser;
+use itertools::Itertools;
 use notify::{Config, RecommendedWatcher, RecursiveMode, Watcher};
+use prqlc::utils::parallel_map;
+use prqlc::{CompilationCache, ErrorMessage, ErrorMessages, SourceTree};
 use walkdir::WalkDir;
 
 use super::jinja;
@@ -29,27 +34,171 @@ pub fn run(command: &mut WatchArgs) -> Result<()> {
     };
     let path = Path::new(&command.path);
 
+    if let Some(mut project) = Project::load(path)? {
+        project.compile(&opt, &mut io::stdout())?;
+
+        println!("Watching path \"{}\"", path.display());
+        return watch(path, |changed| {
+            if project.update(changed) {
+                if let Err(e) = project.compile(&opt, &mut io::stdout()) {
+                    println!("{e}");
+                }
+            }
+        });
+    }
+
+    // Files are compiled again on every change event, and editors often save
+    // a file with several events, so keep a cache for each file. Each file is
+    // a separate query, so they don't share a cache.
+    let mut caches = HashMap::new();
+
     // initial compile
-    find_and_compile(path, &opt)?;
+    find_and_compile(path, &opt, &mut caches)?;
 
     // watch and compile
     println!("Watching path \"{}\"", path.display());
-    watch_and_compile(path, &opt)?;
+    let cwd = std::env::current_dir().ok();
+    watch(path, |changed| {
+        // to make display nicer, try to convert to relative paths
+        let relative_path = if let Some(cwd) = &cwd {
+            changed.strip_prefix(cwd).unwrap_or(changed)
+        } else {
+            changed
+        };
 
-    Ok(())
+        let cache = caches.entry(cache_key(changed)).or_default();
+        if let Err(e) = compile_path(relative_path, &opt, cache, &mut io::stdout()) {
+            println!("{e}");
+        }
+    })
 }
 
-fn find_and_compile(path: &Path, opt: &prqlc::Options) -> Result<()> {
-    for entry in WalkDir::new(path) {
-        compile_path(entry?.path(), opt)?;
+/// A directory of files which form a single source tree, with a root file
+/// like `Project.prql`. Its files are loaded once and kept up to date as they
+/// change, and all compilations share a cache, so a change only resolves the
+/// files which depend on the changed file again.
+struct Project {
+    /// The path of the directory, as given.
+    path: PathBuf,
+
+    /// The canonical path of the directory, to match the paths of changes.
+    dir: PathBuf,
+
+    sources: SourceTree,
+    cache: CompilationCache,
+}
+
+impl Project {
+    /// Loads the files at `path` as a project, if it is a directory with a
+    /// root file. A directory of a single file is a single query, like a
+    /// directory without a root file is a set of queries.
+    fn load(path: &Path) -> Result<Option<Self>> {
+        if !path.is_dir() {
+            return Ok(None);
+        }
+        let sources = super::read_dir(path)?;
+        if sources.sources.len() < 2 || sources.root_file().is_none() {
+            return Ok(None);
+        }
+
+        Ok(Some(Project {
+            path: path.to_path_buf(),
+            dir: path.canonicalize()?,
+            sources,
+            cache: CompilationCache::new(),
+        }))
     }
 
-    Ok(())
+    /// Updates the file at `path` from disk, returning whether the sources
+    /// have changed.
+    fn update(&mut self, path: &Path) -> bool {
+        if !is_prql(path) {
+            return false;
+        }
+        let Ok(relative_path) = cache_key(path)
+            .strip_prefix(&self.dir)
+            .map(Path::to_path_buf)
+        else {
+            return false;
+        };
+
+        match fs::read_to_string(path) {
+            Ok(content) => {
+                if self.sources.sources.get(&relative_path) == Some(&content) {
+                    return false;
+                }
+                self.sources.insert(relative_path, content);
+                true
+            }
+            // the file has been removed
+            Err(_) => self.sources.sources.remove(&relative_path).is_some(),
+        }
+    }
+
+    /// Compiles the main query of the project into a `.sql` file next to the
+    /// root file.
+    fn compile(&mut self, opt: &prqlc::Options, out: &mut impl Write) -> Result<()> {
+        let Some(root) = self.sources.root_file() else {
+            return Ok(());
+        };
+        let root_path = self.path.join(root);
+
+        writeln!(out, "Compiling {}", root_path.display())?;
+        let sql_string = self.cache.compile(&self.sources, opt)?;
+        fs::write(root_path.with_extension("sql"), sql_string)?;
+
+        Ok(())
+    }
 }
 
-fn watch_and_compile(path: &Path, opt: &prqlc::Options) -> Result<()> {
-    let cwd = std::env::current_dir().ok();
+/// Compiles all files at `path` in parallel, since each is a separate query.
+/// Their output is printed in the order of the files, and compilation
+/// continues past a file that fails. The errors of all files are returned
+/// together.
+fn find_and_compile(
+    path: &Path,
+    opt: &prqlc::Options,
+    caches: &mut HashMap<PathBuf, CompilationCache>,
+) -> Result<()> {
+    let paths: Vec<PathBuf> = WalkDir::new(path)
+        .sort_by_file_name()
+        .into_iter()
+        .map_ok(|entry| entry.into_path())
+        .filter_ok(|path| is_prql(path))
+        .try_collect()?;
+
+    let results = parallel_map(paths, |path| {
+        let mut cache = CompilationCache::new();
+        let mut output = Vec::new();
+        let result = compile_path(&path, opt, &mut cache, &mut output);
+        (path, cache, output, result)
+    });
 
+    let mut errors = Vec::new();
+    for (path, cache, output, result) in results {
+        io::stdout().write_all(&output)?;
+        caches.insert(cache_key(&path), cache);
+
+        if let Err(e) = result {
+            match e.downcast::<ErrorMessages>() {
+                Ok(messages) => errors.extend(messages.inner),
+                Err(e) => errors.push(ErrorMessage::from(prqlc::Error::new_simple(format!(
+                    "{}: {e}",
+                    path.display()
+                )))),
+            }
+        }
+    }
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(ErrorMessages::from(errors).into())
+    }
+}
+
+/// Calls `on_change` with the path of each file which is created, modified or
+/// removed at `path`.
+fn watch(path: &Path, mut on_change: impl FnMut(&Path)) -> Result<()> {
     let (tx, rx) = std::sync::mpsc::channel();
 
     // Automatically select the best implementation for current platform.
@@ -68,22 +217,15 @@ fn watch_and_compile(path: &Path, opt: &prqlc::Options) -> Result<()> {
                     | notify::event::CreateKind::Any
                     | notify::event::CreateKind::Other,
                 )
-                | notify::EventKind::Modify(_) => {
+                | notify::EventKind::Modify(_)
+                | notify::EventKind::Remove(_) => {
                     for path in event.paths {
-                        // to make display nicer, try to convert to relative paths
-                        let relative_path = if let Some(cwd) = &cwd {
-                            path.strip_prefix(cwd).unwrap_or(&path)
-                        } else {
-                            &path
-                        };
-
-                        let _ignore = compile_path(relative_path, opt);
+                        on_change(&path);
                     }
                 }
 
                 notify::EventKind::Access(_)
                 | notify::EventKind::Create(notify::event::CreateKind::Folder)
-                | notify::EventKind::Remove(_)
                 | notify::EventKind::Other => {}
             },
             Err(e) => println!("watch error: {e:?}"),
@@ -93,9 +235,14 @@ fn watch_and_compile(path: &Path, opt: &prqlc::Options) -> Result<()> {
     Ok(())
 }
 
-fn compile_path(path: &Path, opt: &prqlc::Options) -> Result<()> {
+fn compile_path(
+    path: &Path,
+    opt: &prqlc::Options,
+    cache: &mut CompilationCache,
+    out: &mut impl Write,
+) -> Result<()> {
     // filter to only .prql files
-    if path.extension() != Some(OsStr::new("prql")) {
+    if !is_prql(path) {
         return Ok(());
     }
 
@@ -115,16 +262,9 @@ fn compile_path(path: &Path, opt: &prqlc::Options) -> Result<()> {
     let (prql_string, jinja_context) = jinja::pre_process(&prql_string)?;
 
     // compile
-    println!("Compiling {}", prql_path.display());
-    let sql_string = match prqlc::compile(&prql_string, opt) {
-        Ok(sql_string) => sql_string,
-        Err(errs) => {
-            for err in errs.inner {
-                println!("{err}");
-            }
-            return Err(anyhow!("failed to compile"));
-        }
-    };
+    writeln!(out, "Compiling {}", prql_path.display())?;
+    let sources = SourceTree::single(prql_path.to_path_buf(), prql_string);
+    let sql_string = cache.compile(&sources, opt)?;
 
     // post-process Jinja
     let sql_string = jinja::post_process(&sql_string, jinja_context);
@@ -134,3 +274,83 @@ fn compile_path(path: &Path, opt: &prqlc::Options) -> Result<()> {
 
     Ok(())
 }
+
+fn is_prql(path: &Path) -> bool {
+    path.extension() == Some(OsStr::new("prql"))
+}
+
+/// Paths of a file may be relative or absolute, depending on whether they
+/// come from walking the directory or from a change event. A removed file
+/// can't be canonicalized itself, but its directory can.
+fn cache_key(path: &Path) -> PathBuf {
+    path.canonicalize()
+        .or_else(|e| match (path.parent(), path.file_name()) {
+            (Some(parent), Some(name)) => Ok(parent.canonicalize()?.join(name)),
+            _ => Err(e),
+        })
+        .unwrap_or_else(|_| path.to_path_buf())
+}
+
+#[cfg(test)]
+mod test {
+    use tempfile::TempDir;
+
+    use super::*;
+
+    #[test]
+    fn test_find_and_compile() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir = temp_dir.path();
+        fs::write(dir.join("a.prql"), "from x | select {y = z +}").unwrap();
+        fs::write(dir.join("b.prql"), "from x | take 1").unwrap();
+        fs::write(dir.join("c.prql"), "from x | derive {y = std.coutn z}").unwrap();
+
+        let opt = prqlc::Options::default();
+        let mut caches = HashMap::new();
+        let error = find_and_compile(dir, &opt, &mut caches).unwrap_err();
+
+        // the errors of both files are reported, and the file between them
+        // is compiled
+        let messages = error.downcast::<ErrorMessages>().unwrap();
+        assert_eq!(messages.inner.len(), 2);
+        assert!(dir.join("b.sql").exists());
+        assert_eq!(caches.len(), 3);
+    }
+
+    #[test]
+    fn test_compile_project() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir = temp_dir.path();
+        fs::write(dir.join("Project.prql"), "from reports.monthly").unwrap();
+        fs::write(dir.join("reports.prql"), "let monthly = (from dates.spine)").unwrap();
+        fs::write(dir.join("dates.prql"), "let spine = (from days)").unwrap();
+        fs::write(dir.join("other.prql"), "let x = (from y)").unwrap();
+
+        let opt = prqlc::Options::default().no_format();
+        let mut project = Project::load(dir).unwrap().unwrap();
+        project.compile(&opt, &mut Vec::new()).unwrap();
+        assert!(fs::read_to_string(dir.join("Project.sql"))
+            .unwrap()
+            .contains("FROM days"));
+
+        // an event without a change doesn't compile again
+        assert!(!project.update(&dir.join("dates.prql")));
+
+        fs::write(dir.join("dates.prql"), "let spine = (from calendar)").unwrap();
+        assert!(project.update(&dir.join("dates.prql")));
+        project.compile(&opt, &mut Vec::new()).unwrap();
+        assert!(fs::read_to_string(dir.join("Project.sql"))
+            .unwrap()
+            .contains("FROM calendar"));
+        // only `dates` and the files which depend on it are resolved again
+        assert_eq!(project.cache.stats().stmts_reused, 2);
+
+        fs::remove_file(dir.join("other.prql")).unwrap();
+        assert!(project.update(&dir.join("other.prql")));
+        assert_eq!(project.sources.sources.len(), 3);
+
+        // a directory of separate queries isn't a project
+        fs::remove_file(dir.join("Project.prql")).unwrap();
+        assert!(Project::load(dir).unwrap().is_none());
+    }
+}
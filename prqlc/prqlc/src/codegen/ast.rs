@@ -9,7 +9,7 @@ use crate::codegen::SeparatedExprs;
 use crate::pr;
 
 pub(crate) fn write_expr(expr: &pr::Expr) -> String {
-    expr.write(WriteOpt::new_width(u16::MAX)).unwrap()
+    expr.write(WriteOpt::new_width(u16::MAX.into())).unwrap()
 }
 
 fn write_within<T: WriteSource>(
@@ -28,10 +28,14 @@ impl WriteSource for pr::Expr {
         let mut r = String::new();
 
         if let Some(alias) = &self.alias {
-            r += opt.consume(&write_ident_part(alias))?;
+            let alias = write_ident_part(alias);
+            let padding = opt.alias_width.saturating_sub(alias.len());
+            r += opt.consume(&alias)?;
+            r += opt.consume(" ".repeat(padding))?.as_str();
             r += opt.consume(" = ")?;
             opt.unbound_expr = false;
         }
+        opt.alias_width = 0;
 
         if !needs_parenthesis(self, &opt) {
             r += &self.kind.write(opt.clone())?;
@@ -46,6 +50,10 @@ impl WriteSource for pr::Expr {
         };
         Some(r)
     }
+
+    fn span(&self) -> Option<pr::Span> {
+        self.span
+    }
 }
 
 fn needs_parenthesis(this: &pr::Expr, opt: &WriteOpt) -> bool {
@@ -97,20 +105,32 @@ impl WriteSource for pr::ExprKind {
                 inline: " | ",
                 line_end: "",
                 exprs: &pipeline.exprs,
+                break_lines: opt.break_pipelines && pipeline.exprs.len() > 1,
             }
             .write_between("(", ")", opt),
 
-            Tuple(fields) => SeparatedExprs {
-                exprs: fields,
-                inline: ", ",
-                line_end: ",",
+            Tuple(fields) => {
+                if opt.align_assignments {
+                    opt.alias_width = (fields.iter())
+                        .filter_map(|f| f.alias.as_ref())
+                        .map(|alias| write_ident_part(alias).len())
+                        .max()
+                        .unwrap_or_default();
+                }
+                SeparatedExprs {
+                    exprs: fields,
+                    inline: ", ",
+                    line_end: ",",
+                    break_lines: false,
+                }
+                .write_between("{", "}", opt)
             }
-            .write_between("{", "}", opt),
 
             Array(items) => SeparatedExprs {
                 exprs: items,
                 inline: ", ",
                 line_end: ",",
+                break_lines: false,
             }
             .write_between("[", "]", opt),
 
@@ -222,12 +242,15 @@ impl WriteSource for pr::ExprKind {
                     exprs: cases,
                     inline: ", ",
                     line_end: ",",
+                    break_lines: false,
                 }
                 .write_between("[", "]", opt)?;
                 Some(r)
             }
             Param(id) => Some(format!("${id}")),
             Internal(operator_name) => Some(format!("internal {operator_name}")),
+            // Nothing was parsed, so there's nothing to write.
+            Error => Some(String::new()),
         }
     }
 }
@@ -317,7 +340,7 @@ fn can_bind_left(expr: &pr::ExprKind) -> bool {
 impl WriteSource for pr::Ident {
     fn write(&self, mut opt: WriteOpt) -> Option<String> {
         let width = self.path.iter().map(|p| p.len() + 1).sum::<usize>() + self.name.len();
-        opt.consume_width(width as u16)?;
+        opt.consume_width(width)?;
 
         let mut r = String::new();
         for part in &self.path {
@@ -365,8 +388,17 @@ impl WriteSource for Vec<pr::Stmt> {
                 r += "\n";
             }
 
-            r += &opt.write_indent();
-            r += &stmt.write_or_expand(opt.clone());
+            if let Some(trivia) = opt.trivia.clone() {
+                if let Some(span) = stmt.span {
+                    let start = trivia.node_start(span.start);
+                    r += &trivia.write_leading(start, &opt.write_indent(), false);
+                }
+                r += &opt.write_indent();
+                r += &trivia.write_stmt(stmt, opt.clone());
+            } else {
+                r += &opt.write_indent();
+                r += &stmt.write_or_expand(opt.clone());
+            }
         }
         Some(r)
     }
@@ -422,8 +454,21 @@ impl WriteSource for pr::Stmt {
                     let val = var_def.value.as_ref().unwrap();
                     match &val.kind {
                         pr::ExprKind::Pipeline(pipeline) => {
-                            for expr in &pipeline.exprs {
+                            for (index, expr) in pipeline.exprs.iter().enumerate() {
+                                let is_last = index + 1 == pipeline.exprs.len();
+                                // Comments before the first and after the last
+                                // step belong to the statement.
+                                let trivia = opt.trivia.as_ref().zip(expr.span);
+                                if let Some((trivia, span)) = trivia.filter(|_| index > 0) {
+                                    r += &trivia.write_leading(span.start, "", true);
+                                }
                                 r += &expr.write(opt.clone())?;
+                                if let Some((trivia, span)) = trivia.filter(|_| !is_last) {
+                                    if let Some(comment) = trivia.trailing(span.end) {
+                                        r += "  ";
+                                        r += &comment;
+                                    }
+                                }
                                 r += "\n";
                             }
                         }
@@ -464,6 +509,7 @@ impl WriteSource for pr::Stmt {
                 r += &import_def.name.write(opt)?;
                 r += "\n";
             }
+            pr::StmtKind::Error => {}
         }
         Some(r)
     }
@@ -0,0 +1,443 @@
+//! Concrete syntax tree used by the formatter.
+//!
+//! The PL AST drops everything that doesn't affect semantics — comments, blank
+//! lines and line wraps. [Cst] keeps the parsed statements alongside the full
+//! token stream of the source, so the writer can look up the trivia around each
+//! node and print it back.
+//!
+//! Comments are placed:
+//! - on their own lines before a statement, a pipeline step or an item of a
+//!   tuple / array,
+//! - at the end of the line of a statement, a pipeline step or an item of a
+//!   tuple / array.
+//!
+//! Any statement containing a comment that the writer can't place is emitted
+//! verbatim, so formatting never loses a comment. This is the case for
+//! comments elsewhere, such as within the body of a function or after the
+//! opening bracket of a tuple; the whole statement is then left unformatted.
+
+use std::rc::Rc;
+
+use itertools::Itertools;
+
+use super::{WriteOpt, WriteSource};
+use crate::lr::{Token, TokenKind};
+use crate::pr;
+use crate::ErrorMessages;
+
+pub(crate) struct Cst {
+    stmts: Vec<pr::Stmt>,
+    trivia: Rc<Trivia>,
+}
+
+/// The source and its tokens, including trivia.
+pub(crate) struct Trivia {
+    source: String,
+    tokens: Vec<Token>,
+}
+
+/// An item of trivia on its own line.
+#[derive(Debug, Clone, PartialEq)]
+enum TriviaItem {
+    Comment(String),
+    BlankLine,
+}
+
+impl Cst {
+    pub fn parse(source: &str) -> Result<Self, ErrorMessages> {
+        let tokens = crate::prql_to_tokens(source)?.0;
+        let stmts = crate::prql_to_pl(source)?.stmts;
+
+        let mut source = source.to_string();
+        if !source.ends_with('\n') {
+            source.push('\n');
+        }
+
+        Ok(Cst {
+            stmts,
+            trivia: Rc::new(Trivia { source, tokens }),
+        })
+    }
+
+    pub fn write(&self, options: &crate::FormatOptions) -> String {
+        let opt = WriteOpt {
+            trivia: Some(self.trivia.clone()),
+            ..WriteOpt::from(options)
+        };
+        let mut r = self.stmts.write_or_expand(opt);
+
+        // comments after the last statement
+        let mut tail = self.trivia.leading(self.trivia.source.len());
+        while tail.last() == Some(&TriviaItem::BlankLine) {
+            tail.pop();
+        }
+        if r.is_empty() {
+            tail = drop_leading_blank_lines(tail);
+        }
+        for item in tail {
+            match item {
+                TriviaItem::Comment(comment) => r += &comment,
+                TriviaItem::BlankLine => {}
+            }
+            r += "\n";
+        }
+        r
+    }
+}
+
+impl Trivia {
+    /// Start of the first semantic token of a node. Spans of statements
+    /// include the new lines preceding them.
+    pub fn node_start(&self, span_start: usize) -> usize {
+        self.tokens
+            .iter()
+            .find(|t| t.span.start >= span_start && !is_trivia(&t.kind))
+            .map_or(span_start, |t| t.span.start)
+    }
+
+    /// Comments and blank lines between the previous semantic token and a
+    /// node starting at `start`. Comments on the line of the previous token
+    /// belong to that token (see [Trivia::trailing]).
+    fn leading(&self, start: usize) -> Vec<TriviaItem> {
+        let mut preceding = self
+            .tokens
+            .iter()
+            .rev()
+            .filter(|t| t.span.end <= start && !is_trivia(&t.kind));
+
+        let mut prev = preceding.next();
+        // Aliases aren't part of the span of an expression, so we step over
+        // `alias =`.
+        if prev.is_some_and(|t| t.kind == TokenKind::Control('=')) {
+            let mut lookahead = preceding.clone();
+            if lookahead
+                .next()
+                .is_some_and(|t| matches!(t.kind, TokenKind::Ident(_)))
+            {
+                prev = lookahead.next();
+            }
+        }
+        let (from, skip_first) = prev.map_or((0, false), |t| (t.span.end, true));
+
+        let mut lines = self.source[from..start].split('\n').collect_vec();
+        // the last line is the indentation of the node itself
+        lines.pop();
+        if skip_first && !lines.is_empty() {
+            lines.remove(0);
+        }
+
+        let mut items = Vec::new();
+        for line in lines {
+            let line = line.trim();
+            if line.is_empty() {
+                if items.last() != Some(&TriviaItem::BlankLine) {
+                    items.push(TriviaItem::BlankLine);
+                }
+            } else if line.starts_with('#') {
+                items.push(TriviaItem::Comment(line.to_string()));
+            }
+        }
+        items
+    }
+
+    /// A comment on the same line after a node ending at `end`, possibly
+    /// following a comma.
+    pub fn trailing(&self, end: usize) -> Option<String> {
+        let line = self.source[end..].split('\n').next()?.trim_start();
+        let line = line.strip_prefix(',').unwrap_or(line).trim_start();
+
+        line.starts_with('#').then(|| line.trim_end().to_string())
+    }
+
+    /// True if there are comments before or after a node.
+    pub fn has_comments(&self, span: Option<pr::Span>) -> bool {
+        let Some(span) = span else {
+            return false;
+        };
+        self.trailing(span.end).is_some()
+            || (self.leading(span.start).iter()).any(|i| matches!(i, TriviaItem::Comment(_)))
+    }
+
+    /// Writes the comments preceding a node starting at `start`, each
+    /// followed by a new line.
+    pub fn write_leading(&self, start: usize, indent: &str, keep_leading_blank: bool) -> String {
+        let mut items = self.leading(start);
+        if !keep_leading_blank {
+            items = drop_leading_blank_lines(items);
+        }
+
+        let mut r = String::new();
+        for item in items {
+            if let TriviaItem::Comment(comment) = item {
+                r += indent;
+                r += &comment;
+            }
+            r += "\n";
+        }
+        r
+    }
+
+    /// Writes a statement together with its trailing comment. If the writer
+    /// loses or moves any of the comments within the statement, the statement
+    /// is emitted verbatim instead.
+    pub fn write_stmt(&self, stmt: &pr::Stmt, opt: WriteOpt) -> String {
+        let Some(span) = stmt.span else {
+            return stmt.write_or_expand(opt);
+        };
+        let start = self.node_start(span.start);
+        let trailing = self.trailing(span.end);
+
+        let mut formatted = stmt.write_or_expand(opt);
+        if let Some(trailing) = &trailing {
+            if !formatted.trim_end().ends_with(trailing.as_str()) {
+                let body = formatted.strip_suffix('\n').unwrap_or(&formatted);
+                formatted = format!("{body}  {trailing}\n");
+            }
+        }
+
+        let mut expected = self.comments_within(start, span.end);
+        expected.extend(trailing);
+        if comments_of(&formatted).as_ref() == Some(&expected) {
+            return formatted;
+        }
+
+        let end = self.source[span.end..]
+            .find('\n')
+            .map_or(self.source.len(), |i| span.end + i);
+        let mut r = self.source[start..end]
+            .lines()
+            .map(str::trim_end)
+            .join("\n");
+        r += "\n";
+        r
+    }
+
+    fn comments_within(&self, start: usize, end: usize) -> Vec<String> {
+        comments(
+            self.tokens
+                .iter()
+                .filter(|t| t.span.start >= start && t.span.start < end),
+        )
+    }
+}
+
+fn is_trivia(kind: &TokenKind) -> bool {
+    matches!(
+        kind,
+        TokenKind::Start | TokenKind::NewLine | TokenKind::Comment(_) | TokenKind::DocComment(_)
+    )
+}
+
+fn drop_leading_blank_lines(items: Vec<TriviaItem>) -> Vec<TriviaItem> {
+    items
+        .into_iter()
+        .skip_while(|i| *i == TriviaItem::BlankLine)
+        .collect()
+}
+
+/// Comments in written source, or `None` if it can't be lexed.
+fn comments_of(source: &str) -> Option<Vec<String>> {
+    let tokens = prqlc_parser::lexer::lex_source(source).ok()?;
+    Some(comments(tokens.0.iter()))
+}
+
+fn comments<'a>(tokens: impl Iterator<Item = &'a Token>) -> Vec<String> {
+    fn push(kind: &TokenKind, res: &mut Vec<String>) {
+        match kind {
+            TokenKind::Comment(c) => res.push(format!("#{}", c.trim_end())),
+            TokenKind::DocComment(c) => res.push(format!("#!{}", c.trim_end())),
+            TokenKind::LineWrap(kinds) => kinds.iter().for_each(|k| push(k, res)),
+            _ => {}
+        }
+    }
+
+    let mut res = Vec::new();
+    for token in tokens {
+        push(&token.kind, &mut res);
+    }
+    res
+}
+
+#[cfg(test)]
+mod test {
+    use insta::assert_snapshot;
+
+    use super::*;
+
+    fn format(source: &str) -> String {
+        let options = crate::FormatOptions::default();
+        let formatted = Cst::parse(source).unwrap().write(&options);
+
+        // formatting is idempotent
+        let again = Cst::parse(&formatted).unwrap().write(&options);
+        similar_asserts::assert_eq!(formatted, again);
+
+        formatted
+    }
+
+    #[test]
+    fn test_comments_between_stmts() {
+        assert_snapshot!(format(r#"
+# header
+
+let a = 5   # five
+# about b
+let b = 6
+
+
+# last
+"#), @r"
+        # header
+
+        let a = 5  # five
+
+        # about b
+        let b = 6
+
+        # last
+        ");
+    }
+
+    #[test]
+    fn test_comments_in_pipeline() {
+        assert_snapshot!(format(r#"
+from tracks
+# only the long ones
+filter length > 100   # milliseconds
+
+sort {-length}
+take 10
+"#), @r"
+        from tracks
+        # only the long ones
+        filter length > 100  # milliseconds
+
+        sort {-length}
+        take 10
+        ");
+    }
+
+    #[test]
+    fn test_comments_in_tuple() {
+        assert_snapshot!(format(r#"
+from employees
+derive {a = 1,   # one
+  # two
+  b = 2}
+"#), @r"
+        from employees
+        derive {
+          a = 1,  # one
+          # two
+          b = 2,
+        }
+        ");
+    }
+
+    #[test]
+    fn test_comments_in_nested_pipeline() {
+        assert_snapshot!(format(r#"
+let x = (
+  from a
+  # c
+  select b
+)
+"#), @r"
+        let x = (
+          from a
+          # c
+          select b
+        )
+        ");
+    }
+
+    #[test]
+    fn test_unplaceable_comment_is_verbatim() {
+        assert_snapshot!(format(r#"
+from a
+select {  # columns
+  b, c}
+"#), @r"
+        from a
+        select {  # columns
+          b, c}
+        ");
+    }
+
+    #[test]
+    fn test_unplaceable_comment_in_function_is_verbatim() {
+        // comments within the body of a function can't be placed, so the
+        // whole statement is left as it was written
+        assert_snapshot!(format(r#"
+let f = x -> (
+  # c
+  x + 1 # d
+)
+"#), @r"
+        let f = x -> (
+          # c
+          x + 1 # d
+        )
+        ");
+    }
+
+    #[test]
+    fn test_doc_comments() {
+        assert_snapshot!(format(r#"
+#! Doc comment
+let a = 5
+"#), @r"
+        #! Doc comment
+        let a = 5
+        ");
+    }
+
+    #[test]
+    fn test_options() {
+        let source = r#"
+from tracks
+derive {a = 1, bbb = 2, cc = (x | y)}   # assignments
+select {a, bbb}
+"#;
+        let options = crate::FormatOptions::default()
+            .with_max_width(30)
+            .with_indent_width(4)
+            .with_break_pipelines(true)
+            .with_trailing_commas(false)
+            .with_align_assignments(true);
+
+        let formatted = Cst::parse(source).unwrap().write(&options);
+        assert_snapshot!(formatted, @r"
+        from tracks
+        derive {
+            a   = 1,
+            bbb = 2,
+            cc  = (
+                x
+                y
+            )
+        }  # assignments
+        select {a, bbb}
+        ");
+
+        let again = Cst::parse(&formatted).unwrap().write(&options);
+        similar_asserts::assert_eq!(formatted, again);
+    }
+
+    #[test]
+    fn test_large_indent_width() {
+        // two levels of indentation are wider than `u16::MAX`
+        let options = crate::FormatOptions::default()
+            .with_indent_width(u16::MAX)
+            .with_break_pipelines(true);
+        let formatted = Cst::parse("from t\nderive {a = (x | y)}\n")
+            .unwrap()
+            .write(&options);
+        assert!(formatted.starts_with("from t\nderive {"), "{formatted}");
+    }
+
+    #[test]
+    fn test_only_comments() {
+        assert_snapshot!(format("\n\n# just a comment\n"), @"# just a comment");
+    }
+}
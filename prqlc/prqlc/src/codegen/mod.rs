@@ -1,7 +1,13 @@
+use std::rc::Rc;
+
 pub(crate) use ast::write_expr;
+pub(crate) use cst::Cst;
 pub(crate) use types::{write_ty, write_ty_kind};
 
+use crate::pr;
+
 mod ast;
+mod cst;
 mod types;
 
 pub trait WriteSource {
@@ -11,6 +17,12 @@ pub trait WriteSource {
     /// Returns `None` if source does not fit into [WriteOpt::rem_width].
     fn write(&self, opt: WriteOpt) -> Option<String>;
 
+    /// Span of the source this was parsed from. Used to find the comments
+    /// surrounding it.
+    fn span(&self) -> Option<pr::Span> {
+        None
+    }
+
     fn write_between<S: ToString>(
         &self,
         prefix: S,
@@ -45,21 +57,25 @@ impl<T: WriteSource> WriteSource for &T {
     fn write(&self, opt: WriteOpt) -> Option<String> {
         (*self).write(opt)
     }
+
+    fn span(&self) -> Option<pr::Span> {
+        (*self).span()
+    }
 }
 
 #[derive(Clone)]
 pub struct WriteOpt {
-    /// String to emit as one indentation level
-    pub tab: &'static str,
+    /// Number of spaces to emit as one indentation level
+    pub indent_width: usize,
 
     /// Maximum number of characters per line
-    pub max_width: u16,
+    pub max_width: usize,
 
     /// Current indent used when emitting lines
-    pub indent: u16,
+    pub indent: usize,
 
     /// Current remaining number of characters in line
-    pub rem_width: u16,
+    pub rem_width: usize,
 
     /// Strength of the context
     /// For top-level exprs or exprs in parenthesis, this will be 0.
@@ -76,6 +92,22 @@ pub struct WriteOpt {
     /// For example:
     /// `join foo` has an unbound expr, since `join foo ==bar` produced a binary op.
     pub unbound_expr: bool,
+
+    /// Write pipelines one transform per line, even if they fit on one line.
+    pub break_pipelines: bool,
+
+    /// Emit a separator after the last of the exprs written one per line.
+    pub trailing_commas: bool,
+
+    /// Align the `=` of aliased items written one per line.
+    pub align_assignments: bool,
+
+    /// Width to pad the alias of the next expr to. Reset after it's written.
+    pub alias_width: usize,
+
+    /// Comments and blank lines of the source being formatted. When `None`,
+    /// only the AST is written.
+    pub trivia: Option<Rc<cst::Trivia>>,
 }
 
 #[derive(Clone, PartialEq)]
@@ -88,7 +120,7 @@ pub enum Position {
 impl Default for WriteOpt {
     fn default() -> Self {
         Self {
-            tab: "  ",
+            indent_width: 2,
             max_width: 50,
 
             indent: 0,
@@ -96,12 +128,29 @@ impl Default for WriteOpt {
             context_strength: 0,
             binary_position: Position::Unspecified,
             unbound_expr: false,
+            break_pipelines: false,
+            trailing_commas: true,
+            align_assignments: false,
+            alias_width: 0,
+            trivia: None,
+        }
+    }
+}
+
+impl From<&crate::FormatOptions> for WriteOpt {
+    fn from(options: &crate::FormatOptions) -> Self {
+        WriteOpt {
+            indent_width: options.indent_width.into(),
+            break_pipelines: options.break_pipelines,
+            trailing_commas: options.trailing_commas,
+            align_assignments: options.align_assignments,
+            ..WriteOpt::new_width(options.max_width.into())
         }
     }
 }
 
 impl WriteOpt {
-    fn new_width(max_width: u16) -> Self {
+    fn new_width(max_width: usize) -> Self {
         WriteOpt {
             max_width,
             rem_width: max_width,
@@ -109,13 +158,13 @@ impl WriteOpt {
         }
     }
 
-    fn consume_width(&mut self, width: u16) -> Option<()> {
+    fn consume_width(&mut self, width: usize) -> Option<()> {
         self.rem_width = self.rem_width.checked_sub(width)?;
         Some(())
     }
 
     fn reset_line(&mut self) -> Option<()> {
-        let ident = self.tab.len() as u16 * self.indent;
+        let ident = self.indent_width * self.indent;
         self.rem_width = self.max_width.checked_sub(ident)?;
         Some(())
     }
@@ -127,12 +176,12 @@ impl WriteOpt {
         } else {
             source.as_ref().len()
         };
-        self.consume_width(width as u16)?;
+        self.consume_width(width)?;
         Some(source)
     }
 
     fn write_indent(&self) -> String {
-        self.tab.repeat(self.indent as usize)
+        " ".repeat(self.indent_width * self.indent)
     }
 }
 
@@ -147,13 +196,21 @@ struct SeparatedExprs<'a, T: WriteSource> {
     /// The separator to use when writing the expressions on separate lines, for
     /// example `","` (`/n` is implied)
     line_end: &'static str,
+    /// Write the expressions one per line, even if they fit on a single line.
+    break_lines: bool,
 }
 
 impl<T: WriteSource> WriteSource for SeparatedExprs<'_, T> {
     fn write(&self, mut opt: WriteOpt) -> Option<String> {
-        // try inline
-        if let Some(inline) = self.write_inline(opt.clone()) {
-            return Some(inline);
+        let trivia = opt.trivia.clone();
+
+        // try inline, unless there are comments which need their own lines
+        let has_comments =
+            (trivia.as_ref()).is_some_and(|t| self.exprs.iter().any(|e| t.has_comments(e.span())));
+        if !has_comments && !self.break_lines {
+            if let Some(inline) = self.write_inline(opt.clone()) {
+                return Some(inline);
+            }
         }
 
         // one per line
@@ -162,14 +219,26 @@ impl<T: WriteSource> WriteSource for SeparatedExprs<'_, T> {
 
             let mut r = String::new();
 
-            for expr in self.exprs {
+            for (index, expr) in self.exprs.iter().enumerate() {
                 r += "\n";
+                if let (Some(trivia), Some(span)) = (&trivia, expr.span()) {
+                    r += &trivia.write_leading(span.start, &opt.write_indent(), index > 0);
+                }
                 r += &opt.write_indent();
                 opt.reset_line()?;
-                opt.rem_width.checked_sub(self.line_end.len() as u16)?;
+                opt.rem_width.checked_sub(self.line_end.len())?;
 
                 r += &expr.write(opt.clone())?;
-                r += self.line_end;
+                if index + 1 < self.exprs.len() || opt.trailing_commas {
+                    r += self.line_end;
+                }
+
+                if let (Some(trivia), Some(span)) = (&trivia, expr.span()) {
+                    if let Some(comment) = trivia.trailing(span.end) {
+                        r += "  ";
+                        r += &comment;
+                    }
+                }
             }
             opt.indent -= 1;
             r += "\n";
@@ -182,6 +251,8 @@ impl<T: WriteSource> WriteSource for SeparatedExprs<'_, T> {
 
 impl<T: WriteSource> SeparatedExprs<'_, T> {
     fn write_inline(&self, mut opt: WriteOpt) -> Option<String> {
+        opt.alias_width = 0;
+
         let mut exprs = Vec::new();
         for expr in self.exprs {
             let expr = expr.write(opt.clone())?;
@@ -189,13 +260,13 @@ impl<T: WriteSource> SeparatedExprs<'_, T> {
             if expr.contains('\n') {
                 return None;
             }
-            opt.consume_width(expr.len() as u16)?;
+            opt.consume_width(expr.len())?;
 
             exprs.push(expr);
         }
 
         let separators = self.inline.len() * (exprs.len().checked_sub(1).unwrap_or_default());
-        opt.consume_width(separators as u16)?;
+        opt.consume_width(separators)?;
 
         Some(exprs.join(self.inline))
     }
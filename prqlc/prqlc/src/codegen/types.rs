@@ -5,11 +5,11 @@ use crate::codegen::SeparatedExprs;
 use super::{WriteOpt, WriteSource};
 
 pub(crate) fn write_ty(ty: &pr::Ty) -> String {
-    ty.write(WriteOpt::new_width(u16::MAX)).unwrap()
+    ty.write(WriteOpt::new_width(u16::MAX.into())).unwrap()
 }
 
 pub(crate) fn write_ty_kind(ty: &pr::TyKind) -> String {
-    ty.write(WriteOpt::new_width(u16::MAX)).unwrap()
+    ty.write(WriteOpt::new_width(u16::MAX.into())).unwrap()
 }
 
 impl WriteSource for pr::Ty {
@@ -42,6 +42,7 @@ impl WriteSource for pr::TyKind {
                 exprs: elements,
                 inline: ", ",
                 line_end: ",",
+                break_lines: false,
             }
             .write_between("{", "}", opt),
             Array(Some(elem)) => Some(format!("[{}]", elem.write(opt)?)),
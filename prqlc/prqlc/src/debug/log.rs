@@ -3,8 +3,8 @@
 
 use chrono::prelude::*;
 use serde::Serialize;
-use std::marker::PhantomData;
-use std::{sync::RwLock, time::SystemTime};
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
 use strum_macros::AsRefStr;
 
 use crate::ir::{decl, pl, rq};
@@ -12,57 +12,50 @@ use crate::sql::pq_ast;
 use prqlc_parser::lexer::lr;
 use prqlc_parser::parser::pr;
 
-/// Stores debug info about current compilation.
-/// Is reset by [log_start] and [log_finish].
-static CURRENT_LOG: RwLock<Option<DebugLog>> = RwLock::new(None);
-
-pub fn log_start() {
-    let mut lock = CURRENT_LOG.write().unwrap();
-    assert!(lock.is_none());
-
-    let started_at: DateTime<Utc> = SystemTime::now().into();
-    let started_at = format!("{}", started_at.format("%+"));
-
-    *lock = Some(DebugLog {
-        started_at,
-        version: crate::compiler_version().to_string(),
-        entries: Vec::new(),
-
-        suppress_count: 0,
-    });
-}
+/// Collects a debug log of the compilations it is passed to, with
+/// [crate::Options::with_debug_log].
+///
+/// Clones of a logger add to the same log. The default logger is disabled and
+/// discards all entries, so compilations which aren't given a logger don't
+/// collect a log.
+#[derive(Clone, Default)]
+pub struct DebugLogger(Option<Arc<Mutex<DebugLog>>>);
+
+impl DebugLogger {
+    /// A logger which collects entries into a new log.
+    pub fn new() -> Self {
+        DebugLogger(Some(Arc::new(Mutex::new(DebugLog::new()))))
+    }
 
-pub fn log_finish() -> Option<DebugLog> {
-    let mut lock = CURRENT_LOG.write().unwrap();
-    lock.take()
-}
+    /// Takes the log collected so far, leaving a new empty log in its place.
+    /// Is `None` for a disabled logger.
+    pub fn take(&self) -> Option<DebugLog> {
+        let log = self.0.as_ref()?;
+        let mut log = log.lock().unwrap();
+        Some(std::mem::replace(&mut *log, DebugLog::new()))
+    }
 
-/// Will discard any new entries until the lock is dropped.
-pub fn log_suppress() -> Option<LogSuppressLock> {
-    LogSuppressLock::new()
-}
+    pub fn is_enabled(&self) -> bool {
+        self.0.is_some()
+    }
 
-pub fn log_stage(stage: Stage) {
-    log_entry(|| DebugEntryKind::NewStage(stage));
-}
+    pub fn stage(&self, stage: Stage) {
+        self.entry(|| DebugEntryKind::NewStage(stage));
+    }
 
-pub fn log_entry(entry: impl FnOnce() -> DebugEntryKind) {
-    let mut lock: std::sync::RwLockWriteGuard<'_, Option<DebugLog>> = CURRENT_LOG.write().unwrap();
-    if let Some(log) = lock.as_mut() {
-        if log.suppress_count > 0 {
-            return;
+    pub fn entry(&self, entry: impl FnOnce() -> DebugEntryKind) {
+        if let Some(log) = &self.0 {
+            let kind = entry();
+            log.lock().unwrap().entries.push(DebugEntry { kind });
         }
-
-        log.entries.push(DebugEntry { kind: entry() });
     }
 }
 
-pub fn log_is_enabled() -> bool {
-    let lock: std::sync::RwLockReadGuard<'_, Option<DebugLog>> = CURRENT_LOG.read().unwrap();
-    if let Some(log) = lock.as_ref() {
-        log.suppress_count == 0
-    } else {
-        false
+impl std::fmt::Debug for DebugLogger {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("DebugLogger")
+            .field(&self.is_enabled())
+            .finish()
     }
 }
 
@@ -71,9 +64,18 @@ pub struct DebugLog {
     pub(super) started_at: String,
     pub(super) version: String,
     pub(super) entries: Vec<DebugEntry>,
+}
+
+impl DebugLog {
+    fn new() -> Self {
+        let started_at: DateTime<Utc> = SystemTime::now().into();
 
-    #[serde(skip)]
-    suppress_count: usize,
+        DebugLog {
+            started_at: format!("{}", started_at.format("%+")),
+            version: crate::compiler_version().to_string(),
+            entries: Vec::new(),
+        }
+    }
 }
 
 #[derive(Serialize)]
@@ -149,26 +151,58 @@ pub enum StageSql {
     Main,
 }
 
-pub struct LogSuppressLock(PhantomData<usize>);
+#[cfg(test)]
+mod test {
+    use std::thread;
 
-impl LogSuppressLock {
-    fn new() -> Option<Self> {
-        let mut lock = CURRENT_LOG.write().unwrap();
-        if let Some(log) = lock.as_mut() {
-            log.suppress_count += 1;
+    use super::*;
+    use crate::Options;
 
-            Some(LogSuppressLock(PhantomData))
-        } else {
-            None
-        }
+    fn compile_with_log(prql: &str) -> DebugLog {
+        let (sql, log) = crate::compile_with_debug_log(prql, &Options::default().no_format());
+        sql.unwrap();
+        log
     }
-}
 
-impl Drop for LogSuppressLock {
-    fn drop(&mut self) {
-        let mut lock = CURRENT_LOG.write().unwrap();
-        if let Some(log) = lock.as_mut() {
-            log.suppress_count -= 1;
-        }
+    fn logged_sql(log: &DebugLog) -> Vec<&str> {
+        log.entries
+            .iter()
+            .filter_map(|entry| match &entry.kind {
+                DebugEntryKind::ReprSql(sql) => Some(sql.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_concurrent_logs() {
+        let logs: Vec<_> = thread::scope(|scope| {
+            let handles: Vec<_> = ["from a", "from b"]
+                .map(|prql| scope.spawn(move || compile_with_log(prql)))
+                .into_iter()
+                .collect();
+            handles.into_iter().map(|h| h.join().unwrap()).collect()
+        });
+
+        assert!(logged_sql(&logs[0])
+            .iter()
+            .all(|sql| sql.contains("FROM a")));
+        assert!(logged_sql(&logs[1])
+            .iter()
+            .all(|sql| sql.contains("FROM b")));
+    }
+
+    #[test]
+    fn test_shared_logger() {
+        let logger = DebugLogger::new();
+        let options = Options::default().with_debug_log(logger.clone());
+        crate::compile("from a", &options).unwrap();
+        crate::compile("from b", &Options::default()).unwrap();
+        crate::compile("from c", &options).unwrap();
+
+        let log = logger.take().unwrap();
+        assert_eq!(logged_sql(&log).len(), 2);
+        assert!(logged_sql(&logger.take().unwrap()).is_empty());
+        assert!(DebugLogger::default().take().is_none());
     }
 }
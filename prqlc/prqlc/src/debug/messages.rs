@@ -1,18 +1,35 @@
+use std::cell::RefCell;
+
 use log::{Metadata, Record};
 
 use crate::debug;
 
+thread_local! {
+    /// Logger of the current compilation on this thread.
+    /// Is set by [with_message_log].
+    static CURRENT_LOGGER: RefCell<Option<debug::DebugLogger>> = const { RefCell::new(None) };
+}
+
+/// Redirects the messages of the `log` crate into the log of the
+/// [DebugLogger](debug::DebugLogger) of the compilation they come from.
+///
+/// A `log` crate logger is global, so this one is installed once, with
+/// `log::set_logger(&MessageLogger)`, and forwards each message to the logger
+/// of the compilation running on the current thread.
 pub struct MessageLogger;
 
 impl log::Log for MessageLogger {
     fn enabled(&self, _metadata: &Metadata) -> bool {
         // if log is enabled, enable all message levels
-        super::log_is_enabled()
+        CURRENT_LOGGER.with_borrow(|logger| logger.as_ref().is_some_and(|l| l.is_enabled()))
     }
 
     fn log(&self, record: &Record) {
-        if self.enabled(record.metadata()) {
-            super::log_entry(|| {
+        CURRENT_LOGGER.with_borrow(|logger| {
+            let Some(logger) = logger else {
+                return;
+            };
+            logger.entry(|| {
                 debug::DebugEntryKind::Message(debug::Message {
                     level: record.level().to_string(),
                     file: record.file().map(|x| x.to_string()),
@@ -21,8 +38,64 @@ impl log::Log for MessageLogger {
                     text: format!("{}", record.args()),
                 })
             });
-        }
+        });
     }
 
     fn flush(&self) {}
 }
+
+/// Runs the compilation `f`, with [MessageLogger] forwarding the messages of
+/// the current thread to `logger`.
+pub(crate) fn with_message_log<R>(logger: &debug::DebugLogger, f: impl FnOnce() -> R) -> R {
+    /// Restores the logger of an outer compilation, even when `f` panics.
+    struct Restore(Option<debug::DebugLogger>);
+
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            let outer = self.0.take();
+            CURRENT_LOGGER.with_borrow_mut(|logger| *logger = outer);
+        }
+    }
+
+    let outer = CURRENT_LOGGER.with_borrow_mut(|current| current.replace(logger.clone()));
+    let _restore = Restore(outer);
+
+    f()
+}
+
+#[cfg(test)]
+mod test {
+    use log::Log;
+
+    use super::*;
+
+    fn log(text: &str) {
+        MessageLogger.log(&Record::builder().args(format_args!("{text}")).build());
+    }
+
+    fn texts(logger: &debug::DebugLogger) -> Vec<String> {
+        (logger.take().unwrap().entries.into_iter())
+            .filter_map(|entry| match entry.kind {
+                debug::DebugEntryKind::Message(message) => Some(message.text),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_messages_of_each_compilation() {
+        let a = debug::DebugLogger::new();
+        let b = debug::DebugLogger::new();
+
+        with_message_log(&a, || {
+            log("a");
+            with_message_log(&b, || log("b"));
+            log("a again");
+        });
+        // outside of a compilation, messages are discarded
+        log("none");
+
+        assert_eq!(texts(&a), ["a", "a again"]);
+        assert_eq!(texts(&b), ["b"]);
+    }
+}
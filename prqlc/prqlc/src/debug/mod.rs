@@ -3,5 +3,6 @@ mod messages;
 mod render_html;
 
 pub use crate::debug::log::*;
+pub(crate) use messages::with_message_log;
 pub use messages::MessageLogger;
 pub use render_html::render_log_to_html;
@@ -0,0 +1,82 @@
+//! Long-form explanations of the codes of errors, as printed by
+//! `prqlc explain`.
+//!
+//! Each code is stable: once an error has a code, it keeps it, and codes of
+//! errors which no longer exist aren't reused.
+
+/// Explanations of error codes, in Markdown.
+const EXPLANATIONS: &[(&str, &str)] = &[
+    ("E0001", include_str!("E0001.md")),
+    ("E0002", include_str!("E0002.md")),
+    ("E0003", include_str!("E0003.md")),
+    ("E0004", include_str!("E0004.md")),
+    ("E0005", include_str!("E0005.md")),
+    ("E0006", include_str!("E0006.md")),
+    ("E0007", include_str!("E0007.md")),
+    ("E0008", include_str!("E0008.md")),
+    ("E0009", include_str!("E0009.md")),
+    ("E0010", include_str!("E0010.md")),
+    ("E0011", include_str!("E0011.md")),
+    ("E0012", include_str!("E0012.md")),
+    ("E0013", include_str!("E0013.md")),
+    ("E0014", include_str!("E0014.md")),
+    ("E0015", include_str!("E0015.md")),
+    ("E0016", include_str!("E0016.md")),
+    ("E0017", include_str!("E0017.md")),
+    ("E0018", include_str!("E0018.md")),
+    ("E0019", include_str!("E0019.md")),
+    ("E0020", include_str!("E0020.md")),
+    ("E0021", include_str!("E0021.md")),
+    ("E0022", include_str!("E0022.md")),
+    ("E0023", include_str!("E0023.md")),
+];
+
+/// Returns the explanation of an error code such as `E0001`, in Markdown.
+///
+/// ```
+/// let explanation = prqlc::explain_error_code("E0001").unwrap();
+/// assert!(explanation.starts_with("A query has no main pipeline."));
+/// ```
+pub fn explain_error_code(code: &str) -> Option<&'static str> {
+    EXPLANATIONS
+        .iter()
+        .find(|(c, _)| c.eq_ignore_ascii_case(code))
+        .map(|(_, explanation)| *explanation)
+}
+
+/// All error codes, in order.
+pub fn error_codes() -> impl Iterator<Item = &'static str> {
+    EXPLANATIONS.iter().map(|(code, _)| *code)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{compile, Options};
+
+    /// The first `prql` example of each explanation is erroneous, and must
+    /// fail with the code it explains.
+    #[test]
+    fn test_examples_fail_with_their_code() {
+        for (code, explanation) in EXPLANATIONS {
+            let Some((_, example)) = explanation.split_once("```prql\n") else {
+                continue;
+            };
+            let example = example.split("```").next().unwrap();
+
+            let errors = compile(example, &Options::default()).unwrap_err();
+            assert_eq!(
+                errors.inner[0].code.as_deref(),
+                Some(*code),
+                "example of {code} fails with: {errors}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_codes_are_sequential() {
+        for (index, code) in error_codes().enumerate() {
+            assert_eq!(code, format!("E{:04}", index + 1));
+        }
+    }
+}
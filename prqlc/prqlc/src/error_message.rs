@@ -2,17 +2,17 @@ use std::collections::HashMap;
 use std::error::Error as StdError;
 use std::fmt::{self, Debug, Display, Formatter};
 use std::ops::Range;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
-use ariadne::{Cache, Config, Label, Report, ReportKind, Source};
+use ariadne::{Cache, Config, IndexType, Label, Report, ReportKind, Source};
 use serde::Serialize;
 
 use crate::Span;
-use crate::{Error, Errors, MessageKind, SourceTree};
+use crate::{Error, Errors, Fix, MessageKind, SourceTree};
 
 #[derive(Clone, Serialize)]
 pub struct ErrorMessage {
-    /// Message kind. Currently only Error is implemented.
+    /// Message kind. Lints are reported as warnings.
     pub kind: MessageKind,
     /// Machine-readable identifier of the error
     pub code: Option<String>,
@@ -20,7 +20,9 @@ pub struct ErrorMessage {
     pub reason: String,
     /// A list of suggestions of how to fix the error
     pub hints: Vec<String>,
-    /// Character offset of error origin within a source file
+    /// Edits of the source which would fix the error, the most likely first
+    pub fixes: Vec<Fix>,
+    /// Byte offsets of error origin within a source file
     pub span: Option<Span>,
     /// Annotated code, containing cause and hints.
     pub display: Option<String>,
@@ -37,6 +39,11 @@ pub struct SourceLocation {
     pub start: (usize, usize),
 
     pub end: (usize, usize),
+
+    /// Path of the source file within the source tree. Is `None` when
+    /// compiling a single source, which has no path.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<PathBuf>,
 }
 
 impl Display for ErrorMessage {
@@ -54,7 +61,11 @@ impl Display for ErrorMessage {
                 .map(|c| format!("[{c}] "))
                 .unwrap_or_default();
 
-            writeln!(f, "{}Error: {}", code, &self.reason)?;
+            let kind = match self.kind {
+                MessageKind::Error => "Error",
+                MessageKind::Warning | MessageKind::Lint => "Warning",
+            };
+            writeln!(f, "{}{}: {}", code, kind, &self.reason)?;
             for hint in &self.hints {
                 // TODO: consider alternative formatting for hints.
                 writeln!(f, "↳ Hint: {hint}")?;
@@ -78,6 +89,7 @@ impl From<Error> for ErrorMessage {
             kind: e.kind,
             reason: e.reason.to_string(),
             hints: e.hints,
+            fixes: e.fixes,
             span: e.span,
             display: None,
             location: None,
@@ -133,6 +145,45 @@ impl ErrorMessages {
         serde_json::to_string(self).unwrap()
     }
 
+    /// Applies the first fix of each message to the sources its span is in,
+    /// and returns how many were applied. Fixes which overlap a fix before
+    /// them are skipped.
+    pub fn apply_fixes(&self, sources: &mut SourceTree) -> usize {
+        let mut fixes_by_source: HashMap<u16, Vec<&Fix>> = HashMap::new();
+        for fix in self.inner.iter().filter_map(|e| e.fixes.first()) {
+            (fixes_by_source.entry(fix.span.source_id))
+                .or_default()
+                .push(fix);
+        }
+
+        let mut applied = 0;
+        for (source_id, mut fixes) in fixes_by_source {
+            let Some(path) = sources.get_path(source_id).cloned() else {
+                continue;
+            };
+            let Some(source) = sources.sources.get_mut(&path) else {
+                continue;
+            };
+
+            let mut fixed = String::with_capacity(source.len());
+            let mut position = 0;
+            fixes.sort_by_key(|fix| fix.span.start);
+            for fix in fixes {
+                let (start, end) = (fix.span.start, fix.span.end);
+                if start < position || source.get(start..end).is_none() {
+                    continue;
+                }
+                fixed.push_str(&source[position..start]);
+                fixed.push_str(&fix.replacement);
+                position = end;
+                applied += 1;
+            }
+            fixed.push_str(&source[position..]);
+            *source = fixed;
+        }
+        applied
+    }
+
     /// Computes message location and builds the pretty display.
     pub fn composed(mut self, sources: &SourceTree) -> Self {
         let mut cache = FileTreeCache::new(sources);
@@ -148,7 +199,7 @@ impl ErrorMessages {
             let Ok(source) = cache.fetch(source_path) else {
                 continue;
             };
-            e.location = e.compose_location(source);
+            e.location = e.compose_location(source, source_path);
 
             assert!(
                 e.location.is_some(),
@@ -165,13 +216,19 @@ impl ErrorMessages {
 impl ErrorMessage {
     fn compose_display(&self, source_path: PathBuf, cache: &mut FileTreeCache) -> Option<String> {
         // We always pass color to ariadne as true, and then (currently) strip later.
-        let config = Config::default().with_color(true);
+        let config = Config::default()
+            .with_color(true)
+            .with_index_type(IndexType::Byte);
 
         // Create a span tuple with the source path and the error range
         let span = Range::from(self.span?);
         let error_span = (source_path.clone(), span.start..span.end);
 
-        let mut report = Report::build(ReportKind::Error, error_span.clone())
+        let kind = match self.kind {
+            MessageKind::Error => ReportKind::Error,
+            MessageKind::Warning | MessageKind::Lint => ReportKind::Warning,
+        };
+        let mut report = Report::build(kind, error_span.clone())
             .with_config(config)
             .with_label(Label::new(error_span).with_message(&self.reason));
 
@@ -197,18 +254,24 @@ impl ErrorMessage {
             .map(|x| crate::utils::maybe_strip_colors(x.as_str()))
     }
 
-    fn compose_location(&self, source: &Source) -> Option<SourceLocation> {
+    fn compose_location(&self, source: &Source, source_path: &Path) -> Option<SourceLocation> {
         let span = self.span?;
 
-        let start = source.get_offset_line(span.start)?;
-        let end = source.get_offset_line(span.end)?;
         Some(SourceLocation {
-            start: (start.1, start.2),
-            end: (end.1, end.2),
+            start: line_and_column(source, span.start)?,
+            end: line_and_column(source, span.end)?,
+            path: Some(source_path.to_path_buf()).filter(|p| !p.as_os_str().is_empty()),
         })
     }
 }
 
+/// Converts a byte offset into a line and a column in chars.
+fn line_and_column(source: &Source, offset: usize) -> Option<(usize, usize)> {
+    let (line, line_index, column) = source.get_byte_line(offset)?;
+    let text = source.get_line_text(line)?;
+    Some((line_index, text.get(..column)?.chars().count()))
+}
+
 struct FileTreeCache<'a> {
     file_tree: &'a SourceTree,
     cache: HashMap<PathBuf, Source>,
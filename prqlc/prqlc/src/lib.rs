@@ -53,11 +53,14 @@
 //!
 //! - Compile PRQL queries to SQL at build time.
 //!
-//!   For inline strings, use the `prqlc-macros` crate; for example:
+//!   For inline strings and files, use the `prqlc-macros` crate; for example:
 //!   ```ignore
 //!   let sql: &str = prql_to_sql!("from albums | select {title, artist_id}");
+//!   let sql: &str = include_prql!("queries/albums.prql", target = "sql.postgres");
 //!   ```
 //!
+//!   Its `prql!` macro also declares a struct for the rows of the result.
+//!
 //!   For compiling whole files (`.prql` to `.sql`), call `prqlc` from
 //!   `build.rs`. See [this example
 //!   project](https://github.com/PRQL/prql/tree/main/prqlc/prqlc/examples/compile-files).
@@ -99,20 +102,35 @@ use std::sync::OnceLock;
 use std::{collections::HashMap, path::PathBuf, str::FromStr};
 
 use anstream::adapter::strip_str;
+use itertools::Itertools;
 use semver::Version;
 use serde::{Deserialize, Serialize};
 use strum::VariantNames;
 
+pub use cache::{CacheStats, CompilationCache};
+pub use error_codes::{error_codes, explain_error_code};
 pub use error_message::{ErrorMessage, ErrorMessages, SourceLocation};
-pub use prqlc_parser::error::{Error, ErrorSource, Errors, MessageKind, Reason, WithErrorInfo};
+pub use lint::LintRule;
+pub use manifest::{
+    BuildTarget, Dependency, LockFile, LockedPackage, Manifest, Package, PackageSource,
+    ProjectManifest, TargetManifest,
+};
+pub use prqlc_parser::error::{
+    Error, ErrorSource, Errors, Fix, MessageKind, Reason, WithErrorInfo,
+};
 pub use prqlc_parser::lexer::lr;
 pub use prqlc_parser::parser::pr;
 pub use prqlc_parser::span::Span;
+pub use warnings::collect_warnings;
 
+mod cache;
 mod codegen;
 pub mod debug;
+mod error_codes;
 mod error_message;
 pub mod ir;
+mod lint;
+mod manifest;
 pub mod parser;
 pub mod semantic;
 pub mod sql;
@@ -120,6 +138,7 @@ pub mod sql;
 pub mod utils;
 #[cfg(not(feature = "cli"))]
 pub(crate) mod utils;
+mod warnings;
 
 pub type Result<T, E = Error> = core::result::Result<T, E>;
 
@@ -187,33 +206,386 @@ pub fn compiler_version() -> Version {
 /// [`sql::Dialect`](sql/enum.Dialect.html) for options and supported SQL
 /// dialects.
 pub fn compile(prql: &str, options: &Options) -> Result<String, ErrorMessages> {
-    let sources = SourceTree::from(prql);
+    debug::with_message_log(&options.debug_log, || {
+        let sources = SourceTree::from(prql);
+        let log = &options.debug_log;
+
+        Ok(&sources)
+            .and_then(|sources| parser::parse(sources, log))
+            .and_then(|ast| {
+                semantic::resolve_and_lower(ast, &[], None, log)
+                    .map_err(|e| e.with_source(ErrorSource::NameResolver).into())
+            })
+            .and_then(|rq| {
+                sql::compile(rq, options).map_err(|e| e.with_source(ErrorSource::SQL).into())
+            })
+            .map_err(|e| display_errors(ErrorMessages::from(e).composed(&sources), options))
+    })
+}
 
-    Ok(&sources)
-        .and_then(parser::parse)
-        .and_then(|ast| {
-            semantic::resolve_and_lower(ast, &[], None)
-                .map_err(|e| e.with_source(ErrorSource::NameResolver).into())
+/// Compile a PRQL project of multiple files into a SQL string, like
+/// [compile].
+///
+/// `main_path` is the path of the relation to compile, such as
+/// `["reports", "monthly"]` for the relation `monthly` of `reports.prql`. When
+/// it is empty, the relation `main` is compiled. The locations of errors
+/// include the path of the file they are in.
+///
+/// ```
+/// use prqlc::{compile_tree, Options, SourceTree};
+///
+/// let sources = SourceTree::new(
+///     [
+///         ("Project.prql".into(), "from shared.top_albums | select {title}".into()),
+///         ("shared.prql".into(), "let top_albums = (from albums | take 10)".into()),
+///     ],
+///     None,
+/// );
+/// let opts = Options::default().no_format().no_signature();
+/// let sql = compile_tree(&sources, &[], &opts).unwrap();
+/// assert_eq!(
+///     sql,
+///     "WITH top_albums AS (SELECT * FROM albums LIMIT 10) SELECT title FROM top_albums"
+/// );
+/// ```
+pub fn compile_tree(
+    sources: &SourceTree,
+    main_path: &[String],
+    options: &Options,
+) -> Result<String, ErrorMessages> {
+    debug::with_message_log(&options.debug_log, || {
+        let log = &options.debug_log;
+
+        Ok(sources)
+            .and_then(|sources| parser::parse(sources, log))
+            .and_then(|ast| {
+                semantic::resolve_and_lower(ast, main_path, None, log)
+                    .map_err(|e| e.with_source(ErrorSource::NameResolver).into())
+            })
+            .and_then(|rq| {
+                sql::compile(rq, options).map_err(|e| e.with_source(ErrorSource::SQL).into())
+            })
+            .map_err(|e| display_errors(ErrorMessages::from(e).composed(sources), options))
+    })
+}
+
+/// Splits a dotted path of a relation, such as `reports.monthly`, into the
+/// `main_path` of [compile_tree]. An empty string is an empty path.
+///
+/// ```
+/// assert_eq!(prqlc::split_main_path("reports.monthly"), ["reports", "monthly"]);
+/// assert!(prqlc::split_main_path("").is_empty());
+/// ```
+pub fn split_main_path(main_path: &str) -> Vec<String> {
+    main_path
+        .split('.')
+        .filter(|x| !x.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Compile a PRQL string into a SQL string, like [compile], along with the
+/// warnings of the compilation and information about the query.
+///
+/// ```
+/// use prqlc::{compile_full, sql::Dialect, OutputColumn, Options};
+///
+/// let prql = "from albums | select {title, n = 5}";
+/// let result = compile_full(prql, &Options::default()).unwrap();
+/// assert_eq!(result.tables, vec!["albums"]);
+/// assert_eq!(result.dialect, Dialect::Generic);
+/// assert_eq!(
+///     result.columns[1],
+///     OutputColumn::Single { name: Some("n".to_string()), ty: Some("int".to_string()) }
+/// );
+/// ```
+pub fn compile_full(prql: &str, options: &Options) -> Result<CompileResult, ErrorMessages> {
+    compile_full_tree(&SourceTree::from(prql), &[], options)
+}
+
+/// Compile a PRQL project of multiple files like [compile_tree], along with
+/// the warnings of the compilation and information about the query, like
+/// [compile_full].
+pub fn compile_full_tree(
+    sources: &SourceTree,
+    main_path: &[String],
+    options: &Options,
+) -> Result<CompileResult, ErrorMessages> {
+    debug::with_message_log(&options.debug_log, || {
+        let display = |e: Errors| display_errors(ErrorMessages::from(e).composed(sources), options);
+
+        let log = &options.debug_log;
+
+        let (result, warnings) = collect_warnings(|| -> Result<_, Errors> {
+            let ast = parser::parse(sources, log)?;
+            let root_mod = semantic::resolve(ast, log)
+                .map_err(|e| e.with_source(ErrorSource::NameResolver))?;
+
+            let columns = output_columns(&root_mod, main_path);
+
+            let query = semantic::lower(root_mod, main_path, None, log)
+                .map_err(|e| e.with_source(ErrorSource::NameResolver))?;
+            let tables = query
+                .tables
+                .iter()
+                .filter_map(|table| match &table.relation.kind {
+                    ir::rq::RelationKind::ExternRef(ir::pl::TableExternRef::LocalTable(name)) => {
+                        Some(name.to_string())
+                    }
+                    _ => None,
+                })
+                .sorted()
+                .dedup()
+                .collect();
+
+            let Target::Sql(dialect) = options.target;
+            let dialect = sql::resolve_dialect(&query.def, dialect)?;
+
+            let sql = sql::compile(query, options).map_err(|e| e.with_source(ErrorSource::SQL))?;
+            Ok((sql, columns, tables, dialect))
+        });
+        let (sql, columns, tables, dialect) = result.map_err(display)?;
+
+        Ok(CompileResult {
+            sql,
+            warnings: display(Errors(warnings)).inner,
+            columns,
+            tables,
+            dialect,
         })
-        .and_then(|rq| {
-            sql::compile(rq, options).map_err(|e| e.with_source(ErrorSource::SQL).into())
+    })
+}
+
+/// The result of [compile_full] and [compile_full_tree].
+#[derive(Debug, Clone, Serialize)]
+pub struct CompileResult {
+    pub sql: String,
+
+    /// Issues which don't prevent compilation, such as the use of deprecated
+    /// functions.
+    pub warnings: Vec<ErrorMessage>,
+
+    /// Columns of the result of the query.
+    pub columns: Vec<OutputColumn>,
+
+    /// Names of the database tables which the query reads from.
+    pub tables: Vec<String>,
+
+    /// The dialect of the SQL: the one of [Options::target], or else the one
+    /// in the query header.
+    pub dialect: sql::Dialect,
+}
+
+/// A column of the result of a query.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub enum OutputColumn {
+    /// A column with a type if it could be inferred, in PRQL syntax.
+    Single {
+        name: Option<String>,
+        ty: Option<String>,
+    },
+
+    /// Any other columns of a relation, as with `SELECT *`.
+    Wildcard,
+}
+
+fn output_columns(root_mod: &ir::decl::RootModule, main_path: &[String]) -> Vec<OutputColumn> {
+    let Some(main) = root_mod
+        .find_main_rel(main_path)
+        .ok()
+        .and_then(|(main, _)| main.as_relation_var())
+    else {
+        return Vec::new();
+    };
+    let Some(fields) = main.ty.as_ref().and_then(|ty| ty.as_relation()) else {
+        return Vec::new();
+    };
+    let declared = declared_column_types(root_mod, main);
+
+    fields
+        .iter()
+        .enumerate()
+        .map(|(index, field)| match field {
+            pr::TyTupleField::Single(name, ty) => OutputColumn::Single {
+                name: name.clone(),
+                ty: (ty.as_ref())
+                    .or_else(|| declared.get(index)?.as_ref())
+                    .map(codegen::write_ty),
+            },
+            pr::TyTupleField::Wildcard(_) => OutputColumn::Wildcard,
         })
-        .map_err(|e| {
-            let error_messages = ErrorMessages::from(e).composed(&sources);
-            match options.display {
-                DisplayOptions::AnsiColor => error_messages,
-                DisplayOptions::Plain => ErrorMessages {
-                    inner: error_messages
-                        .inner
-                        .into_iter()
-                        .map(|e| ErrorMessage {
-                            display: e.display.map(|s| strip_str(&s).to_string()),
-                            ..e
-                        })
-                        .collect(),
-                },
+        .collect()
+}
+
+/// Types of the columns of a relation which are copies of the columns of a
+/// declared table, such as `let albums <[{title = text}]>`, found by following
+/// the lineage of the relation back to its input tables.
+fn declared_column_types(
+    root_mod: &ir::decl::RootModule,
+    expr: &ir::pl::Expr,
+) -> Vec<Option<pr::Ty>> {
+    let Some(lineage) = &expr.lineage else {
+        return Vec::new();
+    };
+    let frames = semantic::reporting::collect_frames(expr.clone());
+
+    let mut inputs = HashMap::new();
+    for (_, frame) in &frames.frames {
+        inputs.extend(frame.inputs.iter().map(|input| (input.id, &input.table)));
+    }
+    inputs.extend(lineage.inputs.iter().map(|input| (input.id, &input.table)));
+    let nodes: HashMap<_, _> = frames.nodes.iter().map(|n| (n.id, n)).collect();
+
+    // follow references to other columns, back to a column of an input table
+    let source = |mut id: usize, target_name: Option<&String>| {
+        if let Some(table) = inputs.get(&id) {
+            return Some((*table, target_name?.clone()));
+        }
+        loop {
+            let node = nodes.get(&id)?;
+            let [target] = node.targets[..] else {
+                return None;
+            };
+            let name = &node.ident.as_ref()?.as_ident()?.name;
+            if let Some(table) = inputs.get(&target) {
+                return Some((*table, name.clone()));
             }
+            id = target;
+        }
+    };
+
+    (lineage.columns.iter())
+        .map(|column| {
+            let ir::pl::LineageColumn::Single {
+                target_id,
+                target_name,
+                ..
+            } = column
+            else {
+                return None;
+            };
+            let (table, column) = source(*target_id, target_name.as_ref())?;
+
+            let decl = root_mod.module.get(table)?.kind.as_table_decl()?;
+            let fields = decl.ty.as_ref()?.as_relation()?;
+            fields.iter().find_map(|field| match field {
+                pr::TyTupleField::Single(Some(name), ty) if *name == column => ty.clone(),
+                _ => None,
+            })
         })
+        .collect()
+}
+
+/// Like [compile], but also returns a log of each stage of the compilation,
+/// which can be serialized to JSON or rendered with
+/// [debug::render_log_to_html].
+///
+/// Each call collects its own log, so compilations can run concurrently.
+pub fn compile_with_debug_log(
+    prql: &str,
+    options: &Options,
+) -> (Result<String, ErrorMessages>, debug::DebugLog) {
+    let debug_log = debug::DebugLogger::new();
+    let options = options.clone().with_debug_log(debug_log.clone());
+    let result = compile(prql, &options);
+    (result, debug_log.take().unwrap())
+}
+
+/// Compile each public relation of a PRQL project into its own SQL statement,
+/// rather than only `main`.
+///
+/// Relations are ordered so that each comes after the relations it
+/// references, which it refers to by name. With `create_views`, each statement
+/// creates a view of the relation, so the statements can be run in order as a
+/// script. Relations with a name starting with `_` are private: they are
+/// inlined into the relations that reference them.
+///
+/// Use [relations_to_script] to join the statements.
+pub fn compile_relations(
+    sources: &SourceTree,
+    options: &Options,
+    create_views: bool,
+) -> Result<Vec<CompiledRelation>, ErrorMessages> {
+    debug::with_message_log(&options.debug_log, || {
+        let log = &options.debug_log;
+
+        Ok(sources)
+            .and_then(|sources| parser::parse(sources, log))
+            .and_then(|ast| {
+                semantic::resolve_and_lower_relations(ast, None, log)
+                    .map_err(|e| e.with_source(ErrorSource::NameResolver).into())
+            })
+            .and_then(|queries| relations_to_sql(queries, options, create_views))
+            .map_err(|e| display_errors(ErrorMessages::from(e).composed(sources), options))
+    })
+}
+
+/// Generates the SQL of each relation lowered by [compile_relations].
+pub(crate) fn relations_to_sql(
+    queries: Vec<(ir::pl::Ident, ir::rq::RelationalQuery)>,
+    options: &Options,
+    create_views: bool,
+) -> Result<Vec<CompiledRelation>, Errors> {
+    // relations are independent once resolved, so generate their SQL in
+    // parallel, reporting the errors of all of them
+    let compile = |(name, rq): (ir::pl::Ident, _)| {
+        let sql = sql::compile_relation(rq, name.clone(), create_views, options)
+            .map_err(|e| e.with_source(ErrorSource::SQL))?;
+        Ok(CompiledRelation { name, sql })
+    };
+    // unless there is a debug log, which would interleave the entries of the
+    // relations
+    let results: Vec<Result<_, Error>> = if options.debug_log.is_enabled() {
+        queries.into_iter().map(compile).collect()
+    } else {
+        utils::parallel_map(queries, compile)
+    };
+    let (relations, errors): (Vec<_>, Vec<Error>) = results.into_iter().partition_result();
+    if errors.is_empty() {
+        Ok(relations)
+    } else {
+        Err(Errors(errors))
+    }
+}
+
+/// A relation compiled by [compile_relations].
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompiledRelation {
+    /// Fully qualified name of the relation.
+    pub name: ir::pl::Ident,
+
+    /// A single SQL statement, without a trailing `;`.
+    pub sql: String,
+}
+
+/// Join compiled relations into a SQL script, followed by the signature
+/// comment if it is enabled in `options`.
+pub fn relations_to_script(relations: &[CompiledRelation], options: &Options) -> String {
+    let mut script = relations
+        .iter()
+        .map(|relation| format!("{};\n", relation.sql))
+        .collect::<Vec<_>>()
+        .join("\n");
+    if let Some(signature) = sql::signature_comment(options) {
+        script += &format!("\n{signature}\n");
+    }
+    script
+}
+
+fn display_errors(error_messages: ErrorMessages, options: &Options) -> ErrorMessages {
+    match options.display {
+        DisplayOptions::AnsiColor => error_messages,
+        DisplayOptions::Plain => ErrorMessages {
+            inner: error_messages
+                .inner
+                .into_iter()
+                .map(|e| ErrorMessage {
+                    display: e.display.map(|s| strip_str(&s).to_string()),
+                    ..e
+                })
+                .collect(),
+        },
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -256,7 +628,8 @@ impl FromStr for Target {
         Err(Error::new(Reason::NotFound {
             name: format!("{s:?}"),
             namespace: "target".to_string(),
-        }))
+        })
+        .with_code("E0011"))
     }
 }
 
@@ -292,6 +665,13 @@ pub struct Options {
     /// - Strip colors from the output (possibly also with a library such as
     ///   `anstream`).
     pub display: DisplayOptions,
+
+    /// Logger which collects a log of each stage of the compilation. See
+    /// [debug::DebugLogger].
+    ///
+    /// Defaults to a disabled logger.
+    #[serde(skip)]
+    pub debug_log: debug::DebugLogger,
 }
 
 impl Default for Options {
@@ -302,6 +682,7 @@ impl Default for Options {
             signature_comment: true,
             color: true,
             display: DisplayOptions::AnsiColor,
+            debug_log: debug::DebugLogger::default(),
         }
     }
 }
@@ -340,6 +721,11 @@ impl Options {
         self.display = display;
         self
     }
+
+    pub fn with_debug_log(mut self, debug_log: debug::DebugLogger) -> Self {
+        self.debug_log = debug_log;
+        self
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, strum::EnumString)]
@@ -375,13 +761,14 @@ pub fn prql_to_pl(prql: &str) -> Result<pr::ModuleDef, ErrorMessages> {
 
 /// Parse PRQL into a PL AST
 pub fn prql_to_pl_tree(prql: &SourceTree) -> Result<pr::ModuleDef, ErrorMessages> {
-    parser::parse(prql).map_err(|e| ErrorMessages::from(e).composed(prql))
+    parser::parse(prql, &debug::DebugLogger::default())
+        .map_err(|e| ErrorMessages::from(e).composed(prql))
 }
 
 /// Perform semantic analysis and convert PL to RQ.
 // TODO: rename this to `pl_to_rq_simple`
 pub fn pl_to_rq(pl: pr::ModuleDef) -> Result<ir::rq::RelationalQuery, ErrorMessages> {
-    semantic::resolve_and_lower(pl, &[], None)
+    semantic::resolve_and_lower(pl, &[], None, &debug::DebugLogger::default())
         .map_err(|e| e.with_source(ErrorSource::NameResolver).into())
 }
 
@@ -391,7 +778,8 @@ pub fn pl_to_rq_tree(
     main_path: &[String],
     database_module_path: &[String],
 ) -> Result<ir::rq::RelationalQuery, ErrorMessages> {
-    semantic::resolve_and_lower(pl, main_path, Some(database_module_path))
+    let log = debug::DebugLogger::default();
+    semantic::resolve_and_lower(pl, main_path, Some(database_module_path), &log)
         .map_err(|e| e.with_source(ErrorSource::NameResolver).into())
 }
 
@@ -405,6 +793,129 @@ pub fn pl_to_prql(pl: &pr::ModuleDef) -> Result<String, ErrorMessages> {
     Ok(codegen::WriteSource::write(&pl.stmts, codegen::WriteOpt::default()).unwrap())
 }
 
+/// Format PRQL source.
+///
+/// Unlike [prql_to_pl] followed by [pl_to_prql], this retains comments and
+/// blank lines, and formatting an already formatted source returns it
+/// unchanged.
+pub fn format_prql(prql: &str, options: &FormatOptions) -> Result<String, ErrorMessages> {
+    Ok(codegen::Cst::parse(prql)?.write(options))
+}
+
+/// Options for formatting PRQL with [format_prql].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FormatOptions {
+    /// Maximum number of characters per line. Lines only exceed it when an
+    /// expression can't be broken up.
+    ///
+    /// Defaults to 50.
+    pub max_width: u16,
+
+    /// Number of spaces per indentation level.
+    ///
+    /// Defaults to 2.
+    pub indent_width: u16,
+
+    /// Write each transform of a nested pipeline on its own line, even if the
+    /// pipeline fits on a single line. Transforms of the main pipeline are
+    /// always on their own lines.
+    ///
+    /// Defaults to false.
+    pub break_pipelines: bool,
+
+    /// Emit a comma after the last item of tuples and arrays that span
+    /// multiple lines.
+    ///
+    /// Defaults to true.
+    pub trailing_commas: bool,
+
+    /// Align the `=` of assignments in tuples that span multiple lines, such
+    /// as `derive {...}`.
+    ///
+    /// Defaults to false.
+    pub align_assignments: bool,
+}
+
+impl Default for FormatOptions {
+    fn default() -> Self {
+        Self {
+            max_width: 50,
+            indent_width: 2,
+            break_pipelines: false,
+            trailing_commas: true,
+            align_assignments: false,
+        }
+    }
+}
+
+impl FormatOptions {
+    pub fn with_max_width(mut self, max_width: u16) -> Self {
+        self.max_width = max_width;
+        self
+    }
+
+    pub fn with_indent_width(mut self, indent_width: u16) -> Self {
+        self.indent_width = indent_width;
+        self
+    }
+
+    pub fn with_break_pipelines(mut self, break_pipelines: bool) -> Self {
+        self.break_pipelines = break_pipelines;
+        self
+    }
+
+    pub fn with_trailing_commas(mut self, trailing_commas: bool) -> Self {
+        self.trailing_commas = trailing_commas;
+        self
+    }
+
+    pub fn with_align_assignments(mut self, align_assignments: bool) -> Self {
+        self.align_assignments = align_assignments;
+        self
+    }
+}
+
+/// Lint PRQL, returning what the enabled lint rules found.
+///
+/// Lints are problems which don't prevent a query from compiling, but are
+/// likely to be mistakes. A query which doesn't compile returns its errors.
+pub fn lint(prql: &str, options: &LintOptions) -> Result<Vec<ErrorMessage>, ErrorMessages> {
+    lint_tree(&SourceTree::from(prql), options)
+}
+
+/// Lint a tree of PRQL sources. See [lint].
+pub fn lint_tree(
+    sources: &SourceTree,
+    options: &LintOptions,
+) -> Result<Vec<ErrorMessage>, ErrorMessages> {
+    Ok(sources)
+        .and_then(|sources| parser::parse(sources, &debug::DebugLogger::default()))
+        .and_then(|ast| {
+            lint::lint(ast, &options.allow)
+                .map_err(|e| e.with_source(ErrorSource::NameResolver).into())
+        })
+        .map(|lints| ErrorMessages::from(Errors(lints)).composed(sources).inner)
+        .map_err(|e| ErrorMessages::from(e).composed(sources))
+}
+
+/// Options for linting PRQL with [lint].
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LintOptions {
+    /// Lint rules which are disabled.
+    ///
+    /// Defaults to none.
+    pub allow: Vec<LintRule>,
+}
+
+impl LintOptions {
+    pub fn with_allow(mut self, rule: LintRule) -> Self {
+        self.allow.push(rule);
+        self
+    }
+}
+
 /// JSON serialization and deserialization functions
 pub mod json {
     use super::*;
@@ -429,8 +940,20 @@ pub mod json {
         serde_json::from_str(json).map_err(convert_json_err)
     }
 
+    /// JSON serialization
+    pub fn from_compile_result(result: &CompileResult) -> Result<String, ErrorMessages> {
+        serde_json::to_string(result).map_err(convert_json_err)
+    }
+
+    /// JSON deserialization of an object that maps paths to sources
+    pub fn to_source_tree(json: &str) -> Result<SourceTree, ErrorMessages> {
+        let sources: HashMap<PathBuf, String> =
+            serde_json::from_str(json).map_err(convert_json_err)?;
+        Ok(SourceTree::new(sources, None))
+    }
+
     fn convert_json_err(err: serde_json::Error) -> ErrorMessages {
-        ErrorMessages::from(Error::new_simple(err.to_string()))
+        ErrorMessages::from(Error::new_simple(err.to_string()).with_code("E0023"))
     }
 }
 
@@ -488,6 +1011,13 @@ impl SourceTree {
     pub fn get_path(&self, source_id: u16) -> Option<&PathBuf> {
         self.source_ids.get(&source_id)
     }
+
+    /// The file of the root module, which contains the main query: the only
+    /// file, or the file whose name starts with an uppercase letter, such as
+    /// `Project.prql`.
+    pub fn root_file(&self) -> Option<&PathBuf> {
+        parser::find_root(self)
+    }
 }
 
 impl<S: ToString> From<S> for SourceTree {
@@ -506,7 +1036,8 @@ pub mod internal {
     ) -> Result<semantic::reporting::FrameCollector, ErrorMessages> {
         let ast = Some(pl.clone());
 
-        let root_module = semantic::resolve(pl).map_err(ErrorMessages::from)?;
+        let root_module =
+            semantic::resolve(pl, &debug::DebugLogger::default()).map_err(ErrorMessages::from)?;
 
         let (main, _) = root_module.find_main_rel(&[]).unwrap();
         let mut fc =
@@ -527,7 +1058,7 @@ pub mod internal {
         }
 
         fn convert_json_err(err: serde_json::Error) -> ErrorMessages {
-            ErrorMessages::from(Error::new_simple(err.to_string()))
+            ErrorMessages::from(Error::new_simple(err.to_string()).with_code("E0023"))
         }
     }
 }
@@ -585,7 +1116,10 @@ mod tests {
                     namespace: "target",
                 },
                 hints: [],
-                code: None,
+                code: Some(
+                    "E0011",
+                ),
+                fixes: [],
             },
         )
         "#);
@@ -600,7 +1134,10 @@ mod tests {
                     namespace: "target",
                 },
                 hints: [],
-                code: None,
+                code: Some(
+                    "E0011",
+                ),
+                fixes: [],
             },
         )
         "#);
@@ -0,0 +1,352 @@
+//! Lints: checks for queries which compile, but are likely to contain a
+//! mistake.
+//!
+//! Rules run either over PR, when they only need the syntax (such as the
+//! contents of s-strings), or over resolved PL, when they need to know about
+//! relations and their columns.
+
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+use strum::VariantNames;
+
+use crate::debug::DebugLogger;
+use crate::{pr, semantic, Error, MessageKind, Reason, Result, Span, WithErrorInfo};
+
+mod pl_rules;
+mod pr_rules;
+
+/// A lint rule.
+///
+/// Rules can be disabled with [crate::LintOptions::allow], or for a single
+/// statement with an annotation, such as `@{lint={allow=[take_without_sort]}}`.
+#[derive(
+    Debug,
+    PartialEq,
+    Eq,
+    Clone,
+    Copy,
+    Serialize,
+    Deserialize,
+    strum::Display,
+    strum::EnumString,
+    strum::IntoStaticStr,
+    strum::VariantNames,
+)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum LintRule {
+    /// All columns of a join are selected, as with `SELECT *`.
+    SelectStarJoin,
+    /// A `sort` followed by a `group`, which discards the order.
+    SortBeforeGroup,
+    /// A `take` without a preceding `sort`, so the rows taken are arbitrary.
+    TakeWithoutSort,
+    /// An s-string that compares to `NULL` with `=`, which is never true.
+    SstringNullComparison,
+    /// A `let` declaration that is never used.
+    UnusedLet,
+    /// A column that replaces an existing column with the same name.
+    ShadowedColumn,
+    /// An s-string containing a string literal, which may be user data.
+    SstringUserData,
+}
+
+/// A single finding of a lint rule.
+struct Lint {
+    rule: LintRule,
+    span: Option<Span>,
+    message: String,
+    hint: String,
+}
+
+impl From<Lint> for Error {
+    fn from(lint: Lint) -> Self {
+        Error {
+            kind: MessageKind::Lint,
+            span: lint.span,
+            reason: Reason::Simple(lint.message),
+            hints: vec![
+                lint.hint,
+                format!("allow it with `@{{lint={{allow={}}}}}`", lint.rule),
+            ],
+            code: None,
+            fixes: Vec::new(),
+        }
+    }
+}
+
+/// Runs all lint rules that are not in `allow` over a module tree, which must
+/// not yet contain std.
+pub(crate) fn lint(module_tree: pr::ModuleDef, allow: &[LintRule]) -> Result<Vec<Error>> {
+    let suppressions = collect_suppressions(&module_tree.stmts)?;
+
+    let mut lints = Vec::new();
+    pr_rules::lint(&module_tree, &mut lints);
+
+    let root_module = semantic::resolve(module_tree, &DebugLogger::default())?;
+    pl_rules::lint(&root_module.module, &mut lints);
+
+    let lints = lints
+        .into_iter()
+        .filter(|lint| !allow.contains(&lint.rule))
+        .filter(|lint| {
+            !suppressions.iter().any(|(span, rules)| {
+                rules.contains(&lint.rule) && lint.span.is_some_and(|s| contains(span, &s))
+            })
+        })
+        .sorted_by_key(|lint| lint.span.map(|s| (s.source_id, s.start, s.end)))
+        .dedup_by(|a, b| a.rule == b.rule && a.span == b.span)
+        .map(Error::from)
+        .collect();
+    Ok(lints)
+}
+
+/// Finds statements with a `lint` annotation, returning their spans and the
+/// rules they allow. Annotations on a module apply to all of its statements.
+fn collect_suppressions(stmts: &[pr::Stmt]) -> Result<Vec<(Span, Vec<LintRule>)>> {
+    let mut suppressions = Vec::new();
+    for stmt in stmts {
+        if let Some(span) = stmt.span {
+            let mut rules = Vec::new();
+            for annotation in &stmt.annotations {
+                rules.extend(allowed_by_annotation(annotation)?);
+            }
+            if !rules.is_empty() {
+                suppressions.push((span, rules));
+            }
+        }
+        if let pr::StmtKind::ModuleDef(module_def) = &stmt.kind {
+            suppressions.extend(collect_suppressions(&module_def.stmts)?);
+        }
+    }
+    Ok(suppressions)
+}
+
+/// Reads the rules from `@{lint={allow=[rule_a, rule_b]}}`. A single rule
+/// doesn't need the array.
+fn allowed_by_annotation(annotation: &pr::Annotation) -> Result<Vec<LintRule>> {
+    let pr::ExprKind::Tuple(fields) = &annotation.expr.kind else {
+        return Ok(Vec::new());
+    };
+    let lint_fields = fields
+        .iter()
+        .filter(|f| f.alias.as_deref() == Some("lint"))
+        .filter_map(|f| f.kind.as_tuple());
+
+    let mut rules = Vec::new();
+    for lint_field in lint_fields.flatten() {
+        if lint_field.alias.as_deref() != Some("allow") {
+            return Err(Error::new_simple("unknown lint setting")
+                .push_hint("use `@{lint={allow=[rule_name]}}`")
+                .with_span(lint_field.span)
+                .with_code("E0020"));
+        }
+        let names = match &lint_field.kind {
+            pr::ExprKind::Array(items) => items.iter().collect(),
+            _ => vec![lint_field],
+        };
+        for name in names {
+            rules.push(parse_rule(name)?);
+        }
+    }
+    Ok(rules)
+}
+
+fn parse_rule(expr: &pr::Expr) -> Result<LintRule> {
+    let rule = match &expr.kind {
+        pr::ExprKind::Ident(ident) if ident.path.is_empty() => ident.name.parse().ok(),
+        _ => None,
+    };
+    rule.ok_or_else(|| {
+        Error::new_simple("unknown lint rule")
+            .push_hint(format!(
+                "available rules: {}",
+                LintRule::VARIANTS.join(", ")
+            ))
+            .with_span(expr.span)
+            .with_code("E0020")
+    })
+}
+
+fn contains(outer: &Span, inner: &Span) -> bool {
+    outer.source_id == inner.source_id && outer.start <= inner.start && inner.end <= outer.end
+}
+
+#[cfg(test)]
+mod test {
+    use insta::assert_snapshot;
+
+    use crate::{LintOptions, LintRule};
+
+    fn lint(prql: &str) -> String {
+        lint_with(prql, LintOptions::default())
+    }
+
+    fn lint_with(prql: &str, options: LintOptions) -> String {
+        let lints = crate::lint(prql, &options).unwrap();
+        lints
+            .iter()
+            .map(|lint| {
+                let (line, col) = lint.location.as_ref().unwrap().start;
+                let rule = (lint.hints.last())
+                    .and_then(|h| h.strip_prefix("allow it with `@{lint={allow="))
+                    .and_then(|h| h.strip_suffix("}}`"))
+                    .unwrap();
+                format!("{}:{} [{rule}] {}\n", line + 1, col + 1, lint.reason)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_take_without_sort() {
+        assert_snapshot!(lint(r#"
+        from employees
+        take 10
+        "#), @"3:9 [take_without_sort] `take` without a `sort` returns arbitrary rows");
+
+        // Only a sort within the group applies to a take within it
+        assert_snapshot!(lint(r#"
+        from employees
+        sort salary
+        take 10
+        group {dept} (take 1)
+        group {title} (sort age | take 1)
+        "#), @"5:23 [take_without_sort] `take` without a `sort` returns arbitrary rows");
+    }
+
+    #[test]
+    fn test_sort_before_group() {
+        assert_snapshot!(lint(r#"
+        from employees
+        sort salary
+        derive {gross = salary + benefits}
+        group {dept} (aggregate {total = sum gross})
+        "#), @"3:9 [sort_before_group] this order is discarded by the `group` which follows");
+
+        // A group without columns keeps the order
+        assert_snapshot!(lint(r#"
+        from employees
+        sort salary
+        group {} (aggregate {total = sum salary})
+        "#), @"");
+    }
+
+    #[test]
+    fn test_select_star_join() {
+        assert_snapshot!(lint(r#"
+        from employees
+        join departments (==dept_id)
+        "#), @"3:9 [select_star_join] all columns of this join are selected");
+
+        assert_snapshot!(lint(r#"
+        from employees
+        join departments (==dept_id)
+        select {employees.*, departments.name}
+        "#), @"3:9 [select_star_join] all columns of this join are selected");
+
+        assert_snapshot!(lint(r#"
+        from employees
+        join departments (==dept_id)
+        select {employees.name, departments.name}
+        "#), @"");
+    }
+
+    #[test]
+    fn test_shadowed_column() {
+        assert_snapshot!(lint(r#"
+        from employees
+        select {name, salary}
+        derive {salary = salary * 1.1}
+        derive {bonus = salary * 0.1}
+        "#), @"4:26 [shadowed_column] `salary` replaces an existing column with the same name");
+    }
+
+    #[test]
+    fn test_sstring() {
+        assert_snapshot!(lint(r#"
+        from employees
+        filter s"manager_id = NULL"
+        filter s"{name} <> 'admin'"
+        derive {x = s"COALESCE(bonus, 0)"}
+        "#), @r"
+        3:16 [sstring_null_comparison] comparing to NULL with `=` is never true
+        4:16 [sstring_user_data] s-string contains a string literal, which may be user data
+        ");
+    }
+
+    #[test]
+    fn test_unused_let() {
+        assert_snapshot!(lint(r#"
+        let high_earners = (from employees | filter salary > 100000)
+        let _scratch = (from employees)
+        let double = x -> x * 2
+
+        module helpers {
+          let unused = 5
+        }
+
+        from high_earners
+        "#), @r"
+        4:22 [unused_let] `double` is declared but never used
+        7:24 [unused_let] `unused` is declared but never used
+        ");
+
+        // A name is used only within the module it refers to
+        assert_snapshot!(lint(r#"
+        let top = (from employees | sort salary | take 10)
+
+        module helpers {
+          let top = 5
+          let bonus = 2
+        }
+
+        from top
+        derive {x = helpers.bonus}
+        "#), @"5:21 [unused_let] `top` is declared but never used");
+
+        // Without a main pipeline, the declarations may be used elsewhere
+        assert_snapshot!(lint(r#"
+        let high_earners = (from employees | filter salary > 100000)
+        "#), @"");
+    }
+
+    #[test]
+    fn test_allow() {
+        let prql = r#"
+        let x = 5
+
+        @{lint={allow=take_without_sort}}
+        let top = (from employees | take 10)
+
+        @{lint={allow=[unused_let, select_star_join]}}
+        let joined = (from employees | join departments (==dept_id))
+
+        from top
+        take 5
+        "#;
+        assert_snapshot!(lint(prql), @r"
+        2:17 [unused_let] `x` is declared but never used
+        11:9 [take_without_sort] `take` without a `sort` returns arbitrary rows
+        ");
+
+        let options = LintOptions::default()
+            .with_allow(LintRule::UnusedLet)
+            .with_allow(LintRule::TakeWithoutSort);
+        assert_snapshot!(lint_with(prql, options), @"");
+    }
+
+    #[test]
+    fn test_allow_unknown_rule() {
+        let err = crate::lint(
+            r#"
+        from employees
+
+        @{lint={allow=[take_without_sorting]}}
+        let x = 5
+        "#,
+            &LintOptions::default(),
+        )
+        .unwrap_err();
+        assert_snapshot!(err.inner[0].reason, @"unknown lint rule");
+    }
+}
@@ -0,0 +1,108 @@
+//! Lint rules which need resolved relations.
+
+use super::{Lint, LintRule};
+use crate::ir::decl::{DeclKind, Module, TableDecl, TableExpr};
+use crate::ir::pl::{Expr, ExprKind, LineageColumn, TransformCall, TransformKind};
+use crate::semantic::{NS_DEFAULT_DB, NS_STD};
+
+pub(super) fn lint(module: &Module, lints: &mut Vec<Lint>) {
+    for (name, decl) in &module.names {
+        match &decl.kind {
+            DeclKind::Module(module) if name != NS_STD && name != NS_DEFAULT_DB => {
+                lint(module, lints);
+            }
+            DeclKind::TableDecl(TableDecl {
+                expr: TableExpr::RelationVar(expr),
+                ..
+            }) => {
+                lint_select_star_join(expr, lints);
+                lint_relation(expr, lints);
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Checks a relation and all relations it is built from. Groups and windows
+/// have already been flattened by the resolver, so each transform has the
+/// `sort` that applies to it.
+fn lint_relation(expr: &Expr, lints: &mut Vec<Lint>) {
+    let ExprKind::TransformCall(call) = &expr.kind else {
+        return;
+    };
+
+    match call.kind.as_ref() {
+        TransformKind::Take { .. } if call.sort.is_empty() => {
+            lints.push(Lint {
+                rule: LintRule::TakeWithoutSort,
+                span: expr.span,
+                message: "`take` without a `sort` returns arbitrary rows".to_string(),
+                hint: "add a `sort` before the `take`".to_string(),
+            });
+        }
+        TransformKind::Derive { assigns } => lint_shadowed_columns(call, assigns, lints),
+        TransformKind::Join { with, .. } => lint_relation(with, lints),
+        TransformKind::Append(bottom) => lint_relation(bottom, lints),
+        _ => {}
+    }
+
+    lint_relation(&call.input, lints);
+}
+
+fn lint_shadowed_columns(call: &TransformCall, assigns: &Expr, lints: &mut Vec<Lint>) {
+    let Some(lineage) = &call.input.lineage else {
+        return;
+    };
+    let assigns = match &assigns.kind {
+        ExprKind::Tuple(fields) => fields.iter().collect(),
+        _ => vec![assigns],
+    };
+    for assign in assigns {
+        let Some(alias) = &assign.alias else {
+            continue;
+        };
+        let shadows = lineage.columns.iter().any(|col| match col {
+            LineageColumn::Single {
+                name: Some(name), ..
+            } => &name.name == alias,
+            _ => false,
+        });
+        if shadows {
+            lints.push(Lint {
+                rule: LintRule::ShadowedColumn,
+                span: assign.span,
+                message: format!("`{alias}` replaces an existing column with the same name"),
+                hint: "give the new column a different name".to_string(),
+            });
+        }
+    }
+}
+
+/// Checks whether a relation ends up selecting all columns of a join, which
+/// compiles to `SELECT *` and includes any columns the relations share twice.
+fn lint_select_star_join(expr: &Expr, lints: &mut Vec<Lint>) {
+    let Some(lineage) = &expr.lineage else {
+        return;
+    };
+    if !lineage
+        .columns
+        .iter()
+        .any(|col| matches!(col, LineageColumn::All { .. }))
+    {
+        return;
+    }
+
+    let mut expr = expr;
+    while let ExprKind::TransformCall(call) = &expr.kind {
+        if let TransformKind::Join { .. } = call.kind.as_ref() {
+            lints.push(Lint {
+                rule: LintRule::SelectStarJoin,
+                span: expr.span,
+                message: "all columns of this join are selected".to_string(),
+                hint: "`select` the columns which are needed after the `join`".to_string(),
+            });
+            return;
+        }
+        expr = &call.input;
+    }
+}
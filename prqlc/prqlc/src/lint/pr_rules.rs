@@ -0,0 +1,158 @@
+//! Lint rules which only need the syntax of the query.
+
+use std::collections::HashSet;
+use std::sync::OnceLock;
+
+use regex::Regex;
+
+use super::{Lint, LintRule};
+use crate::pr::{self, InterpolateItem};
+use crate::utils::walk_stmts;
+
+pub(super) fn lint(module_def: &pr::ModuleDef, lints: &mut Vec<Lint>) {
+    walk_stmts(&module_def.stmts, &mut |expr| match &expr.kind {
+        pr::ExprKind::SString(items) => lint_sstring(expr, items, lints),
+        pr::ExprKind::Pipeline(pipeline) => lint_sort_before_group(&pipeline.exprs, lints),
+        _ => {}
+    });
+
+    if has_main(&module_def.stmts) {
+        let mut used = HashSet::new();
+        collect_used(&module_def.stmts, &[], &mut used);
+        lint_unused_lets(&module_def.stmts, &[], &used, lints);
+    }
+}
+
+fn lint_sstring(expr: &pr::Expr, items: &[InterpolateItem], lints: &mut Vec<Lint>) {
+    static NULL_COMPARISON: OnceLock<Regex> = OnceLock::new();
+    static STRING_LITERAL: OnceLock<Regex> = OnceLock::new();
+    let null_comparison = NULL_COMPARISON
+        .get_or_init(|| Regex::new(r"(?i)(=|!=|<>)\s*null\b|\bnull\s*(=|!=|<>)").unwrap());
+    let string_literal = STRING_LITERAL.get_or_init(|| Regex::new(r"'[^']*'").unwrap());
+
+    let text = items.iter().filter_map(|item| match item {
+        InterpolateItem::String(s) => Some(s.as_str()),
+        InterpolateItem::Expr { .. } => None,
+    });
+    for text in text {
+        if null_comparison.is_match(text) {
+            lints.push(Lint {
+                rule: LintRule::SstringNullComparison,
+                span: expr.span,
+                message: "comparing to NULL with `=` is never true".to_string(),
+                hint: "use `IS NULL`, or compare with `== null` in PRQL".to_string(),
+            });
+        }
+        if string_literal.is_match(text) {
+            lints.push(Lint {
+                rule: LintRule::SstringUserData,
+                span: expr.span,
+                message: "s-string contains a string literal, which may be user data".to_string(),
+                hint: "use an interpolated PRQL string or a `$param`, which are escaped"
+                    .to_string(),
+            });
+        }
+    }
+}
+
+/// The resolver drops a `sort` which is followed by a `group`, since SQL
+/// doesn't guarantee the order of rows within a group. So this needs to check
+/// the syntax, before the sort is gone.
+fn lint_sort_before_group(steps: &[pr::Expr], lints: &mut Vec<Lint>) {
+    let mut sort = None;
+    for step in steps {
+        match transform_name(step) {
+            Some("sort") => sort = Some(step),
+            Some("derive" | "select" | "filter") => {}
+            Some("group") if sort.is_some() && !groups_by_nothing(step) => {
+                lints.push(Lint {
+                    rule: LintRule::SortBeforeGroup,
+                    span: sort.and_then(|s| s.span),
+                    message: "this order is discarded by the `group` which follows".to_string(),
+                    hint: "move the `sort` into the `group` pipeline, or after the `group`"
+                        .to_string(),
+                });
+                sort = None;
+            }
+            _ => sort = None,
+        }
+    }
+}
+
+fn transform_name(step: &pr::Expr) -> Option<&str> {
+    let call = step.kind.as_func_call()?;
+    let ident = call.name.kind.as_ident()?;
+    Some(ident.name.as_str())
+}
+
+/// `group {}` keeps the order, since it has a single group.
+fn groups_by_nothing(group: &pr::Expr) -> bool {
+    let call = group.kind.as_func_call().unwrap();
+    call.args
+        .first()
+        .and_then(|by| by.kind.as_tuple())
+        .is_some_and(|fields| fields.is_empty())
+}
+
+fn has_main(stmts: &[pr::Stmt]) -> bool {
+    stmts.iter().any(|stmt| match &stmt.kind {
+        pr::StmtKind::VarDef(var_def) => var_def.kind != pr::VarDefKind::Let,
+        pr::StmtKind::ModuleDef(module_def) => has_main(&module_def.stmts),
+        _ => false,
+    })
+}
+
+/// Collects the fully qualified names which an ident within `module` may
+/// refer to. Since this runs before name resolution, an ident counts as a use
+/// of any declaration it could resolve to, from the innermost module outwards.
+/// Each prefix of the ident counts too, so `rel.col` uses `rel`.
+fn collect_used(stmts: &[pr::Stmt], module: &[String], used: &mut HashSet<Vec<String>>) {
+    for stmt in stmts {
+        match &stmt.kind {
+            pr::StmtKind::ModuleDef(module_def) => {
+                let module = [module, std::slice::from_ref(&module_def.name)].concat();
+                collect_used(&module_def.stmts, &module, used);
+            }
+            _ => walk_stmts(std::slice::from_ref(stmt), &mut |expr| {
+                let pr::ExprKind::Ident(ident) = &expr.kind else {
+                    return;
+                };
+                let ident = ident.iter().cloned().collect::<Vec<_>>();
+                for scope in 0..=module.len() {
+                    for len in 1..=ident.len() {
+                        used.insert([&module[..scope], &ident[..len]].concat());
+                    }
+                }
+            }),
+        }
+    }
+}
+
+fn lint_unused_lets(
+    stmts: &[pr::Stmt],
+    module: &[String],
+    used: &HashSet<Vec<String>>,
+    lints: &mut Vec<Lint>,
+) {
+    for stmt in stmts {
+        match &stmt.kind {
+            pr::StmtKind::VarDef(var_def)
+                if var_def.kind == pr::VarDefKind::Let
+                    && !var_def.name.starts_with('_')
+                    && !used.contains(&[module, std::slice::from_ref(&var_def.name)].concat()) =>
+            {
+                lints.push(Lint {
+                    rule: LintRule::UnusedLet,
+                    span: var_def.value.as_ref().map_or(stmt.span, |v| v.span),
+                    message: format!("`{}` is declared but never used", var_def.name),
+                    hint: "remove it, or prefix its name with `_`".to_string(),
+                });
+            }
+            pr::StmtKind::ModuleDef(module_def) => {
+                let module = [module, std::slice::from_ref(&module_def.name)].concat();
+                lint_unused_lets(&module_def.stmts, &module, used, lints);
+            }
+            _ => {}
+        }
+    }
+}
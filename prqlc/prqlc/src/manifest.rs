@@ -0,0 +1,607 @@
+//! Project manifest: configuration of a multi-file project, which is read from
+//! a `prql.toml` file at the root of the project.
+//!
+//! ```toml
+//! [project]
+//! name = "analytics"
+//! source = "src"
+//! target = "sql.postgres"
+//!
+//! [format]
+//! max_width = 80
+//!
+//! [dependencies]
+//! common = { path = "../common", version = "^1.2" }
+//! calendar = { archive = "vendor/calendar-0.3.0.tar.gz" }
+//!
+//! [targets.warehouse]
+//! target = "sql.snowflake"
+//! output = "build/warehouse"
+//! ```
+//!
+//! Dependencies are packages: other projects, whose sources are mounted as a
+//! module named after the dependency. The packages a project was built with
+//! are recorded in a lock file, `prql.lock`.
+//!
+//! Reading files is left to the caller, since this crate doesn't access the
+//! file system.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use itertools::Itertools;
+use semver::{Version, VersionReq};
+use serde::{Deserialize, Serialize};
+
+use crate::debug::DebugLogger;
+use crate::pr::StmtKind;
+use crate::{split_main_path, Error, FormatOptions, Options, SourceTree, Target, WithErrorInfo};
+
+/// Configuration of a PRQL project.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Manifest {
+    pub project: ProjectManifest,
+
+    /// Options for formatting the sources of the project.
+    pub format: FormatOptions,
+
+    /// Other projects whose sources are included as modules, by the name of
+    /// the module.
+    pub dependencies: BTreeMap<String, Dependency>,
+
+    /// Named targets, which override the settings of `project`. Without any,
+    /// the project is built once with the settings of `project`.
+    pub targets: BTreeMap<String, TargetManifest>,
+}
+
+/// The `[project]` section of a [Manifest].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ProjectManifest {
+    pub name: Option<String>,
+
+    /// Version of the project, when it's used as a package.
+    pub version: Option<Version>,
+
+    /// Directory containing the sources, relative to the manifest.
+    ///
+    /// Defaults to the directory of the manifest.
+    pub source: PathBuf,
+
+    /// Path of the relation to compile.
+    ///
+    /// Defaults to `main`.
+    pub main: String,
+
+    /// Target to compile to, such as `sql.postgres`.
+    ///
+    /// Defaults to `sql.any`, which uses the `target` of the query header.
+    pub target: String,
+
+    /// Directory to write compiled SQL into, relative to the manifest.
+    ///
+    /// Defaults to `build`.
+    pub output: PathBuf,
+
+    /// Compile every public relation into its own file, rather than only
+    /// `main`. See [crate::compile_relations].
+    ///
+    /// Defaults to false.
+    pub all_relations: bool,
+
+    /// Wrap each relation in `CREATE VIEW`, when compiling all relations.
+    ///
+    /// Defaults to false.
+    pub create_views: bool,
+
+    /// Emit the compiler signature as a comment after generated SQL.
+    ///
+    /// Defaults to true.
+    pub signature_comment: bool,
+}
+
+impl Default for ProjectManifest {
+    fn default() -> Self {
+        Self {
+            name: None,
+            version: None,
+            source: PathBuf::from("."),
+            main: "main".to_string(),
+            target: "sql.any".to_string(),
+            output: PathBuf::from("build"),
+            all_relations: false,
+            create_views: false,
+            signature_comment: true,
+        }
+    }
+}
+
+/// A dependency of a project, in the `[dependencies]` section of a
+/// [Manifest]. Exactly one of `path` and `archive` must be set.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Dependency {
+    /// Directory of the package, relative to the manifest.
+    pub path: Option<PathBuf>,
+
+    /// A vendored `.tar` or `.tar.gz` archive of the package, relative to
+    /// the manifest.
+    pub archive: Option<PathBuf>,
+
+    /// Versions of the package which can be used. Requires the package to
+    /// declare its version in its own manifest.
+    pub version: Option<VersionReq>,
+}
+
+/// Where the sources of a package are read from.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PackageSource {
+    Path(PathBuf),
+    Archive(PathBuf),
+}
+
+impl Dependency {
+    pub fn source(&self, name: &str) -> Result<PackageSource, Error> {
+        match (&self.path, &self.archive) {
+            (Some(path), None) => Ok(PackageSource::Path(path.clone())),
+            (None, Some(archive)) => Ok(PackageSource::Archive(archive.clone())),
+            _ => Err(Error::new_simple(format!(
+                "dependency `{name}` must have either a `path` or an `archive`"
+            ))
+            .with_code("E0021")),
+        }
+    }
+}
+
+impl fmt::Display for PackageSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Paths are written with `/` so lock files are the same on all
+        // platforms
+        let (kind, path) = match self {
+            PackageSource::Path(path) => ("path", path),
+            PackageSource::Archive(path) => ("archive", path),
+        };
+        let path = path.iter().map(|part| part.to_string_lossy()).join("/");
+        write!(f, "{kind}+{path}")
+    }
+}
+
+/// The sources of a dependency, with its manifest if it has one.
+#[derive(Debug, Clone)]
+pub struct Package {
+    pub sources: SourceTree,
+    pub manifest: Option<Manifest>,
+}
+
+impl Package {
+    /// Checks that the package satisfies the version requirement of
+    /// `dependency`, and that its `prql version:` header is supported by this
+    /// compiler. Returns its entry in the lock file, with the `checksum` of its
+    /// contents, which the caller computes as it reads them.
+    pub fn lock(
+        &self,
+        name: &str,
+        dependency: &Dependency,
+        checksum: String,
+    ) -> Result<LockedPackage, Error> {
+        let version = self
+            .manifest
+            .as_ref()
+            .and_then(|m| m.project.version.clone());
+        if let Some(requirement) = &dependency.version {
+            let Some(version) = &version else {
+                return Err(Error::new_simple(format!(
+                    "package `{name}` doesn't declare a version, but version {requirement} is required"
+                ))
+                .push_hint("set `version` in the `[project]` section of its `prql.toml`")
+                .with_code("E0021"));
+            };
+            if !requirement.matches(version) {
+                return Err(Error::new_simple(format!(
+                    "package `{name}` has version {version}, but version {requirement} is required"
+                ))
+                .with_code("E0021"));
+            }
+        }
+
+        let prql_version = self.prql_version()?;
+        if let Some(requirement) = &prql_version {
+            crate::semantic::check_compiler_version(requirement)
+                .map_err(|e| e.push_hint(format!("required by package `{name}`")))?;
+        }
+
+        Ok(LockedPackage {
+            name: name.to_string(),
+            source: dependency.source(name)?.to_string(),
+            version,
+            prql_version,
+            checksum,
+        })
+    }
+
+    /// The requirement of the `prql version:` header of the root file.
+    fn prql_version(&self) -> Result<Option<VersionReq>, Error> {
+        let Some(root) = crate::parser::find_root(&self.sources) else {
+            return Ok(None);
+        };
+        let stmts =
+            crate::parser::parse_source(&self.sources.sources[root], 0, &DebugLogger::default())
+                .map_err(|errors| errors.into_iter().next().unwrap())?;
+        Ok(stmts.into_iter().find_map(|stmt| match stmt.kind {
+            StmtKind::QueryDef(def) => def.version,
+            _ => None,
+        }))
+    }
+}
+
+/// Packages which a project was built with, read from and written to
+/// `prql.lock`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LockFile {
+    /// Version of the format of the lock file.
+    pub version: u32,
+
+    /// Packages, ordered by name.
+    #[serde(default, rename = "package")]
+    pub packages: Vec<LockedPackage>,
+}
+
+/// A package in a [LockFile].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LockedPackage {
+    pub name: String,
+    pub source: String,
+    pub version: Option<Version>,
+    pub prql_version: Option<VersionReq>,
+    /// SHA-256 hash of the package, as `sha256:<hex>`. For an archive, this
+    /// is the hash of the archive file.
+    pub checksum: String,
+}
+
+impl LockFile {
+    /// Name of the lock file.
+    pub const FILE_NAME: &'static str = "prql.lock";
+
+    pub fn new(mut packages: Vec<LockedPackage>) -> Self {
+        packages.sort_by(|a, b| a.name.cmp(&b.name));
+        LockFile {
+            version: 1,
+            packages,
+        }
+    }
+
+    /// Names of the packages which were added, removed or changed, compared
+    /// to `other`.
+    pub fn changed_packages(&self, other: &LockFile) -> Vec<String> {
+        let names = self.packages.iter().chain(&other.packages).map(|p| &p.name);
+        names
+            .unique()
+            .filter(|name| {
+                let find =
+                    |lock: &LockFile| lock.packages.iter().find(|p| &&p.name == name).cloned();
+                find(self) != find(other)
+            })
+            .sorted()
+            .cloned()
+            .collect()
+    }
+}
+
+/// A named target in the `[targets]` section of a [Manifest]. Settings which
+/// aren't set are taken from `[project]`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TargetManifest {
+    pub main: Option<String>,
+    pub target: Option<String>,
+    pub output: Option<PathBuf>,
+    pub all_relations: Option<bool>,
+    pub create_views: Option<bool>,
+    pub signature_comment: Option<bool>,
+}
+
+/// Settings for a single build of a project, combined from `[project]` and
+/// one of the `[targets]`.
+#[derive(Debug, Clone)]
+pub struct BuildTarget {
+    /// Name of the target, or `None` for a project without `[targets]`.
+    pub name: Option<String>,
+
+    pub main_path: Vec<String>,
+
+    /// Output directory, relative to the manifest.
+    pub output: PathBuf,
+
+    pub all_relations: bool,
+
+    pub create_views: bool,
+
+    pub options: Options,
+}
+
+impl Manifest {
+    /// Name of the manifest file.
+    pub const FILE_NAME: &'static str = "prql.toml";
+
+    /// Settings of each build of the project, ordered by target name.
+    pub fn build_targets(&self) -> Result<Vec<BuildTarget>, Error> {
+        if self.targets.is_empty() {
+            return Ok(vec![self.build_target(None, &TargetManifest::default())?]);
+        }
+
+        self.targets
+            .iter()
+            .map(|(name, target)| self.build_target(Some(name.clone()), target))
+            .collect()
+    }
+
+    /// Settings of a single build of the project. Without a name, this is the
+    /// build with the settings of `[project]`.
+    pub fn find_build_target(&self, name: Option<&str>) -> Result<BuildTarget, Error> {
+        let Some(name) = name else {
+            return self.build_target(None, &TargetManifest::default());
+        };
+        let target = self.targets.get(name).ok_or_else(|| {
+            Error::new_simple(format!("target `{name}` is not in the manifest"))
+                .push_hint(format!(
+                    "available targets: {}",
+                    self.targets.keys().cloned().collect::<Vec<_>>().join(", ")
+                ))
+                .with_code("E0021")
+        })?;
+        self.build_target(Some(name.to_string()), target)
+    }
+
+    fn build_target(
+        &self,
+        name: Option<String>,
+        target: &TargetManifest,
+    ) -> Result<BuildTarget, Error> {
+        let project = &self.project;
+
+        let main = target.main.as_ref().unwrap_or(&project.main);
+        let main_path = split_main_path(main);
+        if main_path.is_empty() {
+            return Err(Error::new_simple(
+                "`main` must be the path of a relation, such as `main` or `reports.daily`",
+            ));
+        }
+
+        let target_name = target.target.as_ref().unwrap_or(&project.target);
+        let options = Options::default()
+            .with_target(Target::from_str(target_name)?)
+            .with_signature_comment(
+                target
+                    .signature_comment
+                    .unwrap_or(project.signature_comment),
+            );
+
+        Ok(BuildTarget {
+            name,
+            main_path,
+            output: target.output.clone().unwrap_or(project.output.clone()),
+            all_relations: target.all_relations.unwrap_or(project.all_relations),
+            create_views: target.create_views.unwrap_or(project.create_views),
+            options,
+        })
+    }
+}
+
+impl SourceTree {
+    /// Adds the sources of a dependency as a module named `name`. The root
+    /// file of the dependency becomes the module itself, and its other files
+    /// become submodules.
+    ///
+    /// Fails if the sources already contain a module named `name`.
+    pub fn insert_dependency(&mut self, name: &str, dependency: SourceTree) -> Result<(), Error> {
+        let module_file = Path::new(name).with_extension("prql");
+        let existing = (self.sources.keys())
+            .filter(|path| **path == module_file || path.starts_with(name))
+            .sorted()
+            .next();
+        if let Some(existing) = existing {
+            return Err(Error::new_simple(format!(
+                "dependency `{name}` has the same name as the module of `{}`",
+                existing.display()
+            ))
+            .push_hint("rename the dependency or the module"));
+        }
+
+        let root = crate::parser::find_root(&dependency).cloned();
+        for (path, content) in dependency.sources.into_iter().sorted() {
+            let path = if Some(&path) == root.as_ref() {
+                Path::new(name).with_extension("prql")
+            } else {
+                Path::new(name).join(path)
+            };
+            self.insert(path, content);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use insta::assert_snapshot;
+    use itertools::Itertools;
+
+    use super::*;
+
+    fn summarize(targets: &[BuildTarget]) -> String {
+        targets
+            .iter()
+            .map(|t| {
+                format!(
+                    "{}: main={} output={} target={:?}\n",
+                    t.name.as_deref().unwrap_or("-"),
+                    t.main_path.join("."),
+                    t.output.display(),
+                    t.options.target,
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_build_targets() {
+        let mut manifest = Manifest::default();
+        manifest.project.target = "sql.postgres".to_string();
+        manifest.project.main = "reports.daily".to_string();
+
+        assert_snapshot!(summarize(&manifest.build_targets().unwrap()), @"-: main=reports.daily output=build target=Sql(Some(Postgres))");
+
+        manifest.targets.insert(
+            "warehouse".to_string(),
+            TargetManifest {
+                target: Some("sql.snowflake".to_string()),
+                output: Some(PathBuf::from("build/warehouse")),
+                ..Default::default()
+            },
+        );
+        manifest
+            .targets
+            .insert("local".to_string(), TargetManifest::default());
+
+        assert_snapshot!(summarize(&manifest.build_targets().unwrap()), @r"
+        local: main=reports.daily output=build target=Sql(Some(Postgres))
+        warehouse: main=reports.daily output=build/warehouse target=Sql(Some(Snowflake))
+        ");
+
+        let err = manifest.find_build_target(Some("prod")).unwrap_err();
+        assert_snapshot!(err.reason, @"target `prod` is not in the manifest");
+        assert_snapshot!(err.hints.join("\n"), @"available targets: local, warehouse");
+
+        manifest.targets.get_mut("local").unwrap().main = Some(String::new());
+        let err = manifest.find_build_target(Some("local")).unwrap_err();
+        assert_snapshot!(err.reason, @"`main` must be the path of a relation, such as `main` or `reports.daily`");
+    }
+
+    #[test]
+    fn test_insert_dependency() {
+        let mut sources = SourceTree::new(
+            [("Project.prql".into(), "from common.orders".to_string())],
+            None,
+        );
+        let common = SourceTree::new(
+            [
+                ("Common.prql".into(), "let orders = (from o)".to_string()),
+                ("dates.prql".into(), "let today = 1".to_string()),
+            ],
+            None,
+        );
+        sources.insert_dependency("common", common.clone()).unwrap();
+
+        let paths = sources
+            .sources
+            .keys()
+            .sorted()
+            .map(|p| p.display())
+            .join(", ");
+        assert_snapshot!(paths, @"Project.prql, common/dates.prql, common.prql");
+
+        let database_module_path = [crate::semantic::NS_DEFAULT_DB.to_string()];
+        let pl = crate::prql_to_pl_tree(&sources).unwrap();
+        let sql = crate::pl_to_rq_tree(pl, &[], &database_module_path)
+            .and_then(|rq| crate::rq_to_sql(rq, &crate::Options::default().no_signature()))
+            .unwrap();
+        assert_snapshot!(sql, @r"
+        WITH orders AS (
+          SELECT
+            *
+          FROM
+            o
+        )
+        SELECT
+          *
+        FROM
+          orders
+        ");
+
+        // a dependency can't replace a module of the project
+        let err = sources.insert_dependency("common", common).unwrap_err();
+        assert_snapshot!(err.reason, @"dependency `common` has the same name as the module of `common/dates.prql`");
+
+        let mut sources = SourceTree::new(
+            [("common/dates.prql".into(), "let today = 2".to_string())],
+            None,
+        );
+        let err = sources
+            .insert_dependency("common", SourceTree::from("let orders = 1"))
+            .unwrap_err();
+        assert_snapshot!(err.reason, @"dependency `common` has the same name as the module of `common/dates.prql`");
+    }
+
+    fn package(version: Option<&str>, header: &str) -> Package {
+        let manifest = version.map(|v| {
+            let mut manifest = Manifest::default();
+            manifest.project.version = Some(v.parse().unwrap());
+            manifest
+        });
+        Package {
+            sources: SourceTree::from(format!("{header}\nlet spine = (from days)\n")),
+            manifest,
+        }
+    }
+
+    #[test]
+    fn test_lock_package() {
+        let dependency = Dependency {
+            path: Some(PathBuf::from("../dates")),
+            version: Some("^1.2".parse().unwrap()),
+            ..Default::default()
+        };
+
+        let locked = package(Some("1.4.0"), r#"prql version:"0.13""#)
+            .lock("dates", &dependency, String::new())
+            .unwrap();
+        let version = locked.version.unwrap();
+        let prql_version = locked.prql_version.unwrap();
+        assert_snapshot!(format!("{} {version} {prql_version}", locked.source), @"path+../dates 1.4.0 ^0.13");
+
+        let err = package(Some("2.0.0"), "")
+            .lock("dates", &dependency, String::new())
+            .unwrap_err();
+        assert_snapshot!(err.reason, @"package `dates` has version 2.0.0, but version ^1.2 is required");
+
+        let err = package(None, "")
+            .lock("dates", &dependency, String::new())
+            .unwrap_err();
+        assert_snapshot!(err.reason, @"package `dates` doesn't declare a version, but version ^1.2 is required");
+
+        let err = package(Some("1.4.0"), r#"prql version:"99""#)
+            .lock("dates", &dependency, String::new())
+            .unwrap_err();
+        assert_snapshot!(err.hints.join("\n"), @"required by package `dates`");
+
+        let err = Dependency::default().source("dates").unwrap_err();
+        assert_snapshot!(err.reason, @"dependency `dates` must have either a `path` or an `archive`");
+    }
+
+    #[test]
+    fn test_changed_packages() {
+        let dependency = Dependency {
+            archive: Some(PathBuf::from("vendor/dates.tar")),
+            ..Default::default()
+        };
+        let locked = |header| {
+            package(None, header)
+                .lock("dates", &dependency, String::new())
+                .unwrap()
+        };
+
+        let lock_file = LockFile::new(vec![locked("")]);
+        assert!(lock_file.changed_packages(&lock_file).is_empty());
+
+        let changed = LockFile::new(vec![locked(r#"prql version:"0.13""#)]);
+        assert_eq!(changed.changed_packages(&lock_file), vec!["dates"]);
+        assert_eq!(
+            LockFile::new(vec![]).changed_packages(&lock_file),
+            vec!["dates"]
+        );
+    }
+}
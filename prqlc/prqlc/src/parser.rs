@@ -3,16 +3,31 @@ use std::{collections::HashMap, path::Path};
 
 use itertools::Itertools;
 
-use crate::debug;
+use crate::debug::{self, DebugLogger};
 use crate::lr;
 use crate::pr;
 use crate::{Error, Errors, Result, SourceTree, WithErrorInfo};
 
-pub fn parse(file_tree: &SourceTree) -> Result<pr::ModuleDef, Errors> {
+pub fn parse(file_tree: &SourceTree, log: &DebugLogger) -> Result<pr::ModuleDef, Errors> {
+    parse_with(file_tree, log, |_, content, id| {
+        parse_source(content, id, log)
+    })
+}
+
+/// Like [parse], but with a custom function to parse each file of the tree,
+/// which receives the path, contents and source id of the file.
+pub(crate) fn parse_with<F>(
+    file_tree: &SourceTree,
+    log: &DebugLogger,
+    mut parse_file: F,
+) -> Result<pr::ModuleDef, Errors>
+where
+    F: FnMut(&Path, &str, u16) -> Result<Vec<pr::Stmt>, Vec<Error>>,
+{
     // register a new stage of the compiler
     // (here should register lexer stage first, but that all happens in a single call to prqlc_parser)
-    debug::log_entry(|| debug::DebugEntryKind::ReprPrql(file_tree.clone()));
-    debug::log_stage(debug::Stage::Parsing);
+    log.entry(|| debug::DebugEntryKind::ReprPrql(file_tree.clone()));
+    log.stage(debug::Stage::Parsing);
 
     let source_files = linearize_tree(file_tree)?;
 
@@ -37,7 +52,7 @@ pub fn parse(file_tree: &SourceTree) -> Result<pr::ModuleDef, Errors> {
             .map(|x| **x)
             .expect("source tree has malformed ids");
 
-        match parse_source(source_file.content, id) {
+        match parse_file(source_file.file_path, source_file.content, id) {
             Ok(stmts) => {
                 insert_stmts_at_path(&mut root, source_file.module_path, stmts);
             }
@@ -45,7 +60,7 @@ pub fn parse(file_tree: &SourceTree) -> Result<pr::ModuleDef, Errors> {
         }
     }
     if errors.is_empty() {
-        debug::log_entry(|| debug::DebugEntryKind::ReprPr(root.clone()));
+        log.entry(|| debug::DebugEntryKind::ReprPr(root.clone()));
         Ok(root)
     } else {
         Err(Errors(errors))
@@ -57,11 +72,15 @@ pub fn parse(file_tree: &SourceTree) -> Result<pr::ModuleDef, Errors> {
 // our logging is in `prqlc` and we want to log the LR. (We could split the logging
 // out into a separate crate, but it has dependencies on `prqlc` internals and
 // would be an effort)
-pub(crate) fn parse_source(source: &str, source_id: u16) -> Result<Vec<pr::Stmt>, Vec<Error>> {
+pub(crate) fn parse_source(
+    source: &str,
+    source_id: u16,
+    log: &DebugLogger,
+) -> Result<Vec<pr::Stmt>, Vec<Error>> {
     let (tokens, mut errors) = prqlc_parser::lexer::lex_source_recovery(source, source_id);
 
     let ast = if let Some(tokens) = tokens {
-        debug::log_entry(|| debug::DebugEntryKind::ReprLr(lr::Tokens(tokens.clone())));
+        log.entry(|| debug::DebugEntryKind::ReprLr(lr::Tokens(tokens.clone())));
 
         let (ast, parse_errors) = prqlc_parser::parser::parse_lr_to_pr(source_id, tokens);
         errors.extend(parse_errors);
@@ -84,25 +103,16 @@ struct SourceFile<'a> {
 }
 
 fn linearize_tree(tree: &SourceTree) -> Result<Vec<SourceFile<'_>>> {
-    // find root
-    let root_path;
-
-    if tree.sources.len() == 1 {
-        // if there is only one file, use that as the root
-        root_path = tree.sources.keys().next().unwrap();
-    } else if let Some(root) = tree.sources.get_key_value(&PathBuf::from("")) {
-        // if there is an empty path, that's the root
-        root_path = root.0;
-    } else if let Some(root) = tree.sources.keys().find(path_starts_with_uppercase) {
-        root_path = root;
+    let root_path = if let Some(root) = find_root(tree) {
+        root
     } else {
         if tree.sources.is_empty() {
             // TODO: should we allow non `.prql` files? We could require `.prql`
             // for modules but then allow any file if a single file is passed
             // (python allows this, for example)
-            return Err(Error::new_simple(
-                "No `.prql` files found in the source tree",
-            ));
+            return Err(
+                Error::new_simple("No `.prql` files found in the source tree").with_code("E0002"),
+            );
         }
 
         let file_names = tree
@@ -117,7 +127,7 @@ fn linearize_tree(tree: &SourceTree) -> Result<Vec<SourceFile<'_>>> {
         ))
         .push_hint("add a file that starts with uppercase letter to the root directory")
         .with_code("E0002"));
-    }
+    };
 
     let mut sources: Vec<_> = Vec::with_capacity(tree.sources.len());
 
@@ -150,6 +160,18 @@ fn linearize_tree(tree: &SourceTree) -> Result<Vec<SourceFile<'_>>> {
     Ok(sources)
 }
 
+/// Finds the file of the root module: the only file, the file with an empty
+/// path, or a file in the root directory that starts with an uppercase letter.
+pub(crate) fn find_root(tree: &SourceTree) -> Option<&PathBuf> {
+    if tree.sources.len() == 1 {
+        tree.sources.keys().next()
+    } else if let Some(root) = tree.sources.get_key_value(&PathBuf::from("")) {
+        Some(root.0)
+    } else {
+        tree.sources.keys().find(path_starts_with_uppercase)
+    }
+}
+
 fn insert_stmts_at_path(module: &mut pr::ModuleDef, mut path: Vec<String>, stmts: Vec<pr::Stmt>) {
     if path.is_empty() {
         module.stmts.extend(stmts);
@@ -195,10 +217,9 @@ pub fn os_path_to_prql_path(path: &Path) -> Result<Vec<String>> {
     // split by /
     path.components()
         .map(|x| {
-            x.as_os_str()
-                .to_str()
-                .map(str::to_string)
-                .ok_or_else(|| Error::new_simple(format!("Invalid file path: {path:?}")))
+            x.as_os_str().to_str().map(str::to_string).ok_or_else(|| {
+                Error::new_simple(format!("Invalid file path: {path:?}")).with_code("E0002")
+            })
         })
         .try_collect()
 }
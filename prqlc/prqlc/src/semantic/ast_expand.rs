@@ -7,7 +7,7 @@ use crate::ir::decl;
 use crate::ir::pl::{self, new_binop};
 use crate::pr;
 use crate::semantic::{NS_THAT, NS_THIS};
-use crate::{Error, Result};
+use crate::{Error, Result, WithErrorInfo};
 
 /// An AST pass that maps AST to PL.
 pub fn expand_expr(expr: pr::Expr) -> Result<pl::Expr> {
@@ -70,6 +70,13 @@ pub fn expand_expr(expr: pr::Expr) -> Result<pl::Expr> {
         ),
         pr::ExprKind::Param(v) => pl::ExprKind::Param(v),
         pr::ExprKind::Internal(v) => pl::ExprKind::Internal(v),
+        pr::ExprKind::Error => {
+            return Err(
+                Error::new_simple("cannot compile an expression that failed to parse")
+                    .with_span(expr.span)
+                    .with_code("E0004"),
+            )
+        }
     };
 
     Ok(pl::Expr {
@@ -141,14 +148,16 @@ fn expand_unary(pr::UnaryExpr { op, expr }: pr::UnaryExpr) -> Result<pl::ExprKin
         Add => return Ok(expr.kind),
         EqSelf => {
             let pl::ExprKind::Ident(ident) = expr.kind else {
-                return Err(Error::new_simple(
-                    "self-equality operator requires a column name",
-                ));
+                return Err(
+                    Error::new_simple("self-equality operator requires a column name")
+                        .with_code("E0008"),
+                );
             };
             if !ident.path.is_empty() {
                 return Err(Error::new_simple(
                     "self-equality operator does not support namespace prefix",
-                ));
+                )
+                .with_code("E0008"));
             }
 
             let left = pl::Expr {
@@ -228,7 +237,7 @@ fn expand_func_params(value: Vec<pr::FuncParam>) -> Result<Vec<pl::FuncParam>> {
 fn expand_stmt(value: pr::Stmt) -> Result<pl::Stmt> {
     Ok(pl::Stmt {
         id: None,
-        kind: expand_stmt_kind(value.kind)?,
+        kind: expand_stmt_kind(value.kind).with_span_fallback(value.span)?,
         span: value.span,
         annotations: value
             .annotations
@@ -266,6 +275,12 @@ fn expand_stmt_kind(value: pr::StmtKind) -> Result<pl::StmtKind> {
             alias: v.alias,
             name: v.name,
         }),
+        pr::StmtKind::Error => {
+            return Err(
+                Error::new_simple("cannot compile a statement that failed to parse")
+                    .with_code("E0004"),
+            )
+        }
     })
 }
 
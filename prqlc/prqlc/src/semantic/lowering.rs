@@ -6,7 +6,7 @@ use enum_as_inner::EnumAsInner;
 use itertools::Itertools;
 use prqlc_parser::generic::{InterpolateItem, Range, SwitchCase};
 use prqlc_parser::lexer::lr::Literal;
-use semver::{Prerelease, Version};
+use semver::{Prerelease, Version, VersionReq};
 
 use crate::compiler_version;
 use crate::ir::decl::{self, DeclKind, Module, RootModule, TableExpr};
@@ -17,7 +17,7 @@ use crate::ir::rq::{
     self, CId, RelationColumn, RelationLiteral, RelationalQuery, TId, TableDecl, Transform,
 };
 use crate::pr::TyTupleField;
-use crate::semantic::write_pl;
+use crate::semantic::{write_pl, NS_MAIN, NS_STD};
 use crate::utils::{toposort, IdGenerator};
 use crate::{Error, Reason, Result, Span, WithErrorInfo};
 
@@ -72,16 +72,82 @@ pub fn lower_to_ir(
     let tables = TableExtractor::extract(&root_mod.module);
 
     // prune and toposort
-    let tables = toposort_tables(tables, &main_ident);
+    let tables = toposort_tables(tables, &main_ident, &HashSet::new())?;
 
-    // lower tables
     let mut l = Lowerer::new(root_mod, database_module_path);
+    let query = lower_tables(&mut l, def, tables, &main_ident, &HashMap::new())?;
+    Ok((query, l.root_mod))
+}
+
+/// Convert each public relation declared outside of the database module into
+/// its own [RelationalQuery], ordered so that each comes after the relations
+/// it references. Relations refer to each other by name, as if they were
+/// tables in the database (or views), rather than being inlined.
+///
+/// A relation is public unless it is `main` or has a name starting with `_`.
+/// Private relations are inlined into the queries that reference them.
+pub fn lower_relations(
+    root_mod: RootModule,
+    database_module_path: &[String],
+) -> Result<Vec<(Ident, RelationalQuery)>> {
+    let tables = TableExtractor::extract(&root_mod.module);
+
+    let public: HashSet<Ident> = tables
+        .iter()
+        .filter(|(ident, (table, _))| {
+            matches!(table.expr, TableExpr::RelationVar(_))
+                && ident.name != NS_MAIN
+                && !ident.starts_with_part(NS_STD)
+                && !ident.starts_with_path(database_module_path)
+                && !ident.iter().any(|part| part.starts_with('_'))
+        })
+        .map(|(ident, _)| ident.clone())
+        .collect();
+
+    let dependencies = table_dependencies(&tables, &HashSet::new());
+    let order = toposort(&dependencies, None).ok_or_else(cycle_error)?;
+
+    // columns of the relations lowered so far, which later relations reference
+    let mut views = HashMap::new();
+    let mut queries = Vec::new();
+    let mut l = Lowerer::new(root_mod, database_module_path);
+    for ident in order.into_iter().filter(|i| public.contains(*i)) {
+        let def = l
+            .root_mod
+            .find_query_def(ident)
+            .cloned()
+            .unwrap_or_default();
+        validate_query_def(&def)?;
+
+        let tables = toposort_tables(tables.clone(), ident, &public)?;
+        l.reset();
+        let query = lower_tables(&mut l, def, tables, ident, &views)?;
+        views.insert(ident.clone(), query.relation.columns.clone());
+        queries.push((ident.clone(), query));
+    }
+    Ok(queries)
+}
+
+/// Lowers the toposorted `tables` into a query of the main table. `views`, with
+/// their columns, are referenced by their name, rather than being lowered.
+fn lower_tables(
+    l: &mut Lowerer,
+    def: QueryDef,
+    tables: TableDecls,
+    main_ident: &Ident,
+    views: &HashMap<Ident, Vec<RelationColumn>>,
+) -> Result<RelationalQuery> {
     let mut main_relation = None;
     for (fq_ident, (table, declared_at)) in tables {
-        let is_main = fq_ident == main_ident;
+        let is_main = &fq_ident == main_ident;
+
+        if let Some(columns) = views.get(&fq_ident).filter(|_| !is_main) {
+            l.lower_view_ref(fq_ident, columns.clone());
+            continue;
+        }
 
         l.lower_table_decl(table, fq_ident)
-            .map_err(with_span_if_not_exists(|| get_span_of_id(&l, declared_at)))?;
+            .map_err(with_span_if_not_exists(|| get_span_of_id(l, declared_at)))?;
 
         if is_main {
             let main_table = l.table_buffer.pop().unwrap();
@@ -89,12 +155,11 @@ pub fn lower_to_ir(
         }
     }
 
-    let query = RelationalQuery {
+    Ok(RelationalQuery {
         def,
-        tables: l.table_buffer,
+        tables: std::mem::take(&mut l.table_buffer),
         relation: main_relation.unwrap(),
-    };
-    Ok((query, l.root_mod))
+    })
 }
 
 fn extern_ref_to_relation(
@@ -117,7 +182,8 @@ fn extern_ref_to_relation(
     let Some(extern_name) = extern_name else {
         let database_module = Ident::from_path(database_module_path.to_vec());
         return Err(Error::new_simple("this table is not in the current database")
-            .push_hint(format!("If this is a table in the current database, move its declaration into module {database_module}")));
+            .push_hint(format!("If this is a table in the current database, move its declaration into module {database_module}"))
+            .with_code("E0014"));
     };
 
     // put wildcards last
@@ -142,20 +208,28 @@ fn tuple_fields_to_relation_columns(columns: Vec<TyTupleField>) -> Vec<RelationC
 
 fn validate_query_def(query_def: &QueryDef) -> Result<()> {
     if let Some(requirement) = &query_def.version {
-        let current_version = compiler_version();
+        check_compiler_version(requirement)?;
+    }
+    Ok(())
+}
 
-        // We need to remove the pre-release part of the version, because
-        // otherwise those will fail the match.
-        let clean_version = Version {
-            pre: Prerelease::EMPTY,
-            ..current_version.clone()
-        };
+/// Checks that the version of this compiler satisfies a `prql version:`
+/// requirement.
+pub fn check_compiler_version(requirement: &VersionReq) -> Result<()> {
+    let current_version = compiler_version();
 
-        if !requirement.matches(&clean_version) {
-            return Err(Error::new_simple(format!(
-                "This query requires version {requirement} of PRQL that is not supported by prqlc version {clean_version} (shortened from {current_version}). Please upgrade the compiler."
-            )));
-        }
+    // We need to remove the pre-release part of the version, because
+    // otherwise those will fail the match.
+    let clean_version = Version {
+        pre: Prerelease::EMPTY,
+        ..current_version.clone()
+    };
+
+    if !requirement.matches(&clean_version) {
+        return Err(Error::new_simple(format!(
+            "This query requires version {requirement} of PRQL that is not supported by prqlc version {clean_version} (shortened from {current_version}). Please upgrade the compiler."
+        ))
+        .with_code("E0012"));
     }
     Ok(())
 }
@@ -212,6 +286,15 @@ impl Lowerer {
         }
     }
 
+    /// Clears the state of the previous query, so the next one can be lowered
+    /// from the same module.
+    fn reset(&mut self) {
+        *self = Lowerer {
+            root_mod: std::mem::take(&mut self.root_mod),
+            ..Lowerer::new(RootModule::default(), &self.database_module_path)
+        };
+    }
+
     fn lower_table_decl(&mut self, table: decl::TableDecl, fq_ident: Ident) -> Result<()> {
         let decl::TableDecl { ty, expr } = table;
 
@@ -242,6 +325,27 @@ impl Lowerer {
         Ok(())
     }
 
+    /// Lower a reference to a relation which is compiled separately, and so can
+    /// be referred to by its name.
+    fn lower_view_ref(&mut self, fq_ident: Ident, columns: Vec<RelationColumn>) {
+        let relation = rq::Relation {
+            kind: rq::RelationKind::ExternRef(LocalTable(fq_ident.clone())),
+            columns,
+        };
+
+        let id = *self
+            .table_mapping
+            .entry(fq_ident)
+            .or_insert_with(|| self.tid.gen());
+
+        let table = TableDecl {
+            id,
+            name: None,
+            relation,
+        };
+        self.table_buffer.push(table);
+    }
+
     /// Lower an expression into a instance of a table in the query
     fn lower_table_ref(&mut self, expr: pl::Expr) -> Result<rq::TableRef> {
         let mut expr = expr;
@@ -432,7 +536,8 @@ impl Lowerer {
                     who: None,
                     expected: "a pipeline that resolves to a table".to_string(),
                     found: format!("`{}`", found_str),
-                });
+                })
+                .with_code("E0007");
 
                 // Provide better hints for common mistakes
                 if found_str.starts_with("internal std.sub") {
@@ -684,7 +789,8 @@ impl Lowerer {
                         return Err(Error::new_simple(
                             "column references a table not accessible in this context",
                         )
-                        .push_hint("join is not supported inside group"));
+                        .push_hint("join is not supported inside group")
+                        .with_code("E0016"));
                     };
 
                     match &self.node_mapping[&input.id] {
@@ -794,7 +900,8 @@ impl Lowerer {
                         who: None,
                         expected: "an identifier".to_string(),
                         found: write_pl(e),
-                    }));
+                    })
+                    .with_code("E0008"));
                 }
             }
         }
@@ -884,7 +991,8 @@ impl Lowerer {
                     } else {
                         return Err(
                             Error::new_simple("This wildcard usage is not yet supported.")
-                                .with_span(span),
+                                .with_span(span)
+                                .with_code("E0017"),
                         );
                     }
                 } else if let Some(id) = expr.target_id {
@@ -906,7 +1014,8 @@ impl Lowerer {
                 } else {
                     return Err(
                         Error::new_simple("This wildcard usage is not yet supported.")
-                            .with_span(span),
+                            .with_span(span)
+                            .with_code("E0017"),
                     );
                 }
             }
@@ -947,7 +1056,8 @@ impl Lowerer {
                             "table variable cannot be used as a scalar value",
                         )
                         .push_hint("use a join instead, or inline the subquery")
-                        .with_span(arg.span));
+                        .with_span(arg.span)
+                        .with_code("E0016"));
                     }
                 }
 
@@ -961,7 +1071,8 @@ impl Lowerer {
                 return Err(
                     Error::new_simple("table instance cannot be referenced directly")
                         .push_hint("column name might be missing?")
-                        .with_span(span),
+                        .with_span(span)
+                        .with_code("E0016"),
                 );
             }
 
@@ -978,7 +1089,8 @@ impl Lowerer {
                     found: format!("`{}`", write_pl(expr.clone())),
                 })
                 .push_hint("this is probably a 'bad type' error (we are working on that)")
-                .with_span(expr.span));
+                .with_span(expr.span)
+                .with_code("E0007"));
             }
 
             pl::ExprKind::Internal(_) => {
@@ -1020,7 +1132,8 @@ impl Lowerer {
                         "This table contains unnamed columns that need to be referenced by name",
                     )
                     .with_span(self.root_mod.span_map.get(&id).cloned())
-                    .push_hint("the name may have been overridden later in the pipeline.")),
+                    .push_hint("the name may have been overridden later in the pipeline.")
+                    .with_code("E0016")),
                 };
                 log::trace!("lookup cid of name={name:?} in input {input_columns:?}");
 
@@ -1168,7 +1281,8 @@ fn validate_take_range(range: &Range<rq::Expr>, span: Option<Span>) -> Result<()
             expected: "a positive int range".to_string(),
             found: range_display,
         })
-        .with_span(span))
+        .with_span(span)
+        .with_code("E0008"))
     } else {
         Ok(())
     }
@@ -1178,12 +1292,12 @@ fn validate_take_range(range: &Range<rq::Expr>, span: Option<Span>) -> Result<()
 struct TableExtractor {
     path: Vec<String>,
 
-    tables: Vec<(Ident, (decl::TableDecl, Option<usize>))>,
+    tables: TableDecls,
 }
 
 impl TableExtractor {
     /// Finds table declarations in a module, recursively.
-    fn extract(root_module: &Module) -> Vec<(Ident, (decl::TableDecl, Option<usize>))> {
+    fn extract(root_module: &Module) -> TableDecls {
         let mut te = TableExtractor::default();
         te.extract_from_module(root_module);
         te.tables
@@ -1210,21 +1324,47 @@ impl TableExtractor {
     }
 }
 
+/// Table declarations with their fully qualified names and the ids of the
+/// statements declaring them.
+type TableDecls = Vec<(Ident, (decl::TableDecl, Option<usize>))>;
+
 /// Does a topological sort of the pipeline definitions and prunes all definitions that
 /// are not needed for the main pipeline. To do this, it needs to collect references
-/// between pipelines.
+/// between pipelines. The dependencies of `views` are not needed, since views are
+/// referenced by name.
 fn toposort_tables(
-    tables: Vec<(Ident, (decl::TableDecl, Option<usize>))>,
+    tables: TableDecls,
     main_table: &Ident,
-) -> Vec<(Ident, (decl::TableDecl, Option<usize>))> {
-    let tables: HashMap<_, _, RandomState> = HashMap::from_iter(tables);
+    views: &HashSet<Ident>,
+) -> Result<TableDecls> {
+    let views: HashSet<_> = views.iter().filter(|v| *v != main_table).cloned().collect();
+    let dependencies = table_dependencies(&tables, &views);
+
+    let sort = toposort(&dependencies, Some(main_table)).ok_or_else(cycle_error)?;
+
+    let mut tables: HashMap<_, _, RandomState> = HashMap::from_iter(tables);
+    Ok(sort
+        .into_iter()
+        .map(|ident| tables.remove_entry(ident).unwrap())
+        .collect_vec())
+}
 
+fn cycle_error() -> Error {
+    Error::new_simple("relations reference each other in a cycle").with_code("E0015")
+}
+
+/// Collects references between pipelines, ignoring those of `views`.
+fn table_dependencies(
+    tables: &[(Ident, (decl::TableDecl, Option<usize>))],
+    views: &HashSet<Ident>,
+) -> Vec<(Ident, Vec<Ident>)> {
     let mut dependencies: Vec<(Ident, Vec<Ident>)> = Vec::new();
-    for (ident, table) in &tables {
-        let deps = if let TableExpr::RelationVar(e) = &table.0.expr {
-            TableDepsCollector::collect(*e.clone())
-        } else {
-            vec![]
+    for (ident, table) in tables {
+        let deps = match &table.0.expr {
+            TableExpr::RelationVar(e) if !views.contains(ident) => {
+                TableDepsCollector::collect(*e.clone())
+            }
+            _ => vec![],
         };
 
         dependencies.push((ident.clone(), deps));
@@ -1232,13 +1372,7 @@ fn toposort_tables(
 
     // sort just to make sure lowering is stable
     dependencies.sort_by(|a, b| a.0.cmp(&b.0));
-
-    let sort = toposort(&dependencies, Some(main_table)).unwrap();
-
-    let mut tables = tables;
-    sort.into_iter()
-        .map(|ident| tables.remove_entry(ident).unwrap())
-        .collect_vec()
+    dependencies
 }
 
 #[derive(Default)]
@@ -6,17 +6,19 @@ mod module;
 pub mod reporting;
 mod resolver;
 
-pub use lowering::lower_to_ir;
+pub use lowering::{check_compiler_version, lower_relations, lower_to_ir};
 
 use self::resolver::Resolver;
 pub use self::resolver::ResolverOptions;
+use crate::debug::{self, DebugLogger};
 use crate::ir::decl::{Module, RootModule};
 use crate::ir::pl::{self, ImportDef, ModuleDef, Stmt, StmtKind, TypeDef, VarDef};
 use crate::ir::rq::RelationalQuery;
+use crate::parser;
 use crate::parser::is_mod_def_for;
 use crate::pr;
+use crate::utils::IdGenerator;
 use crate::WithErrorInfo;
-use crate::{debug, parser};
 use crate::{Error, Reason, Result};
 
 /// Runs semantic analysis on the query and lowers PL to RQ.
@@ -24,50 +26,117 @@ pub fn resolve_and_lower(
     file_tree: pr::ModuleDef,
     main_path: &[String],
     database_module_path: Option<&[String]>,
+    log: &DebugLogger,
 ) -> Result<RelationalQuery> {
-    let root_mod = resolve(file_tree)?;
+    let root_mod = resolve(file_tree, log)?;
+    lower(root_mod, main_path, database_module_path, log)
+}
 
-    debug::log_stage(debug::Stage::Semantic(debug::StageSemantic::Lowering));
+/// Lowers a resolved root module into the query of its main relation. See
+/// [lower_to_ir].
+pub fn lower(
+    root_mod: RootModule,
+    main_path: &[String],
+    database_module_path: Option<&[String]>,
+    log: &DebugLogger,
+) -> Result<RelationalQuery> {
+    log.stage(debug::Stage::Semantic(debug::StageSemantic::Lowering));
     let default_db = [NS_DEFAULT_DB.to_string()];
     let database_module_path = database_module_path.unwrap_or(&default_db);
     let (query, _) = lowering::lower_to_ir(root_mod, main_path, database_module_path)?;
 
-    debug::log_entry(|| debug::DebugEntryKind::ReprRq(query.clone()));
+    log.entry(|| debug::DebugEntryKind::ReprRq(query.clone()));
     Ok(query)
 }
 
+/// Runs semantic analysis on the project and lowers each of its public
+/// relations into its own query. See [lower_relations].
+pub fn resolve_and_lower_relations(
+    file_tree: pr::ModuleDef,
+    database_module_path: Option<&[String]>,
+    log: &DebugLogger,
+) -> Result<Vec<(pl::Ident, RelationalQuery)>> {
+    let root_mod = resolve(file_tree, log)?;
+    lower_all(root_mod, database_module_path, log)
+}
+
+/// Lowers each public relation of a resolved root module into its own query.
+/// See [lower_relations].
+pub fn lower_all(
+    root_mod: RootModule,
+    database_module_path: Option<&[String]>,
+    log: &DebugLogger,
+) -> Result<Vec<(pl::Ident, RelationalQuery)>> {
+    log.stage(debug::Stage::Semantic(debug::StageSemantic::Lowering));
+    let default_db = [NS_DEFAULT_DB.to_string()];
+    let database_module_path = database_module_path.unwrap_or(&default_db);
+    let queries = lowering::lower_relations(root_mod, database_module_path)?;
+
+    for (_, query) in &queries {
+        log.entry(|| debug::DebugEntryKind::ReprRq(query.clone()));
+    }
+    Ok(queries)
+}
+
 /// Runs semantic analysis on the query.
-pub fn resolve(mut module_tree: pr::ModuleDef) -> Result<RootModule> {
+pub fn resolve(module_tree: pr::ModuleDef, log: &DebugLogger) -> Result<RootModule> {
+    let stmts = expand_with_std(module_tree, log)?;
+
+    let mut root_module = new_root_module();
+    let mut id = IdGenerator::new();
+
+    log.stage(debug::Stage::Semantic(debug::StageSemantic::Resolver));
+    for stmt in stmts {
+        resolve_stmt(&mut root_module, &mut id, stmt)?;
+    }
+    log.entry(|| debug::DebugEntryKind::ReprDecl(root_module.clone()));
+
+    Ok(root_module)
+}
+
+/// Loads std into the module tree and expands it into the top-level
+/// statements of PL, which [resolve_stmt] resolves one at a time.
+pub(crate) fn expand_with_std(
+    mut module_tree: pr::ModuleDef,
+    log: &DebugLogger,
+) -> Result<Vec<Stmt>> {
     load_std_lib(&mut module_tree);
 
-    // expand AST into PL
-    debug::log_stage(debug::Stage::Semantic(debug::StageSemantic::AstExpand));
+    log.stage(debug::Stage::Semantic(debug::StageSemantic::AstExpand));
     let root_module_def = ast_expand::expand_module_def(module_tree)?;
-    debug::log_entry(|| debug::DebugEntryKind::ReprPl(root_module_def.clone()));
+    log.entry(|| debug::DebugEntryKind::ReprPl(root_module_def.clone()));
+
+    Ok(root_module_def.stmts)
+}
 
-    // init new root module
-    let mut root_module = RootModule {
+pub(crate) fn new_root_module() -> RootModule {
+    RootModule {
         module: Module::new_root(),
         ..Default::default()
-    };
-    let mut resolver = Resolver::new(&mut root_module);
-
-    // resolve the module def into the root module
-    debug::log_stage(debug::Stage::Semantic(debug::StageSemantic::Resolver));
-    resolver.fold_statements(root_module_def.stmts)?;
-    debug::log_entry(|| debug::DebugEntryKind::ReprDecl(root_module.clone()));
+    }
+}
 
-    Ok(root_module)
+/// Resolves a top-level statement into the root module. Ids are generated by
+/// `id`, which must be shared by all statements of the root module.
+pub(crate) fn resolve_stmt(
+    root_module: &mut RootModule,
+    id: &mut IdGenerator<usize>,
+    stmt: Stmt,
+) -> Result<()> {
+    let mut resolver = Resolver::new(root_module);
+    resolver.id = std::mem::take(id);
+    let result = resolver.fold_statements(vec![stmt]);
+    *id = resolver.id;
+    result
 }
 
 /// Preferred way of injecting std module.
 pub fn load_std_lib(module_tree: &mut pr::ModuleDef) {
     if !module_tree.stmts.iter().any(|s| is_mod_def_for(s, NS_STD)) {
         log::debug!("loading std.prql");
-        let _suppressed = debug::log_suppress();
 
         let std_source = include_str!("std.prql");
-        match parser::parse_source(std_source, 0) {
+        match parser::parse_source(std_source, 0, &DebugLogger::default()) {
             Ok(stmts) => {
                 let stmt = pr::Stmt::new(pr::StmtKind::ModuleDef(pr::ModuleDef {
                     name: "std".to_string(),
@@ -144,6 +213,7 @@ impl pl::Expr {
                 found: format!("`{}`", write_pl(pl::Expr::new(i))),
             })
             .with_span(self.span)
+            .with_code("E0007")
         })
     }
 }
@@ -161,18 +231,26 @@ pub mod test {
     use insta::assert_yaml_snapshot;
 
     use super::{resolve, resolve_and_lower, RootModule};
+    use crate::debug::DebugLogger;
     use crate::ir::rq::RelationalQuery;
     use crate::parser::parse;
     use crate::Errors;
 
     pub fn parse_resolve_and_lower(query: &str) -> Result<RelationalQuery, Errors> {
         let source_tree = query.into();
-        Ok(resolve_and_lower(parse(&source_tree)?, &[], None)?)
+        let log = DebugLogger::default();
+        Ok(resolve_and_lower(
+            parse(&source_tree, &log)?,
+            &[],
+            None,
+            &log,
+        )?)
     }
 
     pub fn parse_and_resolve(query: &str) -> Result<RootModule, Errors> {
         let source_tree = query.into();
-        Ok(resolve(parse(&source_tree)?)?)
+        let log = DebugLogger::default();
+        Ok(resolve(parse(&source_tree, &log)?, &log)?)
     }
 
     #[test]
@@ -1,5 +1,7 @@
 use std::collections::{HashMap, HashSet};
 
+use itertools::Itertools;
+
 use super::{
     NS_DEFAULT_DB, NS_INFER, NS_INFER_MODULE, NS_MAIN, NS_PARAM, NS_QUERY_DEF, NS_SELF, NS_STD,
     NS_THAT, NS_THIS,
@@ -8,8 +10,8 @@ use crate::ir::decl::{Decl, DeclKind, Module, RootModule, TableDecl, TableExpr};
 use crate::ir::pl::{Annotation, Expr, Ident, Lineage, LineageColumn};
 use crate::pr::QueryDef;
 use crate::pr::{Span, Ty, TyKind, TyTupleField};
-use crate::Error;
 use crate::Result;
+use crate::{Error, WithErrorInfo};
 
 impl Module {
     pub fn singleton<S: ToString>(name: S, entry: Decl) -> Module {
@@ -75,9 +77,10 @@ impl Module {
             if let DeclKind::Module(inner) = &mut entry.kind {
                 inner.insert(remaining.unwrap(), decl)
             } else {
-                Err(Error::new_simple(
-                    "path does not resolve to a module or a table",
-                ))
+                Err(
+                    Error::new_simple("path does not resolve to a module or a table")
+                        .with_code("E0005"),
+                )
             }
         }
     }
@@ -191,6 +194,30 @@ impl Module {
         res
     }
 
+    /// Names of the declarations which [Module::lookup] can find in the
+    /// module at `path`, including those found by following redirects.
+    pub fn names_visible_in(&self, path: &[String]) -> HashSet<String> {
+        fn names_in(module: &Module, path: &[String]) -> HashSet<String> {
+            let Some((prefix, path)) = path.split_first() else {
+                return module.names.keys().cloned().collect();
+            };
+            match module.names.get(prefix).map(|decl| &decl.kind) {
+                Some(DeclKind::Module(ns)) => ns.names_visible_in(path),
+                Some(DeclKind::LayeredModules(stack)) => (stack.iter())
+                    .flat_map(|ns| ns.names_visible_in(path))
+                    .collect(),
+                _ => HashSet::new(),
+            }
+        }
+
+        let mut res = names_in(self, path);
+        for redirect in &self.redirects {
+            let path = redirect.iter().chain(path).cloned().collect_vec();
+            res.extend(names_in(self, &path));
+        }
+        res
+    }
+
     pub(super) fn insert_frame(&mut self, lineage: &Lineage, namespace: &str) {
         let namespace = self.names.entry(namespace.to_string()).or_default();
         let namespace = namespace.kind.as_module_mut().unwrap();
@@ -388,9 +415,9 @@ impl RootModule {
     ) -> Result<()> {
         let existing = self.module.get(&ident);
         if existing.is_some() {
-            return Err(Error::new_simple(format!(
-                "duplicate declarations of {ident}"
-            )));
+            return Err(
+                Error::new_simple(format!("duplicate declarations of {ident}")).with_code("E0013"),
+            );
         }
 
         let decl = Decl {
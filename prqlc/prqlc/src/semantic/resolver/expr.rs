@@ -7,6 +7,7 @@ use crate::pr::{Ty, TyKind, TyTupleField};
 use crate::semantic::resolver::{flatten, types, Resolver};
 use crate::semantic::{NS_INFER, NS_SELF, NS_THAT, NS_THIS};
 use crate::utils::IdGenerator;
+use crate::warnings::emit_warning;
 use crate::Result;
 use crate::{Error, Reason, Span, WithErrorInfo};
 
@@ -21,7 +22,7 @@ impl pl::PlFold for Resolver<'_> {
                 self.root_mod.module.shadow(NS_THIS);
                 self.root_mod.module.shadow(NS_THAT);
 
-                let fq_ident = self.resolve_ident(&ident)?;
+                let fq_ident = self.resolve_ident(&ident, ty.span)?;
 
                 let decl = self.root_mod.module.get(&fq_ident).unwrap();
                 let decl_ty = decl.kind.as_ty().ok_or_else(|| {
@@ -30,6 +31,7 @@ impl pl::PlFold for Resolver<'_> {
                         expected: "a type".to_string(),
                         found: decl.to_string(),
                     })
+                    .with_code("E0007")
                 })?;
                 let mut ty = decl_ty.clone();
                 ty.name = ty.name.or(Some(fq_ident.name));
@@ -76,12 +78,19 @@ impl pl::PlFold for Resolver<'_> {
             pl::ExprKind::Ident(ident) => {
                 log::debug!("resolving ident {ident}...");
                 let fq_ident = self
-                    .resolve_ident(&ident)
+                    .resolve_ident(&ident, node.span)
                     .map_err(|e| e.with_span(node.span))?;
                 log::debug!("... resolved to {fq_ident}");
+                self.warn_if_other_relations_may_contain(&ident, &fq_ident, node.span);
                 let entry = self.root_mod.module.get(&fq_ident).unwrap();
                 log::debug!("... which is {entry}");
 
+                if is_deprecated(&entry.annotations) {
+                    emit_warning(
+                        Error::new_simple(format!("`{ident}` is deprecated")).with_span(node.span),
+                    );
+                }
+
                 match &entry.kind {
                     DeclKind::Infer(_) => pl::Expr {
                         kind: pl::ExprKind::Ident(fq_ident),
@@ -141,7 +150,8 @@ impl pl::PlFold for Resolver<'_> {
                             expected: "a value".to_string(),
                             found: "a type".to_string(),
                         })
-                        .with_span(*span));
+                        .with_span(*span)
+                        .with_code("E0007"));
                     }
 
                     _ => pl::Expr {
@@ -327,3 +337,18 @@ fn ty_of_lineage(lineage: &pl::Lineage) -> Ty {
             .collect(),
     )
 }
+
+/// Whether a declaration is annotated with `@{deprecated}`.
+fn is_deprecated(annotations: &[pl::Annotation]) -> bool {
+    annotations
+        .iter()
+        .any(|annotation| match &annotation.expr.kind {
+            pl::ExprKind::Tuple(fields) => fields.iter().any(|field| {
+                field
+                    .kind
+                    .as_ident()
+                    .is_some_and(|i| i.name == "deprecated")
+            }),
+            _ => false,
+        })
+}
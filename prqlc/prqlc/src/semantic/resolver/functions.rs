@@ -28,7 +28,8 @@ impl Resolver<'_> {
                 "Too many arguments to function `{}`",
                 closure.as_debug_name()
             ))
-            .with_span(span));
+            .with_span(span)
+            .with_code("E0009"));
         }
 
         let enough_args = closure.args.len() == closure.params.len();
@@ -187,7 +188,8 @@ impl Resolver<'_> {
             return Err(Error::new_simple(format!(
                 "unknown named argument `{name}` to closure {:?}",
                 closure.name_hint
-            )));
+            ))
+            .with_code("E0009"));
         }
 
         // positional
@@ -258,13 +260,15 @@ impl Resolver<'_> {
                                 expected: "a table or query".to_string(),
                                 found: "an empty array `[]`".to_string(),
                             })
-                            .with_span(arg.span),
+                            .with_span(arg.span)
+                            .with_code("E0007"),
                             ExprKind::Tuple(v) if v.is_empty() => Error::new(Reason::Expected {
                                 who: None,
                                 expected: "a table or query".to_string(),
                                 found: "an empty tuple `{}`".to_string(),
                             })
-                            .with_span(arg.span),
+                            .with_span(arg.span)
+                            .with_code("E0007"),
                             _ => Error::new_bug(4317).with_span(closure.body.span),
                         }
                     })?;
@@ -7,12 +7,19 @@ use crate::ir::decl::{Decl, DeclKind, Module};
 use crate::ir::pl::{Expr, ExprKind};
 use crate::pr::Ident;
 use crate::semantic::{NS_INFER, NS_INFER_MODULE, NS_SELF, NS_THAT, NS_THIS};
+use crate::warnings::emit_warning;
 use crate::Error;
+use crate::Fix;
 use crate::Result;
+use crate::Span;
 use crate::WithErrorInfo;
 
 impl Resolver<'_> {
-    pub(super) fn resolve_ident(&mut self, ident: &Ident) -> Result<Ident, Error> {
+    pub(super) fn resolve_ident(
+        &mut self,
+        ident: &Ident,
+        span: Option<Span>,
+    ) -> Result<Ident, Error> {
         let mut res = if let Some(default_namespace) = self.default_namespace.clone() {
             self.resolve_ident_core(ident, Some(&default_namespace))
         } else {
@@ -34,7 +41,7 @@ impl Resolver<'_> {
                 let decl = self.root_mod.module.get(fq_ident).unwrap();
                 if let DeclKind::Import(target) = &decl.kind {
                     let target = target.clone();
-                    return self.resolve_ident(&target);
+                    return self.resolve_ident(&target, span);
                 }
             }
             Err(e) => {
@@ -43,6 +50,10 @@ impl Resolver<'_> {
                     self.root_mod
                 );
 
+                if e.code == Some("E0005") && ident.name != "*" {
+                    res = res.map_err(|e| self.suggest_names(e, ident, span));
+                }
+
                 // attach available names
                 let mut available_names = Vec::new();
                 available_names.extend(self.collect_columns_in_module(NS_THIS));
@@ -56,6 +67,55 @@ impl Resolver<'_> {
         res
     }
 
+    /// Attaches names in scope which are similar to `ident`, as a hint and as
+    /// fixes which replace it.
+    fn suggest_names(&self, mut error: Error, ident: &Ident, span: Option<Span>) -> Error {
+        let mut names = HashSet::new();
+        for depth in 0..=self.current_module_path.len() {
+            let path = (self.current_module_path[..depth].iter())
+                .chain(&ident.path)
+                .cloned()
+                .collect_vec();
+            names.extend(self.root_mod.module.names_visible_in(&path));
+        }
+        if let Some(default_namespace) = &self.default_namespace {
+            let path = std::iter::once(default_namespace)
+                .chain(&ident.path)
+                .cloned()
+                .collect_vec();
+            names.extend(self.root_mod.module.names_visible_in(&path));
+        }
+
+        // allow about one typo per three characters, but don't suggest names
+        // which have nothing in common with it
+        let len = ident.name.chars().count();
+        let max_distance = (len / 3).max(1);
+        let suggestions = (names.into_iter())
+            .filter(|name| !name.starts_with('_') && *name != ident.name)
+            .map(|name| (strsim::damerau_levenshtein(&ident.name, &name), name))
+            .filter(|(distance, _)| *distance <= max_distance && *distance < len)
+            .sorted()
+            .take(3)
+            .map(|(_, name)| ident.clone().with_name(name).to_string())
+            .collect_vec();
+        if suggestions.is_empty() {
+            return error;
+        }
+
+        let quoted = suggestions.iter().map(|s| format!("`{s}`")).join(", ");
+        error = if suggestions.len() == 1 {
+            error.push_hint(format!("did you mean {quoted}?"))
+        } else {
+            error.push_hint(format!("did you mean one of {quoted}?"))
+        };
+        if let Some(span) = span {
+            for replacement in suggestions {
+                error = error.push_fix(Fix { span, replacement });
+            }
+        }
+        error
+    }
+
     fn collect_columns_in_module(&mut self, mod_name: &str) -> Vec<Ident> {
         let mut cols = Vec::new();
 
@@ -93,7 +153,7 @@ impl Resolver<'_> {
             // }
             return self
                 .resolve_ident_wildcard(ident)
-                .map_err(Error::new_simple);
+                .map_err(|e| Error::new_simple(e).with_code("E0005"));
         }
 
         // base case: direct lookup
@@ -135,13 +195,67 @@ impl Resolver<'_> {
             Ok(inferred_ident) => Ok(inferred_ident),
 
             // Was not able to infer.
-            Err(None) => Err(Error::new_simple(
-                format!("Unknown name `{}`", &ident).to_string(),
-            )),
+            Err(None) => Err(
+                Error::new_simple(format!("Unknown name `{}`", &ident).to_string())
+                    .with_code("E0005"),
+            ),
             Err(Some(msg)) => Err(msg),
         }
     }
 
+    /// Warns when `ident` was resolved to a column of one relation of a frame,
+    /// but other relations of the frame have unknown columns, which might
+    /// contain a column of the same name.
+    pub(super) fn warn_if_other_relations_may_contain(
+        &self,
+        ident: &Ident,
+        fq_ident: &Ident,
+        span: Option<Span>,
+    ) {
+        let [frame, relation] = fq_ident.path.as_slice() else {
+            return;
+        };
+        if (frame != NS_THIS && frame != NS_THAT) || ident.path.contains(relation) {
+            return;
+        }
+        let decl = self.root_mod.module.get(fq_ident);
+        if !decl.is_some_and(|d| d.kind.is_column()) {
+            return;
+        }
+
+        let Some(DeclKind::Module(frame_mod)) =
+            self.root_mod.module.names.get(frame).map(|d| &d.kind)
+        else {
+            return;
+        };
+        let others = (frame_mod.names.iter())
+            .filter(|(name, _)| *name != relation)
+            .filter(|(_, decl)| {
+                decl.kind
+                    .as_module()
+                    .is_some_and(|m| m.names.contains_key(NS_INFER))
+            })
+            .map(|(name, _)| format!("`{name}`"))
+            .sorted()
+            .collect_vec();
+        if others.is_empty() {
+            return;
+        }
+
+        let warning = Error::new_simple(format!(
+            "`{}` is resolved to `{relation}.{}`, but {} might also contain it",
+            ident.name,
+            fq_ident.name,
+            others.join(", ")
+        ))
+        .push_hint(format!(
+            "use `{relation}.{}` to make this explicit",
+            fq_ident.name
+        ))
+        .with_span(span);
+        emit_warning(warning);
+    }
+
     /// Try lookup of the ident with name replaced. If unsuccessful, recursively retry parent ident.
     fn resolve_ident_fallback(
         &mut self,
@@ -168,7 +282,7 @@ impl Resolver<'_> {
                 // single match, great!
                 let infer_ident = decls.into_iter().next().unwrap();
                 self.infer_decl(infer_ident, ident)
-                    .map_err(|x| Some(Error::new_simple(x)))
+                    .map_err(|x| Some(Error::new_simple(x).with_code("E0005")))
             }
             0 => Err(None),
             _ => Err(Some(ambiguous_error(decls, Some(&ident.name)))),
@@ -260,5 +374,7 @@ fn ambiguous_error(idents: HashSet<Ident>, replace_name: Option<&String>) -> Err
     }
     chunks.sort();
     let hint = format!("could be any of: {}", chunks.join(", "));
-    Error::new_simple("Ambiguous name").push_hint(hint)
+    Error::new_simple("Ambiguous name")
+        .push_hint(hint)
+        .with_code("E0006")
 }
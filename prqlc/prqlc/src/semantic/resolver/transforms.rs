@@ -87,7 +87,8 @@ impl Resolver<'_> {
                             })
                             // Possibly this should refer to the item after the `take` where
                             // one exists?
-                            .with_span(expr.span));
+                            .with_span(expr.span)
+                            .with_code("E0008"));
                         }
                     }
                 };
@@ -132,7 +133,8 @@ impl Resolver<'_> {
                                         expected: "inner, left, right or full".to_string(),
                                         found: folded.to_string(),
                                     })
-                                    .with_span(span))
+                                    .with_span(span)
+                                    .with_code("E0008"))
                                 }
                             }
                         }
@@ -186,6 +188,7 @@ impl Resolver<'_> {
                             found: write_pl(expanding.clone()),
                         })
                         .with_span(expanding.span)
+                        .with_code("E0008")
                     })?
                 };
 
@@ -199,6 +202,7 @@ impl Resolver<'_> {
                             found: write_pl(rolling.clone()),
                         })
                         .with_span(rolling.span)
+                        .with_code("E0008")
                     })?
                 };
 
@@ -210,6 +214,7 @@ impl Resolver<'_> {
                             found: write_pl(expr.clone()),
                         })
                         .with_span(expr.span)
+                        .with_code("E0008")
                     })?;
                     into_literal_range(range_tuple)?
                 };
@@ -222,6 +227,7 @@ impl Resolver<'_> {
                             found: write_pl(expr.clone()),
                         })
                         .with_span(expr.span)
+                        .with_code("E0008")
                     })?;
                     into_literal_range(range_tuple)?
                 };
@@ -300,7 +306,8 @@ impl Resolver<'_> {
                     expected: "a pattern".to_string(),
                     found: write_pl(pattern.clone()),
                 })
-                .with_span(pattern.span));
+                .with_span(pattern.span)
+                .with_code("E0008"));
             }
 
             "tuple_every" => {
@@ -380,7 +387,8 @@ impl Resolver<'_> {
                             expected: "a string literal".to_string(),
                             found: format!("`{}`", write_pl(text_expr.clone())),
                         })
-                        .with_span(text_expr.span));
+                        .with_span(text_expr.span)
+                        .with_code("E0008"));
                     }
                 };
 
@@ -391,9 +399,9 @@ impl Resolver<'_> {
                         .to_string();
                     match format.as_str() {
                         "csv" => from_text::parse_csv(&text)
-                            .map_err(|r| Error::new_simple(r).with_span(span))?,
+                            .map_err(|r| Error::new_simple(r).with_span(span).with_code("E0008"))?,
                         "json" => from_text::parse_json(&text)
-                            .map_err(|r| Error::new_simple(r).with_span(span))?,
+                            .map_err(|r| Error::new_simple(r).with_span(span).with_code("E0008"))?,
 
                         _ => {
                             return Err(Error::new(Reason::Expected {
@@ -401,7 +409,8 @@ impl Resolver<'_> {
                                 expected: "csv or json".to_string(),
                                 found: format,
                             })
-                            .with_span(span))
+                            .with_span(span)
+                            .with_code("E0008"))
                         }
                     }
                 };
@@ -463,7 +472,8 @@ impl Resolver<'_> {
                 return Err(
                     Error::new_simple(format!("unknown operator {internal_name}"))
                         .push_hint("this is a bug in prqlc")
-                        .with_span(func.body.span),
+                        .with_span(func.body.span)
+                        .with_code("E0022"),
                 )
             }
         };
@@ -493,7 +503,8 @@ impl Resolver<'_> {
                     found: format!("assign to `{alias}`"),
                 })
                 .push_hint(format!("move assign into the tuple: `[{alias} = ...]`"))
-                .with_span(expr.span));
+                .with_span(expr.span)
+                .with_code("E0008"));
             }
 
             expr
@@ -598,7 +609,9 @@ fn into_literal_range(range: (Expr, Expr)) -> Result<(Option<i64>, Option<i64>)>
         match bound.kind {
             ExprKind::Literal(Literal::Null) => Ok(None),
             ExprKind::Literal(Literal::Integer(i)) => Ok(Some(i)),
-            _ => Err(Error::new_simple("expected an int literal").with_span(bound.span)),
+            _ => Err(Error::new_simple("expected an int literal")
+                .with_span(bound.span)
+                .with_code("E0008")),
         }
     }
     Ok((into_int(range.0)?, into_int(range.1)?))
@@ -677,7 +690,9 @@ impl TransformCall {
 
         fn lineage_or_default(expr: &Expr) -> Result<Lineage> {
             expr.lineage.clone().ok_or_else(|| {
-                Error::new_simple("expected {expr:?} to have table type").with_span(expr.span)
+                Error::new_simple("expected {expr:?} to have table type")
+                    .with_span(expr.span)
+                    .with_code("E0007")
             })
         }
 
@@ -753,7 +768,8 @@ fn append(mut top: Lineage, bottom: Lineage) -> Result<Lineage, Error> {
             "top has {} columns, but bottom has {}",
             top.columns.len(),
             bottom.columns.len()
-        ));
+        ))
+        .with_code("E0018");
     }
 
     // Merge inputs from both relations so lineage can track both sources
@@ -817,7 +833,8 @@ fn append(mut top: Lineage, bottom: Lineage) -> Result<Lineage, Error> {
             ))
             .push_hint(
                 "make sure that top and bottom relations of append has the same column layout",
-            )),
+            )
+            .with_code("E0018")),
         });
     }
 
@@ -117,7 +117,8 @@ impl Resolver<'_> {
                         expected: "a table".to_string(),
                         found: found_desc,
                     })
-                    .with_span(found.span));
+                    .with_span(found.span)
+                    .with_code("E0007"));
                 };
 
                 let frame = self.declare_table_for_literal(id, None, found.alias.clone());
@@ -210,7 +211,8 @@ where
         who,
         expected: display_ty(expected),
         found: display_ty(found_ty),
-    });
+    })
+    .with_code("E0007");
 
     if found_ty.kind.is_function() && !expected.kind.is_function() {
         let found = found_ty.kind.as_function().unwrap();
@@ -19,7 +19,7 @@ use serde::{Deserialize, Serialize};
 use sqlparser::ast::DateTimeField;
 use strum::VariantNames;
 
-use crate::{Error, Result};
+use crate::{Error, Result, WithErrorInfo};
 
 /// SQL dialect.
 ///
@@ -217,9 +217,10 @@ pub(super) trait DialectHandler: Any + Debug {
     }
 
     fn translate_chrono_item(&self, _item: Item) -> Result<String> {
-        Err(Error::new_simple(
-            "Date formatting is not yet supported for this dialect",
-        ))
+        Err(
+            Error::new_simple("Date formatting is not yet supported for this dialect")
+                .with_code("E0010"),
+        )
     }
 
     fn supports_zero_columns(&self) -> bool {
@@ -261,7 +262,7 @@ impl dyn DialectHandler {
 
 impl DialectHandler for GenericDialect {
     fn translate_chrono_item(&self, _item: Item) -> Result<String> {
-        Err(Error::new_simple("Date formatting requires a dialect"))
+        Err(Error::new_simple("Date formatting requires a dialect").with_code("E0010"))
     }
 }
 
@@ -309,9 +310,10 @@ impl DialectHandler for PostgresDialect {
             }
             Item::Space(spaces) => spaces.to_string(),
             _ => {
-                return Err(Error::new_simple(
-                    "PRQL doesn't support this format specifier",
-                ))
+                return Err(
+                    Error::new_simple("PRQL doesn't support this format specifier")
+                        .with_code("E0008"),
+                )
             }
         })
     }
@@ -378,9 +380,10 @@ impl DialectHandler for RedshiftDialect {
             }
             Item::Space(spaces) => spaces.to_string(),
             _ => {
-                return Err(Error::new_simple(
-                    "PRQL doesn't support this format specifier",
-                ))
+                return Err(
+                    Error::new_simple("PRQL doesn't support this format specifier")
+                        .with_code("E0008"),
+                )
             }
         })
     }
@@ -476,9 +479,10 @@ impl DialectHandler for MsSqlDialect {
             }
             Item::Space(spaces) => spaces.to_string(),
             _ => {
-                return Err(Error::new_simple(
-                    "PRQL doesn't support this format specifier",
-                ))
+                return Err(
+                    Error::new_simple("PRQL doesn't support this format specifier")
+                        .with_code("E0008"),
+                )
             }
         })
     }
@@ -518,9 +522,10 @@ impl DialectHandler for MySqlDialect {
             Item::Literal(literal) => literal.replace('\'', "''").replace('%', "%%"),
             Item::Space(spaces) => spaces.to_string(),
             _ => {
-                return Err(Error::new_simple(
-                    "PRQL doesn't support this format specifier",
-                ))
+                return Err(
+                    Error::new_simple("PRQL doesn't support this format specifier")
+                        .with_code("E0008"),
+                )
             }
         })
     }
@@ -569,9 +574,10 @@ impl DialectHandler for ClickHouseDialect {
             }
             Item::Space(spaces) => spaces.to_string(),
             _ => {
-                return Err(Error::new_simple(
-                    "PRQL doesn't support this format specifier",
-                ))
+                return Err(
+                    Error::new_simple("PRQL doesn't support this format specifier")
+                        .with_code("E0008"),
+                )
             }
         })
     }
@@ -665,9 +671,10 @@ impl DialectHandler for DuckDbDialect {
             Item::Literal(literal) => literal.replace('\'', "''").replace('%', "%%"),
             Item::Space(spaces) => spaces.to_string(),
             _ => {
-                return Err(Error::new_simple(
-                    "PRQL doesn't support this format specifier",
-                ))
+                return Err(
+                    Error::new_simple("PRQL doesn't support this format specifier")
+                        .with_code("E0008"),
+                )
             }
         })
     }
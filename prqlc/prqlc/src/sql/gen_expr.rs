@@ -203,7 +203,8 @@ fn process_array_in(
         }
         _ => Err(
             Error::new_simple("args to `std.array_in` must be an expression and an array")
-                .with_span(expr.span),
+                .with_span(expr.span)
+                .with_code("E0008"),
         ),
     }
 }
@@ -242,7 +243,8 @@ fn process_date_to_text(
     } else {
         Err(
             Error::new_simple("`std.date.to_text` only supports a string literal as format")
-                .with_span(expr.span),
+                .with_span(expr.span)
+                .with_code("E0008"),
         )
     }
 }
@@ -425,7 +427,8 @@ pub(super) fn translate_literal(l: Literal, ctx: &Context) -> Result<sql_ast::Ex
                     return Err(Error::new_simple(format!(
                         "Unsupported interval unit: {}",
                         vau.unit
-                    )))
+                    ))
+                    .with_code("E0008"))
                 }
             };
             match ctx.dialect.interval_quoting_style(&sql_parser_datetime) {
@@ -612,7 +615,8 @@ pub(super) fn translate_star(ctx: &Context, span: Option<Span>) -> Result<String
     if !ctx.query.allow_stars {
         Err(
             Error::new_simple("Target dialect does not support * in this position.")
-                .with_span(span),
+                .with_span(span)
+                .with_code("E0010"),
         )
     } else {
         Ok("*".to_string())
@@ -667,7 +671,11 @@ fn unpack_as_int_literal(bound: rq::Expr) -> Result<i64> {
     Some(bound.kind)
         .and_then(|x| x.into_literal().ok())
         .and_then(|x| x.into_integer().ok())
-        .ok_or_else(|| Error::new_simple("expected an integer literal").with_span(bound.span))
+        .ok_or_else(|| {
+            Error::new_simple("expected an integer literal")
+                .with_span(bound.span)
+                .with_code("E0008")
+        })
 }
 
 fn try_range_into_int(range: Range<rq::Expr>) -> Result<Range<i64>> {
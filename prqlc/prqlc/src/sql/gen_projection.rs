@@ -41,7 +41,8 @@ pub(super) fn try_into_exprs(
             if !excluded.is_empty() {
                 return Err(
                     Error::new_simple("Excluding columns not supported as this position")
-                        .with_span(span),
+                        .with_span(span)
+                        .with_code("E0010"),
                 );
             }
         }
@@ -12,10 +12,12 @@ use sqlparser::ast::{
 use super::gen_expr::*;
 use super::gen_projection::*;
 use super::operators::translate_operator;
-use super::pq::ast::{Cte, CteKind, RelationExpr, RelationExprKind, SqlRelation, SqlTransform};
+use super::pq::ast::{
+    Cte, CteKind, RelationExpr, RelationExprKind, SqlQuery, SqlRelation, SqlTransform,
+};
 use super::{Context, Dialect};
-use crate::debug;
-use crate::ir::pl::{JoinSide, Literal};
+use crate::debug::{self, DebugLogger};
+use crate::ir::pl::{self, JoinSide, Literal};
 use crate::ir::rq::{CId, Expr, ExprKind, RelationLiteral, RelationalQuery};
 use crate::utils::{BreakUp, Pluck};
 use crate::{Error, Result, WithErrorInfo};
@@ -23,11 +25,32 @@ use prqlc_parser::generic::InterpolateItem;
 
 type Transform = SqlTransform<RelationExpr, ()>;
 
-pub fn translate_query(query: RelationalQuery, dialect: Option<Dialect>) -> Result<sql_ast::Query> {
+pub fn translate_query(
+    query: RelationalQuery,
+    dialect: Option<Dialect>,
+    log: &DebugLogger,
+) -> Result<sql_ast::Query> {
     // compile from RQ to PQ
-    let (pq_query, mut ctx) = super::pq::compile_query(query, dialect)?;
+    let (pq_query, ctx) = super::pq::compile_query(query, dialect, log)?;
+
+    translate_pq_query(pq_query, ctx)
+}
+
+/// Translates a relation into the name and the query of a view.
+pub fn translate_view(
+    query: RelationalQuery,
+    dialect: Option<Dialect>,
+    name: pl::Ident,
+    log: &DebugLogger,
+) -> Result<(sql_ast::ObjectName, sql_ast::Query)> {
+    let (pq_query, ctx) = super::pq::compile_query(query, dialect, log)?;
+
+    let name = sql_ast::ObjectName::from(translate_ident(Some(name), None, &ctx));
+    Ok((name, translate_pq_query(pq_query, ctx)?))
+}
 
-    debug::log_stage(debug::Stage::Sql(debug::StageSql::Main));
+fn translate_pq_query(pq_query: SqlQuery, mut ctx: Context) -> Result<sql_ast::Query> {
+    ctx.log.stage(debug::Stage::Sql(debug::StageSql::Main));
     let mut query = translate_relation(pq_query.main_relation, &mut ctx)?;
 
     if !pq_query.ctes.is_empty() {
@@ -46,7 +69,8 @@ pub fn translate_query(query: RelationalQuery, dialect: Option<Dialect>) -> Resu
         });
     }
 
-    debug::log_entry(|| debug::DebugEntryKind::ReprSqlParser(Box::new(query.clone())));
+    ctx.log
+        .entry(|| debug::DebugEntryKind::ReprSqlParser(Box::new(query.clone())));
     Ok(query)
 }
 
@@ -579,7 +603,8 @@ pub(super) fn translate_query_sstring(
 
     Err(
         Error::new_simple("s-strings representing a table must start with `SELECT `".to_string())
-            .push_hint("this is a limitation by current compiler implementation"),
+            .push_hint("this is a limitation by current compiler implementation")
+            .with_code("E0019"),
     )
 }
 
@@ -11,56 +11,115 @@ mod pq;
 pub use dialect::{Dialect, SupportLevel};
 pub use pq::ast as pq_ast;
 
+use std::str::FromStr;
+
 use self::dialect::DialectHandler;
 use self::pq::ast::Cte;
 use self::pq::context::AnchorContext;
-use crate::debug;
-use crate::ir::rq;
+use crate::debug::{self, DebugLogger};
+use crate::ir::{pl, rq};
 use crate::Result;
-use crate::{compiler_version, Options};
+use crate::{compiler_version, Options, Target};
 
 /// Translate a PRQL AST into a SQL string.
 pub fn compile(query: rq::RelationalQuery, options: &Options) -> Result<String> {
     let crate::Target::Sql(dialect) = options.target;
-    let sql_ast = gen_query::translate_query(query, dialect)?;
+    let sql_ast = gen_query::translate_query(query, dialect, &options.debug_log)?;
 
-    let sql = sql_ast.to_string();
+    let sql = format_sql(sql_ast.to_string(), options);
 
-    // formatting
-    let sql = if options.format {
-        let formatted = sqlformat::format(
-            &sql,
-            &sqlformat::QueryParams::default(),
-            &sqlformat::FormatOptions::default(),
-        );
+    options
+        .debug_log
+        .entry(|| debug::DebugEntryKind::ReprSql(sql.clone()));
 
-        formatted + "\n"
+    // signature
+    let sql = if let Some(signature) = signature_comment(options) {
+        let pre = if options.format { "\n" } else { " " };
+        let post = if options.format { "\n" } else { "" };
+        format!("{sql}{pre}{signature}{post}")
     } else {
         sql
     };
 
-    debug::log_entry(|| debug::DebugEntryKind::ReprSql(sql.clone()));
+    Ok(sql)
+}
 
-    // signature
-    let sql = if options.signature_comment {
-        let pre = if options.format { "\n" } else { " " };
-        let post = if options.format { "\n" } else { "" };
-        let target = dialect
-            .map(|d| format!("target:sql.{d} "))
-            .unwrap_or_default();
-        let signature = format!(
-            "{pre}-- Generated by PRQL compiler version:{} {}(https://prql-lang.org){post}",
-            compiler_version(),
-            target,
-        );
-        sql + &signature
+/// Translate the query of a relation into a single SQL statement, which is a
+/// `CREATE VIEW` statement when `create_view` is set.
+///
+/// Unlike [compile], the statement has no signature comment or trailing
+/// newline, so that statements of several relations can be joined together.
+pub fn compile_relation(
+    query: rq::RelationalQuery,
+    name: pl::Ident,
+    create_view: bool,
+    options: &Options,
+) -> Result<String> {
+    let crate::Target::Sql(dialect) = options.target;
+    let (name, sql_ast) = gen_query::translate_view(query, dialect, name, &options.debug_log)?;
+
+    let sql = format_sql(sql_ast.to_string(), options);
+    let sql = sql.trim_end();
+    let sql = if create_view {
+        let sep = if options.format { "\n" } else { " " };
+        format!("CREATE VIEW {name} AS{sep}{sql}")
     } else {
-        sql
+        sql.to_string()
     };
 
+    options
+        .debug_log
+        .entry(|| debug::DebugEntryKind::ReprSql(sql.clone()));
     Ok(sql)
 }
 
+/// The dialect of `dialect` if it's set, otherwise the dialect of the
+/// query's `target`, otherwise the generic dialect.
+pub(crate) fn resolve_dialect(
+    query_def: &pl::QueryDef,
+    dialect: Option<Dialect>,
+) -> Result<Dialect> {
+    if let Some(dialect) = dialect {
+        return Ok(dialect);
+    }
+    let target = query_def.other.get("target");
+    let Target::Sql(maybe_dialect) = target
+        .map(|s| Target::from_str(s))
+        .transpose()?
+        .unwrap_or_default();
+    Ok(maybe_dialect.unwrap_or_default())
+}
+
+fn format_sql(sql: String, options: &Options) -> String {
+    if options.format {
+        let formatted = sqlformat::format(
+            &sql,
+            &sqlformat::QueryParams::default(),
+            &sqlformat::FormatOptions::default(),
+        );
+
+        formatted + "\n"
+    } else {
+        sql
+    }
+}
+
+/// The comment which marks SQL as generated by the compiler, if enabled.
+pub(crate) fn signature_comment(options: &Options) -> Option<String> {
+    if !options.signature_comment {
+        return None;
+    }
+    let crate::Target::Sql(dialect) = options.target;
+    let target = dialect
+        .map(|d| format!("target:sql.{d} "))
+        .unwrap_or_default();
+    Some(format!(
+        "-- Generated by PRQL compiler version:{} {}(https://prql-lang.org)",
+        compiler_version(),
+        target,
+    ))
+}
+
 #[derive(Debug)]
 struct Context {
     pub dialect: Box<dyn DialectHandler>,
@@ -75,6 +134,8 @@ struct Context {
     query_stack: Vec<QueryOpts>,
 
     pub ctes: Vec<Cte>,
+
+    pub log: DebugLogger,
 }
 
 #[derive(Clone, Debug)]
@@ -111,7 +172,7 @@ impl Default for QueryOpts {
 }
 
 impl Context {
-    fn new(dialect: Dialect, anchor: AnchorContext) -> Self {
+    fn new(dialect: Dialect, anchor: AnchorContext, log: DebugLogger) -> Self {
         Context {
             dialect: dialect.handler(),
             dialect_enum: dialect,
@@ -119,6 +180,7 @@ impl Context {
             query: QueryOpts::default(),
             query_stack: Vec::new(),
             ctes: Vec::new(),
+            log,
         }
     }
 
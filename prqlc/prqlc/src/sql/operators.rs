@@ -7,17 +7,16 @@ use itertools::Itertools;
 
 use super::gen_expr::{translate_operand, ExprOrSource, SourceExpr};
 use super::{Context, Dialect};
+use crate::debug::DebugLogger;
 use crate::ir::{decl, pl, rq};
+use crate::semantic;
 use crate::utils::Pluck;
 use crate::Result;
-use crate::{debug, semantic};
 use crate::{Error, WithErrorInfo};
 
 fn std() -> &'static decl::Module {
     static STD: OnceLock<decl::Module> = OnceLock::new();
     STD.get_or_init(|| {
-        let _suppressed = debug::log_suppress();
-
         let std_lib = crate::SourceTree::new(
             [(
                 PathBuf::from("std.prql"),
@@ -25,8 +24,9 @@ fn std() -> &'static decl::Module {
             )],
             None,
         );
-        let ast = crate::parser::parse(&std_lib).unwrap();
-        let context = semantic::resolve(ast).unwrap();
+        let log = DebugLogger::default();
+        let ast = crate::parser::parse(&std_lib, &log).unwrap();
+        let context = semantic::resolve(ast, &log).unwrap();
 
         context.module
     })
@@ -63,7 +63,8 @@ pub(super) fn translate_operator(
             return Err(Error::new_simple(format!(
                 "operator {} is not supported for dialect {}",
                 name, ctx.dialect_enum
-            )))
+            ))
+            .with_code("E0010"))
         }
         pl::ExprKind::SString(items) => items,
         _ => panic!("Bad RQ operator implementation. Expected s-string or null"),
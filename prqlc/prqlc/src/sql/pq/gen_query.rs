@@ -1,39 +1,35 @@
 //! This module is responsible for translating RQ to PQ.
 
-use std::str::FromStr;
-
 use itertools::Itertools;
 
-use super::super::{Context, Dialect};
+use super::super::{Context, Dialect, SupportLevel};
 use super::anchor::{self, anchor_split};
 use super::ast::{self as pq, fold_sql_transform, PqMapper};
 use super::context::{AnchorContext, RIId, RelationAdapter, RelationStatus};
 use super::{postprocess, preprocess};
-use crate::debug;
+use crate::debug::{self, DebugLogger};
 use crate::ir::rq::{self, RqFold};
 use crate::utils::BreakUp;
-use crate::{Result, Target};
+use crate::warnings::emit_warning;
+use crate::{Error, Result};
 
 pub(in super::super) fn compile_query(
     query: rq::RelationalQuery,
     dialect: Option<Dialect>,
+    log: &DebugLogger,
 ) -> Result<(pq::SqlQuery, Context)> {
-    debug::log_stage(debug::Stage::Sql(debug::StageSql::Anchor));
+    log.stage(debug::Stage::Sql(debug::StageSql::Anchor));
 
-    let dialect = if let Some(dialect) = dialect {
-        dialect
-    } else {
-        let target = query.def.other.get("target");
-        let Target::Sql(maybe_dialect) = target
-            .map(|s| Target::from_str(s))
-            .transpose()?
-            .unwrap_or_default();
-        maybe_dialect.unwrap_or_default()
-    };
+    let dialect = super::super::resolve_dialect(&query.def, dialect)?;
+    if let SupportLevel::Nascent = dialect.support_level() {
+        emit_warning(Error::new_simple(format!(
+            "support for the {dialect} dialect is nascent, so the SQL may not be valid"
+        )));
+    }
 
     let (anchor, main_relation) = AnchorContext::of(query);
 
-    let mut ctx = Context::new(dialect, anchor);
+    let mut ctx = Context::new(dialect, anchor, log.clone());
 
     // compile main relation that will recursively compile CTEs
     let main_relation = compile_relation(main_relation.into(), &mut ctx)?;
@@ -45,11 +41,11 @@ pub(in super::super) fn compile_query(
         main_relation,
         ctes,
     };
-    debug::log_entry(|| debug::DebugEntryKind::ReprPq(query.clone()));
+    log.entry(|| debug::DebugEntryKind::ReprPq(query.clone()));
 
-    debug::log_stage(debug::Stage::Sql(debug::StageSql::Postprocess));
+    log.stage(debug::Stage::Sql(debug::StageSql::Postprocess));
     let query = postprocess::postprocess(query, &mut ctx);
-    debug::log_entry(|| debug::DebugEntryKind::ReprPq(query.clone()));
+    log.entry(|| debug::DebugEntryKind::ReprPq(query.clone()));
 
     Ok((query, ctx))
 }
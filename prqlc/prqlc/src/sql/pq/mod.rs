@@ -28,7 +28,7 @@ mod test {
     fn parse_and_resolve(source: &str) -> Result<SqlQuery, Errors> {
         let query = crate::semantic::test::parse_resolve_and_lower(source)?;
 
-        let (sql, _) = compile_query(query, Some(Dialect::Generic))?;
+        let (sql, _) = compile_query(query, Some(Dialect::Generic), &Default::default())?;
         Ok(sql)
     }
 
@@ -12,6 +12,7 @@ use crate::ir::rq::{
     self, maybe_binop, new_binop, CId, Compute, Expr, ExprKind, RqFold, Transform, Window,
 };
 use crate::sql::Context;
+use crate::warnings::emit_warning;
 use crate::{debug, Error, Result, WithErrorInfo};
 use prqlc_parser::generic::{InterpolateItem, Range};
 
@@ -33,7 +34,8 @@ pub(in crate::sql) fn preprocess(
         .and_then(|p| intersect(p, ctx))
         .map(reorder)
         .map(|p| {
-            debug::log_entry(|| debug::DebugEntryKind::ReprPqEarly(p.clone()));
+            ctx.log
+                .entry(|| debug::DebugEntryKind::ReprPqEarly(p.clone()));
             p
         })
 }
@@ -146,7 +148,7 @@ pub(in crate::sql) fn distinct(
                 let range_int = range
                     .clone()
                     .try_map(as_int)
-                    .map_err(|_| Error::new_simple("Invalid take arguments"))?;
+                    .map_err(|_| Error::new_simple("Invalid take arguments").with_code("E0008"))?;
 
                 let take_only_first =
                     range_int.start.unwrap_or(1) == 1 && matches!(range_int.end, Some(1));
@@ -371,9 +373,17 @@ pub(in crate::sql) fn except(
             // can we fall back to anti-join?
             if ctx.anchor.contains_wildcard(&top) || ctx.anchor.contains_wildcard(&bottom) {
                 return Err(Error::new_simple(format!("The dialect {:?} does not support EXCEPT ALL", ctx.dialect))
-                    .push_hint("providing more column information will allow the query to be translated to an anti-join."));
+                    .push_hint("providing more column information will allow the query to be translated to an anti-join.")
+                    .with_code("E0010"));
             } else {
                 // Don't create Except, fallback to anti-join.
+                emit_warning(
+                    Error::new_simple(format!(
+                        "The dialect {} does not support EXCEPT ALL, so an anti-join is used instead",
+                        ctx.dialect_enum
+                    ))
+                    .push_hint("rows of the top relation are removed whenever they occur in the bottom relation, rather than once per occurrence"),
+                );
                 continue;
             }
         }
@@ -462,9 +472,17 @@ pub(in crate::sql) fn intersect(
             // can we fall back to anti-join?
             if ctx.anchor.contains_wildcard(&top) || ctx.anchor.contains_wildcard(&bottom) {
                 return Err(Error::new_simple(format!("The dialect {:?} does not support INTERSECT ALL", ctx.dialect))
-                    .push_hint("providing more column information will allow the query to be translated to an anti-join."));
+                    .push_hint("providing more column information will allow the query to be translated to an anti-join.")
+                    .with_code("E0010"));
             } else {
                 // Don't create Intercept, fallback to inner join.
+                emit_warning(
+                    Error::new_simple(format!(
+                        "The dialect {} does not support INTERSECT ALL, so an inner join is used instead",
+                        ctx.dialect_enum
+                    ))
+                    .push_hint("rows which occur more than once in both relations may be repeated more often than with INTERSECT ALL"),
+                );
                 continue;
             }
         }
@@ -1,13 +1,17 @@
 mod id_gen;
+mod parallel;
 mod toposort;
+mod walk;
 
 use std::{io::stderr, sync::OnceLock};
 
 use anstream::adapter::strip_str;
 pub use id_gen::{IdGenerator, NameGenerator};
 use itertools::Itertools;
+pub use parallel::parallel_map;
 use regex::Regex;
 pub use toposort::toposort;
+pub use walk::walk_stmts;
 
 use crate::Result;
 
@@ -0,0 +1,79 @@
+use std::panic;
+use std::thread;
+
+use itertools::Itertools;
+
+use crate::warnings::{collect_warnings, emit_warning};
+
+/// Calls `f` on each item on as many threads as there are cores, returning the
+/// results in the order of the items.
+///
+/// Runs on the current thread on targets without threads. Warnings are
+/// collected on each thread and emitted on the current one, in the order of
+/// the items.
+pub fn parallel_map<T, R, F>(items: Vec<T>, f: F) -> Vec<R>
+where
+    T: Send,
+    R: Send,
+    F: Fn(T) -> R + Sync,
+{
+    let threads = thread::available_parallelism().map_or(1, |n| n.get());
+    if cfg!(target_family = "wasm") || threads == 1 || items.len() < 2 {
+        return items.into_iter().map(f).collect();
+    }
+
+    let chunk_size = items.len().div_ceil(threads);
+    let mut items = items.into_iter().peekable();
+    let mut chunks = Vec::new();
+    while items.peek().is_some() {
+        chunks.push(items.by_ref().take(chunk_size).collect::<Vec<_>>());
+    }
+
+    let f = &f;
+    thread::scope(|scope| {
+        let handles: Vec<_> = chunks
+            .into_iter()
+            .map(|chunk| {
+                scope.spawn(move || collect_warnings(|| chunk.into_iter().map(f).collect_vec()))
+            })
+            .collect();
+
+        let mut results = Vec::new();
+        for handle in handles {
+            match handle.join() {
+                Ok((chunk, warnings)) => {
+                    results.extend(chunk);
+                    warnings.into_iter().for_each(emit_warning);
+                }
+                Err(payload) => panic::resume_unwind(payload),
+            }
+        }
+        results
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use itertools::Itertools;
+
+    use super::parallel_map;
+    use crate::warnings::{collect_warnings, emit_warning};
+    use crate::Error;
+
+    #[test]
+    fn test_parallel_map_keeps_order() {
+        let items: Vec<usize> = (0..100).collect();
+        let squares = parallel_map(items, |x| x * x);
+        assert_eq!(squares, (0..100).map(|x| x * x).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_parallel_map_emits_warnings() {
+        let items: Vec<usize> = (0..10).collect();
+        let (_, warnings) = collect_warnings(|| {
+            parallel_map(items, |x| emit_warning(Error::new_simple(x.to_string())))
+        });
+        let reasons = warnings.iter().map(|w| w.reason.to_string()).collect_vec();
+        assert_eq!(reasons, (0..10).map(|x| x.to_string()).collect_vec());
+    }
+}
@@ -0,0 +1,66 @@
+//! Walking the expressions of PR, for checks which only need to look at each
+//! expression, rather than to transform the tree.
+
+use crate::pr::{self, InterpolateItem};
+
+/// Calls `f` on all expressions within the values of declarations.
+pub fn walk_stmts(stmts: &[pr::Stmt], f: &mut impl FnMut(&pr::Expr)) {
+    for stmt in stmts {
+        match &stmt.kind {
+            pr::StmtKind::VarDef(var_def) => {
+                if let Some(value) = &var_def.value {
+                    walk_expr(value, f);
+                }
+            }
+            pr::StmtKind::ModuleDef(module_def) => walk_stmts(&module_def.stmts, f),
+            _ => {}
+        }
+    }
+}
+
+/// Calls `f` on the expression and all expressions nested within it.
+fn walk_expr(expr: &pr::Expr, f: &mut impl FnMut(&pr::Expr)) {
+    use pr::ExprKind::*;
+
+    f(expr);
+    match &expr.kind {
+        Ident(_) | Literal(_) | Param(_) | Internal(_) | Error => {}
+        Pipeline(pipeline) => pipeline.exprs.iter().for_each(|e| walk_expr(e, f)),
+        Tuple(items) | Array(items) => items.iter().for_each(|e| walk_expr(e, f)),
+        Range(range) => {
+            for bound in [&range.start, &range.end].into_iter().flatten() {
+                walk_expr(bound, f);
+            }
+        }
+        Binary(binary) => {
+            walk_expr(&binary.left, f);
+            walk_expr(&binary.right, f);
+        }
+        Unary(unary) => walk_expr(&unary.expr, f),
+        FuncCall(call) => {
+            walk_expr(&call.name, f);
+            call.args.iter().for_each(|e| walk_expr(e, f));
+            call.named_args.values().for_each(|e| walk_expr(e, f));
+        }
+        Func(func) => {
+            walk_expr(&func.body, f);
+            let params = func.params.iter().chain(&func.named_params);
+            for default_value in params.filter_map(|p| p.default_value.as_ref()) {
+                walk_expr(default_value, f);
+            }
+        }
+        SString(items) | FString(items) => {
+            for item in items {
+                if let InterpolateItem::Expr { expr, .. } = item {
+                    walk_expr(expr, f);
+                }
+            }
+        }
+        Case(cases) => {
+            for case in cases {
+                walk_expr(&case.condition, f);
+                walk_expr(&case.value, f);
+            }
+        }
+    }
+}
@@ -0,0 +1,92 @@
+//! Collecting warnings, which don't stop compilation, from any stage of it.
+//!
+//! Stages call [emit_warning] wherever they are, rather than passing warnings
+//! up along with their results. Warnings are kept per thread, and only within
+//! [collect_warnings]; they are otherwise discarded.
+
+use std::cell::RefCell;
+
+use crate::{Error, MessageKind};
+
+thread_local! {
+    /// Warnings of the current compilation on this thread.
+    /// Is set by [collect_warnings].
+    static CURRENT_WARNINGS: RefCell<Option<Vec<Error>>> = const { RefCell::new(None) };
+}
+
+/// Collects the warnings of all compilation `f` does on the current thread.
+///
+/// Warnings with the same message and span are only reported once, since
+/// some expressions are resolved more than once.
+///
+/// ```
+/// use prqlc::{collect_warnings, compile, Options};
+///
+/// let prql = "from t | aggregate {n = count_distinct x}";
+/// let (res, warnings) = collect_warnings(|| compile(prql, &Options::default()));
+/// assert!(res.is_ok());
+/// assert_eq!(warnings[0].reason.to_string(), "`count_distinct` is deprecated");
+/// ```
+pub fn collect_warnings<R>(f: impl FnOnce() -> R) -> (R, Vec<Error>) {
+    /// Restores the warnings of an outer scope, even when `f` panics.
+    struct Restore(Option<Vec<Error>>);
+
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            let outer = self.0.take();
+            CURRENT_WARNINGS.with_borrow_mut(|warnings| *warnings = outer);
+        }
+    }
+
+    let outer = CURRENT_WARNINGS.with_borrow_mut(|warnings| warnings.replace(Vec::new()));
+    let restore = Restore(outer);
+
+    let result = f();
+
+    let warnings = CURRENT_WARNINGS.with_borrow_mut(Option::take).unwrap();
+    drop(restore);
+    (result, warnings)
+}
+
+/// Reports a warning to the enclosing [collect_warnings], if there is one.
+pub(crate) fn emit_warning(warning: Error) {
+    CURRENT_WARNINGS.with_borrow_mut(|warnings| {
+        let Some(warnings) = warnings.as_mut() else {
+            return;
+        };
+
+        let warning = Error {
+            kind: MessageKind::Warning,
+            ..warning
+        };
+        let is_duplicate = warnings
+            .iter()
+            .any(|w| w.span == warning.span && w.reason.to_string() == warning.reason.to_string());
+        if !is_duplicate {
+            warnings.push(warning);
+        }
+    });
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_nested_warnings() {
+        let ((), outer) = collect_warnings(|| {
+            emit_warning(Error::new_simple("a"));
+
+            let ((), inner) = collect_warnings(|| emit_warning(Error::new_simple("b")));
+            assert_eq!(inner.len(), 1);
+            assert_eq!(inner[0].reason.to_string(), "b");
+
+            emit_warning(Error::new_simple("a"));
+            emit_warning(Error::new_simple("c"));
+        });
+
+        let reasons: Vec<_> = outer.iter().map(|w| w.reason.to_string()).collect();
+        assert_eq!(reasons, vec!["a", "c"]);
+        assert!(matches!(outer[0].kind, MessageKind::Warning));
+    }
+}
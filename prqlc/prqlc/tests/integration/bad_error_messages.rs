@@ -25,7 +25,7 @@ fn test_bad_error_messages() {
     from film
     group
     "###).unwrap_err(), @r"
-    Error:
+    [E0007] Error:
        ╭─[ :3:5 ]
        │
      3 │     group
@@ -45,7 +45,7 @@ fn test_bad_error_messages() {
     from employees
     filter f location
     "#).unwrap_err(), @r"
-    Error:
+    [E0005] Error:
        ╭─[ :5:14 ]
        │
      5 │     filter f location
@@ -59,7 +59,7 @@ fn test_bad_error_messages() {
     select tracks
     from artists
     "###).unwrap_err(), @r"
-    Error:
+    [E0007] Error:
        ╭─[ :3:5 ]
        │
      3 │     from artists
@@ -93,14 +93,17 @@ fn interpolation_end() {
                 found: "end of input",
             },
             hints: [],
-            code: None,
+            code: Some(
+                "E0003",
+            ),
+            fixes: [],
         },
     ]
     "#);
 
     // PARSER output (full compilation error):
     assert_snapshot!(compile(source).unwrap_err(), @r#"
-    Error:
+    [E0003] Error:
        ╭─[ :1:21 ]
        │
      1 │ from x | select f"{}
@@ -117,7 +120,7 @@ fn select_with_extra_fstr() {
     from foo
     select lower f"{x}/{y}"
     "#).unwrap_err(), @r#"
-    Error:
+    [E0005] Error:
        ╭─[ :3:21 ]
        │
      3 │     select lower f"{x}/{y}"
@@ -137,7 +140,7 @@ fn misplaced_type_error() {
     from t
     select (true && foo)
     "###).unwrap_err(), @r"
-    Error:
+    [E0007] Error:
        ╭─[ :2:15 ]
        │
      2 │     let foo = 123
@@ -153,7 +156,7 @@ fn test_hint_missing_args() {
     from film
     select {film_id, lag film_id}
     "###).unwrap_err(), @r"
-    Error:
+    [E0007] Error:
        ╭─[ :3:22 ]
        │
      3 │     select {film_id, lag film_id}
@@ -170,7 +173,7 @@ fn test_relation_literal_contains_literals() {
     assert_snapshot!(compile(r###"
     [{a=(1+1)}]
     "###).unwrap_err(), @r"
-    Error:
+    [E0007] Error:
        ╭─[ :2:9 ]
        │
      2 │     [{a=(1+1)}]
@@ -197,7 +200,7 @@ fn nested_groups() {
       )
     )
     "###).unwrap_err(), @r"
-    Error:
+    [E0022] Error:
         ╭─[ :9:9 ]
         │
       9 │ ╭─▶         aggregate {
@@ -214,7 +217,7 @@ fn just_std() {
     assert_snapshot!(compile(r###"
     std
     "###).unwrap_err(), @r"
-    Error:
+    [E0022] Error:
        ╭─[ :1:1 ]
        │
      1 │ ╭─▶
@@ -15,7 +15,7 @@ fn test_errors() {
     derive y = (addadd 4 5 6)
     "###).unwrap_err(),
         @r"
-    Error:
+    [E0009] Error:
        ╭─[ :5:17 ]
        │
      5 │     derive y = (addadd 4 5 6)
@@ -28,7 +28,7 @@ fn test_errors() {
     from a select b
     "###).unwrap_err(),
         @r"
-    Error:
+    [E0009] Error:
        ╭─[ :2:5 ]
        │
      2 │     from a select b
@@ -43,7 +43,7 @@ fn test_errors() {
     select b
     "###).unwrap_err(),
         @r"
-    Error:
+    [E0005] Error:
        ╭─[ :4:12 ]
        │
      4 │     select b
@@ -59,7 +59,7 @@ fn test_errors() {
     take 1.8
     "###).unwrap_err(),
         @r"
-    Error:
+    [E0008] Error:
        ╭─[ :3:10 ]
        │
      3 │     take 1.8
@@ -76,20 +76,23 @@ fn test_errors() {
         Error {
             kind: Error,
             span: Some(
-                0:22-23,
+                0:22-25,
             ),
             reason: Unexpected {
                 found: "'’'",
             },
             hints: [],
-            code: None,
+            code: Some(
+                "E0003",
+            ),
+            fixes: [],
         },
     ]
     "#);
 
     // PARSER output (full compilation error):
     assert_snapshot!(compile(mississippi).unwrap_err(), @r"
-    Error:
+    [E0003] Error:
        ╭─[ :1:23 ]
        │
      1 │ Mississippi has four S’s and four I’s.
@@ -99,7 +102,7 @@ fn test_errors() {
     ");
 
     assert_snapshot!(compile("Answer: T-H-A-T!").unwrap_err(), @r"
-    Error:
+    [E0004] Error:
        ╭─[ :1:16 ]
        │
      1 │ Answer: T-H-A-T!
@@ -118,7 +121,7 @@ fn test_union_all_sqlite() {
     from film
     remove film2
     "###).unwrap_err(), @r"
-    Error: The dialect SQLiteDialect does not support EXCEPT ALL
+    [E0010] Error: The dialect SQLiteDialect does not support EXCEPT ALL
     ↳ Hint: providing more column information will allow the query to be translated to an anti-join.
     ")
 }
@@ -130,7 +133,7 @@ fn test_regex_dialect() {
     from foo
     filter bar ~= 'love'
     "###).unwrap_err(), @r"
-    Error:
+    [E0010] Error:
        ╭─[ :4:12 ]
        │
      4 │     filter bar ~= 'love'
@@ -148,7 +151,7 @@ fn test_bad_function_type() {
     "###,
     )
     .unwrap_err(), @r"
-    Error:
+    [E0007] Error:
        ╭─[ :3:16 ]
        │
      3 │     group foo (take)
@@ -188,7 +191,7 @@ fn test_ambiguous() {
     select date
     "#)
     .unwrap_err(), @r"
-    Error:
+    [E0006] Error:
        ╭─[ :4:12 ]
        │
      4 │     select date
@@ -211,7 +214,7 @@ fn test_ambiguous_join() {
     select x
     "#)
     .unwrap_err(), @r"
-    Error:
+    [E0006] Error:
        ╭─[ :5:12 ]
        │
      5 │     select x
@@ -233,7 +236,7 @@ fn test_ambiguous_inference() {
     select x
     "#)
     .unwrap_err(), @r"
-    Error:
+    [E0006] Error:
        ╭─[ :4:12 ]
        │
      4 │     select x
@@ -252,7 +255,7 @@ fn date_to_text_generic() {
   derive {
     d_str = (d | date.to_text "%Y/%m/%d")
   }"#).unwrap_err(), @r#"
-    Error:
+    [E0010] Error:
        ╭─[ :4:31 ]
        │
      4 │     d_str = (d | date.to_text "%Y/%m/%d")
@@ -271,7 +274,7 @@ fn date_to_text_not_supported_dialect() {
   derive {
     d_str = (d | date.to_text "%Y/%m/%d")
   }"#).unwrap_err(), @r#"
-    Error:
+    [E0010] Error:
        ╭─[ :6:31 ]
        │
      6 │     d_str = (d | date.to_text "%Y/%m/%d")
@@ -288,7 +291,7 @@ fn date_to_text_with_column_format() {
   select {my_date, my_format}
   select {std.date.to_text my_date my_format}
   "#).unwrap_err(), @r"
-    Error:
+    [E0008] Error:
        ╭─[ :4:11 ]
        │
      4 │   select {std.date.to_text my_date my_format}
@@ -307,7 +310,7 @@ fn date_to_text_unsupported_chrono_item() {
     derive {
       d_str = (d | date.to_text "%_j")
     }"#).unwrap_err(), @r#"
-    Error:
+    [E0008] Error:
        ╭─[ :6:33 ]
        │
      6 │       d_str = (d | date.to_text "%_j")
@@ -324,7 +327,7 @@ fn available_columns() {
     select foo
     select bar
     "#).unwrap_err(), @r"
-    Error:
+    [E0005] Error:
        ╭─[ :4:12 ]
        │
      4 │     select bar
@@ -339,7 +342,7 @@ fn available_columns() {
 #[test]
 fn empty_interpolations() {
     assert_snapshot!(compile(r#"from x | select f"{}" "#).unwrap_err(), @r#"
-    Error:
+    [E0004] Error:
        ╭─[ :1:20 ]
        │
      1 │ from x | select f"{}"
@@ -387,7 +390,7 @@ fn negative_number_in_transform() {
     from artists
     sort -name
     "###).unwrap_err(), @r"
-    Error: expected a pipeline that resolves to a table, but found `internal std.sub`
+    [E0007] Error: expected a pipeline that resolves to a table, but found `internal std.sub`
     ↳ Hint: wrap negative numbers in parentheses, e.g. `sort (-column_name)`
     ");
 
@@ -395,7 +398,7 @@ fn negative_number_in_transform() {
     from pets
     take -10
     "###).unwrap_err(), @r"
-    Error: expected a pipeline that resolves to a table, but found `internal std.sub`
+    [E0007] Error: expected a pipeline that resolves to a table, but found `internal std.sub`
     ↳ Hint: wrap negative numbers in parentheses, e.g. `sort (-column_name)`
     ");
 
@@ -405,7 +408,7 @@ fn negative_number_in_transform() {
     sort -val
   )
   "###).unwrap_err(), @r"
-    Error: expected a pipeline that resolves to a table, but found `internal std.sub`
+    [E0007] Error: expected a pipeline that resolves to a table, but found `internal std.sub`
     ↳ Hint: wrap negative numbers in parentheses, e.g. `sort (-column_name)`
     ");
 }
@@ -415,7 +418,7 @@ fn empty_tuple_or_array_from() {
     assert_snapshot!(compile(r###"
     from {}
     "###).unwrap_err(), @r"
-    Error:
+    [E0007] Error:
        ╭─[ :2:10 ]
        │
      2 │     from {}
@@ -427,7 +430,7 @@ fn empty_tuple_or_array_from() {
     assert_snapshot!(compile(r###"
     from []
     "###).unwrap_err(), @r"
-    Error:
+    [E0007] Error:
        ╭─[ :2:10 ]
        │
      2 │     from []
@@ -440,7 +443,7 @@ fn empty_tuple_or_array_from() {
     from {}
     select a
     "###).unwrap_err(), @r"
-    Error:
+    [E0007] Error:
        ╭─[ :2:10 ]
        │
      2 │     from {}
@@ -457,7 +460,7 @@ fn window_rows_expects_range() {
     from t
     group sid (window rows:2 (sid))
     "###).unwrap_err(), @r"
-    Error:
+    [E0008] Error:
        ╭─[ :3:28 ]
        │
      3 │     group sid (window rows:2 (sid))
@@ -470,7 +473,7 @@ fn window_rows_expects_range() {
     from t
     group sid (window range:2 (sid))
     "###).unwrap_err(), @r"
-    Error:
+    [E0008] Error:
        ╭─[ :3:29 ]
        │
      3 │     group sid (window range:2 (sid))
@@ -487,7 +490,7 @@ fn bare_lambda_expression() {
     assert_snapshot!(compile(r###"
     x -> y
     "###).unwrap_err(), @r"
-    Error:
+    [E0007] Error:
        ╭─[ :2:5 ]
        │
      2 │     x -> y
@@ -496,3 +499,63 @@ fn bare_lambda_expression() {
     ───╯
     ");
 }
+
+#[test]
+fn unknown_name_suggestions() {
+    assert_snapshot!(compile(r###"
+    from employees
+    select {first_name, salary}
+    derive {total = sumn salary, name = frist_name}
+    "###).unwrap_err(), @r"
+    [E0005] Error:
+       ╭─[ :4:21 ]
+       │
+     4 │     derive {total = sumn salary, name = frist_name}
+       │                     ──┬─
+       │                       ╰─── Unknown name `sumn`
+       │
+       │ Help: did you mean `sum`?
+       │
+       │ Note: available columns: employees.first_name, employees.salary
+    ───╯
+    ");
+}
+
+#[test]
+fn apply_unknown_name_fixes() {
+    let prql = r###"
+    let min_salary = 1000
+
+    from employees
+    select {first_name, salary}
+    filter salary > min_salry
+    "###;
+    let mut sources = prqlc::SourceTree::from(prql);
+    let errors = prqlc::compile(prql, &prqlc::Options::default()).unwrap_err();
+    assert_eq!(errors.inner[0].fixes.len(), 1);
+
+    assert_eq!(errors.apply_fixes(&mut sources), 1);
+    let fixed = sources.sources.values().next().unwrap();
+    assert!(fixed.contains("filter salary > min_salary"));
+    compile(fixed).unwrap();
+}
+
+#[test]
+fn error_location_in_source_tree() {
+    let sources = prqlc::SourceTree::new(
+        [
+            ("Project.prql".into(), "from shared.top_albums\nselect {title}".into()),
+            (
+                "shared.prql".into(),
+                "let top_albums = (\n  from albums\n  select {title, year}\n  filter yaer > 2000\n)".into(),
+            ),
+        ],
+        None,
+    );
+    let errors = prqlc::compile_tree(&sources, &[], &prqlc::Options::default()).unwrap_err();
+
+    let location = errors.inner[0].location.as_ref().unwrap();
+    assert_eq!(location.path.as_deref(), Some("shared.prql".as_ref()));
+    assert_eq!(location.start, (3, 9));
+    assert_snapshot!(errors.to_json(), @r#"{"inner":[{"kind":"Error","code":"E0005","reason":"Unknown name `yaer`","hints":["did you mean `year`?","available columns: albums.title, albums.year"],"fixes":[{"span":"2:65-69","replacement":"year"}],"span":"2:65-69","display":"[E0005] Error: \n   ╭─[ shared.prql:4:10 ]\n   │\n 4 │   filter yaer > 2000\n   │          ──┬─  \n   │            ╰─── Unknown name `yaer`\n   │ \n   │ Help: did you mean `year`?\n   │ \n   │ Note: available columns: albums.title, albums.year\n───╯\n","location":{"start":[3,9],"end":[3,13],"path":"shared.prql"}}]}"#);
+}
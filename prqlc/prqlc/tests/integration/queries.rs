@@ -110,6 +110,8 @@ mod compileall {
 }
 
 mod fmt {
+    use prqlc::lr::TokenKind;
+
     use super::*;
 
     test_each_path! { in "./prqlc/prqlc/tests/integration/queries" => run }
@@ -118,8 +120,8 @@ mod fmt {
         let test_name = prql_path.file_stem().unwrap().to_str().unwrap();
         let prql = fs::read_to_string(prql_path).unwrap();
 
-        let pl = prqlc::prql_to_pl(&prql).unwrap();
-        let formatted = prqlc::pl_to_prql(&pl).unwrap();
+        let options = prqlc::FormatOptions::default();
+        let formatted = prqlc::format_prql(&prql, &options).unwrap();
 
         with_settings!({ input_file => prql_path }, {
             assert_snapshot!(test_name, &formatted, &prql)
@@ -127,6 +129,25 @@ mod fmt {
 
         // Check the formatted queries can still compile
         prqlc::prql_to_pl(&formatted).unwrap();
+
+        // Check formatting retains all comments
+        similar_asserts::assert_eq!(comments(&formatted), comments(&prql));
+
+        // Check formatting is idempotent
+        similar_asserts::assert_eq!(prqlc::format_prql(&formatted, &options).unwrap(), formatted);
+    }
+
+    /// The comments of a source, sorted.
+    fn comments(prql: &str) -> Vec<String> {
+        let tokens = prqlc::prql_to_tokens(prql).unwrap();
+        let mut comments = (tokens.0.into_iter())
+            .filter_map(|token| match token.kind {
+                TokenKind::Comment(c) | TokenKind::DocComment(c) => Some(c.trim_end().to_string()),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        comments.sort();
+        comments
     }
 }
 
@@ -1,5 +1,5 @@
 //! Simple tests for "this PRQL creates this SQL" go here.
-use insta::assert_snapshot;
+use insta::{assert_debug_snapshot, assert_snapshot};
 use prqlc::{sql, ErrorMessages, Options, SourceTree, Target};
 use rstest::rstest;
 
@@ -659,7 +659,7 @@ fn test_remove_04() {
     remove artist
     "#).unwrap_err(),
         @r"
-    Error: The dialect SQLiteDialect does not support EXCEPT ALL
+    [E0010] Error: The dialect SQLiteDialect does not support EXCEPT ALL
     ↳ Hint: providing more column information will allow the query to be translated to an anti-join.
     "
     );
@@ -896,7 +896,7 @@ fn test_intersect_06() {
     intersect artist
     "#).unwrap_err(),
         @r"
-    Error: The dialect SQLiteDialect does not support INTERSECT ALL
+    [E0010] Error: The dialect SQLiteDialect does not support INTERSECT ALL
     ↳ Hint: providing more column information will allow the query to be translated to an anti-join.
     "
     );
@@ -1539,7 +1539,7 @@ fn test_in_values_err_01() {
     from employees
     derive { ng = ([1, 2] | in [3, 4]) }
     "###).unwrap_err()), @r"
-    Error:
+    [E0008] Error:
        ╭─[ :3:29 ]
        │
      3 │     derive { ng = ([1, 2] | in [3, 4]) }
@@ -2253,7 +2253,7 @@ fn test_take_07() {
     from employees
     take 0..1
     "###).unwrap_err()), @r"
-    Error:
+    [E0008] Error:
        ╭─[ :3:5 ]
        │
      3 │     take 0..1
@@ -2269,7 +2269,7 @@ fn test_take_08() {
     from employees
     take (-1..)
     "###).unwrap_err()), @r"
-    Error:
+    [E0008] Error:
        ╭─[ :3:5 ]
        │
      3 │     take (-1..)
@@ -2286,7 +2286,7 @@ fn test_take_09() {
     select a
     take 5..5.6
     "###).unwrap_err()), @r"
-    Error:
+    [E0008] Error:
        ╭─[ :4:5 ]
        │
      4 │     take 5..5.6
@@ -2302,7 +2302,7 @@ fn test_take_10() {
     from employees
     take (-1)
     "###).unwrap_err()), @r"
-    Error:
+    [E0008] Error:
        ╭─[ :3:5 ]
        │
      3 │     take (-1)
@@ -2810,7 +2810,7 @@ fn test_join_side_literal_err() {
     from x
     join y (==id) side:my_side
     "###).unwrap_err()), @r"
-    Error:
+    [E0008] Error:
        ╭─[ :5:24 ]
        │
      5 │     join y (==id) side:my_side
@@ -2849,7 +2849,7 @@ fn test_join_side_literal_via_func_err() {
     from x
     my_join default_db.y this.id s:"four"
     "###).unwrap_err()), @r#"
-    Error:
+    [E0008] Error:
        ╭─[ :3:19 ]
        │
      3 │         join side:_param.s m (c == that.k) tbl
@@ -2925,7 +2925,7 @@ fn test_from_json() {
     let source_tree = SourceTree::from(original_prql);
 
     let sql_from_prql = Ok(prqlc::prql_to_pl_tree(&source_tree).unwrap())
-        .and_then(|ast| prqlc::semantic::resolve_and_lower(ast, &[], None))
+        .and_then(|ast| prqlc::semantic::resolve_and_lower(ast, &[], None, &Default::default()))
         .and_then(|rq| sql::compile(rq, &Options::default()))
         .unwrap();
 
@@ -3141,7 +3141,7 @@ fn test_bare_s_string_04() {
     assert_snapshot!(compile(r#"
     s"SELECTfoo"
     "#).unwrap_err(), @r"
-    Error: s-strings representing a table must start with `SELECT `
+    [E0019] Error: s-strings representing a table must start with `SELECT `
     ↳ Hint: this is a limitation by current compiler implementation
     ");
 }
@@ -3940,7 +3940,7 @@ fn test_unused_alias() {
     from account
     select n = {account.name}
     "###).unwrap_err(), @r"
-    Error:
+    [E0008] Error:
        ╭─[ :3:16 ]
        │
      3 │     select n = {account.name}
@@ -4106,7 +4106,7 @@ fn test_direct_table_references() {
     "#,
     )
     .unwrap_err(), @r#"
-    Error:
+    [E0016] Error:
        ╭─[ :3:15 ]
        │
      3 │     select s"{x}.field"
@@ -4142,8 +4142,8 @@ fn test_table_variable_in_scalar_context() {
     filter actor_id == mod_id
     "#,
     )
-    .unwrap_err(), @r#"
-    Error:
+    .unwrap_err(), @r"
+    [E0016] Error:
        ╭─[ :5:24 ]
        │
      5 │     filter actor_id == mod_id
@@ -4152,7 +4152,7 @@ fn test_table_variable_in_scalar_context() {
        │
        │ Help: use a join instead, or inline the subquery
     ───╯
-    "#);
+    ");
 }
 
 #[test]
@@ -4198,13 +4198,13 @@ fn test_group_all() {
 
     from a=albums
     group a.* (aggregate {count this})
-        "###).unwrap_err(), @"Error: Target dialect does not support * in this position.");
+        "###).unwrap_err(), @"[E0010] Error: Target dialect does not support * in this position.");
 
     assert_snapshot!(compile(
         r###"
     from e=albums
     group !{genre_id} (aggregate {count this})
-        "###).unwrap_err(), @"Error: Excluding columns not supported as this position");
+        "###).unwrap_err(), @"[E0010] Error: Excluding columns not supported as this position");
 }
 
 #[test]
@@ -4816,17 +4816,17 @@ fn test_header_target_error() {
     assert_snapshot!(compile(r#"
     prql target:foo
     from a
-    "#).unwrap_err(),@r#"Error: target `"foo"` not found"#);
+    "#).unwrap_err(),@r#"[E0011] Error: target `"foo"` not found"#);
 
     assert_snapshot!(compile(r#"
     prql target:sql.foo
     from a
-    "#).unwrap_err(),@r#"Error: target `"sql.foo"` not found"#);
+    "#).unwrap_err(),@r#"[E0011] Error: target `"sql.foo"` not found"#);
 
     assert_snapshot!(compile(r#"
     prql target:foo.bar
     from a
-    "#).unwrap_err(),@r#"Error: target `"foo.bar"` not found"#);
+    "#).unwrap_err(),@r#"[E0011] Error: target `"foo.bar"` not found"#);
 
     // TODO: Can we use the span of:
     // - Ideally just `dialect`?
@@ -4835,7 +4835,7 @@ fn test_header_target_error() {
     prql dialect:foo.bar
     from a
     "#).unwrap_err(),@r"
-    Error:
+    [E0004] Error:
        ╭─[ :1:1 ]
        │
      1 │ ╭─▶
@@ -5849,7 +5849,7 @@ fn test_group_exclude() {
         "###,
     )
     .unwrap_err(), @r"
-    Error:
+    [E0005] Error:
        ╭─[ :4:27 ]
        │
      4 │     group {a} (derive c = a + 1)
@@ -6969,3 +6969,251 @@ fn test_aggregate_with_operations_and_filter() {
       COALESCE(SUM(customer_id), 0) * 2 > 0
     ");
 }
+
+#[test]
+fn test_compile_relations() {
+    let sources = SourceTree::from(
+        r#"
+    let _recent = (from orders | filter date > @2024-01-01)
+
+    let customer_totals = (
+      from _recent
+      group customer_id (aggregate {total = sum amount})
+    )
+
+    module reports {
+      let top = (from big_customers | sort {-total} | take 10)
+    }
+
+    let big_customers = (from customer_totals | filter total > 1000)
+    "#,
+    );
+    let options = Options::default().no_signature();
+
+    let relations = prqlc::compile_relations(&sources, &options, true).unwrap();
+    assert_snapshot!(prqlc::relations_to_script(&relations, &options), @r#"
+    CREATE VIEW customer_totals AS
+    WITH _recent AS (
+      SELECT
+        *
+      FROM
+        orders
+      WHERE
+        date > DATE '2024-01-01'
+    )
+    SELECT
+      customer_id,
+      COALESCE(SUM(amount), 0) AS total
+    FROM
+      _recent
+    GROUP BY
+      customer_id;
+
+    CREATE VIEW big_customers AS
+    SELECT
+      customer_id,
+      total
+    FROM
+      customer_totals
+    WHERE
+      total > 1000;
+
+    CREATE VIEW reports."top" AS
+    SELECT
+      *
+    FROM
+      big_customers
+    ORDER BY
+      total DESC
+    LIMIT
+      10;
+    "#);
+
+    let options = options.with_format(false);
+    let relations = prqlc::compile_relations(&sources, &options, false).unwrap();
+    let names = relations
+        .iter()
+        .map(|r| r.name.to_string())
+        .collect::<Vec<_>>();
+    assert_snapshot!(names.join(", "), @"customer_totals, big_customers, reports.top");
+    assert_snapshot!(relations[2].sql, @"SELECT * FROM big_customers ORDER BY total DESC LIMIT 10");
+
+    // Columns of a view are known, even if its declaration only has a wildcard
+    let sources = SourceTree::from(
+        r#"
+    let all_orders = (from orders)
+    let totals = (from all_orders | select {id, total})
+    "#,
+    );
+    let relations = prqlc::compile_relations(&sources, &options, false).unwrap();
+    assert_snapshot!(relations[1].sql, @"SELECT id, total FROM all_orders");
+}
+
+#[test]
+fn test_compile_full() {
+    let prql = r#"
+    prql target:sql.postgres
+
+    from employees
+    join departments (==dept_id)
+    group {departments.name} (
+      aggregate {
+        titles = count_distinct employees.title,
+        n = 5,
+      }
+    )
+    derive {label = f"{name}!"}
+    "#;
+    let options = Options::default()
+        .no_signature()
+        .with_display(prqlc::DisplayOptions::Plain);
+
+    let result = prqlc::compile_full(prql, &options).unwrap();
+    assert_eq!(result.sql, compile(prql).unwrap());
+    assert_eq!(result.dialect, sql::Dialect::Postgres);
+    assert_eq!(result.tables, vec!["departments", "employees"]);
+    assert_debug_snapshot!(result.columns, @r#"
+    [
+        Single {
+            name: Some(
+                "name",
+            ),
+            ty: None,
+        },
+        Single {
+            name: Some(
+                "titles",
+            ),
+            ty: None,
+        },
+        Single {
+            name: Some(
+                "n",
+            ),
+            ty: Some(
+                "int",
+            ),
+        },
+        Single {
+            name: Some(
+                "label",
+            ),
+            ty: Some(
+                "text",
+            ),
+        },
+    ]
+    "#);
+    assert_eq!(result.warnings.len(), 1);
+    assert_snapshot!(result.warnings[0].to_string(), @r"
+    Warning:
+       ╭─[ :8:18 ]
+       │
+     8 │         titles = count_distinct employees.title,
+       │                  ───────┬──────
+       │                         ╰──────── `count_distinct` is deprecated
+    ───╯
+    ");
+}
+
+#[test]
+fn test_compile_full_tree() {
+    let sources = SourceTree::new(
+        [
+            ("Project.prql".into(), "from shared.top_albums".into()),
+            (
+                "shared.prql".into(),
+                "let top_albums = (from albums | aggregate {n = count_distinct title})".into(),
+            ),
+        ],
+        None,
+    );
+    let options = Options::default().no_format().no_signature();
+    let main_path = ["shared".to_string(), "top_albums".to_string()];
+
+    let result = prqlc::compile_full_tree(&sources, &main_path, &options).unwrap();
+    assert_snapshot!(result.sql, @"SELECT COUNT(DISTINCT title) AS n FROM albums");
+    assert_eq!(result.tables, vec!["albums"]);
+    assert_eq!(result.columns.len(), 1);
+    let location = result.warnings[0].location.as_ref().unwrap();
+    assert_eq!(location.path.as_deref(), Some("shared.prql".as_ref()));
+}
+
+#[test]
+fn test_compile_full_declared_types() {
+    // columns which are copies of the columns of a declared table have their
+    // declared types
+    let sources = SourceTree::new(
+        [
+            (
+                "".into(),
+                "from albums | derive {released = year, next_year = year + 1} \
+                 | select {title, released, next_year}"
+                    .into(),
+            ),
+            (
+                "default_db.prql".into(),
+                "let albums <[{album_id = int, title = text, year = int}]>".into(),
+            ),
+        ],
+        None,
+    );
+    let result = prqlc::compile_full_tree(&sources, &[], &Options::default()).unwrap();
+    let types: Vec<_> = (result.columns.iter())
+        .map(|column| match column {
+            prqlc::OutputColumn::Single { ty, .. } => ty.as_deref(),
+            prqlc::OutputColumn::Wildcard => None,
+        })
+        .collect();
+    assert_eq!(types, [Some("text"), Some("int"), None]);
+}
+
+#[test]
+fn test_compile_warnings() {
+    let warnings = |prql: &str| {
+        let options = Options::default().with_display(prqlc::DisplayOptions::Plain);
+        let result = prqlc::compile_full(prql, &options).unwrap();
+        result
+            .warnings
+            .iter()
+            .map(|w| w.to_string())
+            .collect::<String>()
+    };
+
+    // an argument of another type is still an error
+    let prql = r#"
+    let double = x <float> -> x * 2
+    from t
+    derive {y = double 3}
+    "#;
+    let error = prqlc::compile_full(prql, &Options::default()).unwrap_err();
+    assert_snapshot!(error.inner[0].reason, @"function double, param `x` expected type `float`, but found type `int`");
+
+    // a column which another relation might also contain
+    assert_snapshot!(warnings(r#"
+    from a = (from x | select {id, n})
+    join b (==id)
+    select {n, b.m}
+    "#), @r"
+    Warning:
+       ╭─[ :4:13 ]
+       │
+     4 │     select {n, b.m}
+       │             ┬
+       │             ╰── `n` is resolved to `a.n`, but `b` might also contain it
+       │
+       │ Help: use `a.n` to make this explicit
+    ───╯
+    ");
+
+    // a fallback of the dialect, which changes the result
+    assert_snapshot!(warnings(r#"
+    prql target:sql.sqlite
+
+    from a = (from x | select {id, n})
+    remove (from b | select {id, n})
+    "#), @r"
+    Warning: The dialect sqlite does not support EXCEPT ALL, so an anti-join is used instead
+    ↳ Hint: rows of the top relation are removed whenever they occur in the bottom relation, rather than once per occurrence
+    ");
+}